@@ -0,0 +1,315 @@
+//! Tamper-evident, append-only audit log.
+//!
+//! Each entry records who did what and when: the actor, the time, the command
+//! and the events it led to, with the hash of the entry before it. An entry's
+//! hash covers all of these, so altering, removing, inserting or reordering
+//! entries breaks the chain at that point, which [`verify_chain`] reports.
+//!
+//! The chain cannot show that entries were cut from the end, so the hash of the
+//! latest entry, [`AuditLog::head_hash`], should be lodged somewhere the log's
+//! writers cannot change, e.g. with each day's compliance report.
+//!
+//! Hashes are SHA-256 over the entry's JSON. serde_json writes object keys in
+//! sorted order, so the same entry always hashes the same after storage.
+
+use chrono::NaiveDateTime;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+use crate::simple_types::ClientId;
+
+/// The previous hash of the first entry in a log.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuditLogError {
+    Unavailable(String),
+    Serialisation(String),
+}
+
+impl std::fmt::Display for AuditLogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            AuditLogError::Unavailable(ref desc) => write!(f, "Audit log unavailable: {}", desc),
+            AuditLogError::Serialisation(ref desc) => write!(f, "Audit entry could not be serialised: {}", desc),
+        }
+    }
+}
+
+impl std::error::Error for AuditLogError {}
+
+/// Who issued a command.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Actor {
+    /// A member of staff, by user name.
+    Staff(String),
+    Client(ClientId),
+    /// A scheduled job or integration, by name.
+    System(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditEntry {
+    sequence: u64,
+    actor: Actor,
+    recorded_at: NaiveDateTime,
+    command: serde_json::Value,
+    events: Vec<serde_json::Value>,
+    previous_hash: String,
+    hash: String,
+}
+
+impl AuditEntry {
+    /// Starts at 1 for the first entry.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn actor(&self) -> &Actor {
+        &self.actor
+    }
+
+    pub fn recorded_at(&self) -> NaiveDateTime {
+        self.recorded_at
+    }
+
+    pub fn command(&self) -> &serde_json::Value {
+        &self.command
+    }
+
+    pub fn events(&self) -> &Vec<serde_json::Value> {
+        &self.events
+    }
+
+    pub fn previous_hash(&self) -> &String {
+        &self.previous_hash
+    }
+
+    pub fn hash(&self) -> &String {
+        &self.hash
+    }
+
+    /// The hash of everything in the entry apart from the hash itself.
+    fn content_hash(&self) -> Result<String, AuditLogError> {
+        let content = serde_json::to_vec(&HashedContent {
+            sequence: self.sequence,
+            actor: &self.actor,
+            recorded_at: self.recorded_at,
+            command: &self.command,
+            events: &self.events,
+            previous_hash: &self.previous_hash,
+        }).map_err(|e| AuditLogError::Serialisation(e.to_string()))?;
+
+        Ok(format!("{:x}", Sha256::digest(content)))
+    }
+}
+
+#[derive(Serialize)]
+struct HashedContent<'a> {
+    sequence: u64,
+    actor: &'a Actor,
+    recorded_at: NaiveDateTime,
+    command: &'a serde_json::Value,
+    events: &'a [serde_json::Value],
+    previous_hash: &'a str,
+}
+
+/// Port for where audit entries are kept. Stores only ever append.
+pub trait AuditLogStore {
+    fn append(&mut self, entry: AuditEntry) -> Result<(), AuditLogError>;
+
+    fn last_entry(&self) -> Result<Option<AuditEntry>, AuditLogError>;
+
+    /// Every entry, oldest first.
+    fn entries(&self) -> Result<Vec<AuditEntry>, AuditLogError>;
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryAuditLogStore {
+    entries: Vec<AuditEntry>,
+}
+
+impl InMemoryAuditLogStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A store holding entries read back from elsewhere, e.g. an archive to verify.
+    pub fn from_entries(entries: Vec<AuditEntry>) -> Self {
+        Self { entries }
+    }
+}
+
+impl AuditLogStore for InMemoryAuditLogStore {
+    fn append(&mut self, entry: AuditEntry) -> Result<(), AuditLogError> {
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    fn last_entry(&self) -> Result<Option<AuditEntry>, AuditLogError> {
+        Ok(self.entries.last().cloned())
+    }
+
+    fn entries(&self) -> Result<Vec<AuditEntry>, AuditLogError> {
+        Ok(self.entries.clone())
+    }
+}
+
+pub struct AuditLog<'a> {
+    store: &'a mut dyn AuditLogStore,
+}
+
+impl<'a> AuditLog<'a> {
+    pub fn new(store: &'a mut dyn AuditLogStore) -> Self {
+        Self { store }
+    }
+
+    /// Appends an entry for `command` and the events it led to, chained to the latest entry.
+    pub fn record<C: Serialize, E: Serialize>(
+        &mut self,
+        actor: &Actor,
+        recorded_at: NaiveDateTime,
+        command: &C,
+        events: &[E],
+    ) -> Result<AuditEntry, AuditLogError> {
+        let to_value = |value: serde_json::Result<serde_json::Value>| value.map_err(|e| AuditLogError::Serialisation(e.to_string()));
+        let last_entry = self.store.last_entry()?;
+
+        let mut entry = AuditEntry {
+            sequence: last_entry.as_ref().map_or(1, |entry| entry.sequence + 1),
+            actor: actor.clone(),
+            recorded_at,
+            command: to_value(serde_json::to_value(command))?,
+            events: events.iter().map(|event| to_value(serde_json::to_value(event))).collect::<Result<_, _>>()?,
+            previous_hash: last_entry.map_or_else(|| GENESIS_HASH.to_string(), |entry| entry.hash),
+            hash: String::new(),
+        };
+        entry.hash = entry.content_hash()?;
+
+        self.store.append(entry.clone())?;
+        Ok(entry)
+    }
+
+    /// The hash of the latest entry, or [`GENESIS_HASH`] for an empty log.
+    pub fn head_hash(&self) -> Result<String, AuditLogError> {
+        Ok(self.store.last_entry()?.map_or_else(|| GENESIS_HASH.to_string(), |entry| entry.hash))
+    }
+
+    pub fn verify(&self) -> Result<Vec<ChainBreak>, AuditLogError> {
+        Ok(verify_chain(&self.store.entries()?))
+    }
+}
+
+/// A point where the chain shows the log was changed after it was written.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ChainBreak {
+    /// The entry no longer matches its own hash, so its content was altered.
+    Altered { sequence: u64 },
+    /// The entry does not follow on from the one before it, so entries were
+    /// removed, inserted or reordered.
+    Unlinked { sequence: u64, expected_previous_hash: String, previous_hash: String },
+    /// The sequence numbers skip or repeat.
+    OutOfSequence { expected: u64, sequence: u64 },
+}
+
+/// Checks every entry against its hash and the entry before it. An empty
+/// result means the chain is intact.
+pub fn verify_chain(entries: &[AuditEntry]) -> Vec<ChainBreak> {
+    let mut breaks = Vec::new();
+    let mut expected_previous_hash = GENESIS_HASH.to_string();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let expected_sequence = index as u64 + 1;
+        if entry.sequence != expected_sequence {
+            breaks.push(ChainBreak::OutOfSequence { expected: expected_sequence, sequence: entry.sequence });
+        }
+        if entry.previous_hash != expected_previous_hash {
+            breaks.push(ChainBreak::Unlinked {
+                sequence: entry.sequence,
+                expected_previous_hash: expected_previous_hash.clone(),
+                previous_hash: entry.previous_hash.clone(),
+            });
+        }
+        if entry.content_hash().ok().as_ref() != Some(&entry.hash) {
+            breaks.push(ChainBreak::Altered { sequence: entry.sequence });
+        }
+        expected_previous_hash = entry.hash.clone();
+    }
+
+    breaks
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    use super::*;
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 6, 3).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn log_of_three_entries() -> Vec<AuditEntry> {
+        let mut store = InMemoryAuditLogStore::new();
+        let mut audit_log = AuditLog::new(&mut store);
+        let adviser = Actor::Staff("mark.ashworth".to_string());
+
+        audit_log.record(&adviser, at(9), &json!({ "QueueInvite": { "reference": "inv-1" } }), &[json!({ "InviteQueued": { "reference": "inv-1" } })]).unwrap();
+        audit_log.record(&Actor::System("reminders".to_string()), at(10), &json!({ "RunDueReminders": {} }), &Vec::<serde_json::Value>::new()).unwrap();
+        audit_log.record(&adviser, at(11), &json!({ "HandleClientResponse": { "Decline": {} } }), &[json!({ "ReviewMeetingDeclined": {} })]).unwrap();
+
+        store.entries().unwrap()
+    }
+
+    #[test]
+    fn test_entries_are_chained() {
+        let entries = log_of_three_entries();
+
+        assert_eq!(entries.iter().map(|entry| entry.sequence()).collect::<Vec<u64>>(), vec![1, 2, 3]);
+        assert_eq!(entries[0].previous_hash(), GENESIS_HASH);
+        assert_eq!(entries[1].previous_hash(), entries[0].hash());
+        assert_eq!(entries[2].previous_hash(), entries[1].hash());
+        assert_eq!(entries[0].hash().len(), 64);
+        assert!(verify_chain(&entries).is_empty());
+    }
+
+    #[test]
+    fn test_chain_survives_storage_as_json() {
+        let entries = log_of_three_entries();
+        let stored = serde_json::to_string(&entries).unwrap();
+        let read_back: Vec<AuditEntry> = serde_json::from_str(&stored).unwrap();
+
+        let mut store = InMemoryAuditLogStore::from_entries(read_back);
+        let audit_log = AuditLog::new(&mut store);
+        assert!(audit_log.verify().unwrap().is_empty());
+        assert_eq!(audit_log.head_hash().unwrap(), *entries[2].hash());
+    }
+
+    #[test]
+    fn test_verifier_finds_altered_entry() {
+        let mut stored: serde_json::Value = serde_json::to_value(log_of_three_entries()).unwrap();
+        stored[2]["events"][0] = json!({ "ReviewMeetingBooked": {} });
+        let entries: Vec<AuditEntry> = serde_json::from_value(stored).unwrap();
+
+        assert_eq!(verify_chain(&entries), vec![ChainBreak::Altered { sequence: 3 }]);
+    }
+
+    #[test]
+    fn test_verifier_finds_removed_and_rehashed_entries() {
+        let mut entries = log_of_three_entries();
+        let removed = entries.remove(1);
+
+        let breaks = verify_chain(&entries);
+        assert_eq!(breaks, vec![
+            ChainBreak::OutOfSequence { expected: 2, sequence: 3 },
+            ChainBreak::Unlinked { sequence: 3, expected_previous_hash: entries[0].hash().clone(), previous_hash: removed.hash().clone() },
+        ]);
+
+        // Rewriting an entry and its hash still breaks the link to the next entry.
+        let mut entries = log_of_three_entries();
+        entries[0].actor = Actor::Staff("someone.else".to_string());
+        entries[0].hash = entries[0].content_hash().unwrap();
+        assert!(matches!(verify_chain(&entries).as_slice(), [ChainBreak::Unlinked { sequence: 2, .. }]));
+    }
+}
@@ -0,0 +1,262 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use chrono::NaiveDateTime;
+use serde::{Serialize, Deserialize};
+
+use crate::{icalendar, simple_types::ConstrainedIndividualNameString100};
+
+#[derive(Debug)]
+pub enum CalendarError {
+    UnknownAdviser(String),
+    Unavailable(String),
+    InvalidCalendar(String),
+    InvalidSearch(String),
+}
+
+impl std::fmt::Display for CalendarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            CalendarError::UnknownAdviser(ref desc) => write!(f, "Unknown adviser: {}", desc),
+            CalendarError::Unavailable(ref desc) => write!(f, "Calendar unavailable: {}", desc),
+            CalendarError::InvalidCalendar(ref desc) => write!(f, "Invalid calendar: {}", desc),
+            CalendarError::InvalidSearch(ref desc) => write!(f, "Invalid slot search: {}", desc),
+        }
+    }
+}
+
+impl std::error::Error for CalendarError {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum MeetingLocation {
+    InPerson(String),
+    VideoCall(String),
+    Telephone,
+}
+
+impl MeetingLocation {
+    /// Whether the adviser has to travel to or from the meeting.
+    pub fn requires_travel(&self) -> bool {
+        matches!(self, MeetingLocation::InPerson(_))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CalendarEntry {
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    summary: String,
+    location: Option<MeetingLocation>,
+}
+
+impl CalendarEntry {
+    pub fn new(
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        summary: String,
+        location: Option<MeetingLocation>,
+    ) -> Result<Self, CalendarError> {
+        if end <= start {
+            Err(CalendarError::InvalidCalendar(format!("Entry '{}' must end after it starts.", summary)))
+        } else {
+            Ok(Self { start, end, summary, location })
+        }
+    }
+
+    pub fn start(&self) -> NaiveDateTime {
+        self.start
+    }
+
+    pub fn end(&self) -> NaiveDateTime {
+        self.end
+    }
+
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    pub fn location(&self) -> Option<&MeetingLocation> {
+        self.location.as_ref()
+    }
+
+    pub fn requires_travel(&self) -> bool {
+        self.location.as_ref().is_some_and(MeetingLocation::requires_travel)
+    }
+
+    fn overlaps(&self, from: NaiveDateTime, to: NaiveDateTime) -> bool {
+        self.start < to && self.end > from
+    }
+}
+
+/// Port to an adviser's diary. Times are UK local time.
+pub trait AdviserCalendar {
+    /// Entries for the adviser that overlap the period from `from` up to `to`.
+    fn entries_between(
+        &self,
+        adviser_name: &ConstrainedIndividualNameString100,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<CalendarEntry>, CalendarError>;
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryAdviserCalendar {
+    entries: HashMap<String, Vec<CalendarEntry>>,
+}
+
+impl InMemoryAdviserCalendar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_entry(&mut self, adviser_name: &ConstrainedIndividualNameString100, entry: CalendarEntry) {
+        self.entries.entry(adviser_name.value().to_string()).or_default().push(entry);
+    }
+}
+
+impl AdviserCalendar for InMemoryAdviserCalendar {
+    fn entries_between(
+        &self,
+        adviser_name: &ConstrainedIndividualNameString100,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<CalendarEntry>, CalendarError> {
+        let entries = self.entries.get(adviser_name.value()).map(Vec::as_slice).unwrap_or_default();
+
+        Ok(entries.iter().filter(|entry| entry.overlaps(from, to)).cloned().collect())
+    }
+}
+
+/// Adviser calendar backed by iCalendar files exported from each adviser's diary.
+#[derive(Debug, Default)]
+pub struct IcsFileAdviserCalendar {
+    calendar_files: HashMap<String, PathBuf>,
+}
+
+impl IcsFileAdviserCalendar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_calendar_file(mut self, adviser_name: &ConstrainedIndividualNameString100, path: PathBuf) -> Self {
+        self.calendar_files.insert(adviser_name.value().to_string(), path);
+        self
+    }
+
+    /// Reads the VEVENTs in iCalendar content as calendar entries. Cancelled
+    /// and transparent (free) events do not block time and are skipped.
+    pub fn parse_entries(content: &str) -> Result<Vec<CalendarEntry>, CalendarError> {
+        let calendars = icalendar::parse(content).map_err(CalendarError::InvalidCalendar)?;
+        let mut entries = Vec::new();
+
+        for event in calendars.iter().flat_map(|calendar| calendar.components_named("VEVENT")) {
+            let is_cancelled = event.property("STATUS").is_some_and(|status| status.value().eq_ignore_ascii_case("CANCELLED"));
+            let is_free = event.property("TRANSP").is_some_and(|transp| transp.value().eq_ignore_ascii_case("TRANSPARENT"));
+            if is_cancelled || is_free {
+                continue;
+            }
+
+            let start = event
+                .property("DTSTART")
+                .ok_or_else(|| CalendarError::InvalidCalendar("Event is missing DTSTART.".to_string()))?
+                .local_date_time()
+                .map_err(CalendarError::InvalidCalendar)?;
+            let end = match event.property("DTEND") {
+                Some(end) => end.local_date_time().map_err(CalendarError::InvalidCalendar)?,
+                None => start + chrono::Duration::days(1),
+            };
+            let summary = event.property("SUMMARY").map(|summary| summary.text()).unwrap_or_default();
+            let location = event.property("LOCATION").map(|location| location.text()).filter(|location| !location.is_empty()).map(|location| {
+                if location.starts_with("https://") || location.starts_with("http://") {
+                    MeetingLocation::VideoCall(location)
+                } else {
+                    MeetingLocation::InPerson(location)
+                }
+            });
+
+            entries.push(CalendarEntry::new(start, end, summary, location)?);
+        }
+
+        Ok(entries)
+    }
+}
+
+impl AdviserCalendar for IcsFileAdviserCalendar {
+    fn entries_between(
+        &self,
+        adviser_name: &ConstrainedIndividualNameString100,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<CalendarEntry>, CalendarError> {
+        let path = self
+            .calendar_files
+            .get(adviser_name.value())
+            .ok_or_else(|| CalendarError::UnknownAdviser(adviser_name.value().to_string()))?;
+        let content = fs::read_to_string(path)
+            .map_err(|e| CalendarError::Unavailable(format!("{}: {}", path.display(), e)))?;
+
+        Ok(Self::parse_entries(&content)?.into_iter().filter(|entry| entry.overlaps(from, to)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn date_time(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn adviser() -> ConstrainedIndividualNameString100 {
+        ConstrainedIndividualNameString100::try_from("Mark Ashworth".to_string()).unwrap()
+    }
+
+    const ADVISER_ICS: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:1\r\nDTSTART;TZID=Europe/London:20240612T100000\r\nDTEND;TZID=Europe/London:20240612T110000\r\nSUMMARY:Review\\, Smith\r\nLOCATION:12 High Street\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nUID:2\r\nDTSTART:20240612T130000Z\r\nDTEND:20240612T140000Z\r\nSUMMARY:Team call\r\nLOCATION:https://meet.example.com/team\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nUID:3\r\nDTSTART;TZID=Europe/London:20240612T150000\r\nDTEND;TZID=Europe/London:20240612T160000\r\nSUMMARY:Cancelled review\r\nSTATUS:CANCELLED\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+    #[test]
+    fn test_in_memory_calendar_returns_overlapping_entries() {
+        let mut calendar = InMemoryAdviserCalendar::new();
+        calendar.add_entry(&adviser(), CalendarEntry::new(date_time(12, 10, 0), date_time(12, 11, 0), "Review".to_string(), None).unwrap());
+        calendar.add_entry(&adviser(), CalendarEntry::new(date_time(13, 10, 0), date_time(13, 11, 0), "Review".to_string(), None).unwrap());
+
+        let entries = calendar.entries_between(&adviser(), date_time(12, 0, 0), date_time(13, 0, 0)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].start(), date_time(12, 10, 0));
+    }
+
+    #[test]
+    fn test_calendar_entry_must_end_after_start() {
+        assert!(CalendarEntry::new(date_time(12, 10, 0), date_time(12, 10, 0), "Review".to_string(), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_ics_entries() {
+        let entries = IcsFileAdviserCalendar::parse_entries(ADVISER_ICS).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].summary(), "Review, Smith");
+        assert!(entries[0].requires_travel());
+        assert_eq!(entries[1].start(), date_time(12, 14, 0));
+        assert_eq!(entries[1].location(), Some(&MeetingLocation::VideoCall("https://meet.example.com/team".to_string())));
+    }
+
+    #[test]
+    fn test_ics_file_calendar_reads_adviser_file() {
+        let path = std::env::temp_dir().join(format!("gcwm_domain_{}_test_ics_file_calendar_reads_adviser_file.ics", std::process::id()));
+        fs::write(&path, ADVISER_ICS).unwrap();
+        let calendar = IcsFileAdviserCalendar::new().with_calendar_file(&adviser(), path.clone());
+
+        let entries = calendar.entries_between(&adviser(), date_time(12, 12, 0), date_time(12, 18, 0)).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].summary(), "Team call");
+    }
+
+    #[test]
+    fn test_ics_file_calendar_rejects_unknown_adviser() {
+        let calendar = IcsFileAdviserCalendar::new();
+        let result = calendar.entries_between(&adviser(), date_time(12, 0, 0), date_time(13, 0, 0));
+        assert!(matches!(result, Err(CalendarError::UnknownAdviser(_))));
+    }
+}
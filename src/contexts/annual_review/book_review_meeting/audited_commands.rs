@@ -0,0 +1,523 @@
+//! The annual review's state changes, each written to the audit log with the
+//! command that made it and the events that followed.
+//!
+//! Invites, client responses, reminders and review outcomes are changed
+//! through [`AuditedAnnualReview`] so compliance can show from the log that
+//! nothing was altered afterwards. A rejected command changes nothing and is
+//! not logged. Each command changes a copy of the state, which replaces it
+//! only once the entry is written, so if the entry cannot be written the error
+//! is returned and nothing has changed. Reminder timer tables save each change
+//! as it is made, so the copy is written back to the table.
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    audit_log::{Actor, AuditLog, AuditLogError, AuditLogStore},
+    simple_types::ReviewId,
+};
+use super::{
+    delivery_tracking::{DeliveryState, DeliveryTrackingError, DeliveryTrackingEvent, InviteDeliveryTracker},
+    invites::AnnualReviewInvite,
+    mail_transport::InviteDeliveryEvent,
+    print_export::PrintStatusEvent,
+    reminders::{InMemoryReminderTimerTable, ReminderError, ReminderEvent, ReminderScheduler, ReminderTimerTable, ReviewOutcome, ReviewResponse, TaxYearReviewRecord},
+    responses::{ClientResponseCommand, MeetingOutcomeEvent, PendingInvite, ResponseError},
+    types::{TaxYear, ValidatedAnnualReviewInformation, ValidatedClientContactInformation},
+};
+
+#[derive(Debug)]
+pub enum AuditedCommandError {
+    DeliveryTracking(DeliveryTrackingError),
+    Response(ResponseError),
+    Reminder(ReminderError),
+    AuditLog(AuditLogError),
+}
+
+impl std::fmt::Display for AuditedCommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            AuditedCommandError::DeliveryTracking(ref error) => write!(f, "Delivery tracking command rejected: {}", error),
+            AuditedCommandError::Response(ref error) => write!(f, "Client response rejected: {}", error),
+            AuditedCommandError::Reminder(ref error) => write!(f, "Reminder command rejected: {}", error),
+            AuditedCommandError::AuditLog(ref error) => write!(f, "Command not audited: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for AuditedCommandError {}
+
+/// The commands written to the audit log. Invites and contact details are
+/// identified by reference rather than copied in, and free text from the
+/// client is logged as its SHA-256 digest.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AnnualReviewCommand {
+    QueueInvite { reference: String, review_id: ReviewId },
+    RecordDeliveryState { reference: String, state: DeliveryState, reason: String },
+    RecordEmailDelivery { message_ids: Vec<String> },
+    RecordPrintStatus { references: Vec<String> },
+    HandleClientResponse(AuditedClientResponse),
+    ScheduleReminders { review_id: ReviewId },
+    RunDueReminders,
+    AcknowledgeReminders,
+    StopReminders { review_id: ReviewId, response: ReviewResponse },
+    CancelReminders { review_id: ReviewId },
+    RecordReviewOutcome { review_id: ReviewId, tax_year: TaxYear, outcome: ReviewOutcome },
+}
+
+/// A client response as written to the audit log. The digest of the text the
+/// client wrote shows what was received without the log holding it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AuditedClientResponse {
+    AcceptSlot { review_id: ReviewId, slot_start: NaiveDateTime },
+    ProposeTime { review_id: ReviewId, proposed_start: NaiveDateTime, note_digest: Option<String> },
+    Decline { review_id: ReviewId, reason_digest: String },
+    DeclineForTaxYear { review_id: ReviewId, reason_digest: Option<String> },
+    RequestCallBack { review_id: ReviewId, preferred_time_digest: Option<String> },
+}
+
+impl From<&ClientResponseCommand> for AuditedClientResponse {
+    fn from(response: &ClientResponseCommand) -> Self {
+        match response {
+            ClientResponseCommand::AcceptSlot { review_id, slot_start } => Self::AcceptSlot { review_id: review_id.clone(), slot_start: *slot_start },
+            ClientResponseCommand::ProposeTime { review_id, proposed_start, note } => {
+                Self::ProposeTime { review_id: review_id.clone(), proposed_start: *proposed_start, note_digest: note.as_deref().map(digest) }
+            }
+            ClientResponseCommand::Decline { review_id, reason } => Self::Decline { review_id: review_id.clone(), reason_digest: digest(reason) },
+            ClientResponseCommand::DeclineForTaxYear { review_id, reason } => {
+                Self::DeclineForTaxYear { review_id: review_id.clone(), reason_digest: reason.as_deref().map(digest) }
+            }
+            ClientResponseCommand::RequestCallBack { review_id, preferred_time } => {
+                Self::RequestCallBack { review_id: review_id.clone(), preferred_time_digest: preferred_time.as_deref().map(digest) }
+            }
+        }
+    }
+}
+
+/// A meeting outcome event as written to the audit log, with the client's
+/// text replaced by its digest as in [`AuditedClientResponse`].
+#[derive(Serialize)]
+enum AuditedMeetingOutcomeEvent<'a> {
+    AlternativeTimeProposed { review_id: &'a ReviewId, proposed_start: NaiveDateTime, note_digest: Option<String>, received_at: NaiveDateTime },
+    ReviewMeetingDeclined { review_id: &'a ReviewId, reason_digest: String, declined_at: NaiveDateTime },
+    ReviewMeetingDeclinedInTaxYear { review_id: &'a ReviewId, tax_year: TaxYear, reason_digest: Option<String>, declined_at: NaiveDateTime },
+    CallBackRequested { review_id: &'a ReviewId, preferred_time_digest: Option<String>, received_at: NaiveDateTime },
+    #[serde(untagged)]
+    Other(&'a MeetingOutcomeEvent),
+}
+
+impl<'a> From<&'a MeetingOutcomeEvent> for AuditedMeetingOutcomeEvent<'a> {
+    fn from(event: &'a MeetingOutcomeEvent) -> Self {
+        match event {
+            MeetingOutcomeEvent::AlternativeTimeProposed { review_id, proposed_start, note, received_at } => {
+                Self::AlternativeTimeProposed { review_id, proposed_start: *proposed_start, note_digest: note.as_deref().map(digest), received_at: *received_at }
+            }
+            MeetingOutcomeEvent::ReviewMeetingDeclined { review_id, reason, declined_at } => {
+                Self::ReviewMeetingDeclined { review_id, reason_digest: digest(reason), declined_at: *declined_at }
+            }
+            MeetingOutcomeEvent::ReviewMeetingDeclinedInTaxYear { review_id, tax_year, reason, declined_at } => {
+                Self::ReviewMeetingDeclinedInTaxYear { review_id, tax_year: *tax_year, reason_digest: reason.as_deref().map(digest), declined_at: *declined_at }
+            }
+            MeetingOutcomeEvent::CallBackRequested { review_id, preferred_time, received_at } => {
+                Self::CallBackRequested { review_id, preferred_time_digest: preferred_time.as_deref().map(digest), received_at: *received_at }
+            }
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// A reminder event as written to the audit log. A due reminder names its
+/// invite by reference rather than copying in the recipient's contact details.
+#[derive(Serialize)]
+enum AuditedReminderEvent<'a> {
+    ReminderDue { review_id: &'a ReviewId, invite_reference: &'a String, number: u8, due_on: NaiveDate },
+    #[serde(untagged)]
+    Other(&'a ReminderEvent),
+}
+
+impl<'a> From<&'a ReminderEvent> for AuditedReminderEvent<'a> {
+    fn from(event: &'a ReminderEvent) -> Self {
+        match event {
+            ReminderEvent::ReminderDue { review_id, invite_reference, number, due_on, .. } => {
+                Self::ReminderDue { review_id, invite_reference, number: *number, due_on: *due_on }
+            }
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// Makes annual review state changes on behalf of one actor, auditing each one.
+pub struct AuditedAnnualReview<'a> {
+    audit_log: AuditLog<'a>,
+    actor: Actor,
+}
+
+impl<'a> AuditedAnnualReview<'a> {
+    pub fn new(store: &'a mut dyn AuditLogStore, actor: Actor) -> Self {
+        Self { audit_log: AuditLog::new(store), actor }
+    }
+
+    pub fn audit_log(&self) -> &AuditLog<'a> {
+        &self.audit_log
+    }
+
+    pub fn queue_invite(
+        &mut self,
+        tracker: &mut InviteDeliveryTracker,
+        reference: &str,
+        review_id: &ReviewId,
+        invite: AnnualReviewInvite,
+        recorded_at: NaiveDateTime,
+    ) -> Result<Vec<DeliveryTrackingEvent>, AuditedCommandError> {
+        let command = AnnualReviewCommand::QueueInvite { reference: reference.to_string(), review_id: review_id.clone() };
+        self.change(tracker, recorded_at, &command, |tracker| {
+            tracker.queue(reference, review_id, invite, recorded_at).map_err(AuditedCommandError::DeliveryTracking)
+        })
+    }
+
+    pub fn record_delivery_state(
+        &mut self,
+        tracker: &mut InviteDeliveryTracker,
+        reference: &str,
+        state: DeliveryState,
+        reason: &str,
+        contact: &ValidatedClientContactInformation,
+        recorded_at: NaiveDateTime,
+    ) -> Result<Vec<DeliveryTrackingEvent>, AuditedCommandError> {
+        let command = AnnualReviewCommand::RecordDeliveryState { reference: reference.to_string(), state, reason: reason.to_string() };
+        self.change(tracker, recorded_at, &command, |tracker| {
+            tracker.record_state(reference, state, reason, contact, recorded_at).map_err(AuditedCommandError::DeliveryTracking)
+        })
+    }
+
+    pub fn record_email_delivery(
+        &mut self,
+        tracker: &mut InviteDeliveryTracker,
+        delivery_events: &[InviteDeliveryEvent],
+        contact: &ValidatedClientContactInformation,
+        recorded_at: NaiveDateTime,
+    ) -> Result<Vec<DeliveryTrackingEvent>, AuditedCommandError> {
+        let mut message_ids: Vec<String> = delivery_events.iter().map(|event| event.message_id().clone()).collect();
+        message_ids.dedup();
+        let command = AnnualReviewCommand::RecordEmailDelivery { message_ids };
+        self.change(tracker, recorded_at, &command, |tracker| {
+            tracker.record_email_delivery(delivery_events, contact).map_err(AuditedCommandError::DeliveryTracking)
+        })
+    }
+
+    pub fn record_print_status(
+        &mut self,
+        tracker: &mut InviteDeliveryTracker,
+        status_events: &[PrintStatusEvent],
+        contact: &ValidatedClientContactInformation,
+        recorded_at: NaiveDateTime,
+    ) -> Result<Vec<DeliveryTrackingEvent>, AuditedCommandError> {
+        let references = status_events.iter().map(|event| event.reference().clone()).collect();
+        let command = AnnualReviewCommand::RecordPrintStatus { references };
+        self.change(tracker, recorded_at, &command, |tracker| {
+            tracker.record_print_status(status_events, contact).map_err(AuditedCommandError::DeliveryTracking)
+        })
+    }
+
+    /// Books, declines or otherwise answers the invite. Free text the client
+    /// wrote is logged as its digest.
+    pub fn handle_client_response(
+        &mut self,
+        pending_invite: &mut PendingInvite,
+        response: ClientResponseCommand,
+        received_at: NaiveDateTime,
+    ) -> Result<MeetingOutcomeEvent, AuditedCommandError> {
+        let command = AnnualReviewCommand::HandleClientResponse(AuditedClientResponse::from(&response));
+        let mut staged = pending_invite.clone();
+        let event = staged.handle(response, received_at).map_err(AuditedCommandError::Response)?;
+
+        self.audit_log.record(&self.actor, received_at, &command, &[AuditedMeetingOutcomeEvent::from(&event)]).map_err(AuditedCommandError::AuditLog)?;
+        *pending_invite = staged;
+        Ok(event)
+    }
+
+    pub fn schedule_reminders(
+        &mut self,
+        scheduler: &ReminderScheduler,
+        timers: &mut dyn ReminderTimerTable,
+        review_id: &ReviewId,
+        invites: &[AnnualReviewInvite],
+        review_information: &ValidatedAnnualReviewInformation,
+        recorded_at: NaiveDateTime,
+    ) -> Result<Vec<ReminderEvent>, AuditedCommandError> {
+        let command = AnnualReviewCommand::ScheduleReminders { review_id: review_id.clone() };
+        self.change_reminders(timers, recorded_at, &command, |timers| {
+            scheduler.schedule(timers, review_id, invites, review_information, recorded_at.date())
+        })
+    }
+
+    pub fn run_due_reminders(
+        &mut self,
+        scheduler: &ReminderScheduler,
+        timers: &mut dyn ReminderTimerTable,
+        recorded_at: NaiveDateTime,
+    ) -> Result<Vec<ReminderEvent>, AuditedCommandError> {
+        let events = scheduler.run_due(timers, recorded_at.date()).map_err(AuditedCommandError::Reminder)?;
+        self.record_reminder_events(recorded_at, &AnnualReviewCommand::RunDueReminders, &events)?;
+        Ok(events)
+    }
+
+    /// Removes the timers behind reminder events once they have been acted on.
+    pub fn acknowledge_reminders(
+        &mut self,
+        scheduler: &ReminderScheduler,
+        timers: &mut dyn ReminderTimerTable,
+        fired: &[ReminderEvent],
+        recorded_at: NaiveDateTime,
+    ) -> Result<(), AuditedCommandError> {
+        self.change_reminders(timers, recorded_at, &AnnualReviewCommand::AcknowledgeReminders, |timers| {
+            scheduler.acknowledge(timers, fired).map(|()| fired.to_vec())
+        })?;
+        Ok(())
+    }
+
+    pub fn stop_reminders(
+        &mut self,
+        scheduler: &ReminderScheduler,
+        timers: &mut dyn ReminderTimerTable,
+        review_id: &ReviewId,
+        response: ReviewResponse,
+        recorded_at: NaiveDateTime,
+    ) -> Result<Vec<ReminderEvent>, AuditedCommandError> {
+        let command = AnnualReviewCommand::StopReminders { review_id: review_id.clone(), response };
+        self.change_reminders(timers, recorded_at, &command, |timers| scheduler.stop(timers, review_id, response, recorded_at.date()))
+    }
+
+    /// Cancels the reminders to the clients whose deaths are recorded in
+    /// `review_information`, rerouting those to a surviving joint client.
+    pub fn cancel_reminders(
+        &mut self,
+        scheduler: &ReminderScheduler,
+        timers: &mut dyn ReminderTimerTable,
+        review_id: &ReviewId,
+        review_information: &ValidatedAnnualReviewInformation,
+        recorded_at: NaiveDateTime,
+    ) -> Result<Vec<ReminderEvent>, AuditedCommandError> {
+        let command = AnnualReviewCommand::CancelReminders { review_id: review_id.clone() };
+        self.change_reminders(timers, recorded_at, &command, |timers| scheduler.cancel(timers, review_id, review_information, recorded_at.date()))
+    }
+
+    /// Records the outcome against the tax year. The outcome is logged as the resulting event.
+    pub fn record_review_outcome(
+        &mut self,
+        record: &mut TaxYearReviewRecord,
+        review_id: &ReviewId,
+        outcome: ReviewOutcome,
+        recorded_at: NaiveDateTime,
+    ) -> Result<(), AuditedCommandError> {
+        let command = AnnualReviewCommand::RecordReviewOutcome { review_id: review_id.clone(), tax_year: record.tax_year(), outcome };
+        self.change(record, recorded_at, &command, |record| {
+            record.record(review_id, outcome);
+            Ok(vec![outcome])
+        })?;
+        Ok(())
+    }
+
+    /// Makes the change on a copy of `state`, which replaces it once the entry is written.
+    fn change<S: Clone, E: Serialize>(
+        &mut self,
+        state: &mut S,
+        recorded_at: NaiveDateTime,
+        command: &AnnualReviewCommand,
+        change: impl FnOnce(&mut S) -> Result<Vec<E>, AuditedCommandError>,
+    ) -> Result<Vec<E>, AuditedCommandError> {
+        let mut staged = state.clone();
+        let events = change(&mut staged)?;
+
+        self.audit_log.record(&self.actor, recorded_at, command, &events).map_err(AuditedCommandError::AuditLog)?;
+        *state = staged;
+        Ok(events)
+    }
+
+    /// Makes the change on a copy of the timers, which replaces them once the entry is written.
+    fn change_reminders(
+        &mut self,
+        timers: &mut dyn ReminderTimerTable,
+        recorded_at: NaiveDateTime,
+        command: &AnnualReviewCommand,
+        change: impl FnOnce(&mut dyn ReminderTimerTable) -> Result<Vec<ReminderEvent>, ReminderError>,
+    ) -> Result<Vec<ReminderEvent>, AuditedCommandError> {
+        let mut staged = InMemoryReminderTimerTable::copy_of(timers).map_err(AuditedCommandError::Reminder)?;
+        let events = change(&mut staged).map_err(AuditedCommandError::Reminder)?;
+
+        self.record_reminder_events(recorded_at, command, &events)?;
+        staged.write_to(timers).map_err(AuditedCommandError::Reminder)?;
+        Ok(events)
+    }
+
+    fn record_reminder_events(&mut self, recorded_at: NaiveDateTime, command: &AnnualReviewCommand, events: &[ReminderEvent]) -> Result<(), AuditedCommandError> {
+        let audited_events: Vec<AuditedReminderEvent> = events.iter().map(AuditedReminderEvent::from).collect();
+        self.audit_log.record(&self.actor, recorded_at, command, &audited_events).map_err(AuditedCommandError::AuditLog)?;
+        Ok(())
+    }
+}
+
+fn digest(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::{
+        audit_log::{verify_chain, AuditEntry, InMemoryAuditLogStore},
+        holiday_calendar::{HolidayCalendar, Jurisdiction},
+        tests::test_utils::shared::{reply_deadline, ReviewInformationFixture, REVIEW_ID, SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT},
+    };
+    use super::super::{
+        adviser_calendar::MeetingLocation,
+        reminders::{InMemoryReminderTimerTable, ReminderPolicy},
+        slot_finder::MeetingSlot,
+    };
+    use super::*;
+
+    fn date_time(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn review_id() -> ReviewId {
+        ReviewId::try_from(REVIEW_ID.to_string()).unwrap()
+    }
+
+    fn pending_invite(review_information: &ValidatedAnnualReviewInformation) -> PendingInvite {
+        let slot = MeetingSlot::new(date_time(14, 10), date_time(14, 11), MeetingLocation::VideoCall("https://meet.example.com/review".to_string())).unwrap();
+        PendingInvite::new(review_id(), review_information.annual_review_due_date(), reply_deadline(), vec![slot])
+    }
+
+    struct UnavailableAuditLogStore;
+
+    impl AuditLogStore for UnavailableAuditLogStore {
+        fn append(&mut self, _entry: AuditEntry) -> Result<(), AuditLogError> {
+            Err(AuditLogError::Unavailable("Disk full".to_string()))
+        }
+
+        fn last_entry(&self) -> Result<Option<AuditEntry>, AuditLogError> {
+            Ok(None)
+        }
+
+        fn entries(&self) -> Result<Vec<AuditEntry>, AuditLogError> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn scheduled_timers(fixture: &ReviewInformationFixture, scheduler: &ReminderScheduler) -> InMemoryReminderTimerTable {
+        let mut timers = InMemoryReminderTimerTable::new();
+        scheduler.schedule(&mut timers, &review_id(), &fixture.invites(), &fixture.validated(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()).unwrap();
+        timers
+    }
+
+    fn command_names(store: &InMemoryAuditLogStore) -> Vec<String> {
+        store.entries().unwrap().iter().map(|entry| match entry.command() {
+            serde_json::Value::String(name) => name.clone(),
+            serde_json::Value::Object(command) => command.keys().next().unwrap().clone(),
+            other => panic!("Unexpected command {}", other),
+        }).collect()
+    }
+
+    #[test]
+    fn test_invite_to_decline_is_audited_in_a_verified_chain() {
+        let fixture = ReviewInformationFixture::new(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT);
+        let (review_information, invites) = (fixture.validated(), fixture.invites());
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        let scheduler = ReminderScheduler::new(ReminderPolicy::default(), &calendar);
+        let (mut tracker, mut timers, mut pending) = (InviteDeliveryTracker::new(), InMemoryReminderTimerTable::new(), pending_invite(&review_information));
+        let mut record = TaxYearReviewRecord::new(review_information.annual_review_due_date().tax_year());
+        let mut store = InMemoryAuditLogStore::new();
+
+        let mut adviser = AuditedAnnualReview::new(&mut store, Actor::Staff("mark.ashworth".to_string()));
+        adviser.queue_invite(&mut tracker, "inv-1", &review_id(), invites[0].clone(), date_time(1, 9)).unwrap();
+        adviser.record_delivery_state(&mut tracker, "inv-1", DeliveryState::Sent, "Accepted by mail server", review_information.client_contact_information(), date_time(1, 9)).unwrap();
+        adviser.schedule_reminders(&scheduler, &mut timers, &review_id(), &invites, &review_information, date_time(1, 9)).unwrap();
+
+        let decline = ClientResponseCommand::Decline { review_id: review_id(), reason: "Moving to another adviser".to_string() };
+        let event = adviser.handle_client_response(&mut pending, decline.clone(), date_time(2, 9)).unwrap();
+        assert!(matches!(event, MeetingOutcomeEvent::ReviewMeetingDeclined { .. }));
+        adviser.stop_reminders(&scheduler, &mut timers, &review_id(), ReviewResponse::Declined, date_time(2, 9)).unwrap();
+        adviser.record_review_outcome(&mut record, &review_id(), ReviewOutcome::Declined, date_time(2, 9)).unwrap();
+
+        assert!(matches!(adviser.handle_client_response(&mut pending, decline, date_time(3, 9)), Err(AuditedCommandError::Response(ResponseError::AlreadyDeclined(_)))));
+        assert!(adviser.audit_log().verify().unwrap().is_empty());
+
+        assert_eq!(command_names(&store), vec![
+            "QueueInvite", "RecordDeliveryState", "ScheduleReminders", "HandleClientResponse", "StopReminders", "RecordReviewOutcome",
+        ]);
+        let entries = store.entries().unwrap();
+        assert_eq!(entries[3].actor(), &Actor::Staff("mark.ashworth".to_string()));
+        assert_eq!(entries[3].command()["HandleClientResponse"]["Decline"]["reason_digest"], digest("Moving to another adviser"));
+        assert_eq!(entries[3].events()[0]["ReviewMeetingDeclined"]["reason_digest"], digest("Moving to another adviser"));
+        assert!(!serde_json::to_string(&entries[3]).unwrap().contains("Moving to another adviser"));
+        assert_eq!(entries[4].events()[0]["RemindersStopped"]["cancelled_timers"], 4);
+        assert!(verify_chain(&entries).is_empty());
+    }
+
+    #[test]
+    fn test_due_reminders_are_audited_by_reference() {
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        let scheduler = ReminderScheduler::new(ReminderPolicy::default(), &calendar);
+        let mut timers = scheduled_timers(&ReviewInformationFixture::new(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT), &scheduler);
+        let mut store = InMemoryAuditLogStore::new();
+
+        let mut reminders = AuditedAnnualReview::new(&mut store, Actor::System("reminders".to_string()));
+        let events = reminders.run_due_reminders(&scheduler, &mut timers, date_time(9, 7)).unwrap();
+        reminders.acknowledge_reminders(&scheduler, &mut timers, &events, date_time(9, 8)).unwrap();
+
+        assert!(matches!(&events[..], [ReminderEvent::ReminderDue { .. }]));
+        assert_eq!(timers.timers_for(&review_id()).unwrap().len(), 3);
+        assert_eq!(command_names(&store), vec!["RunDueReminders", "AcknowledgeReminders"]);
+        let entry = &store.entries().unwrap()[0];
+        assert_eq!(entry.events()[0], serde_json::json!({
+            "ReminderDue": {
+                "review_id": REVIEW_ID,
+                "invite_reference": format!("{}-01", REVIEW_ID),
+                "number": 1,
+                "due_on": "2024-05-09",
+            }
+        }));
+        assert!(!serde_json::to_string(entry).unwrap().contains("sian@example.com"));
+    }
+
+    #[test]
+    fn test_reminders_are_unchanged_when_the_entry_cannot_be_written() {
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        let scheduler = ReminderScheduler::new(ReminderPolicy::default(), &calendar);
+        let mut timers = scheduled_timers(&ReviewInformationFixture::new(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT), &scheduler);
+        let mut store = UnavailableAuditLogStore;
+        let mut adviser = AuditedAnnualReview::new(&mut store, Actor::System("reminders".to_string()));
+
+        let fired = scheduler.run_due(&timers, date_time(9, 7).date()).unwrap();
+        assert!(matches!(adviser.run_due_reminders(&scheduler, &mut timers, date_time(9, 7)), Err(AuditedCommandError::AuditLog(_))));
+        assert!(matches!(adviser.acknowledge_reminders(&scheduler, &mut timers, &fired, date_time(9, 8)), Err(AuditedCommandError::AuditLog(_))));
+        assert!(matches!(
+            adviser.stop_reminders(&scheduler, &mut timers, &review_id(), ReviewResponse::Booked, date_time(9, 8)),
+            Err(AuditedCommandError::AuditLog(_)),
+        ));
+        assert_eq!(timers.timers_for(&review_id()).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_state_is_unchanged_when_the_entry_cannot_be_written() {
+        let fixture = ReviewInformationFixture::new(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT);
+        let (review_information, invites) = (fixture.validated(), fixture.invites());
+        let (mut tracker, mut pending) = (InviteDeliveryTracker::new(), pending_invite(&review_information));
+        let mut record = TaxYearReviewRecord::new(review_information.annual_review_due_date().tax_year());
+        let (unchanged_pending, unchanged_record) = (pending.clone(), record.clone());
+        let mut store = UnavailableAuditLogStore;
+        let mut adviser = AuditedAnnualReview::new(&mut store, Actor::Staff("mark.ashworth".to_string()));
+
+        let accept = ClientResponseCommand::AcceptSlot { review_id: review_id(), slot_start: date_time(14, 10) };
+        assert!(matches!(adviser.handle_client_response(&mut pending, accept, date_time(2, 9)), Err(AuditedCommandError::AuditLog(_))));
+        assert!(matches!(adviser.queue_invite(&mut tracker, "inv-1", &review_id(), invites[0].clone(), date_time(1, 9)), Err(AuditedCommandError::AuditLog(_))));
+        assert!(matches!(adviser.record_review_outcome(&mut record, &review_id(), ReviewOutcome::Booked, date_time(2, 9)), Err(AuditedCommandError::AuditLog(_))));
+
+        assert_eq!(pending, unchanged_pending);
+        assert!(tracker.invites().is_empty());
+        assert_eq!(record, unchanged_record);
+    }
+}
@@ -0,0 +1,384 @@
+//! Attorneys and deputies authorised to deal with a client's affairs.
+//!
+//! A client who cannot manage their own finances may have given someone a
+//! Lasting (or, before October 2007, Enduring) Power of Attorney, or the Court
+//! of Protection may have appointed a deputy. The authority is only usable once
+//! registered with the Office of the Public Guardian, or once the court order
+//! is made, so a contact without a registration reference is rejected, as is
+//! one whose authority has ended by the date of the review. A health and
+//! welfare LPA gives no authority over money and is rejected too.
+//!
+//! The scope of the authority decides where invites go. An attorney who acts
+//! for the client is sent the invites in the client's place; one the client
+//! has asked to be kept informed is sent a copy while the client still receives
+//! their own.
+
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    redaction::Sensitive,
+    simple_types::{ConstrainedIndividualNameString100, EmailAddress, PostalAddress},
+};
+use super::types::{invalid_date_format, UnvalidatedPostalAddress, ValidatedClientContactInformation, ValidationDetail, ValidationError};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum AttorneyType {
+    PropertyAndFinancialAffairsLpa,
+    HealthAndWelfareLpa,
+    /// Made before 1 October 2007; covers property and financial affairs only.
+    EnduringPowerOfAttorney,
+    /// Appointed by the Court of Protection for property and affairs.
+    Deputy,
+}
+
+impl AttorneyType {
+    pub fn covers_financial_affairs(&self) -> bool {
+        !matches!(self, Self::HealthAndWelfareLpa)
+    }
+}
+
+impl TryFrom<&str> for AttorneyType {
+    type Error = ValidationError;
+
+    fn try_from(attorney_type: &str) -> Result<Self, Self::Error> {
+        match attorney_type {
+            "PropertyAndFinancialAffairsLpa" => Ok(AttorneyType::PropertyAndFinancialAffairsLpa),
+            "HealthAndWelfareLpa" => Ok(AttorneyType::HealthAndWelfareLpa),
+            "EnduringPowerOfAttorney" => Ok(AttorneyType::EnduringPowerOfAttorney),
+            "Deputy" => Ok(AttorneyType::Deputy),
+            _ => Err(ValidationError::InvalidInput(ValidationDetail::new("validation.unknown_attorney_type", &[attorney_type]))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum AuthorityScope {
+    /// The attorney makes the client's financial decisions, so invites go to
+    /// them instead of the client.
+    ActsForClient,
+    /// The client still makes their own decisions and has asked for the
+    /// attorney to be copied in.
+    CopiedIn,
+}
+
+impl TryFrom<&str> for AuthorityScope {
+    type Error = ValidationError;
+
+    fn try_from(scope: &str) -> Result<Self, Self::Error> {
+        match scope {
+            "ActsForClient" => Ok(AuthorityScope::ActsForClient),
+            "CopiedIn" => Ok(AuthorityScope::CopiedIn),
+            _ => Err(ValidationError::InvalidInput(ValidationDetail::new("validation.unknown_authority_scope", &[scope]))),
+        }
+    }
+}
+
+// Unvalidated authorised contact received with the annual review information.
+// Dates use the format "dd/mm/yyyy". The address fields are flattened as for
+// post contacts and may be left out when the attorney has an email address.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnvalidatedAuthorisedContact {
+    attorney_type: String,
+    first_name: Sensitive<String>,
+    client_first_name: Sensitive<String>,
+    #[serde(default)]
+    registration_reference: Option<Sensitive<String>>,
+    #[serde(default)]
+    registered_on: Option<String>,
+    #[serde(default)]
+    ends_on: Option<String>,
+    scope: String,
+    #[serde(default)]
+    email_address: Option<Sensitive<String>>,
+    #[serde(default, flatten)]
+    postal_address: Option<UnvalidatedPostalAddress>,
+}
+
+/// The Office of the Public Guardian's reference for a registered power of
+/// attorney, or the Court of Protection case number for a deputy.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RegistrationReference(Sensitive<String>);
+
+impl TryFrom<String> for RegistrationReference {
+    type Error = ValidationError;
+
+    /// Letters, digits and dashes; spaces are removed and letters uppercased.
+    fn try_from(reference: String) -> Result<Self, Self::Error> {
+        let reference: String = reference.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase();
+
+        if reference.chars().filter(char::is_ascii_alphanumeric).count() < 6 {
+            return Err(ValidationError::InvalidInput(ValidationDetail::new("validation.registration_reference_too_short", &[])));
+        }
+        if reference.len() > 20 || !reference.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(ValidationError::InvalidInput(ValidationDetail::new("validation.registration_reference_format", &[])));
+        }
+
+        Ok(Self(Sensitive::new(reference)))
+    }
+}
+
+impl RegistrationReference {
+    pub fn value(&self) -> &str {
+        self.0.expose()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuthorisedContact {
+    attorney_type: AttorneyType,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    client_first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    registration_reference: RegistrationReference,
+    registered_on: NaiveDate,
+    ends_on: Option<NaiveDate>,
+    scope: AuthorityScope,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    email_address: Option<EmailAddress>,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    postal_address: Option<PostalAddress>,
+}
+
+impl AuthorisedContact {
+    /// An email address or a postal address is needed to send them invites.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        attorney_type: AttorneyType,
+        first_name: ConstrainedIndividualNameString100,
+        client_first_name: ConstrainedIndividualNameString100,
+        registration_reference: RegistrationReference,
+        registered_on: NaiveDate,
+        ends_on: Option<NaiveDate>,
+        scope: AuthorityScope,
+        email_address: Option<EmailAddress>,
+        postal_address: Option<PostalAddress>,
+    ) -> Result<Self, ValidationError> {
+        if !attorney_type.covers_financial_affairs() {
+            return Err(ValidationError::InvalidInput(ValidationDetail::new(
+                "validation.health_and_welfare_lpa", &[first_name.value(), client_first_name.value()],
+            )));
+        }
+        if ends_on.is_some_and(|ends_on| ends_on < registered_on) {
+            return Err(ValidationError::InvalidDate(ValidationDetail::new("validation.authority_ends_before_registration", &[])));
+        }
+        if email_address.is_none() && postal_address.is_none() {
+            return Err(ValidationError::EmptyInput(ValidationDetail::new("validation.address_needed", &[first_name.value()])));
+        }
+
+        Ok(Self { attorney_type, first_name, client_first_name, registration_reference, registered_on, ends_on, scope, email_address, postal_address })
+    }
+
+    pub fn validate(unvalidated: UnvalidatedAuthorisedContact) -> Result<Self, ValidationError> {
+        let parse_date = |date: &str| NaiveDate::parse_from_str(date, "%d/%m/%Y").map_err(|_| invalid_date_format(date));
+        let name = |name: Sensitive<String>| ConstrainedIndividualNameString100::try_from(name.into_inner())
+            .map_err(|e| ValidationError::InvalidName(e.into()));
+
+        let attorney_type = AttorneyType::try_from(unvalidated.attorney_type.as_str())?;
+        let first_name = name(unvalidated.first_name)?;
+        let client_first_name = name(unvalidated.client_first_name)?;
+
+        let (registration_reference, registered_on) = match (unvalidated.registration_reference, unvalidated.registered_on) {
+            (Some(reference), Some(registered_on)) if !reference.expose().trim().is_empty() => {
+                (RegistrationReference::try_from(reference.into_inner())?, parse_date(&registered_on)?)
+            }
+            _ => return Err(ValidationError::InvalidInput(ValidationDetail::new(
+                "validation.authority_not_registered", &[first_name.value(), client_first_name.value()],
+            ))),
+        };
+        let ends_on = unvalidated.ends_on.as_deref().map(parse_date).transpose()?;
+        let scope = AuthorityScope::try_from(unvalidated.scope.as_str())?;
+        let email_address = unvalidated.email_address
+            .map(|email_address| EmailAddress::try_from(email_address.into_inner()))
+            .transpose()
+            .map_err(|e| ValidationError::InvalidEmail(e.into()))?;
+        let postal_address = unvalidated.postal_address.map(UnvalidatedPostalAddress::validate).transpose()?;
+
+        Self::new(attorney_type, first_name, client_first_name, registration_reference, registered_on, ends_on, scope, email_address, postal_address)
+    }
+
+    pub fn attorney_type(&self) -> AttorneyType {
+        self.attorney_type
+    }
+
+    pub fn first_name(&self) -> &ConstrainedIndividualNameString100 {
+        &self.first_name
+    }
+
+    /// The client the attorney acts for.
+    pub fn client_first_name(&self) -> &ConstrainedIndividualNameString100 {
+        &self.client_first_name
+    }
+
+    pub fn registration_reference(&self) -> &RegistrationReference {
+        &self.registration_reference
+    }
+
+    pub fn registered_on(&self) -> NaiveDate {
+        self.registered_on
+    }
+
+    /// The last day of the authority, for deputyships made for a fixed term.
+    pub fn ends_on(&self) -> Option<NaiveDate> {
+        self.ends_on
+    }
+
+    pub fn scope(&self) -> AuthorityScope {
+        self.scope
+    }
+
+    pub fn email_address(&self) -> Option<&EmailAddress> {
+        self.email_address.as_ref()
+    }
+
+    pub fn postal_address(&self) -> Option<&PostalAddress> {
+        self.postal_address.as_ref()
+    }
+
+    pub fn is_in_force(&self, on: NaiveDate) -> bool {
+        self.registered_on <= on && self.ends_on.is_none_or(|ends_on| on <= ends_on)
+    }
+
+    /// Fails when the authority is not yet registered or has ended by `on`.
+    pub fn check_in_force(&self, on: NaiveDate) -> Result<(), ValidationError> {
+        if self.is_in_force(on) {
+            Ok(())
+        } else {
+            Err(ValidationError::InvalidInput(ValidationDetail::new(
+                "validation.authority_not_in_force", &[self.first_name.value(), self.client_first_name.value(), &on.format("%d/%m/%Y").to_string()],
+            )))
+        }
+    }
+}
+
+/// Validates the authorised contacts for a client. Only individuals have
+/// attorneys, and each must act for one of the individuals in the contact,
+/// named by a first name no other client in the contact shares.
+pub fn validate_authorised_contacts(
+    unvalidated: Vec<UnvalidatedAuthorisedContact>,
+    client_contact_information: &ValidatedClientContactInformation,
+) -> Result<Vec<AuthorisedContact>, ValidationError> {
+    let authorised_contacts = unvalidated.into_iter().map(AuthorisedContact::validate).collect::<Result<Vec<_>, _>>()?;
+
+    if !authorised_contacts.is_empty() && client_contact_information.organisation_name().is_some() {
+        return Err(ValidationError::InvalidInput(ValidationDetail::new("validation.attorney_for_organisation", &[])));
+    }
+
+    let client_names: Vec<&ConstrainedIndividualNameString100> = client_contact_information.electronic_recipients().into_iter().map(|(name, _)| name)
+        .chain(client_contact_information.postal_recipients().into_iter().map(|(name, _)| name))
+        .collect();
+    for contact in &authorised_contacts {
+        match client_names.iter().filter(|name| **name == contact.client_first_name()).count() {
+            0 => return Err(ValidationError::InvalidName(ValidationDetail::new("validation.not_a_client", &[contact.client_first_name().value()]))),
+            1 => {}
+            _ => return Err(ValidationError::InvalidName(ValidationDetail::new("validation.ambiguous_client_name", &[contact.client_first_name().value()]))),
+        }
+    }
+
+    Ok(authorised_contacts)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::test_utils::shared::{validated_contact, JOINT_INDIVIDUALS_POST_CONTACT, PRIMARY_DIRECTOR_ELECTRONIC_CONTACT};
+
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn authorised_contact(json: &str) -> Result<AuthorisedContact, ValidationError> {
+        AuthorisedContact::validate(serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn test_validate_registered_attorney() {
+        let contact = authorised_contact(r#"{
+            "attorney_type": "PropertyAndFinancialAffairsLpa",
+            "first_name": "Carol",
+            "client_first_name": "Alice",
+            "registration_reference": "7000 1234 5678",
+            "registered_on": "12/03/2021",
+            "scope": "ActsForClient",
+            "email_address": "carol@example.com"
+        }"#).unwrap();
+
+        assert_eq!(contact.registration_reference().value(), "700012345678");
+        assert_eq!(contact.registered_on(), NaiveDate::from_ymd_opt(2021, 3, 12).unwrap());
+        assert_eq!(contact.scope(), AuthorityScope::ActsForClient);
+        assert!(contact.postal_address().is_none());
+        assert!(contact.is_in_force(date(5, 1)));
+        assert!(!format!("{:?}", contact).contains("700012345678"));
+    }
+
+    #[test]
+    fn test_unregistered_and_ended_authorities_are_rejected() {
+        assert!(matches!(
+            authorised_contact(r#"{
+                "attorney_type": "PropertyAndFinancialAffairsLpa", "first_name": "Carol", "client_first_name": "Alice",
+                "scope": "ActsForClient", "email_address": "carol@example.com"
+            }"#),
+            Err(ValidationError::InvalidInput(_)),
+        ));
+        assert!(matches!(
+            authorised_contact(r#"{
+                "attorney_type": "HealthAndWelfareLpa", "first_name": "Carol", "client_first_name": "Alice",
+                "registration_reference": "7000-1234-5678", "registered_on": "12/03/2021",
+                "scope": "ActsForClient", "email_address": "carol@example.com"
+            }"#),
+            Err(ValidationError::InvalidInput(_)),
+        ));
+        assert!(matches!(
+            authorised_contact(r#"{
+                "attorney_type": "Deputy", "first_name": "Carol", "client_first_name": "Alice",
+                "registration_reference": "13258834", "registered_on": "12/03/2021", "scope": "CopiedIn"
+            }"#),
+            Err(ValidationError::EmptyInput(_)),
+        ));
+
+        let deputy = authorised_contact(r#"{
+            "attorney_type": "Deputy", "first_name": "Carol", "client_first_name": "Alice",
+            "registration_reference": "13258834", "registered_on": "12/03/2021", "ends_on": "31/03/2024",
+            "scope": "ActsForClient", "email_address": "carol@example.com"
+        }"#).unwrap();
+        assert!(deputy.check_in_force(date(3, 31)).is_ok());
+        assert!(matches!(deputy.check_in_force(date(4, 1)), Err(ValidationError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_attorney_must_act_for_an_individual_client() {
+        let unvalidated = |client_first_name: &str| -> Vec<UnvalidatedAuthorisedContact> {
+            serde_json::from_str(&format!(r#"[{{
+                "attorney_type": "EnduringPowerOfAttorney", "first_name": "Carol", "client_first_name": "{}",
+                "registration_reference": "7000-1234-5678", "registered_on": "12/03/2021",
+                "scope": "CopiedIn", "email_address": "carol@example.com"
+            }}]"#, client_first_name)).unwrap()
+        };
+
+        assert_eq!(validate_authorised_contacts(unvalidated("Sarah"), &validated_contact(JOINT_INDIVIDUALS_POST_CONTACT)).unwrap().len(), 1);
+        assert!(matches!(
+            validate_authorised_contacts(unvalidated("Zoe"), &validated_contact(JOINT_INDIVIDUALS_POST_CONTACT)),
+            Err(ValidationError::InvalidName(_)),
+        ));
+        assert!(matches!(
+            validate_authorised_contacts(unvalidated("James"), &validated_contact(PRIMARY_DIRECTOR_ELECTRONIC_CONTACT)),
+            Err(ValidationError::InvalidInput(_)),
+        ));
+    }
+
+    #[test]
+    fn test_attorney_for_a_shared_first_name_is_rejected() {
+        let unvalidated: Vec<UnvalidatedAuthorisedContact> = serde_json::from_str(r#"[{
+            "attorney_type": "EnduringPowerOfAttorney", "first_name": "Carol", "client_first_name": "James",
+            "registration_reference": "7000-1234-5678", "registered_on": "12/03/2021",
+            "scope": "CopiedIn", "email_address": "carol@example.com"
+        }]"#).unwrap();
+
+        let error = validate_authorised_contacts(unvalidated, &validated_contact(&JOINT_INDIVIDUALS_POST_CONTACT.replace("Sarah", "James"))).unwrap_err();
+        assert!(matches!(&error, ValidationError::InvalidName(detail) if detail.key() == "validation.ambiguous_client_name"));
+    }
+}
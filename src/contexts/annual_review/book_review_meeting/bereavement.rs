@@ -0,0 +1,219 @@
+//! Clients who have died, and the executors of their estates.
+//!
+//! A client recorded as deceased is sent no further invites and any reminders
+//! still pending for their review are cancelled. Where they were one of joint
+//! clients, the invites go to the surviving individual alone, written with the
+//! bereaved templates. An attorney's authority ends on the client's death, so
+//! attorneys acting for them are no longer contacted. Correspondence about the
+//! estate is with the executors, which the adviser handles personally.
+
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    redaction::Sensitive,
+    simple_types::{ConstrainedIndividualNameString100, EmailAddress, PostalAddress},
+};
+use super::types::{invalid_date_format, UnvalidatedPostalAddress, ValidatedClientContactInformation, ValidationDetail, ValidationError};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ClientStatus {
+    Living,
+    Deceased { date_of_death: NaiveDate },
+}
+
+// Unvalidated deceased client received with the annual review information.
+// Dates use the format "dd/mm/yyyy". Executors' address fields are flattened
+// as for post contacts and may be left out when they have an email address.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnvalidatedDeceasedClient {
+    first_name: Sensitive<String>,
+    date_of_death: String,
+    #[serde(default)]
+    executors: Vec<UnvalidatedExecutorContact>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnvalidatedExecutorContact {
+    first_name: Sensitive<String>,
+    #[serde(default)]
+    email_address: Option<Sensitive<String>>,
+    #[serde(default, flatten)]
+    postal_address: Option<UnvalidatedPostalAddress>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExecutorContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
+    first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    email_address: Option<EmailAddress>,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    postal_address: Option<PostalAddress>,
+}
+
+impl ExecutorContact {
+    /// An email address or a postal address is needed to write to them.
+    pub fn new(
+        first_name: ConstrainedIndividualNameString100,
+        email_address: Option<EmailAddress>,
+        postal_address: Option<PostalAddress>,
+    ) -> Result<Self, ValidationError> {
+        if email_address.is_none() && postal_address.is_none() {
+            return Err(ValidationError::EmptyInput(ValidationDetail::new("validation.address_needed", &[first_name.value()])));
+        }
+
+        Ok(Self { first_name, email_address, postal_address })
+    }
+
+    pub fn first_name(&self) -> &ConstrainedIndividualNameString100 {
+        &self.first_name
+    }
+
+    pub fn email_address(&self) -> Option<&EmailAddress> {
+        self.email_address.as_ref()
+    }
+
+    pub fn postal_address(&self) -> Option<&PostalAddress> {
+        self.postal_address.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeceasedClient {
+    #[serde(with = "crate::field_encryption::encrypted")]
+    first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    date_of_death: NaiveDate,
+    executors: Vec<ExecutorContact>,
+}
+
+impl DeceasedClient {
+    /// Executors may not be known when the death is first recorded.
+    pub fn new(first_name: ConstrainedIndividualNameString100, date_of_death: NaiveDate, executors: Vec<ExecutorContact>) -> Self {
+        Self { first_name, date_of_death, executors }
+    }
+
+    pub fn validate(unvalidated: UnvalidatedDeceasedClient) -> Result<Self, ValidationError> {
+        let name = |name: Sensitive<String>| ConstrainedIndividualNameString100::try_from(name.into_inner())
+            .map_err(|e| ValidationError::InvalidName(e.into()));
+
+        let date_of_death = NaiveDate::parse_from_str(&unvalidated.date_of_death, "%d/%m/%Y")
+            .map_err(|_| invalid_date_format(&unvalidated.date_of_death))?;
+        let executors = unvalidated.executors.into_iter().map(|executor| {
+            let email_address = executor.email_address
+                .map(|email_address| EmailAddress::try_from(email_address.into_inner()))
+                .transpose()
+                .map_err(|e| ValidationError::InvalidEmail(e.into()))?;
+            let postal_address = executor.postal_address.map(UnvalidatedPostalAddress::validate).transpose()?;
+            ExecutorContact::new(name(executor.first_name)?, email_address, postal_address)
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::new(name(unvalidated.first_name)?, date_of_death, executors))
+    }
+
+    pub fn first_name(&self) -> &ConstrainedIndividualNameString100 {
+        &self.first_name
+    }
+
+    pub fn date_of_death(&self) -> NaiveDate {
+        self.date_of_death
+    }
+
+    pub fn executors(&self) -> &Vec<ExecutorContact> {
+        &self.executors
+    }
+
+    pub fn status(&self) -> ClientStatus {
+        ClientStatus::Deceased { date_of_death: self.date_of_death }
+    }
+}
+
+/// Validates the deceased clients for a client. Each must be one of the
+/// individuals in the contact, named by a first name no other client in the
+/// contact shares, and recorded once; the death of a trustee or director is a
+/// change of contact, not of client.
+pub fn validate_deceased_clients(
+    unvalidated: Vec<UnvalidatedDeceasedClient>,
+    client_contact_information: &ValidatedClientContactInformation,
+) -> Result<Vec<DeceasedClient>, ValidationError> {
+    let deceased_clients = unvalidated.into_iter().map(DeceasedClient::validate).collect::<Result<Vec<_>, _>>()?;
+
+    if !deceased_clients.is_empty() && client_contact_information.organisation_name().is_some() {
+        return Err(ValidationError::InvalidInput(ValidationDetail::new("validation.deceased_organisation", &[])));
+    }
+
+    let client_names: Vec<&ConstrainedIndividualNameString100> = client_contact_information.electronic_recipients().into_iter().map(|(name, _)| name)
+        .chain(client_contact_information.postal_recipients().into_iter().map(|(name, _)| name))
+        .collect();
+    for (index, deceased) in deceased_clients.iter().enumerate() {
+        match client_names.iter().filter(|name| **name == deceased.first_name()).count() {
+            0 => return Err(ValidationError::InvalidName(ValidationDetail::new("validation.not_a_client", &[deceased.first_name().value()]))),
+            1 => {}
+            _ => return Err(ValidationError::InvalidName(ValidationDetail::new("validation.ambiguous_client_name", &[deceased.first_name().value()]))),
+        }
+        if deceased_clients[..index].iter().any(|earlier| earlier.first_name() == deceased.first_name()) {
+            return Err(ValidationError::InvalidInput(ValidationDetail::new("validation.deceased_more_than_once", &[deceased.first_name().value()])));
+        }
+    }
+
+    Ok(deceased_clients)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::test_utils::shared::{validated_contact, JOINT_INDIVIDUALS_POST_CONTACT, MULTIPLE_TRUSTEES_POST_CONTACT};
+
+    use super::*;
+
+    fn deceased_clients(json: &str, client_contact_information: &str) -> Result<Vec<DeceasedClient>, ValidationError> {
+        validate_deceased_clients(serde_json::from_str(json).unwrap(), &validated_contact(client_contact_information))
+    }
+
+    #[test]
+    fn test_validate_deceased_client_with_executors() {
+        let deceased = deceased_clients(r#"[{
+            "first_name": "James",
+            "date_of_death": "14/04/2024",
+            "executors": [{"first_name": "Emma", "email_address": "emma@probate-solicitors.co.uk"}]
+        }]"#, JOINT_INDIVIDUALS_POST_CONTACT).unwrap();
+
+        assert_eq!(deceased[0].status(), ClientStatus::Deceased { date_of_death: NaiveDate::from_ymd_opt(2024, 4, 14).unwrap() });
+        assert_eq!(deceased[0].executors()[0].email_address().unwrap().value(), "emma@probate-solicitors.co.uk");
+        assert!(!format!("{:?}", deceased).contains("Emma"));
+    }
+
+    #[test]
+    fn test_invalid_deceased_clients() {
+        assert!(matches!(
+            deceased_clients(r#"[{"first_name": "Zoe", "date_of_death": "14/04/2024"}]"#, JOINT_INDIVIDUALS_POST_CONTACT),
+            Err(ValidationError::InvalidName(_)),
+        ));
+        assert!(matches!(
+            deceased_clients(r#"[
+                {"first_name": "James", "date_of_death": "14/04/2024"},
+                {"first_name": "James", "date_of_death": "15/04/2024"}
+            ]"#, JOINT_INDIVIDUALS_POST_CONTACT),
+            Err(ValidationError::InvalidInput(_)),
+        ));
+        assert!(matches!(
+            deceased_clients(r#"[{"first_name": "James", "date_of_death": "14/04/2024", "executors": [{"first_name": "Emma"}]}]"#, JOINT_INDIVIDUALS_POST_CONTACT),
+            Err(ValidationError::EmptyInput(_)),
+        ));
+        assert!(matches!(
+            deceased_clients(r#"[{"first_name": "Alice", "date_of_death": "14/04/2024"}]"#, MULTIPLE_TRUSTEES_POST_CONTACT),
+            Err(ValidationError::InvalidInput(_)),
+        ));
+    }
+
+    #[test]
+    fn test_deceased_client_with_a_shared_first_name_is_rejected() {
+        let error = deceased_clients(
+            r#"[{"first_name": "James", "date_of_death": "14/04/2024"}]"#,
+            &JOINT_INDIVIDUALS_POST_CONTACT.replace("Sarah", "James"),
+        ).unwrap_err();
+
+        assert!(matches!(&error, ValidationError::InvalidName(detail) if detail.key() == "validation.ambiguous_client_name"));
+    }
+}
@@ -0,0 +1,382 @@
+//! Signed links that let a client book one of the offered slots without logging in.
+//!
+//! A token is `{key id}.{claims}.{signature}`, where the claims are base64url
+//! JSON binding the client, review, offered slots and expiry, and the signature
+//! is HMAC-SHA256 over the key id and claims. New tokens are signed with the
+//! current key; retired keys are kept only to verify links already sent.
+
+use std::collections::HashSet;
+
+use chrono::NaiveDateTime;
+use hmac::{Hmac, Mac};
+use serde::{Serialize, Deserialize};
+use sha2::Sha256;
+
+use crate::{encoding::{base64_url, base64_url_decode}, simple_types::{ClientId, ReviewId}};
+use super::{responses::ClientResponseCommand, slot_finder::MeetingSlot};
+
+const MIN_SECRET_LENGTH: usize = 32;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, PartialEq)]
+pub enum BookingTokenError {
+    InvalidKey(String),
+    InvalidExpiry(String),
+    Malformed(String),
+    UnknownKey(String),
+    InvalidSignature(String),
+    Expired(String),
+    AlreadyUsed(String),
+    SlotNotOffered(String),
+}
+
+impl std::fmt::Display for BookingTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            BookingTokenError::InvalidKey(ref desc) => write!(f, "Invalid booking link key: {}", desc),
+            BookingTokenError::InvalidExpiry(ref desc) => write!(f, "Invalid booking link expiry: {}", desc),
+            BookingTokenError::Malformed(ref desc) => write!(f, "Malformed booking token: {}", desc),
+            BookingTokenError::UnknownKey(ref desc) => write!(f, "Booking token signed with unknown key: {}", desc),
+            BookingTokenError::InvalidSignature(ref desc) => write!(f, "Booking token signature is invalid: {}", desc),
+            BookingTokenError::Expired(ref desc) => write!(f, "Booking token expired: {}", desc),
+            BookingTokenError::AlreadyUsed(ref desc) => write!(f, "Booking token already used: {}", desc),
+            BookingTokenError::SlotNotOffered(ref desc) => write!(f, "Slot not offered by booking token: {}", desc),
+        }
+    }
+}
+
+impl std::error::Error for BookingTokenError {}
+
+/// An HMAC secret and the id written into tokens signed with it.
+#[derive(Clone)]
+pub struct BookingLinkKey {
+    key_id: String,
+    secret: Vec<u8>,
+}
+
+impl BookingLinkKey {
+    /// The key id must be alphanumeric and the secret at least 32 bytes.
+    pub fn new(key_id: &str, secret: &[u8]) -> Result<Self, BookingTokenError> {
+        if key_id.is_empty() || !key_id.chars().all(|character| character.is_ascii_alphanumeric()) {
+            return Err(BookingTokenError::InvalidKey(format!("Key id '{}' must be alphanumeric", key_id)));
+        }
+        if secret.len() < MIN_SECRET_LENGTH {
+            return Err(BookingTokenError::InvalidKey(format!("Secret for key {} must be at least {} bytes", key_id, MIN_SECRET_LENGTH)));
+        }
+
+        Ok(Self { key_id: key_id.to_string(), secret: secret.to_vec() })
+    }
+
+    pub fn key_id(&self) -> &String {
+        &self.key_id
+    }
+
+    fn mac(&self, signed_content: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(signed_content.as_bytes());
+        mac
+    }
+}
+
+impl std::fmt::Debug for BookingLinkKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BookingLinkKey").field("key_id", &self.key_id).finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BookingLinkKeys {
+    current: BookingLinkKey,
+    retired: Vec<BookingLinkKey>,
+}
+
+impl BookingLinkKeys {
+    pub fn new(current: BookingLinkKey) -> Self {
+        Self { current, retired: Vec::new() }
+    }
+
+    /// Keeps a previous key to verify links signed before rotation.
+    pub fn with_retired_key(mut self, key: BookingLinkKey) -> Self {
+        self.retired.push(key);
+        self
+    }
+
+    fn key(&self, key_id: &str) -> Option<&BookingLinkKey> {
+        std::iter::once(&self.current).chain(&self.retired).find(|key| key.key_id == key_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct BookingTokenClaims {
+    client_id: ClientId,
+    review_id: ReviewId,
+    slot_starts: Vec<NaiveDateTime>,
+    issued_at: NaiveDateTime,
+    expires_at: NaiveDateTime,
+}
+
+/// The contents of a token whose signature and expiry have been checked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedBookingToken {
+    token_id: String,
+    claims: BookingTokenClaims,
+}
+
+impl VerifiedBookingToken {
+    /// Identifies the token when recording that it has been used.
+    pub fn token_id(&self) -> &String {
+        &self.token_id
+    }
+
+    pub fn client_id(&self) -> &ClientId {
+        &self.claims.client_id
+    }
+
+    pub fn review_id(&self) -> &ReviewId {
+        &self.claims.review_id
+    }
+
+    pub fn slot_starts(&self) -> &Vec<NaiveDateTime> {
+        &self.claims.slot_starts
+    }
+
+    pub fn expires_at(&self) -> NaiveDateTime {
+        self.claims.expires_at
+    }
+}
+
+/// Port recording which booking tokens have been redeemed.
+pub trait UsedBookingTokens {
+    /// Records the token as used, returning false if it was already used.
+    fn mark_used(&mut self, token_id: &str) -> bool;
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryUsedBookingTokens {
+    token_ids: HashSet<String>,
+}
+
+impl InMemoryUsedBookingTokens {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl UsedBookingTokens for InMemoryUsedBookingTokens {
+    fn mark_used(&mut self, token_id: &str) -> bool {
+        self.token_ids.insert(token_id.to_string())
+    }
+}
+
+pub struct BookingLinks {
+    keys: BookingLinkKeys,
+}
+
+impl BookingLinks {
+    pub fn new(keys: BookingLinkKeys) -> Self {
+        Self { keys }
+    }
+
+    /// A token offering `slots` to the client, valid until `expires_at`.
+    pub fn issue(
+        &self,
+        client_id: &ClientId,
+        review_id: &ReviewId,
+        slots: &[MeetingSlot],
+        issued_at: NaiveDateTime,
+        expires_at: NaiveDateTime,
+    ) -> Result<String, BookingTokenError> {
+        if expires_at <= issued_at {
+            return Err(BookingTokenError::InvalidExpiry("The link must expire after it is issued".to_string()));
+        }
+
+        let claims = BookingTokenClaims {
+            client_id: client_id.clone(),
+            review_id: review_id.clone(),
+            slot_starts: slots.iter().map(MeetingSlot::start).collect(),
+            issued_at,
+            expires_at,
+        };
+        let encoded_claims = base64_url(serde_json::to_string(&claims).expect("Claims always serialise").as_bytes());
+        let signed_content = format!("{}.{}", self.keys.current.key_id, encoded_claims);
+        let signature = base64_url(&self.keys.current.mac(&signed_content).finalize().into_bytes());
+
+        Ok(format!("{}.{}", signed_content, signature))
+    }
+
+    /// The booking page URL for a token, e.g. `https://book.gcwm.co.uk/review`.
+    pub fn booking_url(base_url: &str, token: &str) -> String {
+        format!("{}?token={}", base_url.trim_end_matches('/'), token)
+    }
+
+    /// Checks the token's signature and expiry without using it up.
+    pub fn verify(&self, token: &str, now: NaiveDateTime) -> Result<VerifiedBookingToken, BookingTokenError> {
+        let malformed = || BookingTokenError::Malformed("Expected key id, claims and signature".to_string());
+        let (signed_content, signature) = token.rsplit_once('.').ok_or_else(malformed)?;
+        let (key_id, encoded_claims) = signed_content.split_once('.').ok_or_else(malformed)?;
+
+        let key = self.keys.key(key_id).ok_or_else(|| BookingTokenError::UnknownKey(key_id.to_string()))?;
+        let signature_bytes = base64_url_decode(signature).ok_or_else(|| BookingTokenError::Malformed("Signature is not base64url".to_string()))?;
+        key.mac(signed_content)
+            .verify_slice(&signature_bytes)
+            .map_err(|_| BookingTokenError::InvalidSignature(format!("Token for key {}", key_id)))?;
+
+        let claims_json = base64_url_decode(encoded_claims).ok_or_else(|| BookingTokenError::Malformed("Claims are not base64url".to_string()))?;
+        let claims: BookingTokenClaims = serde_json::from_slice(&claims_json).map_err(|e| BookingTokenError::Malformed(e.to_string()))?;
+        if now >= claims.expires_at {
+            return Err(BookingTokenError::Expired(format!("Link expired at {}", claims.expires_at)));
+        }
+
+        Ok(VerifiedBookingToken { token_id: signature.to_string(), claims })
+    }
+
+    /// Turns a clicked link into an accept-slot command, using up the token so
+    /// that it cannot book again.
+    pub fn redeem(
+        &self,
+        token: &str,
+        slot_start: NaiveDateTime,
+        now: NaiveDateTime,
+        used_tokens: &mut dyn UsedBookingTokens,
+    ) -> Result<(VerifiedBookingToken, ClientResponseCommand), BookingTokenError> {
+        let verified = self.verify(token, now)?;
+        if !verified.claims.slot_starts.contains(&slot_start) {
+            return Err(BookingTokenError::SlotNotOffered(slot_start.to_string()));
+        }
+        if !used_tokens.mark_used(&verified.token_id) {
+            return Err(BookingTokenError::AlreadyUsed(format!("Link for review {}", verified.claims.review_id.value())));
+        }
+
+        let command = ClientResponseCommand::AcceptSlot { review_id: verified.claims.review_id.clone(), slot_start };
+        Ok((verified, command))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::tests::test_utils::shared::REVIEW_ID;
+    use super::super::adviser_calendar::MeetingLocation;
+    use super::*;
+
+    fn date_time(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn client_id() -> ClientId {
+        ClientId::try_from("3f2b8c1e-5d4a-4e6f-9a7b-0c1d2e3f4a5b".to_string()).unwrap()
+    }
+
+    fn review_id() -> ReviewId {
+        ReviewId::try_from(REVIEW_ID.to_string()).unwrap()
+    }
+
+    fn slots() -> Vec<MeetingSlot> {
+        [14, 15].iter()
+            .map(|day| MeetingSlot::new(date_time(*day, 10), date_time(*day, 11), MeetingLocation::InPerson("Guildford office".to_string())).unwrap())
+            .collect()
+    }
+
+    fn key(key_id: &str, fill: u8) -> BookingLinkKey {
+        BookingLinkKey::new(key_id, &[fill; 32]).unwrap()
+    }
+
+    fn issue(links: &BookingLinks) -> String {
+        links.issue(&client_id(), &review_id(), &slots(), date_time(1, 9), date_time(31, 17)).unwrap()
+    }
+
+    #[test]
+    fn test_redeem_issued_token() {
+        let links = BookingLinks::new(BookingLinkKeys::new(key("k2024", 1)));
+        let token = issue(&links);
+        let mut used_tokens = InMemoryUsedBookingTokens::new();
+
+        let (verified, command) = links.redeem(&token, date_time(15, 10), date_time(2, 9), &mut used_tokens).unwrap();
+
+        assert_eq!(verified.client_id(), &client_id());
+        assert_eq!(verified.slot_starts(), &vec![date_time(14, 10), date_time(15, 10)]);
+        assert_eq!(command, ClientResponseCommand::AcceptSlot { review_id: review_id(), slot_start: date_time(15, 10) });
+    }
+
+    #[test]
+    fn test_reused_token_is_rejected() {
+        let links = BookingLinks::new(BookingLinkKeys::new(key("k2024", 1)));
+        let token = issue(&links);
+        let mut used_tokens = InMemoryUsedBookingTokens::new();
+
+        links.redeem(&token, date_time(15, 10), date_time(2, 9), &mut used_tokens).unwrap();
+        let result = links.redeem(&token, date_time(14, 10), date_time(2, 10), &mut used_tokens);
+
+        assert!(matches!(result, Err(BookingTokenError::AlreadyUsed(_))));
+    }
+
+    #[test]
+    fn test_expired_token_is_rejected() {
+        let links = BookingLinks::new(BookingLinkKeys::new(key("k2024", 1)));
+        let token = issue(&links);
+
+        assert!(links.verify(&token, date_time(31, 16)).is_ok());
+        assert!(matches!(links.verify(&token, date_time(31, 17)), Err(BookingTokenError::Expired(_))));
+        assert!(matches!(
+            links.issue(&client_id(), &review_id(), &slots(), date_time(1, 9), date_time(1, 9)),
+            Err(BookingTokenError::InvalidExpiry(_)),
+        ));
+    }
+
+    #[test]
+    fn test_forged_tokens_are_rejected() {
+        let links = BookingLinks::new(BookingLinkKeys::new(key("k2024", 1)));
+        let token = issue(&links);
+        let (signed_content, signature) = token.rsplit_once('.').unwrap();
+
+        // Claims altered to offer a different slot, keeping the original signature
+        let (key_id, encoded_claims) = signed_content.split_once('.').unwrap();
+        let claims = String::from_utf8(base64_url_decode(encoded_claims).unwrap()).unwrap().replace("2024-05-15T10:00:00", "2024-05-16T10:00:00");
+        let tampered = format!("{}.{}.{}", key_id, base64_url(claims.as_bytes()), signature);
+        assert!(matches!(links.verify(&tampered, date_time(2, 9)), Err(BookingTokenError::InvalidSignature(_))));
+
+        // Signed with a key of the same id but a different secret
+        let forger = BookingLinks::new(BookingLinkKeys::new(key("k2024", 2)));
+        assert!(matches!(links.verify(&issue(&forger), date_time(2, 9)), Err(BookingTokenError::InvalidSignature(_))));
+
+        assert!(matches!(links.verify("not-a-token", date_time(2, 9)), Err(BookingTokenError::Malformed(_))));
+        assert!(matches!(links.verify(&format!("{}.!", signed_content), date_time(2, 9)), Err(BookingTokenError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_slot_must_have_been_offered() {
+        let links = BookingLinks::new(BookingLinkKeys::new(key("k2024", 1)));
+        let mut used_tokens = InMemoryUsedBookingTokens::new();
+
+        let result = links.redeem(&issue(&links), date_time(16, 10), date_time(2, 9), &mut used_tokens);
+
+        assert!(matches!(result, Err(BookingTokenError::SlotNotOffered(_))));
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let old_links = BookingLinks::new(BookingLinkKeys::new(key("k2023", 1)));
+        let token = issue(&old_links);
+
+        let rotated = BookingLinks::new(BookingLinkKeys::new(key("k2024", 2)).with_retired_key(key("k2023", 1)));
+        assert!(rotated.verify(&token, date_time(2, 9)).is_ok());
+        assert!(issue(&rotated).starts_with("k2024."));
+
+        let retired_key_dropped = BookingLinks::new(BookingLinkKeys::new(key("k2024", 2)));
+        assert!(matches!(retired_key_dropped.verify(&token, date_time(2, 9)), Err(BookingTokenError::UnknownKey(_))));
+    }
+
+    #[test]
+    fn test_keys_are_validated() {
+        assert!(matches!(BookingLinkKey::new("k2024", &[1; 16]), Err(BookingTokenError::InvalidKey(_))));
+        assert!(matches!(BookingLinkKey::new("k.2024", &[1; 32]), Err(BookingTokenError::InvalidKey(_))));
+        assert!(!format!("{:?}", key("k2024", 7)).contains("7"));
+    }
+
+    #[test]
+    fn test_booking_url() {
+        assert_eq!(BookingLinks::booking_url("https://book.gcwm.co.uk/review/", "a.b.c"), "https://book.gcwm.co.uk/review?token=a.b.c");
+    }
+}
@@ -0,0 +1,256 @@
+use chrono::NaiveDateTime;
+use serde::{Serialize, Deserialize};
+
+use crate::{icalendar::{self, Component, Property}, simple_types::{ConstrainedIndividualNameString100, EmailAddress, ReviewId}};
+use super::{adviser_calendar::MeetingLocation, slot_finder::MeetingSlot, types::{ValidatedClientContactInformation, ValidationDetail, ValidationError}};
+
+const PRODUCT_ID: &str = "-//Grafton Court Wealth Management//Annual Review//EN";
+const UID_DOMAIN: &str = "gcwm.co.uk";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum BookedReviewMeetingStatus {
+    Confirmed,
+    Cancelled,
+}
+
+/// A booked annual review meeting as sent to calendars. Every change to the
+/// meeting increments the sequence so that calendar clients replace the
+/// earlier version of the event with the same UID.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BookedReviewMeeting {
+    review_id: ReviewId,
+    sequence: u32,
+    status: BookedReviewMeetingStatus,
+    slot: MeetingSlot,
+    adviser_name: ConstrainedIndividualNameString100,
+    organiser_email_address: EmailAddress,
+    attendees: Vec<(ConstrainedIndividualNameString100, EmailAddress)>,
+}
+
+impl BookedReviewMeeting {
+    pub fn new(
+        review_id: ReviewId,
+        slot: MeetingSlot,
+        adviser_name: ConstrainedIndividualNameString100,
+        organiser_email_address: EmailAddress,
+        client_contact_information: &ValidatedClientContactInformation,
+    ) -> Result<Self, ValidationError> {
+        let attendees: Vec<(ConstrainedIndividualNameString100, EmailAddress)> = client_contact_information
+            .electronic_recipients()
+            .into_iter()
+            .map(|(name, email_address)| (name.clone(), email_address.clone()))
+            .collect();
+
+        if attendees.is_empty() {
+            return Err(ValidationError::EmptyInput(ValidationDetail::new("validation.calendar_invite_without_email", &[])));
+        }
+
+        Ok(Self {
+            review_id,
+            sequence: 0,
+            status: BookedReviewMeetingStatus::Confirmed,
+            slot,
+            adviser_name,
+            organiser_email_address,
+            attendees,
+        })
+    }
+
+    pub fn review_id(&self) -> &ReviewId {
+        &self.review_id
+    }
+
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    pub fn status(&self) -> BookedReviewMeetingStatus {
+        self.status
+    }
+
+    pub fn slot(&self) -> &MeetingSlot {
+        &self.slot
+    }
+
+    /// The iTIP method of the current version of the meeting.
+    pub fn method(&self) -> &'static str {
+        match self.status {
+            BookedReviewMeetingStatus::Confirmed => "REQUEST",
+            BookedReviewMeetingStatus::Cancelled => "CANCEL",
+        }
+    }
+
+    pub fn uid(&self) -> String {
+        format!("{}@{}", self.review_id.value(), UID_DOMAIN)
+    }
+
+    pub fn reschedule(&self, slot: MeetingSlot) -> Result<Self, ValidationError> {
+        if self.status == BookedReviewMeetingStatus::Cancelled {
+            return Err(ValidationError::InvalidInput(ValidationDetail::new("validation.reschedule_cancelled_meeting", &[])));
+        }
+
+        Ok(Self { sequence: self.sequence + 1, slot, ..self.clone() })
+    }
+
+    pub fn cancel(&self) -> Result<Self, ValidationError> {
+        if self.status == BookedReviewMeetingStatus::Cancelled {
+            return Err(ValidationError::InvalidInput(ValidationDetail::new("validation.meeting_already_cancelled", &[])));
+        }
+
+        Ok(Self { sequence: self.sequence + 1, status: BookedReviewMeetingStatus::Cancelled, ..self.clone() })
+    }
+
+    /// The iCalendar object for the meeting, METHOD:REQUEST while confirmed and
+    /// METHOD:CANCEL once cancelled. `timestamp` is the UTC time the invite was created.
+    pub fn to_ics(&self, timestamp: NaiveDateTime) -> String {
+        let status = match self.status {
+            BookedReviewMeetingStatus::Confirmed => "CONFIRMED",
+            BookedReviewMeetingStatus::Cancelled => "CANCELLED",
+        };
+
+        let mut event = Component::new("VEVENT")
+            .with_property(Property::new("UID", &self.uid()))
+            .with_property(Property::new("SEQUENCE", &self.sequence.to_string()))
+            .with_property(Property::utc_date_time("DTSTAMP", timestamp))
+            .with_property(Property::london_date_time("DTSTART", self.slot.start()))
+            .with_property(Property::london_date_time("DTEND", self.slot.end()))
+            .with_property(Property::text_value("SUMMARY", &format!("Annual review with {}", self.adviser_name.value())))
+            .with_property(Property::new("STATUS", status))
+            .with_property(
+                Property::new("ORGANIZER", &format!("mailto:{}", self.organiser_email_address.value()))
+                    .with_parameter("CN", self.adviser_name.value()),
+            );
+
+        for (name, email_address) in &self.attendees {
+            event = event.with_property(
+                Property::new("ATTENDEE", &format!("mailto:{}", email_address.value()))
+                    .with_parameter("CN", name.value())
+                    .with_parameter("ROLE", "REQ-PARTICIPANT")
+                    .with_parameter("PARTSTAT", "NEEDS-ACTION")
+                    .with_parameter("RSVP", "TRUE"),
+            );
+        }
+
+        event = match self.slot.location() {
+            MeetingLocation::InPerson(address) => event.with_property(Property::text_value("LOCATION", address)),
+            MeetingLocation::VideoCall(link) => event
+                .with_property(Property::text_value("LOCATION", link))
+                .with_property(Property::new("URL", link))
+                .with_property(Property::text_value("DESCRIPTION", &format!("Join the video call at {}", link))),
+            MeetingLocation::Telephone => event
+                .with_property(Property::text_value("LOCATION", "Telephone"))
+                .with_property(Property::text_value("DESCRIPTION", &format!("{} will call you at the time of the meeting.", self.adviser_name.value()))),
+        };
+
+        Component::new("VCALENDAR")
+            .with_property(Property::new("PRODID", PRODUCT_ID))
+            .with_property(Property::new("VERSION", "2.0"))
+            .with_property(Property::new("CALSCALE", "GREGORIAN"))
+            .with_property(Property::new("METHOD", self.method()))
+            .with_component(icalendar::london_time_zone())
+            .with_component(event)
+            .to_ics_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::tests::test_utils::shared::{validated_contact, ADMINISTRATOR_EMAIL, ADVISER_NAME, JOINT_INDIVIDUALS_ELECTRONIC_CONTACT, JOINT_INDIVIDUALS_POST_CONTACT, REVIEW_ID};
+    use super::*;
+
+    fn date_time(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn booked_meeting(location: MeetingLocation) -> BookedReviewMeeting {
+        let contact = validated_contact(JOINT_INDIVIDUALS_ELECTRONIC_CONTACT);
+
+        BookedReviewMeeting::new(
+            ReviewId::try_from(REVIEW_ID.to_string()).unwrap(),
+            MeetingSlot::new(date_time(12, 10), date_time(12, 11), location).unwrap(),
+            ConstrainedIndividualNameString100::try_from(ADVISER_NAME.to_string()).unwrap(),
+            EmailAddress::try_from(ADMINISTRATOR_EMAIL.to_string()).unwrap(),
+            &contact,
+        ).unwrap()
+    }
+
+    fn event(ics: &str) -> Component {
+        let calendars = icalendar::parse(ics).unwrap();
+        assert_eq!(calendars.len(), 1);
+        let event = calendars[0].components_named("VEVENT").next().unwrap().clone();
+        event
+    }
+
+    #[test]
+    fn test_invite_round_trips_through_parse() {
+        let meeting = booked_meeting(MeetingLocation::InPerson("Grafton Court, 1 High Street".to_string()));
+        let ics = meeting.to_ics(date_time(1, 9));
+
+        let calendars = icalendar::parse(&ics).unwrap();
+        assert_eq!(calendars[0].property("METHOD").unwrap().value(), "REQUEST");
+        assert_eq!(calendars[0].components_named("VTIMEZONE").next().unwrap().property("TZID").unwrap().value(), "Europe/London");
+
+        let event = event(&ics);
+        assert_eq!(event.property("UID").unwrap().value(), format!("{}@gcwm.co.uk", REVIEW_ID));
+        assert_eq!(event.property("SEQUENCE").unwrap().value(), "0");
+        assert_eq!(event.property("DTSTAMP").unwrap().value(), "20240601T090000Z");
+        assert_eq!(event.property("DTSTART").unwrap().local_date_time().unwrap(), date_time(12, 10));
+        assert_eq!(event.property("DTEND").unwrap().local_date_time().unwrap(), date_time(12, 11));
+        assert_eq!(event.property("LOCATION").unwrap().text(), "Grafton Court, 1 High Street");
+        assert_eq!(event.property("ORGANIZER").unwrap().value(), format!("mailto:{}", ADMINISTRATOR_EMAIL));
+        assert_eq!(event.property("ORGANIZER").unwrap().parameter("CN"), Some(ADVISER_NAME));
+
+        let attendees: Vec<&str> = event.properties_named("ATTENDEE").map(|attendee| attendee.value()).collect();
+        assert_eq!(attendees, vec!["mailto:james@example.com", "mailto:sarah@example.com"]);
+    }
+
+    #[test]
+    fn test_video_call_invite_includes_link() {
+        let ics = booked_meeting(MeetingLocation::VideoCall("https://meet.example.com/review".to_string())).to_ics(date_time(1, 9));
+        let event = event(&ics);
+
+        assert_eq!(event.property("URL").unwrap().value(), "https://meet.example.com/review");
+        assert_eq!(event.property("LOCATION").unwrap().text(), "https://meet.example.com/review");
+    }
+
+    #[test]
+    fn test_reschedule_increments_sequence_and_keeps_uid() {
+        let meeting = booked_meeting(MeetingLocation::Telephone);
+        let rescheduled = meeting.reschedule(MeetingSlot::new(date_time(13, 14), date_time(13, 15), MeetingLocation::Telephone).unwrap()).unwrap();
+        let event = event(&rescheduled.to_ics(date_time(2, 9)));
+
+        assert_eq!(event.property("UID").unwrap().value(), meeting.uid());
+        assert_eq!(event.property("SEQUENCE").unwrap().value(), "1");
+        assert_eq!(event.property("DTSTART").unwrap().local_date_time().unwrap(), date_time(13, 14));
+    }
+
+    #[test]
+    fn test_cancellation_uses_cancel_method() {
+        let cancelled = booked_meeting(MeetingLocation::Telephone).reschedule(
+            MeetingSlot::new(date_time(13, 14), date_time(13, 15), MeetingLocation::Telephone).unwrap(),
+        ).unwrap().cancel().unwrap();
+        let ics = cancelled.to_ics(date_time(3, 9));
+
+        assert_eq!(icalendar::parse(&ics).unwrap()[0].property("METHOD").unwrap().value(), "CANCEL");
+        assert_eq!(event(&ics).property("STATUS").unwrap().value(), "CANCELLED");
+        assert_eq!(event(&ics).property("SEQUENCE").unwrap().value(), "2");
+        assert!(cancelled.cancel().is_err());
+        assert!(cancelled.reschedule(MeetingSlot::new(date_time(14, 14), date_time(14, 15), MeetingLocation::Telephone).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_post_contact_cannot_receive_calendar_invite() {
+        let contact = validated_contact(JOINT_INDIVIDUALS_POST_CONTACT);
+
+        let result = BookedReviewMeeting::new(
+            ReviewId::try_from(REVIEW_ID.to_string()).unwrap(),
+            MeetingSlot::new(date_time(12, 10), date_time(12, 11), MeetingLocation::Telephone).unwrap(),
+            ConstrainedIndividualNameString100::try_from(ADVISER_NAME.to_string()).unwrap(),
+            EmailAddress::try_from(ADMINISTRATOR_EMAIL.to_string()).unwrap(),
+            &contact,
+        );
+        assert!(matches!(result, Err(ValidationError::EmptyInput(_))));
+    }
+}
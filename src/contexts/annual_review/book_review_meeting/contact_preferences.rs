@@ -0,0 +1,422 @@
+//! How each client prefers to be contacted, when they must not be, and the
+//! language and format their correspondence must be in.
+
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+
+use super::types::{invalid_date_format, ValidatedClientContactInformation, ValidationDetail, ValidationError};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ContactChannel {
+    Email,
+    Post,
+    Phone,
+    Sms,
+    SecurePortalMessage,
+}
+
+impl TryFrom<&str> for ContactChannel {
+    type Error = ValidationError;
+
+    fn try_from(channel: &str) -> Result<Self, Self::Error> {
+        match channel {
+            "Email" => Ok(ContactChannel::Email),
+            "Post" => Ok(ContactChannel::Post),
+            "Phone" => Ok(ContactChannel::Phone),
+            "Sms" => Ok(ContactChannel::Sms),
+            "SecurePortalMessage" => Ok(ContactChannel::SecurePortalMessage),
+            _ => Err(ValidationError::InvalidInput(ValidationDetail::new("validation.unknown_contact_channel", &[channel]))),
+        }
+    }
+}
+
+/// A format a client needs written correspondence in.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum AccessibleFormat {
+    #[default]
+    Standard,
+    LargePrint,
+    Braille,
+    Audio,
+}
+
+impl TryFrom<&str> for AccessibleFormat {
+    type Error = ValidationError;
+
+    fn try_from(format: &str) -> Result<Self, Self::Error> {
+        match format {
+            "Standard" => Ok(AccessibleFormat::Standard),
+            "LargePrint" => Ok(AccessibleFormat::LargePrint),
+            "Braille" => Ok(AccessibleFormat::Braille),
+            "Audio" => Ok(AccessibleFormat::Audio),
+            _ => Err(ValidationError::InvalidInput(ValidationDetail::new("validation.unknown_accessible_format", &[format]))),
+        }
+    }
+}
+
+/// The language a client's correspondence is written in.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Language {
+    #[default]
+    English,
+    /// Clients in Wales may ask for correspondence in Welsh.
+    Welsh,
+}
+
+impl TryFrom<&str> for Language {
+    type Error = ValidationError;
+
+    /// A language name or locale, e.g. "Welsh", "cy" or "cy-GB".
+    fn try_from(language: &str) -> Result<Self, Self::Error> {
+        match language {
+            "English" | "en" | "en-GB" => Ok(Language::English),
+            "Welsh" | "cy" | "cy-GB" => Ok(Language::Welsh),
+            _ => Err(ValidationError::InvalidInput(ValidationDetail::new("validation.unknown_language", &[language]))),
+        }
+    }
+}
+
+impl Language {
+    pub fn value(&self) -> &'static str {
+        match self {
+            Language::English => "english",
+            Language::Welsh => "welsh",
+        }
+    }
+
+    /// The ISO 639-1 code, e.g. for a `Content-Language` header.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Welsh => "cy",
+        }
+    }
+}
+
+/// How written correspondence must be produced for a client. Standard
+/// English unless the client has asked otherwise.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct CommunicationFormat {
+    language: Language,
+    accessible_format: AccessibleFormat,
+}
+
+impl CommunicationFormat {
+    pub fn new(language: Language, accessible_format: AccessibleFormat) -> Self {
+        Self { language, accessible_format }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn accessible_format(&self) -> AccessibleFormat {
+        self.accessible_format
+    }
+}
+
+// Unvalidated contact preferences received with the annual review information.
+// Dates use the format "dd/mm/yyyy" and are inclusive.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnvalidatedContactPreferences {
+    preferred_channels: Vec<String>,
+    #[serde(default)]
+    do_not_contact: bool,
+    #[serde(default)]
+    blocked_channels: Vec<String>,
+    #[serde(default)]
+    quiet_periods: Vec<UnvalidatedQuietPeriod>,
+    #[serde(default, alias = "locale")]
+    language: Option<String>,
+    #[serde(default)]
+    accessible_format: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnvalidatedQuietPeriod {
+    first_day: String,
+    last_day: String,
+    #[serde(default)]
+    reason: String,
+}
+
+/// Dates, inclusive, on which the client must not be contacted, e.g. after a bereavement.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuietPeriod {
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+    reason: String,
+}
+
+impl QuietPeriod {
+    pub fn new(first_day: NaiveDate, last_day: NaiveDate, reason: &str) -> Result<Self, ValidationError> {
+        if last_day < first_day {
+            return Err(ValidationError::InvalidDate(ValidationDetail::new("validation.quiet_period_ends_before_it_starts", &[])));
+        }
+        // The day after the period must exist for contact to resume.
+        if last_day == NaiveDate::MAX {
+            return Err(ValidationError::InvalidDate(ValidationDetail::new("validation.quiet_period_has_no_end", &[])));
+        }
+
+        Ok(Self { first_day, last_day, reason: reason.trim().to_string() })
+    }
+
+    pub fn first_day(&self) -> NaiveDate {
+        self.first_day
+    }
+
+    pub fn last_day(&self) -> NaiveDate {
+        self.last_day
+    }
+
+    pub fn reason(&self) -> &String {
+        &self.reason
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        date >= self.first_day && date <= self.last_day
+    }
+}
+
+/// Which channel to contact the client on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ChannelDecision {
+    Use(ContactChannel),
+    /// The client is in a quiet period; try again on this date.
+    DeferUntil(NaiveDate),
+    DoNotContact,
+    /// None of the preferred channels can reach the client.
+    NoUsableChannel,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContactPreferences {
+    preferred_channels: Vec<ContactChannel>,
+    do_not_contact: bool,
+    blocked_channels: Vec<ContactChannel>,
+    quiet_periods: Vec<QuietPeriod>,
+    communication_format: CommunicationFormat,
+}
+
+impl ContactPreferences {
+    /// Channels in order of preference, with no repeats.
+    pub fn new(preferred_channels: Vec<ContactChannel>) -> Result<Self, ValidationError> {
+        if preferred_channels.is_empty() {
+            return Err(ValidationError::EmptyInput(ValidationDetail::new("validation.no_preferred_contact_channels", &[])));
+        }
+        if preferred_channels.iter().enumerate().any(|(index, channel)| preferred_channels[..index].contains(channel)) {
+            return Err(ValidationError::InvalidInput(ValidationDetail::new("validation.repeated_preferred_contact_channels", &[])));
+        }
+
+        Ok(Self { preferred_channels, do_not_contact: false, blocked_channels: Vec::new(), quiet_periods: Vec::new(), communication_format: CommunicationFormat::default() })
+    }
+
+    /// Email then post for electronic contacts and post alone for post
+    /// contacts, for clients who have not stated a preference.
+    pub fn default_for(client_contact_information: &ValidatedClientContactInformation) -> Self {
+        let preferred_channels = if client_contact_information.is_electronic() {
+            vec![ContactChannel::Email, ContactChannel::Post]
+        } else {
+            vec![ContactChannel::Post]
+        };
+
+        Self { preferred_channels, do_not_contact: false, blocked_channels: Vec::new(), quiet_periods: Vec::new(), communication_format: CommunicationFormat::default() }
+    }
+
+    pub fn validate(unvalidated: UnvalidatedContactPreferences) -> Result<Self, ValidationError> {
+        let parse_channels = |channels: &[String]| channels.iter().map(|channel| ContactChannel::try_from(channel.as_str())).collect::<Result<Vec<_>, _>>();
+        let parse_date = |date: &str| NaiveDate::parse_from_str(date, "%d/%m/%Y").map_err(|_| invalid_date_format(date));
+
+        let mut preferences = Self::new(parse_channels(&unvalidated.preferred_channels)?)?;
+        if unvalidated.do_not_contact {
+            preferences = preferences.with_do_not_contact();
+        }
+        for channel in parse_channels(&unvalidated.blocked_channels)? {
+            preferences = preferences.with_blocked_channel(channel);
+        }
+        for period in unvalidated.quiet_periods {
+            preferences = preferences.with_quiet_period(QuietPeriod::new(parse_date(&period.first_day)?, parse_date(&period.last_day)?, &period.reason)?);
+        }
+
+        let language = unvalidated.language.as_deref().map(Language::try_from).transpose()?.unwrap_or_default();
+        let accessible_format = unvalidated.accessible_format.as_deref().map(AccessibleFormat::try_from).transpose()?.unwrap_or_default();
+        preferences = preferences.with_communication_format(CommunicationFormat::new(language, accessible_format));
+
+        Ok(preferences)
+    }
+
+    /// The client must not be contacted on any channel.
+    pub fn with_do_not_contact(mut self) -> Self {
+        self.do_not_contact = true;
+        self
+    }
+
+    /// The client must not be contacted on this channel, even if preferred.
+    pub fn with_blocked_channel(mut self, channel: ContactChannel) -> Self {
+        if !self.blocked_channels.contains(&channel) {
+            self.blocked_channels.push(channel);
+        }
+        self
+    }
+
+    pub fn with_quiet_period(mut self, quiet_period: QuietPeriod) -> Self {
+        self.quiet_periods.push(quiet_period);
+        self
+    }
+
+    pub fn with_communication_format(mut self, communication_format: CommunicationFormat) -> Self {
+        self.communication_format = communication_format;
+        self
+    }
+
+    pub fn preferred_channels(&self) -> &Vec<ContactChannel> {
+        &self.preferred_channels
+    }
+
+    pub fn do_not_contact(&self) -> bool {
+        self.do_not_contact
+    }
+
+    pub fn blocked_channels(&self) -> &Vec<ContactChannel> {
+        &self.blocked_channels
+    }
+
+    pub fn quiet_periods(&self) -> &Vec<QuietPeriod> {
+        &self.quiet_periods
+    }
+
+    pub fn communication_format(&self) -> CommunicationFormat {
+        self.communication_format
+    }
+
+    /// The first day on or after `date` outside every quiet period.
+    pub fn next_contactable_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut day = date;
+        while let Some(period) = self.quiet_periods.iter().find(|period| period.contains(day)) {
+            day = period.last_day.succ_opt().expect("Quiet periods end before the last representable date");
+        }
+        day
+    }
+
+    /// The most preferred channel that is not blocked and can reach the client
+    /// on `date`, given the channels `available` from their contact details.
+    pub fn choose_channel(&self, available: &[ContactChannel], date: NaiveDate) -> ChannelDecision {
+        if self.do_not_contact {
+            return ChannelDecision::DoNotContact;
+        }
+
+        let next_contactable_day = self.next_contactable_day(date);
+        if next_contactable_day > date {
+            return ChannelDecision::DeferUntil(next_contactable_day);
+        }
+
+        self.preferred_channels
+            .iter()
+            .find(|channel| available.contains(channel) && !self.blocked_channels.contains(channel))
+            .map(|channel| ChannelDecision::Use(*channel))
+            .unwrap_or(ChannelDecision::NoUsableChannel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::test_utils::shared::{validated_contact, ReviewInformationFixture, JOINT_INDIVIDUALS_POST_CONTACT, SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT};
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn preferences(json: &str) -> Result<ContactPreferences, ValidationError> {
+        ContactPreferences::validate(serde_json::from_str(json).unwrap())
+    }
+
+    const ALL_CHANNELS: [ContactChannel; 5] = [
+        ContactChannel::Email,
+        ContactChannel::Post,
+        ContactChannel::Phone,
+        ContactChannel::Sms,
+        ContactChannel::SecurePortalMessage,
+    ];
+
+    #[test]
+    fn test_first_available_preferred_channel_is_chosen() {
+        let preferences = preferences(r#"{"preferred_channels": ["SecurePortalMessage", "Sms", "Post"]}"#).unwrap();
+
+        assert_eq!(preferences.choose_channel(&ALL_CHANNELS, date(5, 1)), ChannelDecision::Use(ContactChannel::SecurePortalMessage));
+        assert_eq!(preferences.choose_channel(&[ContactChannel::Email, ContactChannel::Post], date(5, 1)), ChannelDecision::Use(ContactChannel::Post));
+        assert_eq!(preferences.choose_channel(&[ContactChannel::Email], date(5, 1)), ChannelDecision::NoUsableChannel);
+    }
+
+    #[test]
+    fn test_blocked_channels_and_do_not_contact() {
+        let preferences = preferences(r#"{"preferred_channels": ["Phone", "Email"], "blocked_channels": ["Phone"]}"#).unwrap();
+        assert_eq!(preferences.choose_channel(&ALL_CHANNELS, date(5, 1)), ChannelDecision::Use(ContactChannel::Email));
+
+        let preferences = preferences.with_do_not_contact();
+        assert_eq!(preferences.choose_channel(&ALL_CHANNELS, date(5, 1)), ChannelDecision::DoNotContact);
+    }
+
+    #[test]
+    fn test_quiet_periods_defer_contact() {
+        let preferences = preferences(r#"{
+            "preferred_channels": ["Email"],
+            "quiet_periods": [
+                {"first_day": "01/05/2024", "last_day": "10/05/2024", "reason": "Bereavement"},
+                {"first_day": "11/05/2024", "last_day": "20/05/2024"}
+            ]
+        }"#).unwrap();
+
+        assert_eq!(preferences.choose_channel(&ALL_CHANNELS, date(4, 30)), ChannelDecision::Use(ContactChannel::Email));
+        assert_eq!(preferences.choose_channel(&ALL_CHANNELS, date(5, 3)), ChannelDecision::DeferUntil(date(5, 21)));
+        assert_eq!(preferences.choose_channel(&ALL_CHANNELS, date(5, 21)), ChannelDecision::Use(ContactChannel::Email));
+        assert_eq!(preferences.quiet_periods()[0].reason(), "Bereavement");
+    }
+
+    #[test]
+    fn test_quiet_period_must_end() {
+        assert!(matches!(QuietPeriod::new(date(5, 1), NaiveDate::MAX, "Bereavement"), Err(ValidationError::InvalidDate(_))));
+        assert!(matches!(QuietPeriod::new(date(5, 10), date(5, 1), "Bereavement"), Err(ValidationError::InvalidDate(_))));
+    }
+
+    #[test]
+    fn test_language_and_accessible_format() {
+        let welsh_large_print = preferences(r#"{"preferred_channels": ["Post"], "language": "Welsh", "accessible_format": "LargePrint"}"#).unwrap();
+        assert_eq!(welsh_large_print.communication_format(), CommunicationFormat::new(Language::Welsh, AccessibleFormat::LargePrint));
+
+        let welsh_locale = preferences(r#"{"preferred_channels": ["Post"], "locale": "cy-GB"}"#).unwrap();
+        assert_eq!(welsh_locale.communication_format().language(), Language::Welsh);
+
+        let standard = preferences(r#"{"preferred_channels": ["Post"]}"#).unwrap();
+        assert_eq!(standard.communication_format(), CommunicationFormat::new(Language::English, AccessibleFormat::Standard));
+
+        assert!(matches!(preferences(r#"{"preferred_channels": ["Post"], "accessible_format": "Moon"}"#), Err(ValidationError::InvalidInput(_))));
+        assert!(matches!(preferences(r#"{"preferred_channels": ["Post"], "language": "Cornish"}"#), Err(ValidationError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_invalid_preferences() {
+        assert!(matches!(preferences(r#"{"preferred_channels": []}"#), Err(ValidationError::EmptyInput(_))));
+        assert!(matches!(preferences(r#"{"preferred_channels": ["Email", "Email"]}"#), Err(ValidationError::InvalidInput(_))));
+        assert!(matches!(preferences(r#"{"preferred_channels": ["Fax"]}"#), Err(ValidationError::InvalidInput(_))));
+        let backwards_quiet_period = preferences(r#"{"preferred_channels": ["Post"], "quiet_periods": [{"first_day": "10/05/2024", "last_day": "01/05/2024"}]}"#);
+        let Err(error @ ValidationError::InvalidDate(_)) = backwards_quiet_period else {
+            panic!("Expected an invalid date");
+        };
+        assert_eq!(error.to_string(), "Invalid date: Quiet period must not end before it starts.");
+        assert_eq!(error.message(Language::Welsh), "Dyddiad annilys: Ni chaiff cyfnod tawel ddod i ben cyn iddo ddechrau.");
+    }
+
+    #[test]
+    fn test_default_preferences_follow_contact_details() {
+        let electronic = ContactPreferences::default_for(&validated_contact(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT));
+        assert_eq!(electronic.preferred_channels(), &vec![ContactChannel::Email, ContactChannel::Post]);
+
+        let post = ContactPreferences::default_for(&validated_contact(JOINT_INDIVIDUALS_POST_CONTACT));
+        assert_eq!(post.preferred_channels(), &vec![ContactChannel::Post]);
+
+        let review_information = ReviewInformationFixture::new(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT).validated();
+        assert_eq!(review_information.contact_preferences(), &electronic);
+    }
+}
@@ -0,0 +1,537 @@
+//! Delivery state of each outbound invite, with fallback from email to post.
+//!
+//! Every invite moves through `Queued -> Sent -> Delivered`, ending in
+//! `Bounced` when an email or text message is refused or `Returned` when a
+//! letter comes back. An email that bounces is replaced by a letter when a
+//! postal address is on file; otherwise the client is recorded as
+//! uncontactable so that someone follows it up.
+
+use chrono::NaiveDateTime;
+use serde::{Serialize, Deserialize};
+
+use crate::simple_types::ReviewId;
+use super::{
+    invites::AnnualReviewInvite,
+    mail_transport::InviteDeliveryEvent,
+    print_export::PrintStatusEvent,
+    types::ValidatedClientContactInformation,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeliveryTrackingError {
+    UnknownReference(String),
+    DuplicateReference(String),
+    InvalidTransition(String),
+    MissingDeliveryAttempt(String),
+}
+
+impl std::fmt::Display for DeliveryTrackingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            DeliveryTrackingError::UnknownReference(ref desc) => write!(f, "Unknown invite reference: {}", desc),
+            DeliveryTrackingError::DuplicateReference(ref desc) => write!(f, "Duplicate invite reference: {}", desc),
+            DeliveryTrackingError::InvalidTransition(ref desc) => write!(f, "Invalid delivery transition: {}", desc),
+            DeliveryTrackingError::MissingDeliveryAttempt(ref desc) => write!(f, "No delivery attempt for invite: {}", desc),
+        }
+    }
+}
+
+impl std::error::Error for DeliveryTrackingError {}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DeliveryState {
+    Queued,
+    Sent,
+    Delivered,
+    /// An email refused by the recipient's mail server.
+    Bounced,
+    /// A letter returned to sender by the postal service.
+    Returned,
+}
+
+impl DeliveryState {
+    pub fn is_final(&self) -> bool {
+        matches!(self, DeliveryState::Delivered | DeliveryState::Bounced | DeliveryState::Returned)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DeliveryChannel {
+    Email,
+    Post,
+    Sms,
+}
+
+/// An invite being delivered. Email invites are referenced by Message-ID and
+/// letters by their print manifest reference.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OutboundInvite {
+    reference: String,
+    review_id: ReviewId,
+    invite: AnnualReviewInvite,
+    state: DeliveryState,
+    fallback_for: Option<String>,
+    queued_at: NaiveDateTime,
+}
+
+impl OutboundInvite {
+    pub fn reference(&self) -> &String {
+        &self.reference
+    }
+
+    pub fn review_id(&self) -> &ReviewId {
+        &self.review_id
+    }
+
+    pub fn invite(&self) -> &AnnualReviewInvite {
+        &self.invite
+    }
+
+    pub fn state(&self) -> DeliveryState {
+        self.state
+    }
+
+    pub fn queued_at(&self) -> NaiveDateTime {
+        self.queued_at
+    }
+
+    /// The reference of the failed email this letter replaces.
+    pub fn fallback_for(&self) -> Option<&String> {
+        self.fallback_for.as_ref()
+    }
+
+    pub fn channel(&self) -> DeliveryChannel {
+        match self.invite {
+            AnnualReviewInvite::SendElectronicAnnualReviewInvite(_) => DeliveryChannel::Email,
+            AnnualReviewInvite::SendPostAnnualReviewInvite(_) => DeliveryChannel::Post,
+            AnnualReviewInvite::SendSmsAnnualReviewInvite(_) => DeliveryChannel::Sms,
+        }
+    }
+}
+
+/// Events recorded on the review's compliance file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DeliveryTrackingEvent {
+    InviteQueued { review_id: ReviewId, reference: String, channel: DeliveryChannel, at: NaiveDateTime },
+    DeliveryStateChanged { review_id: ReviewId, reference: String, from: DeliveryState, to: DeliveryState, reason: String, at: NaiveDateTime },
+    /// A transient failure; the invite stays queued to be sent again.
+    DeliveryDeferred { review_id: ReviewId, reference: String, reason: String, at: NaiveDateTime },
+    FellBackToPost { review_id: ReviewId, failed_reference: String, postal_reference: String, at: NaiveDateTime },
+    ClientUncontactable { review_id: ReviewId, reference: String, reason: String, at: NaiveDateTime },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct InviteDeliveryTracker {
+    invites: Vec<OutboundInvite>,
+}
+
+impl InviteDeliveryTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn invites(&self) -> &Vec<OutboundInvite> {
+        &self.invites
+    }
+
+    pub fn invite(&self, reference: &str) -> Option<&OutboundInvite> {
+        self.invites.iter().find(|invite| invite.reference == reference)
+    }
+
+    /// Removes the invite, e.g. when the client's personal data is erased.
+    pub fn remove(&mut self, reference: &str) -> Option<OutboundInvite> {
+        let index = self.invites.iter().position(|invite| invite.reference == reference)?;
+        Some(self.invites.remove(index))
+    }
+
+    pub fn queue(&mut self, reference: &str, review_id: &ReviewId, invite: AnnualReviewInvite, at: NaiveDateTime) -> Result<Vec<DeliveryTrackingEvent>, DeliveryTrackingError> {
+        self.push(reference, review_id, invite, None, at).map(|event| vec![event])
+    }
+
+    /// Moves an invite to `state`. A bounced email falls back to a letter when
+    /// `contact` has a postal address on file for it. Nothing changes if the
+    /// move or the fallback is not possible.
+    pub fn record_state(
+        &mut self,
+        reference: &str,
+        state: DeliveryState,
+        reason: &str,
+        contact: &ValidatedClientContactInformation,
+        at: NaiveDateTime,
+    ) -> Result<Vec<DeliveryTrackingEvent>, DeliveryTrackingError> {
+        let index = self.invites.iter().position(|invite| invite.reference == reference)
+            .ok_or_else(|| DeliveryTrackingError::UnknownReference(reference.to_string()))?;
+        let invite = self.invites[index].clone();
+        check_transition(&invite, state)?;
+
+        let fallback = match (state, &invite.invite) {
+            (DeliveryState::Bounced, AnnualReviewInvite::SendElectronicAnnualReviewInvite(electronic)) => {
+                contact.postal_address_on_file(electronic.email_address()).map(|postal_address| (
+                    format!("{}-post", reference),
+                    AnnualReviewInvite::SendPostAnnualReviewInvite(electronic.to_post_invite(postal_address.clone())),
+                ))
+            }
+            _ => None,
+        };
+        if let Some((postal_reference, _)) = &fallback {
+            if self.invite(postal_reference).is_some() {
+                return Err(DeliveryTrackingError::DuplicateReference(postal_reference.clone()));
+            }
+        }
+
+        self.invites[index].state = state;
+        let mut events = vec![DeliveryTrackingEvent::DeliveryStateChanged {
+            review_id: invite.review_id.clone(),
+            reference: reference.to_string(),
+            from: invite.state,
+            to: state,
+            reason: reason.to_string(),
+            at,
+        }];
+
+        match (state, &invite.invite) {
+            (DeliveryState::Bounced, AnnualReviewInvite::SendElectronicAnnualReviewInvite(_)) => match fallback {
+                Some((postal_reference, postal_invite)) => {
+                    events.push(self.push(&postal_reference, &invite.review_id, postal_invite, Some(reference.to_string()), at)?);
+                    events.push(DeliveryTrackingEvent::FellBackToPost {
+                        review_id: invite.review_id.clone(),
+                        failed_reference: reference.to_string(),
+                        postal_reference,
+                        at,
+                    });
+                }
+                None => events.push(uncontactable(&invite, "Email bounced and no postal address is on file", at)),
+            },
+            (DeliveryState::Bounced, AnnualReviewInvite::SendSmsAnnualReviewInvite(_)) => {
+                events.push(uncontactable(&invite, "Text message could not be delivered", at))
+            }
+            (DeliveryState::Returned, _) => events.push(uncontactable(&invite, "Letter returned to sender", at)),
+            _ => {}
+        }
+
+        Ok(events)
+    }
+
+    /// Applies the outcome of sending an email. Accepted recipients make the
+    /// invite sent, permanent failures bounce it and transient failures defer it.
+    /// The events are applied together: if any cannot be, none are.
+    pub fn record_email_delivery(
+        &mut self,
+        delivery_events: &[InviteDeliveryEvent],
+        contact: &ValidatedClientContactInformation,
+    ) -> Result<Vec<DeliveryTrackingEvent>, DeliveryTrackingError> {
+        let mut staged = self.clone();
+        let mut events = Vec::new();
+
+        for delivery_event in delivery_events {
+            let (message_id, failure, at) = match delivery_event {
+                InviteDeliveryEvent::DeliveryAttempted { .. } => continue,
+                InviteDeliveryEvent::RecipientAccepted { message_id, .. } => {
+                    let at = attempted_at(delivery_events, message_id)?;
+                    // A retried email can be accepted more than once; only the first moves it to sent.
+                    if staged.invite(message_id).map(OutboundInvite::state) != Some(DeliveryState::Sent) {
+                        events.extend(staged.record_state(message_id, DeliveryState::Sent, "Accepted by mail server", contact, at)?);
+                    }
+                    continue;
+                }
+                InviteDeliveryEvent::RecipientRejected { message_id, failure, .. }
+                | InviteDeliveryEvent::DeliveryFailed { message_id, failure, .. } => (message_id, failure, attempted_at(delivery_events, message_id)?),
+            };
+
+            let reason = match failure.code() {
+                Some(code) => format!("{} {}", code, failure.reason()),
+                None => failure.reason().clone(),
+            };
+            if failure.is_permanent() {
+                events.extend(staged.record_state(message_id, DeliveryState::Bounced, &reason, contact, at)?);
+            } else {
+                let invite = staged.invite(message_id).ok_or_else(|| DeliveryTrackingError::UnknownReference(message_id.clone()))?;
+                events.push(DeliveryTrackingEvent::DeliveryDeferred { review_id: invite.review_id.clone(), reference: message_id.clone(), reason, at });
+            }
+        }
+
+        *self = staged;
+        Ok(events)
+    }
+
+    /// Marks letters as sent once the print vendor has posted them. The status
+    /// events are applied together: if any cannot be, none are.
+    pub fn record_print_status(
+        &mut self,
+        status_events: &[PrintStatusEvent],
+        contact: &ValidatedClientContactInformation,
+    ) -> Result<Vec<DeliveryTrackingEvent>, DeliveryTrackingError> {
+        let mut staged = self.clone();
+        let mut events = Vec::new();
+
+        for status_event in status_events {
+            if let PrintStatusEvent::LetterPosted { reference, posted_at, .. } = status_event {
+                events.extend(staged.record_state(reference, DeliveryState::Sent, "Posted by print vendor", contact, *posted_at)?);
+            }
+        }
+
+        *self = staged;
+        Ok(events)
+    }
+
+    fn push(&mut self, reference: &str, review_id: &ReviewId, invite: AnnualReviewInvite, fallback_for: Option<String>, at: NaiveDateTime) -> Result<DeliveryTrackingEvent, DeliveryTrackingError> {
+        if self.invite(reference).is_some() {
+            return Err(DeliveryTrackingError::DuplicateReference(reference.to_string()));
+        }
+
+        let invite = OutboundInvite { reference: reference.to_string(), review_id: review_id.clone(), invite, state: DeliveryState::Queued, fallback_for, queued_at: at };
+        let event = DeliveryTrackingEvent::InviteQueued { review_id: review_id.clone(), reference: reference.to_string(), channel: invite.channel(), at };
+        self.invites.push(invite);
+        Ok(event)
+    }
+}
+
+/// When the email was sent, from the latest attempt among the delivery events.
+fn attempted_at(delivery_events: &[InviteDeliveryEvent], message_id: &str) -> Result<NaiveDateTime, DeliveryTrackingError> {
+    delivery_events.iter().filter_map(|event| match event {
+        InviteDeliveryEvent::DeliveryAttempted { message_id: attempted, attempt, attempted_at, .. } if attempted == message_id => Some((*attempt, *attempted_at)),
+        _ => None,
+    })
+        .max_by_key(|(attempt, _)| *attempt)
+        .map(|(_, attempted_at)| attempted_at)
+        .ok_or_else(|| DeliveryTrackingError::MissingDeliveryAttempt(message_id.to_string()))
+}
+
+fn check_transition(invite: &OutboundInvite, to: DeliveryState) -> Result<(), DeliveryTrackingError> {
+    let allowed = matches!(
+        (invite.state, to, invite.channel()),
+        (DeliveryState::Queued, DeliveryState::Sent, _)
+            | (DeliveryState::Queued | DeliveryState::Sent, DeliveryState::Bounced, DeliveryChannel::Email | DeliveryChannel::Sms)
+            | (DeliveryState::Sent, DeliveryState::Delivered, _)
+            | (DeliveryState::Sent, DeliveryState::Returned, DeliveryChannel::Post)
+    );
+
+    if allowed {
+        Ok(())
+    } else {
+        Err(DeliveryTrackingError::InvalidTransition(format!(
+            "{:?} invite {} cannot move from {:?} to {:?}", invite.channel(), invite.reference, invite.state, to,
+        )))
+    }
+}
+
+fn uncontactable(invite: &OutboundInvite, reason: &str, at: NaiveDateTime) -> DeliveryTrackingEvent {
+    DeliveryTrackingEvent::ClientUncontactable { review_id: invite.review_id.clone(), reference: invite.reference.clone(), reason: reason.to_string(), at }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::{simple_types::EmailAddress, tests::test_utils::shared::{validated_contact, ReviewInformationFixture, JOINT_INDIVIDUALS_POST_CONTACT, REVIEW_ID, SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT}};
+    use super::super::{
+        invites::InviteContentReference,
+        mail_transport::{DeliveryFailure, DeliveryFailureKind},
+    };
+    use super::*;
+
+    const MESSAGE_ID: &str = "<20240501083000.7c9e6679@gcwm.co.uk>";
+
+    const SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT_WITH_ADDRESS: &str = r#"{
+        "type": "SingleIndividualElectronicContact",
+        "first_name": "Siân",
+        "email_address": "sian@example.com",
+        "postal_address": {
+            "house_name": "",
+            "house_number": "4",
+            "address_line_one": "Mill Road",
+            "address_line_two": "",
+            "address_line_three": "",
+            "address_line_four": "",
+            "city": "Guildford",
+            "county": "Surrey",
+            "postcode": "GU1 2AB",
+            "country": ""
+        }
+    }"#;
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn review_id() -> ReviewId {
+        ReviewId::try_from(REVIEW_ID.to_string()).unwrap()
+    }
+
+    fn tracker_with_invite(client_contact_information: &str, reference: &str) -> InviteDeliveryTracker {
+        let invite = ReviewInformationFixture::new(client_contact_information).invites().remove(0);
+
+        let mut tracker = InviteDeliveryTracker::new();
+        tracker.queue(reference, &review_id(), invite, at(8)).unwrap();
+        tracker
+    }
+
+    fn rejected(kind: DeliveryFailureKind, code: u16) -> Vec<InviteDeliveryEvent> {
+        vec![
+            InviteDeliveryEvent::DeliveryAttempted { review_id: review_id(), message_id: MESSAGE_ID.to_string(), attempt: 1, attempted_at: at(9) },
+            InviteDeliveryEvent::RecipientRejected {
+                review_id: review_id(),
+                message_id: MESSAGE_ID.to_string(),
+                recipient: EmailAddress::try_from("sian@example.com".to_string()).unwrap(),
+                failure: DeliveryFailure::new(kind, Some(code), "Mailbox unavailable"),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_email_lifecycle() {
+        let contact = validated_contact(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT);
+        let mut tracker = tracker_with_invite(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, MESSAGE_ID);
+
+        tracker.record_state(MESSAGE_ID, DeliveryState::Sent, "Accepted", &contact, at(9)).unwrap();
+        let events = tracker.record_state(MESSAGE_ID, DeliveryState::Delivered, "Delivery receipt", &contact, at(10)).unwrap();
+
+        assert_eq!(events, vec![DeliveryTrackingEvent::DeliveryStateChanged {
+            review_id: review_id(),
+            reference: MESSAGE_ID.to_string(),
+            from: DeliveryState::Sent,
+            to: DeliveryState::Delivered,
+            reason: "Delivery receipt".to_string(),
+            at: at(10),
+        }]);
+        assert!(tracker.invite(MESSAGE_ID).unwrap().state().is_final());
+    }
+
+    #[test]
+    fn test_hard_bounce_falls_back_to_post() {
+        let contact = validated_contact(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT_WITH_ADDRESS);
+        let mut tracker = tracker_with_invite(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT_WITH_ADDRESS, MESSAGE_ID);
+
+        let events = tracker.record_email_delivery(&rejected(DeliveryFailureKind::Permanent, 550), &contact).unwrap();
+
+        let postal_reference = format!("{}-post", MESSAGE_ID);
+        assert_eq!(events, vec![
+            DeliveryTrackingEvent::DeliveryStateChanged {
+                review_id: review_id(),
+                reference: MESSAGE_ID.to_string(),
+                from: DeliveryState::Queued,
+                to: DeliveryState::Bounced,
+                reason: "550 Mailbox unavailable".to_string(),
+                at: at(9),
+            },
+            DeliveryTrackingEvent::InviteQueued { review_id: review_id(), reference: postal_reference.clone(), channel: DeliveryChannel::Post, at: at(9) },
+            DeliveryTrackingEvent::FellBackToPost { review_id: review_id(), failed_reference: MESSAGE_ID.to_string(), postal_reference: postal_reference.clone(), at: at(9) },
+        ]);
+
+        let fallback = tracker.invite(&postal_reference).unwrap();
+        assert_eq!(fallback.fallback_for(), Some(&MESSAGE_ID.to_string()));
+        let AnnualReviewInvite::SendPostAnnualReviewInvite(letter) = fallback.invite() else {
+            panic!("Expected a postal invite");
+        };
+        assert_eq!(letter.content_reference(), InviteContentReference::SingleIndividualPostInvite);
+        assert_eq!(letter.postal_address().postcode().value(), "GU1 2AB");
+    }
+
+    #[test]
+    fn test_hard_bounce_without_address_is_recorded() {
+        let contact = validated_contact(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT);
+        let mut tracker = tracker_with_invite(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, MESSAGE_ID);
+
+        let events = tracker.record_email_delivery(&rejected(DeliveryFailureKind::Permanent, 550), &contact).unwrap();
+
+        assert!(matches!(events.last(), Some(DeliveryTrackingEvent::ClientUncontactable { .. })));
+        assert_eq!(tracker.invites().len(), 1);
+    }
+
+    #[test]
+    fn test_transient_failure_is_deferred() {
+        let contact = validated_contact(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT_WITH_ADDRESS);
+        let mut tracker = tracker_with_invite(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT_WITH_ADDRESS, MESSAGE_ID);
+
+        let events = tracker.record_email_delivery(&rejected(DeliveryFailureKind::Transient, 452), &contact).unwrap();
+
+        assert!(matches!(&events[..], [DeliveryTrackingEvent::DeliveryDeferred { .. }]));
+        assert_eq!(tracker.invite(MESSAGE_ID).unwrap().state(), DeliveryState::Queued);
+    }
+
+    #[test]
+    fn test_retried_email_is_sent_at_the_latest_attempt() {
+        let contact = validated_contact(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT_WITH_ADDRESS);
+        let mut tracker = tracker_with_invite(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT_WITH_ADDRESS, MESSAGE_ID);
+
+        let mut delivery_events = rejected(DeliveryFailureKind::Transient, 452);
+        delivery_events.extend([
+            InviteDeliveryEvent::DeliveryAttempted { review_id: review_id(), message_id: MESSAGE_ID.to_string(), attempt: 2, attempted_at: at(11) },
+            InviteDeliveryEvent::RecipientAccepted {
+                review_id: review_id(),
+                message_id: MESSAGE_ID.to_string(),
+                recipient: EmailAddress::try_from("sian@example.com".to_string()).unwrap(),
+            },
+        ]);
+        let events = tracker.record_email_delivery(&delivery_events, &contact).unwrap();
+
+        assert!(matches!(events.last(), Some(DeliveryTrackingEvent::DeliveryStateChanged { to: DeliveryState::Sent, at: sent_at, .. }) if *sent_at == at(11)));
+    }
+
+    #[test]
+    fn test_returned_letter() {
+        let contact = validated_contact(JOINT_INDIVIDUALS_POST_CONTACT);
+        let reference = format!("{}-01", REVIEW_ID);
+        let mut tracker = tracker_with_invite(JOINT_INDIVIDUALS_POST_CONTACT, &reference);
+
+        tracker.record_print_status(&[PrintStatusEvent::LetterPosted { review_id: review_id(), reference: reference.clone(), posted_at: at(16) }], &contact).unwrap();
+        assert_eq!(tracker.invite(&reference).unwrap().state(), DeliveryState::Sent);
+
+        assert!(matches!(
+            tracker.record_state(&reference, DeliveryState::Bounced, "Bounce", &contact, at(17)),
+            Err(DeliveryTrackingError::InvalidTransition(_)),
+        ));
+
+        let events = tracker.record_state(&reference, DeliveryState::Returned, "Addressee gone away", &contact, at(17)).unwrap();
+        assert!(matches!(events.last(), Some(DeliveryTrackingEvent::ClientUncontactable { .. })));
+    }
+
+    #[test]
+    fn test_failed_fallback_leaves_state_unchanged() {
+        let contact = validated_contact(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT_WITH_ADDRESS);
+        let mut tracker = tracker_with_invite(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT_WITH_ADDRESS, MESSAGE_ID);
+        let letter = tracker.invite(MESSAGE_ID).unwrap().invite().clone();
+        tracker.queue(&format!("{}-post", MESSAGE_ID), &review_id(), letter, at(8)).unwrap();
+
+        assert!(matches!(
+            tracker.record_state(MESSAGE_ID, DeliveryState::Bounced, "Bounce", &contact, at(9)),
+            Err(DeliveryTrackingError::DuplicateReference(_)),
+        ));
+        assert_eq!(tracker.invite(MESSAGE_ID).unwrap().state(), DeliveryState::Queued);
+    }
+
+    #[test]
+    fn test_failed_email_delivery_batch_is_not_applied() {
+        let contact = validated_contact(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT);
+        let mut tracker = tracker_with_invite(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, MESSAGE_ID);
+        let accepted = InviteDeliveryEvent::RecipientAccepted {
+            review_id: review_id(),
+            message_id: MESSAGE_ID.to_string(),
+            recipient: EmailAddress::try_from("sian@example.com".to_string()).unwrap(),
+        };
+
+        let mut events = rejected(DeliveryFailureKind::Permanent, 550);
+        events.insert(1, accepted.clone());
+        events.push(InviteDeliveryEvent::DeliveryFailed {
+            review_id: review_id(),
+            message_id: "<unknown@gcwm.co.uk>".to_string(),
+            failure: DeliveryFailure::new(DeliveryFailureKind::Permanent, None, "Connection refused"),
+        });
+        assert!(matches!(tracker.record_email_delivery(&events, &contact), Err(DeliveryTrackingError::MissingDeliveryAttempt(_))));
+        assert_eq!(tracker.invite(MESSAGE_ID).unwrap().state(), DeliveryState::Queued);
+
+        assert!(matches!(tracker.record_email_delivery(&[accepted], &contact), Err(DeliveryTrackingError::MissingDeliveryAttempt(_))));
+        assert_eq!(tracker.invite(MESSAGE_ID).unwrap().state(), DeliveryState::Queued);
+    }
+
+    #[test]
+    fn test_unknown_and_duplicate_references() {
+        let contact = validated_contact(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT);
+        let mut tracker = tracker_with_invite(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, MESSAGE_ID);
+
+        assert!(matches!(tracker.record_state("missing", DeliveryState::Sent, "", &contact, at(9)), Err(DeliveryTrackingError::UnknownReference(_))));
+
+        let invite = tracker.invite(MESSAGE_ID).unwrap().invite().clone();
+        assert!(matches!(tracker.queue(MESSAGE_ID, &review_id(), invite, at(9)), Err(DeliveryTrackingError::DuplicateReference(_))));
+    }
+}
@@ -0,0 +1,204 @@
+use chrono::NaiveDateTime;
+
+use crate::{mime::{EmailMessage, Mailbox, MimePart}, simple_types::{EmailAddress, ReviewId}};
+use super::{
+    calendar_invite::BookedReviewMeeting,
+    invites::SendElectronicAnnualReviewInvite,
+    templates::{join_names, RenderedEmail},
+    types::ValidatedAnnualReviewInformation,
+};
+
+const MESSAGE_ID_DOMAIN: &str = "gcwm.co.uk";
+const CALENDAR_FILENAME: &str = "invite.ics";
+
+/// A review invite email ready to hand to a mail transport.
+#[derive(Clone, PartialEq)]
+pub struct InviteEmail {
+    message_id: String,
+    recipient: EmailAddress,
+    message: EmailMessage,
+}
+
+/// The message itself is left out as its headers and body hold the client's details.
+impl std::fmt::Debug for InviteEmail {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InviteEmail")
+            .field("message_id", &self.message_id)
+            .field("recipient", &self.recipient)
+            .finish_non_exhaustive()
+    }
+}
+
+impl InviteEmail {
+    /// The Message-ID including the angle brackets.
+    pub fn message_id(&self) -> &String {
+        &self.message_id
+    }
+
+    pub fn recipient(&self) -> &EmailAddress {
+        &self.recipient
+    }
+
+    pub fn message(&self) -> &EmailMessage {
+        &self.message
+    }
+
+    pub fn to_rfc5322_string(&self) -> String {
+        self.message.to_rfc5322_string()
+    }
+}
+
+/// Builds the MIME messages for the electronic invites of a review. Replies go
+/// to the administrator email of the review rather than the sender.
+#[derive(Debug, Clone)]
+pub struct InviteEmailBuilder<'a> {
+    sender: Mailbox,
+    review_id: &'a ReviewId,
+    review_information: &'a ValidatedAnnualReviewInformation,
+    calendar_invite: Option<&'a BookedReviewMeeting>,
+}
+
+impl<'a> InviteEmailBuilder<'a> {
+    pub fn new(sender: Mailbox, review_id: &'a ReviewId, review_information: &'a ValidatedAnnualReviewInformation) -> Self {
+        Self { sender, review_id, review_information, calendar_invite: None }
+    }
+
+    /// Attaches the meeting as an `.ics` file and a `text/calendar` alternative
+    /// so calendar clients offer to add it.
+    pub fn with_calendar_invite(mut self, meeting: &'a BookedReviewMeeting) -> Self {
+        self.calendar_invite = Some(meeting);
+        self
+    }
+
+    /// Builds the message for `invite` with its rendered content. `sent_at` is
+    /// the UTC time used for the Date header, Message-ID and calendar stamp.
+    pub fn build(&self, invite: &SendElectronicAnnualReviewInvite, content: &RenderedEmail, sent_at: NaiveDateTime) -> InviteEmail {
+        let recipient_hash = format!("{:016x}", fnv1a(invite.email_address().value().to_lowercase().as_bytes()));
+        let message_id = format!(
+            "<{}.{}.{}@{}>",
+            sent_at.format("%Y%m%d%H%M%S"), self.review_id.value(), recipient_hash, MESSAGE_ID_DOMAIN,
+        );
+
+        let mut alternatives = vec![
+            MimePart::text("plain", content.text_body()),
+            MimePart::text("html", content.html_body()),
+        ];
+        let mut attachments = Vec::new();
+
+        if let Some(meeting) = self.calendar_invite {
+            let ics = meeting.to_ics(sent_at);
+            alternatives.push(MimePart::binary(&format!("text/calendar; charset=UTF-8; method={}", meeting.method()), ics.as_bytes()));
+            attachments.push(MimePart::attachment("application/ics", CALENDAR_FILENAME, ics.as_bytes()));
+        }
+
+        let alternative = MimePart::multipart("alternative", &format!("=_alt_{}", recipient_hash), alternatives);
+        let body = if attachments.is_empty() {
+            alternative
+        } else {
+            let mut parts = vec![alternative];
+            parts.extend(attachments);
+            MimePart::multipart("mixed", &format!("=_mixed_{}", recipient_hash), parts)
+        };
+
+        let recipient = Mailbox::new(Some(&join_names(invite.recipient_names(), invite.format().language())), invite.email_address().clone());
+        let reply_to = Mailbox::new(None, self.review_information.administrator_email().clone());
+
+        let message = EmailMessage::new(body)
+            .with_mailbox_header("From", std::slice::from_ref(&self.sender))
+            .with_mailbox_header("To", &[recipient])
+            .with_mailbox_header("Reply-To", &[reply_to])
+            .with_text_header("Subject", content.subject())
+            .with_date(sent_at)
+            .with_header("Message-ID", &message_id)
+            .with_header("Content-Language", invite.format().language().code());
+
+        InviteEmail { message_id, recipient: invite.email_address().clone(), message }
+    }
+}
+
+/// 64 bit FNV-1a, used to keep Message-IDs and boundaries stable for a recipient
+/// without putting their address in the headers.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::tests::test_utils::shared::{assert_snapshot, ReviewInformationFixture, JOINT_INDIVIDUALS_ELECTRONIC_CONTACT, REVIEW_ID, SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT};
+    use super::super::{adviser_calendar::MeetingLocation, slot_finder::MeetingSlot, templates::InviteTemplates};
+    use super::*;
+
+    fn sent_at() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(8, 30, 0).unwrap()
+    }
+
+    fn sender() -> Mailbox {
+        Mailbox::new(Some("Grafton Court Wealth Management"), EmailAddress::try_from("invites@gcwm.co.uk".to_string()).unwrap())
+    }
+
+    fn build(fixture: &ReviewInformationFixture, meeting: Option<&BookedReviewMeeting>) -> Vec<InviteEmail> {
+        let review_information = fixture.validated();
+        let review_id = ReviewId::try_from(REVIEW_ID.to_string()).unwrap();
+        let templates = InviteTemplates::bundled().unwrap();
+        let mut builder = InviteEmailBuilder::new(sender(), &review_id, &review_information);
+        if let Some(meeting) = meeting {
+            builder = builder.with_calendar_invite(meeting);
+        }
+
+        fixture.electronic_invites().iter().map(|invite| {
+            let content = templates.render_email(invite, review_information.adviser_name()).unwrap();
+            builder.build(invite, &content, sent_at())
+        }).collect()
+    }
+
+    #[test]
+    fn test_invite_email_snapshot() {
+        let emails = build(&ReviewInformationFixture::new(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT), None);
+
+        assert_eq!(emails.len(), 1);
+        assert_snapshot("book_review_meeting/single_individual_invite.eml", &emails[0].to_rfc5322_string());
+    }
+
+    #[test]
+    fn test_invite_email_with_calendar_snapshot() {
+        let fixture = ReviewInformationFixture::new(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT);
+        let review_information = fixture.validated();
+        let start = NaiveDate::from_ymd_opt(2024, 6, 12).unwrap().and_hms_opt(10, 0, 0).unwrap();
+        let meeting = BookedReviewMeeting::new(
+            ReviewId::try_from(REVIEW_ID.to_string()).unwrap(),
+            MeetingSlot::new(start, start + chrono::Duration::hours(1), MeetingLocation::Telephone).unwrap(),
+            review_information.adviser_name().clone(),
+            review_information.administrator_email().clone(),
+            review_information.client_contact_information(),
+        ).unwrap();
+        let emails = build(&fixture, Some(&meeting));
+
+        assert_snapshot("book_review_meeting/single_individual_invite_with_calendar.eml", &emails[0].to_rfc5322_string());
+    }
+
+    #[test]
+    fn test_headers() {
+        let email = &build(&ReviewInformationFixture::new(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT), None)[0];
+        let message = email.message();
+
+        assert_eq!(message.header("From"), Some("Grafton Court Wealth Management <invites@gcwm.co.uk>"));
+        assert_eq!(message.header("To"), Some("=?UTF-8?B?U2nDom4=?= <sian@example.com>"));
+        assert_eq!(message.header("Reply-To"), Some("reviews@gcwm.co.uk"));
+        assert_eq!(message.header("Date"), Some("Wed, 01 May 2024 08:30:00 +0000"));
+        assert_eq!(message.header("Message-ID"), Some(email.message_id().as_str()));
+        assert!(email.to_rfc5322_string().is_ascii());
+    }
+
+    #[test]
+    fn test_message_ids_differ_per_recipient() {
+        let emails = build(&ReviewInformationFixture::new(JOINT_INDIVIDUALS_ELECTRONIC_CONTACT), None);
+
+        assert_eq!(emails.len(), 2);
+        assert_ne!(emails[0].message_id(), emails[1].message_id());
+        assert!(emails[0].message_id().starts_with(&format!("<20240501083000.{}.", REVIEW_ID)));
+        assert!(emails[0].message_id().ends_with("@gcwm.co.uk>"));
+        assert_eq!(emails[1].message().header("To"), Some("Sarah <sarah@example.com>"));
+    }
+}
@@ -0,0 +1,313 @@
+//! Text message invites, measured in SMS segments.
+//!
+//! A message that only uses the GSM 03.38 alphabet is sent with 7 bit
+//! encoding, 160 characters in a single segment or 153 per segment once split.
+//! Characters from the extension table, such as `€` and `[`, take two. Any
+//! other character, e.g. the `â` in Siân, switches the whole message to UCS-2,
+//! 70 code units in a single segment or 67 per segment.
+
+use serde::{Serialize, Deserialize};
+
+use crate::{redaction::Sensitive, simple_types::{ConstrainedIndividualNameString100, PhoneNumber}};
+use super::{
+    invites::SendSmsAnnualReviewInvite,
+    localisation::{format_date, message},
+    templates::join_names,
+};
+
+const GSM_BASIC_CHARACTERS: &str = "@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ !\"#¤%&'()*+,-./0123456789:;<=>?¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüà";
+const GSM_EXTENSION_CHARACTERS: &str = "\u{c}^{}\\[~]|€";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmsError {
+    TooLong(String),
+}
+
+impl std::fmt::Display for SmsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            SmsError::TooLong(ref desc) => write!(f, "Text message too long: {}", desc),
+        }
+    }
+}
+
+impl std::error::Error for SmsError {}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum SmsEncoding {
+    Gsm7,
+    Ucs2,
+}
+
+impl SmsEncoding {
+    fn single_segment_units(&self) -> usize {
+        match self {
+            SmsEncoding::Gsm7 => 160,
+            SmsEncoding::Ucs2 => 70,
+        }
+    }
+
+    fn multipart_segment_units(&self) -> usize {
+        match self {
+            SmsEncoding::Gsm7 => 153,
+            SmsEncoding::Ucs2 => 67,
+        }
+    }
+}
+
+/// The encoding of a message body and how many segments it is sent in.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct SmsLength {
+    encoding: SmsEncoding,
+    units: usize,
+    segments: usize,
+}
+
+impl SmsLength {
+    pub fn measure(body: &str) -> Self {
+        let gsm_units = body.chars().try_fold(0, |units, character| {
+            if GSM_BASIC_CHARACTERS.contains(character) {
+                Some(units + 1)
+            } else if GSM_EXTENSION_CHARACTERS.contains(character) {
+                Some(units + 2)
+            } else {
+                None
+            }
+        });
+
+        let (encoding, units) = match gsm_units {
+            Some(units) => (SmsEncoding::Gsm7, units),
+            None => (SmsEncoding::Ucs2, body.encode_utf16().count()),
+        };
+        let segments = if units <= encoding.single_segment_units() {
+            1
+        } else {
+            units.div_ceil(encoding.multipart_segment_units())
+        };
+
+        Self { encoding, units, segments }
+    }
+
+    pub fn encoding(&self) -> SmsEncoding {
+        self.encoding
+    }
+
+    /// Septets for GSM 7 bit messages or UTF-16 code units for UCS-2 messages.
+    pub fn units(&self) -> usize {
+        self.units
+    }
+
+    pub fn segments(&self) -> usize {
+        self.segments
+    }
+}
+
+/// A text message ready to hand to an SMS gateway.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SmsMessage {
+    recipient: PhoneNumber,
+    body: Sensitive<String>,
+    length: SmsLength,
+}
+
+impl SmsMessage {
+    pub fn new(recipient: PhoneNumber, body: &str) -> Self {
+        Self { recipient, body: Sensitive::new(body.to_string()), length: SmsLength::measure(body) }
+    }
+
+    pub fn recipient(&self) -> &PhoneNumber {
+        &self.recipient
+    }
+
+    pub fn body(&self) -> &String {
+        self.body.expose()
+    }
+
+    pub fn length(&self) -> SmsLength {
+        self.length
+    }
+}
+
+/// Renders SMS invites within a segment limit. The full wording is used when it
+/// fits, then a shorter wording, so long names or a booking link do not run
+/// a message into extra segments.
+#[derive(Debug, Clone)]
+pub struct SmsRenderer<'a> {
+    sender_name: &'a str,
+    adviser_name: &'a ConstrainedIndividualNameString100,
+    booking_url: Option<&'a str>,
+    max_segments: usize,
+}
+
+impl<'a> SmsRenderer<'a> {
+    pub fn new(sender_name: &'a str, adviser_name: &'a ConstrainedIndividualNameString100) -> Self {
+        Self { sender_name, adviser_name, booking_url: None, max_segments: 2 }
+    }
+
+    /// A self-service booking link to include in place of asking the client to reply.
+    pub fn with_booking_url(mut self, booking_url: &'a str) -> Self {
+        self.booking_url = Some(booking_url);
+        self
+    }
+
+    pub fn with_max_segments(mut self, max_segments: usize) -> Self {
+        self.max_segments = max_segments;
+        self
+    }
+
+    pub fn render(&self, invite: &SendSmsAnnualReviewInvite) -> Result<SmsMessage, SmsError> {
+        let full_body = self.full_body(invite);
+        let message = SmsMessage::new(invite.phone_number().clone(), &full_body);
+        if message.length().segments() <= self.max_segments {
+            return Ok(message);
+        }
+
+        let short_body = self.short_body(invite);
+        let message = SmsMessage::new(invite.phone_number().clone(), &short_body);
+        if message.length().segments() <= self.max_segments {
+            return Ok(message);
+        }
+
+        Err(SmsError::TooLong(format!(
+            "{} segments needed for {}, at most {} allowed", message.length().segments(), invite.phone_number(), self.max_segments,
+        )))
+    }
+
+    fn full_body(&self, invite: &SendSmsAnnualReviewInvite) -> String {
+        let language = invite.language();
+        let review = match invite.organisation_name() {
+            Some(organisation_name) => message(language, "sms.organisation_review", &[organisation_name.value()]),
+            None => message(language, "sms.your_review", &[]),
+        };
+        let reply_deadline = format_date(language, invite.reply_deadline());
+        let call_to_action = match self.booking_url {
+            Some(booking_url) => message(language, "sms.book_by_link", &[&reply_deadline, booking_url]),
+            None => message(language, "sms.reply_to_book", &[&reply_deadline]),
+        };
+
+        message(language, "sms.full", &[
+            &join_names(invite.recipient_names(), language),
+            &review,
+            self.adviser_name.value(),
+            self.sender_name,
+            &format_date(language, invite.annual_review_due_date().date()),
+            &call_to_action,
+        ])
+    }
+
+    fn short_body(&self, invite: &SendSmsAnnualReviewInvite) -> String {
+        let language = invite.language();
+        let reply_deadline = invite.reply_deadline().format("%d/%m").to_string();
+        let call_to_action = match self.booking_url {
+            Some(booking_url) => message(language, "sms.short_book_by_link", &[&reply_deadline, booking_url]),
+            None => message(language, "sms.short_reply_to_book", &[&reply_deadline]),
+        };
+
+        message(language, "sms.short", &[
+            self.sender_name,
+            &invite.annual_review_due_date().date().format("%d/%m/%Y").to_string(),
+            &call_to_action,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::tests::test_utils::shared::{ReviewInformationFixture, SINGLE_INDIVIDUAL_POST_CONTACT_WITH_MOBILE};
+    use super::super::{
+        contact_preferences::{AccessibleFormat, CommunicationFormat, ContactChannel, Language},
+        invites::{create_annual_review_invites_for_channel, AnnualReviewInvite},
+    };
+    use super::*;
+
+    const SENDER_NAME: &str = "Grafton Court Wealth Management";
+
+    fn sms_invite() -> (SendSmsAnnualReviewInvite, ConstrainedIndividualNameString100) {
+        let review_information = ReviewInformationFixture::new(SINGLE_INDIVIDUAL_POST_CONTACT_WITH_MOBILE).validated();
+        let invites = create_annual_review_invites_for_channel(
+            review_information.client_contact_information(),
+            review_information.annual_review_due_date(),
+            NaiveDate::from_ymd_opt(2024, 5, 31).unwrap(),
+            ContactChannel::Sms,
+        ).unwrap();
+        let AnnualReviewInvite::SendSmsAnnualReviewInvite(invite) = &invites[0] else {
+            panic!("Expected an SMS invite");
+        };
+        (invite.clone(), review_information.adviser_name().clone())
+    }
+
+    #[test]
+    fn test_gsm_message_lengths() {
+        assert_eq!(SmsLength::measure(&"a".repeat(160)), SmsLength { encoding: SmsEncoding::Gsm7, units: 160, segments: 1 });
+        assert_eq!(SmsLength::measure(&"a".repeat(161)).segments(), 2);
+        assert_eq!(SmsLength::measure(&"a".repeat(306)).segments(), 2);
+        assert_eq!(SmsLength::measure(&"a".repeat(307)).segments(), 3);
+        assert_eq!(SmsLength::measure("Fee: €100 [paid]"), SmsLength { encoding: SmsEncoding::Gsm7, units: 19, segments: 1 });
+        assert_eq!(SmsLength::measure("£5 à l'école").encoding(), SmsEncoding::Gsm7);
+    }
+
+    #[test]
+    fn test_ucs2_message_lengths() {
+        assert_eq!(SmsLength::measure("Siân"), SmsLength { encoding: SmsEncoding::Ucs2, units: 4, segments: 1 });
+        assert_eq!(SmsLength::measure(&format!("â{}", "a".repeat(69))).segments(), 1);
+        assert_eq!(SmsLength::measure(&format!("â{}", "a".repeat(70))).segments(), 2);
+        assert_eq!(SmsLength::measure("👋").units(), 2);
+    }
+
+    #[test]
+    fn test_full_message_is_rendered_when_it_fits() {
+        let (invite, adviser_name) = sms_invite();
+        let message = SmsRenderer::new(SENDER_NAME, &adviser_name).with_max_segments(3).render(&invite).unwrap();
+
+        assert_eq!(message.recipient().value(), "+447700900123");
+        assert_eq!(
+            message.body(),
+            "Hi Siân, your annual review with Mark Ashworth at Grafton Court Wealth Management is due on 30 June 2024. Please reply by 31 May 2024 to book a meeting.",
+        );
+        assert_eq!(message.length().encoding(), SmsEncoding::Ucs2);
+        assert_eq!(message.length().segments(), 3);
+    }
+
+    #[test]
+    fn test_short_message_is_rendered_when_full_message_is_too_long() {
+        let (invite, adviser_name) = sms_invite();
+        let message = SmsRenderer::new(SENDER_NAME, &adviser_name)
+            .with_booking_url("https://book.gcwm.co.uk/b?t=k1.abc.def")
+            .with_max_segments(2)
+            .render(&invite)
+            .unwrap();
+
+        assert_eq!(
+            message.body(),
+            "Grafton Court Wealth Management: annual review due 30/06/2024. Book by 31/05: https://book.gcwm.co.uk/b?t=k1.abc.def",
+        );
+        assert_eq!(message.length().encoding(), SmsEncoding::Gsm7);
+        assert_eq!(message.length().segments(), 1);
+    }
+
+    #[test]
+    fn test_welsh_message() {
+        let (invite, adviser_name) = sms_invite();
+        let AnnualReviewInvite::SendSmsAnnualReviewInvite(invite) = AnnualReviewInvite::SendSmsAnnualReviewInvite(invite)
+            .with_format(CommunicationFormat::new(Language::Welsh, AccessibleFormat::Standard)) else {
+            panic!("Expected an SMS invite");
+        };
+
+        let message = SmsRenderer::new(SENDER_NAME, &adviser_name).with_max_segments(3).render(&invite).unwrap();
+        assert_eq!(
+            message.body(),
+            "Helo Siân, mae eich adolygiad blynyddol gyda Mark Ashworth yn Grafton Court Wealth Management yn ddyledus ar 30 Mehefin 2024. Atebwch erbyn 31 Mai 2024 i drefnu cyfarfod.",
+        );
+    }
+
+    #[test]
+    fn test_message_too_long_for_segment_limit() {
+        let (invite, adviser_name) = sms_invite();
+        let booking_url = format!("https://book.gcwm.co.uk/b?t={}", "x".repeat(200));
+        let result = SmsRenderer::new(SENDER_NAME, &adviser_name).with_booking_url(&booking_url).with_max_segments(1).render(&invite);
+
+        assert!(matches!(result, Err(SmsError::TooLong(_))));
+    }
+}
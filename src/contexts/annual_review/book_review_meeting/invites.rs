@@ -0,0 +1,959 @@
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+
+use crate::simple_types::{ConstainedTrustOrCompanyNameString200, ConstrainedIndividualNameString100, EmailAddress, PhoneNumber, PostalAddress};
+use super::{
+    authorised_contacts::{AuthorisedContact, AuthorityScope},
+    bereavement::{ClientStatus, DeceasedClient, ExecutorContact},
+    contact_preferences::{ChannelDecision, CommunicationFormat, ContactChannel, Language},
+    types::{ValidatedAnnualReviewDueDate, ValidatedAnnualReviewInformation, ValidatedClientContactInformation, ValidationDetail, ValidationError},
+    vulnerability::{TrustedThirdParty, VulnerableClientAlert},
+};
+
+/// The invite content to be rendered for a recipient, one per contact variant.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum InviteContentReference {
+    JointIndividualsElectronicInvite,
+    SingleIndividualElectronicInvite,
+    MultipleTrusteesElectronicInvite,
+    PrimaryTrusteeElectronicInvite,
+    MultipleDirectorsElectronicInvite,
+    PrimaryDirectorElectronicInvite,
+    JointIndividualsPostInvite,
+    SingleIndividualPostInvite,
+    MultipleTrusteesPostInvite,
+    PrimaryTrusteePostInvite,
+    MultipleDirectorsPostInvite,
+    PrimaryDirectorPostInvite,
+    /// Sent to an attorney or deputy about the clients they act for.
+    AttorneyElectronicInvite,
+    AttorneyPostInvite,
+    /// Sent to the surviving individual of joint clients after the other has died.
+    BereavedIndividualElectronicInvite,
+    BereavedIndividualPostInvite,
+}
+
+impl InviteContentReference {
+    pub fn for_contact(client_contact_information: &ValidatedClientContactInformation) -> Self {
+        match client_contact_information {
+            ValidatedClientContactInformation::ValidatedJointIndividualsElectronicContact(_) => Self::JointIndividualsElectronicInvite,
+            ValidatedClientContactInformation::ValidatedSingleIndividualElectronicContact(_) => Self::SingleIndividualElectronicInvite,
+            ValidatedClientContactInformation::ValidatedMultipleTrusteesElectronicContact(_) => Self::MultipleTrusteesElectronicInvite,
+            ValidatedClientContactInformation::ValidatedPrimaryTrusteeElectronicContact(_) => Self::PrimaryTrusteeElectronicInvite,
+            ValidatedClientContactInformation::ValidatedMultipleDirectorsElectronicContact(_) => Self::MultipleDirectorsElectronicInvite,
+            ValidatedClientContactInformation::ValidatedPrimaryDirectorElectronicContact(_) => Self::PrimaryDirectorElectronicInvite,
+            ValidatedClientContactInformation::ValidatedJointIndividualsPostContact(_) => Self::JointIndividualsPostInvite,
+            ValidatedClientContactInformation::ValidatedSingleIndividualPostContact(_) => Self::SingleIndividualPostInvite,
+            ValidatedClientContactInformation::ValidatedMultipleTrusteesPostContact(_) => Self::MultipleTrusteesPostInvite,
+            ValidatedClientContactInformation::ValidatedPrimaryTrusteePostContact(_) => Self::PrimaryTrusteePostInvite,
+            ValidatedClientContactInformation::ValidatedMultipleDirectorsPostContact(_) => Self::MultipleDirectorsPostInvite,
+            ValidatedClientContactInformation::ValidatedPrimaryDirectorPostContact(_) => Self::PrimaryDirectorPostInvite,
+        }
+    }
+
+    /// The letter sent in place of an email that could not be delivered.
+    pub fn postal_equivalent(&self) -> Self {
+        match self {
+            Self::JointIndividualsElectronicInvite => Self::JointIndividualsPostInvite,
+            Self::SingleIndividualElectronicInvite => Self::SingleIndividualPostInvite,
+            Self::MultipleTrusteesElectronicInvite => Self::MultipleTrusteesPostInvite,
+            Self::PrimaryTrusteeElectronicInvite => Self::PrimaryTrusteePostInvite,
+            Self::MultipleDirectorsElectronicInvite => Self::MultipleDirectorsPostInvite,
+            Self::PrimaryDirectorElectronicInvite => Self::PrimaryDirectorPostInvite,
+            Self::AttorneyElectronicInvite => Self::AttorneyPostInvite,
+            Self::BereavedIndividualElectronicInvite => Self::BereavedIndividualPostInvite,
+            post => *post,
+        }
+    }
+
+    pub fn value(&self) -> &'static str {
+        match self {
+            Self::JointIndividualsElectronicInvite => "joint_individuals_electronic_invite",
+            Self::SingleIndividualElectronicInvite => "single_individual_electronic_invite",
+            Self::MultipleTrusteesElectronicInvite => "multiple_trustees_electronic_invite",
+            Self::PrimaryTrusteeElectronicInvite => "primary_trustee_electronic_invite",
+            Self::MultipleDirectorsElectronicInvite => "multiple_directors_electronic_invite",
+            Self::PrimaryDirectorElectronicInvite => "primary_director_electronic_invite",
+            Self::JointIndividualsPostInvite => "joint_individuals_post_invite",
+            Self::SingleIndividualPostInvite => "single_individual_post_invite",
+            Self::MultipleTrusteesPostInvite => "multiple_trustees_post_invite",
+            Self::PrimaryTrusteePostInvite => "primary_trustee_post_invite",
+            Self::MultipleDirectorsPostInvite => "multiple_directors_post_invite",
+            Self::PrimaryDirectorPostInvite => "primary_director_post_invite",
+            Self::AttorneyElectronicInvite => "attorney_electronic_invite",
+            Self::AttorneyPostInvite => "attorney_post_invite",
+            Self::BereavedIndividualElectronicInvite => "bereaved_individual_electronic_invite",
+            Self::BereavedIndividualPostInvite => "bereaved_individual_post_invite",
+        }
+    }
+
+    pub fn is_for_authorised_contact(&self) -> bool {
+        matches!(self, Self::AttorneyElectronicInvite | Self::AttorneyPostInvite)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SendElectronicAnnualReviewInvite {
+    #[serde(with = "crate::field_encryption::encrypted")]
+    recipient_names: Vec<ConstrainedIndividualNameString100>,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    email_address: EmailAddress,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    organisation_name: Option<ConstainedTrustOrCompanyNameString200>,
+    content_reference: InviteContentReference,
+    annual_review_due_date: ValidatedAnnualReviewDueDate,
+    reply_deadline: NaiveDate,
+    #[serde(default)]
+    format: CommunicationFormat,
+    #[serde(default, with = "crate::field_encryption::encrypted")]
+    on_behalf_of: Vec<ConstrainedIndividualNameString100>,
+}
+
+impl SendElectronicAnnualReviewInvite {
+    /// Everyone addressed by the email; more than one when clients share an address.
+    pub fn recipient_names(&self) -> &Vec<ConstrainedIndividualNameString100> {
+        &self.recipient_names
+    }
+
+    pub fn email_address(&self) -> &EmailAddress {
+        &self.email_address
+    }
+
+    pub fn organisation_name(&self) -> Option<&ConstainedTrustOrCompanyNameString200> {
+        self.organisation_name.as_ref()
+    }
+
+    pub fn content_reference(&self) -> InviteContentReference {
+        self.content_reference
+    }
+
+    pub fn annual_review_due_date(&self) -> &ValidatedAnnualReviewDueDate {
+        &self.annual_review_due_date
+    }
+
+    pub fn reply_deadline(&self) -> NaiveDate {
+        self.reply_deadline
+    }
+
+    /// The language and format the email is written in.
+    pub fn format(&self) -> CommunicationFormat {
+        self.format
+    }
+
+    /// The clients an attorney or deputy is sent the invite for; empty for
+    /// invites to the clients themselves.
+    pub fn on_behalf_of(&self) -> &Vec<ConstrainedIndividualNameString100> {
+        &self.on_behalf_of
+    }
+
+    /// The same invite as a letter to `postal_address`.
+    pub fn to_post_invite(&self, postal_address: PostalAddress) -> SendPostAnnualReviewInvite {
+        SendPostAnnualReviewInvite {
+            recipient_names: self.recipient_names.clone(),
+            postal_address,
+            organisation_name: self.organisation_name.clone(),
+            content_reference: self.content_reference.postal_equivalent(),
+            annual_review_due_date: self.annual_review_due_date.clone(),
+            reply_deadline: self.reply_deadline,
+            format: self.format,
+            on_behalf_of: self.on_behalf_of.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SendPostAnnualReviewInvite {
+    #[serde(with = "crate::field_encryption::encrypted")]
+    recipient_names: Vec<ConstrainedIndividualNameString100>,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    postal_address: PostalAddress,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    organisation_name: Option<ConstainedTrustOrCompanyNameString200>,
+    content_reference: InviteContentReference,
+    annual_review_due_date: ValidatedAnnualReviewDueDate,
+    reply_deadline: NaiveDate,
+    #[serde(default)]
+    format: CommunicationFormat,
+    #[serde(default, with = "crate::field_encryption::encrypted")]
+    on_behalf_of: Vec<ConstrainedIndividualNameString100>,
+}
+
+impl SendPostAnnualReviewInvite {
+    /// Everyone addressed by the letter; more than one when clients share an address.
+    pub fn recipient_names(&self) -> &Vec<ConstrainedIndividualNameString100> {
+        &self.recipient_names
+    }
+
+    pub fn postal_address(&self) -> &PostalAddress {
+        &self.postal_address
+    }
+
+    pub fn organisation_name(&self) -> Option<&ConstainedTrustOrCompanyNameString200> {
+        self.organisation_name.as_ref()
+    }
+
+    pub fn content_reference(&self) -> InviteContentReference {
+        self.content_reference
+    }
+
+    pub fn annual_review_due_date(&self) -> &ValidatedAnnualReviewDueDate {
+        &self.annual_review_due_date
+    }
+
+    pub fn reply_deadline(&self) -> NaiveDate {
+        self.reply_deadline
+    }
+
+    /// The language the letter is written in and the format it is produced in.
+    pub fn format(&self) -> CommunicationFormat {
+        self.format
+    }
+
+    /// The clients an attorney or deputy is sent the letter for; empty for
+    /// letters to the clients themselves.
+    pub fn on_behalf_of(&self) -> &Vec<ConstrainedIndividualNameString100> {
+        &self.on_behalf_of
+    }
+}
+
+/// A text message inviting the client to book their review, sent to a mobile number.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SendSmsAnnualReviewInvite {
+    #[serde(with = "crate::field_encryption::encrypted")]
+    recipient_names: Vec<ConstrainedIndividualNameString100>,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    phone_number: PhoneNumber,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    organisation_name: Option<ConstainedTrustOrCompanyNameString200>,
+    annual_review_due_date: ValidatedAnnualReviewDueDate,
+    reply_deadline: NaiveDate,
+    #[serde(default)]
+    language: Language,
+}
+
+impl SendSmsAnnualReviewInvite {
+    /// Everyone addressed by the message; more than one when clients share a number.
+    pub fn recipient_names(&self) -> &Vec<ConstrainedIndividualNameString100> {
+        &self.recipient_names
+    }
+
+    pub fn phone_number(&self) -> &PhoneNumber {
+        &self.phone_number
+    }
+
+    pub fn organisation_name(&self) -> Option<&ConstainedTrustOrCompanyNameString200> {
+        self.organisation_name.as_ref()
+    }
+
+    pub fn annual_review_due_date(&self) -> &ValidatedAnnualReviewDueDate {
+        &self.annual_review_due_date
+    }
+
+    pub fn reply_deadline(&self) -> NaiveDate {
+        self.reply_deadline
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AnnualReviewInvite {
+    SendElectronicAnnualReviewInvite(SendElectronicAnnualReviewInvite),
+    SendPostAnnualReviewInvite(SendPostAnnualReviewInvite),
+    SendSmsAnnualReviewInvite(SendSmsAnnualReviewInvite),
+}
+
+impl AnnualReviewInvite {
+    /// The invite written in the client's language and produced in their
+    /// format. Text messages take only the language.
+    pub fn with_format(self, format: CommunicationFormat) -> Self {
+        match self {
+            Self::SendElectronicAnnualReviewInvite(invite) => Self::SendElectronicAnnualReviewInvite(SendElectronicAnnualReviewInvite { format, ..invite }),
+            Self::SendPostAnnualReviewInvite(invite) => Self::SendPostAnnualReviewInvite(SendPostAnnualReviewInvite { format, ..invite }),
+            Self::SendSmsAnnualReviewInvite(invite) => Self::SendSmsAnnualReviewInvite(SendSmsAnnualReviewInvite { language: format.language(), ..invite }),
+        }
+    }
+
+    /// The invite without `name` among its recipients, or `None` if they were
+    /// its only recipient.
+    pub fn without_recipient(self, name: &ConstrainedIndividualNameString100) -> Option<Self> {
+        let remove = |mut recipient_names: Vec<ConstrainedIndividualNameString100>| {
+            recipient_names.retain(|recipient_name| recipient_name != name);
+            Some(recipient_names).filter(|recipient_names| !recipient_names.is_empty())
+        };
+
+        match self {
+            Self::SendElectronicAnnualReviewInvite(invite) => remove(invite.recipient_names.clone())
+                .map(|recipient_names| Self::SendElectronicAnnualReviewInvite(SendElectronicAnnualReviewInvite { recipient_names, ..invite })),
+            Self::SendPostAnnualReviewInvite(invite) => remove(invite.recipient_names.clone())
+                .map(|recipient_names| Self::SendPostAnnualReviewInvite(SendPostAnnualReviewInvite { recipient_names, ..invite })),
+            Self::SendSmsAnnualReviewInvite(invite) => remove(invite.recipient_names.clone())
+                .map(|recipient_names| Self::SendSmsAnnualReviewInvite(SendSmsAnnualReviewInvite { recipient_names, ..invite })),
+        }
+    }
+
+    /// The invite to a joint client whose partner has died, written with the
+    /// bereaved template. Only individuals' contacts carry phone numbers and
+    /// they have no joint form, so text messages are left as they are.
+    pub fn for_bereaved_individual(self) -> Self {
+        match self {
+            Self::SendElectronicAnnualReviewInvite(invite) => Self::SendElectronicAnnualReviewInvite(SendElectronicAnnualReviewInvite {
+                content_reference: InviteContentReference::BereavedIndividualElectronicInvite,
+                ..invite
+            }),
+            Self::SendPostAnnualReviewInvite(invite) => Self::SendPostAnnualReviewInvite(SendPostAnnualReviewInvite {
+                content_reference: InviteContentReference::BereavedIndividualPostInvite,
+                ..invite
+            }),
+            sms @ Self::SendSmsAnnualReviewInvite(_) => sms,
+        }
+    }
+
+    /// The invite once `deceased_clients` have died. The deceased are no longer
+    /// addressed and a surviving joint client is sent the bereaved invite. An
+    /// attorney is written to only about the clients still living. `None` if
+    /// the invite was about the deceased alone.
+    pub fn after_deaths(self, deceased_clients: &[DeceasedClient]) -> Option<Self> {
+        if deceased_clients.is_empty() {
+            return Some(self);
+        }
+
+        let living = |on_behalf_of: Vec<ConstrainedIndividualNameString100>| {
+            let on_behalf_of: Vec<_> = on_behalf_of.into_iter()
+                .filter(|name| !deceased_clients.iter().any(|deceased| deceased.first_name() == name))
+                .collect();
+            Some(on_behalf_of).filter(|on_behalf_of| !on_behalf_of.is_empty())
+        };
+
+        match self {
+            Self::SendElectronicAnnualReviewInvite(invite) if !invite.on_behalf_of.is_empty() => living(invite.on_behalf_of.clone())
+                .map(|on_behalf_of| Self::SendElectronicAnnualReviewInvite(SendElectronicAnnualReviewInvite { on_behalf_of, ..invite })),
+            Self::SendPostAnnualReviewInvite(invite) if !invite.on_behalf_of.is_empty() => living(invite.on_behalf_of.clone())
+                .map(|on_behalf_of| Self::SendPostAnnualReviewInvite(SendPostAnnualReviewInvite { on_behalf_of, ..invite })),
+            invite => deceased_clients.iter()
+                .try_fold(invite, |invite, deceased| invite.without_recipient(deceased.first_name()))
+                .map(Self::for_bereaved_individual),
+        }
+    }
+
+    /// The invite as a text message to `phone_number`.
+    pub fn to_sms_invite(&self, phone_number: PhoneNumber) -> SendSmsAnnualReviewInvite {
+        let (recipient_names, organisation_name, annual_review_due_date, reply_deadline, language) = match self {
+            Self::SendElectronicAnnualReviewInvite(invite) => (invite.recipient_names(), invite.organisation_name(), invite.annual_review_due_date(), invite.reply_deadline(), invite.format().language()),
+            Self::SendPostAnnualReviewInvite(invite) => (invite.recipient_names(), invite.organisation_name(), invite.annual_review_due_date(), invite.reply_deadline(), invite.format().language()),
+            Self::SendSmsAnnualReviewInvite(invite) => (invite.recipient_names(), invite.organisation_name(), invite.annual_review_due_date(), invite.reply_deadline(), invite.language()),
+        };
+
+        SendSmsAnnualReviewInvite {
+            recipient_names: recipient_names.clone(),
+            phone_number,
+            organisation_name: organisation_name.cloned(),
+            annual_review_due_date: annual_review_due_date.clone(),
+            reply_deadline,
+            language,
+        }
+    }
+}
+
+/// Groups recipients by a shared key, keeping the order each key was first seen.
+fn group_by_recipient<'a, T, K: PartialEq>(
+    recipients: Vec<(&'a ConstrainedIndividualNameString100, &'a T)>,
+    key: impl Fn(&T) -> K,
+) -> Vec<(&'a T, Vec<ConstrainedIndividualNameString100>)> {
+    let mut grouped: Vec<(K, &'a T, Vec<ConstrainedIndividualNameString100>)> = Vec::new();
+
+    for (name, recipient) in recipients {
+        let recipient_key = key(recipient);
+        match grouped.iter_mut().find(|(existing, _, _)| *existing == recipient_key) {
+            Some((_, _, names)) => {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+            None => grouped.push((recipient_key, recipient, vec![name.clone()])),
+        }
+    }
+
+    grouped.into_iter().map(|(_, recipient, names)| (recipient, names)).collect()
+}
+
+/// The invites to send for a client: an email for each distinct email address of
+/// an electronic contact, or a letter for each distinct postal address of a post contact.
+pub fn create_annual_review_invites(
+    client_contact_information: &ValidatedClientContactInformation,
+    annual_review_due_date: &ValidatedAnnualReviewDueDate,
+    reply_deadline: NaiveDate,
+) -> Result<Vec<AnnualReviewInvite>, ValidationError> {
+    if reply_deadline > annual_review_due_date.date() {
+        return Err(ValidationError::InvalidDate(ValidationDetail::new("validation.reply_deadline_after_due_date", &[])));
+    }
+
+    let content_reference = InviteContentReference::for_contact(client_contact_information);
+    let organisation_name = client_contact_information.organisation_name().cloned();
+
+    let electronic_invites = group_by_recipient(
+        client_contact_information.electronic_recipients(),
+        |email_address| email_address.value().to_lowercase(),
+    )
+        .into_iter()
+        .map(|(email_address, recipient_names)| {
+            AnnualReviewInvite::SendElectronicAnnualReviewInvite(SendElectronicAnnualReviewInvite {
+                recipient_names,
+                email_address: email_address.clone(),
+                organisation_name: organisation_name.clone(),
+                content_reference,
+                annual_review_due_date: annual_review_due_date.clone(),
+                reply_deadline,
+                format: CommunicationFormat::default(),
+                on_behalf_of: Vec::new(),
+            })
+        });
+
+    let postal_invites = group_by_recipient(client_contact_information.postal_recipients(), PostalAddress::clone)
+        .into_iter()
+        .map(|(postal_address, recipient_names)| {
+            AnnualReviewInvite::SendPostAnnualReviewInvite(SendPostAnnualReviewInvite {
+                recipient_names,
+                postal_address: postal_address.clone(),
+                organisation_name: organisation_name.clone(),
+                content_reference,
+                annual_review_due_date: annual_review_due_date.clone(),
+                reply_deadline,
+                format: CommunicationFormat::default(),
+                on_behalf_of: Vec::new(),
+            })
+        });
+
+    Ok(electronic_invites.chain(postal_invites).collect())
+}
+
+/// The invites to send on a channel. Emails and secure portal messages carry
+/// the electronic invites, post sends a letter to each postal address,
+/// including addresses on file for electronic contacts, and SMS sends a text
+/// to each mobile number.
+pub fn create_annual_review_invites_for_channel(
+    client_contact_information: &ValidatedClientContactInformation,
+    annual_review_due_date: &ValidatedAnnualReviewDueDate,
+    reply_deadline: NaiveDate,
+    channel: ContactChannel,
+) -> Result<Vec<AnnualReviewInvite>, ValidationError> {
+    let invites = create_annual_review_invites(client_contact_information, annual_review_due_date, reply_deadline)?.into_iter();
+
+    match channel {
+        ContactChannel::Email | ContactChannel::SecurePortalMessage => {
+            Ok(invites.filter(|invite| matches!(invite, AnnualReviewInvite::SendElectronicAnnualReviewInvite(_))).collect())
+        }
+        ContactChannel::Post => Ok(invites
+            .filter_map(|invite| match invite {
+                AnnualReviewInvite::SendPostAnnualReviewInvite(_) => Some(invite),
+                AnnualReviewInvite::SendElectronicAnnualReviewInvite(electronic) => client_contact_information
+                    .postal_address_on_file(electronic.email_address())
+                    .map(|postal_address| AnnualReviewInvite::SendPostAnnualReviewInvite(electronic.to_post_invite(postal_address.clone()))),
+                AnnualReviewInvite::SendSmsAnnualReviewInvite(_) => None,
+            })
+            .collect()),
+        ContactChannel::Sms => {
+            let mobile_numbers = client_contact_information
+                .phone_numbers()
+                .into_iter()
+                .filter(|(_, phone_number)| phone_number.is_mobile())
+                .collect();
+
+            Ok(group_by_recipient(mobile_numbers, |phone_number| phone_number.value().to_string())
+                .into_iter()
+                .map(|(phone_number, recipient_names)| {
+                    AnnualReviewInvite::SendSmsAnnualReviewInvite(SendSmsAnnualReviewInvite {
+                        recipient_names,
+                        phone_number: phone_number.clone(),
+                        organisation_name: client_contact_information.organisation_name().cloned(),
+                        annual_review_due_date: annual_review_due_date.clone(),
+                        reply_deadline,
+                        language: Language::default(),
+                    })
+                })
+                .collect())
+        }
+        ContactChannel::Phone => Err(ValidationError::InvalidInput(ValidationDetail::new("validation.no_written_invite", &[&format!("{:?}", channel)]))),
+    }
+}
+
+/// The invites for attorneys and deputies: an email to each that has an email
+/// address, otherwise a letter. An attorney acting for both joint clients is
+/// sent one invite about them both.
+pub fn create_authorised_contact_invites(
+    authorised_contacts: &[AuthorisedContact],
+    annual_review_due_date: &ValidatedAnnualReviewDueDate,
+    reply_deadline: NaiveDate,
+) -> Vec<AnnualReviewInvite> {
+    let recipients = authorised_contacts.iter().map(|contact| (contact.client_first_name(), contact)).collect();
+
+    group_by_recipient(recipients, |contact| {
+        (contact.first_name().clone(), contact.email_address().map(|email_address| email_address.value().to_lowercase()), contact.postal_address().cloned())
+    })
+        .into_iter()
+        .map(|(contact, on_behalf_of)| match (contact.email_address(), contact.postal_address()) {
+            (Some(email_address), _) => AnnualReviewInvite::SendElectronicAnnualReviewInvite(SendElectronicAnnualReviewInvite {
+                recipient_names: vec![contact.first_name().clone()],
+                email_address: email_address.clone(),
+                organisation_name: None,
+                content_reference: InviteContentReference::AttorneyElectronicInvite,
+                annual_review_due_date: annual_review_due_date.clone(),
+                reply_deadline,
+                format: CommunicationFormat::default(),
+                on_behalf_of,
+            }),
+            (None, postal_address) => AnnualReviewInvite::SendPostAnnualReviewInvite(SendPostAnnualReviewInvite {
+                recipient_names: vec![contact.first_name().clone()],
+                postal_address: postal_address.expect("An authorised contact has an email or postal address").clone(),
+                organisation_name: None,
+                content_reference: InviteContentReference::AttorneyPostInvite,
+                annual_review_due_date: annual_review_due_date.clone(),
+                reply_deadline,
+                format: CommunicationFormat::default(),
+                on_behalf_of,
+            }),
+        })
+        .collect()
+}
+
+/// The channel chosen for a client's invite and the invites to send on it.
+/// Clients reached by phone have no written invite. For a vulnerable client
+/// the plan also carries the adjustments from their vulnerability profile, and
+/// for a client who has died, the executors of their estate.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InvitePlan {
+    decision: ChannelDecision,
+    invites: Vec<AnnualReviewInvite>,
+    reply_deadline: NaiveDate,
+    phone_call_before_letter: bool,
+    trusted_third_party: Option<TrustedThirdParty>,
+    adviser_alert: Option<VulnerableClientAlert>,
+    executors: Vec<ExecutorContact>,
+}
+
+impl InvitePlan {
+    pub fn decision(&self) -> &ChannelDecision {
+        &self.decision
+    }
+
+    pub fn invites(&self) -> &Vec<AnnualReviewInvite> {
+        &self.invites
+    }
+
+    /// The deadline the invites carry, extended for a vulnerable client.
+    pub fn reply_deadline(&self) -> NaiveDate {
+        self.reply_deadline
+    }
+
+    /// The client must be phoned before the letters are sent.
+    pub fn phone_call_before_letter(&self) -> bool {
+        self.phone_call_before_letter
+    }
+
+    /// Someone the adviser may contact about the review on the client's behalf.
+    pub fn trusted_third_party(&self) -> Option<&TrustedThirdParty> {
+        self.trusted_third_party.as_ref()
+    }
+
+    pub fn adviser_alert(&self) -> Option<&VulnerableClientAlert> {
+        self.adviser_alert.as_ref()
+    }
+
+    /// The executors the adviser corresponds with about a deceased client's estate.
+    pub fn executors(&self) -> &Vec<ExecutorContact> {
+        &self.executors
+    }
+}
+
+/// Plans the invites for `date` using the client's contact preferences, any
+/// vulnerability profile, any attorneys or deputies and any clients who have
+/// died. A client an attorney acts for is not sent invites of their own; the
+/// attorney is, as is any attorney copied in, unless the client is not to be
+/// contacted now. A deceased client is sent nothing and their attorneys'
+/// authority has ended; a surviving joint client is sent the bereaved invite.
+/// Fails if a living client's attorney has no authority in force on `date`.
+pub fn plan_annual_review_invites(
+    review_information: &ValidatedAnnualReviewInformation,
+    reply_deadline: NaiveDate,
+    date: NaiveDate,
+) -> Result<InvitePlan, ValidationError> {
+    let deceased_clients = review_information.deceased_clients();
+    if let Some(deceased) = deceased_clients.iter().find(|deceased| deceased.date_of_death() > date) {
+        return Err(ValidationError::InvalidDate(ValidationDetail::new("validation.date_of_death_after", &[deceased.first_name().value(), &date.format("%d/%m/%Y").to_string()])));
+    }
+
+    let authorised_contacts: Vec<AuthorisedContact> = review_information
+        .authorised_contacts()
+        .iter()
+        .filter(|contact| review_information.client_status(contact.client_first_name()) == ClientStatus::Living)
+        .cloned()
+        .collect();
+    for contact in &authorised_contacts {
+        contact.check_in_force(date)?;
+    }
+
+    let vulnerability_profile = review_information.vulnerability_profile();
+    let reply_deadline = match vulnerability_profile {
+        Some(profile) => profile.extend_reply_deadline(reply_deadline, review_information.annual_review_due_date().date()),
+        None => reply_deadline,
+    };
+
+    let decision = review_information.choose_channel(date);
+    let invites = match decision {
+        ChannelDecision::Use(channel @ (ContactChannel::Email | ContactChannel::Post | ContactChannel::Sms | ContactChannel::SecurePortalMessage)) => {
+            create_annual_review_invites_for_channel(
+                review_information.client_contact_information(),
+                review_information.annual_review_due_date(),
+                reply_deadline,
+                channel,
+            )?
+            .into_iter()
+            .map(|invite| invite.with_format(review_information.contact_preferences().communication_format()))
+            .collect()
+        }
+        _ => Vec::new(),
+    };
+    let not_sent_invites = authorised_contacts
+        .iter()
+        .filter(|contact| contact.scope() == AuthorityScope::ActsForClient)
+        .map(AuthorisedContact::client_first_name);
+    let mut invites: Vec<AnnualReviewInvite> = invites
+        .into_iter()
+        .filter_map(|invite| not_sent_invites.clone().try_fold(invite, |invite, name| invite.without_recipient(name)))
+        .filter_map(|invite| invite.after_deaths(deceased_clients))
+        .collect();
+    if !matches!(decision, ChannelDecision::DeferUntil(_) | ChannelDecision::DoNotContact) {
+        invites.extend(create_authorised_contact_invites(&authorised_contacts, review_information.annual_review_due_date(), reply_deadline));
+    }
+
+    let phone_call_before_letter = decision == ChannelDecision::Use(ContactChannel::Post)
+        && !invites.is_empty()
+        && vulnerability_profile.is_some_and(|profile| profile.requires_phone_call_before_letter());
+
+    Ok(InvitePlan {
+        decision,
+        invites,
+        reply_deadline,
+        phone_call_before_letter,
+        trusted_third_party: vulnerability_profile.and_then(|profile| profile.trusted_third_party().cloned()),
+        adviser_alert: vulnerability_profile.map(|profile| profile.adviser_alert(review_information.adviser_name(), date)),
+        executors: deceased_clients.iter().flat_map(|deceased| deceased.executors().iter().cloned()).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::test_utils::shared::{validated_contact, ReviewInformationFixture, JOINT_INDIVIDUALS_ELECTRONIC_CONTACT, JOINT_INDIVIDUALS_POST_CONTACT, MULTIPLE_TRUSTEES_POST_CONTACT, PRIMARY_DIRECTOR_ELECTRONIC_CONTACT, SINGLE_INDIVIDUAL_POST_CONTACT_WITH_MOBILE};
+    use super::*;
+    use super::super::{contact_preferences::AccessibleFormat, vulnerability::VulnerabilityDriver};
+
+    fn due_date() -> ValidatedAnnualReviewDueDate {
+        ValidatedAnnualReviewDueDate::validate("30/06/2024".to_string(), "30/06/2023".to_string()).unwrap()
+    }
+
+    fn reply_deadline() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, 31).unwrap()
+    }
+
+    fn names(names: &[ConstrainedIndividualNameString100]) -> Vec<&str> {
+        names.iter().map(|name| name.value()).collect()
+    }
+
+    #[test]
+    fn test_joint_electronic_contact_produces_email_per_address() {
+        let invites = create_annual_review_invites(&validated_contact(JOINT_INDIVIDUALS_ELECTRONIC_CONTACT), &due_date(), reply_deadline()).unwrap();
+
+        assert_eq!(invites.len(), 2);
+        let AnnualReviewInvite::SendElectronicAnnualReviewInvite(invite) = &invites[1] else {
+            panic!("Expected an electronic invite");
+        };
+        assert_eq!(invite.email_address().value(), "sarah@example.com");
+        assert_eq!(names(invite.recipient_names()), vec!["Sarah"]);
+        assert_eq!(invite.content_reference(), InviteContentReference::JointIndividualsElectronicInvite);
+        assert_eq!(invite.annual_review_due_date(), &due_date());
+        assert_eq!(invite.reply_deadline(), reply_deadline());
+    }
+
+    #[test]
+    fn test_shared_email_address_produces_single_email() {
+        let contact = validated_contact(r#"{
+            "type": "JointIndividualsElectronicContact",
+            "primary_contact_first_name": "James",
+            "individual_two_first_name": "Sarah",
+            "primary_contact_email_address": "the.mays@example.com",
+            "individual_two_email_address": "The.Mays@example.com"
+        }"#);
+
+        let invites = create_annual_review_invites(&contact, &due_date(), reply_deadline()).unwrap();
+
+        assert_eq!(invites.len(), 1);
+        let AnnualReviewInvite::SendElectronicAnnualReviewInvite(invite) = &invites[0] else {
+            panic!("Expected an electronic invite");
+        };
+        assert_eq!(names(invite.recipient_names()), vec!["James", "Sarah"]);
+    }
+
+    #[test]
+    fn test_joint_post_contact_produces_one_letter() {
+        let invites = create_annual_review_invites(&validated_contact(JOINT_INDIVIDUALS_POST_CONTACT), &due_date(), reply_deadline()).unwrap();
+
+        assert_eq!(invites.len(), 1);
+        let AnnualReviewInvite::SendPostAnnualReviewInvite(invite) = &invites[0] else {
+            panic!("Expected a postal invite");
+        };
+        assert_eq!(names(invite.recipient_names()), vec!["James", "Sarah"]);
+        assert_eq!(invite.postal_address().city().value(), "Guildford");
+        assert_eq!(invite.content_reference(), InviteContentReference::JointIndividualsPostInvite);
+    }
+
+    #[test]
+    fn test_trustees_post_contact_produces_letter_per_address() {
+        let invites = create_annual_review_invites(&validated_contact(MULTIPLE_TRUSTEES_POST_CONTACT), &due_date(), reply_deadline()).unwrap();
+
+        assert_eq!(invites.len(), 2);
+        assert!(invites.iter().all(|invite| matches!(invite, AnnualReviewInvite::SendPostAnnualReviewInvite(_))));
+        let AnnualReviewInvite::SendPostAnnualReviewInvite(invite) = &invites[0] else {
+            panic!("Expected a postal invite");
+        };
+        assert_eq!(names(invite.recipient_names()), vec!["Alice", "Bob"]);
+        assert_eq!(invite.organisation_name().unwrap().value(), "May Family Trust");
+    }
+
+    #[test]
+    fn test_director_electronic_contact_produces_email() {
+        let invites = create_annual_review_invites(&validated_contact(PRIMARY_DIRECTOR_ELECTRONIC_CONTACT), &due_date(), reply_deadline()).unwrap();
+
+        assert_eq!(invites.len(), 1);
+        let AnnualReviewInvite::SendElectronicAnnualReviewInvite(invite) = &invites[0] else {
+            panic!("Expected an electronic invite");
+        };
+        assert_eq!(invite.organisation_name().unwrap().value(), "May Holdings Ltd");
+        assert_eq!(invite.content_reference().value(), "primary_director_electronic_invite");
+    }
+
+    #[test]
+    fn test_plan_follows_preferred_channel() {
+        let review_information = ReviewInformationFixture::new(
+            r#"{
+                "type": "SingleIndividualElectronicContact",
+                "first_name": "Siân",
+                "email_address": "sian@example.com",
+                "postal_address": {
+                    "house_name": "",
+                    "house_number": "4",
+                    "address_line_one": "Mill Road",
+                    "address_line_two": "",
+                    "address_line_three": "",
+                    "address_line_four": "",
+                    "city": "Guildford",
+                    "county": "Surrey",
+                    "postcode": "GU1 2AB",
+                    "country": ""
+                }
+            }"#,
+        ).with_contact_preferences(r#"{"preferred_channels": ["Post", "Email"]}"#).validated();
+
+        let plan = plan_annual_review_invites(&review_information, reply_deadline(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()).unwrap();
+
+        assert_eq!(plan.decision(), &ChannelDecision::Use(ContactChannel::Post));
+        assert_eq!(plan.invites().len(), 1);
+        let AnnualReviewInvite::SendPostAnnualReviewInvite(invite) = &plan.invites()[0] else {
+            panic!("Expected a postal invite");
+        };
+        assert_eq!(invite.content_reference(), InviteContentReference::SingleIndividualPostInvite);
+    }
+
+    #[test]
+    fn test_plan_carries_clients_language_and_format() {
+        let review_information = ReviewInformationFixture::new(JOINT_INDIVIDUALS_POST_CONTACT)
+            .with_contact_preferences(r#"{"preferred_channels": ["Post"], "language": "Welsh", "accessible_format": "LargePrint"}"#)
+            .validated();
+
+        let plan = plan_annual_review_invites(&review_information, reply_deadline(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()).unwrap();
+
+        let [AnnualReviewInvite::SendPostAnnualReviewInvite(invite)] = plan.invites().as_slice() else {
+            panic!("Expected one postal invite");
+        };
+        assert_eq!(invite.format(), CommunicationFormat::new(Language::Welsh, AccessibleFormat::LargePrint));
+    }
+
+    #[test]
+    fn test_plan_sends_sms_to_mobile_number() {
+        let review_information = ReviewInformationFixture::new(SINGLE_INDIVIDUAL_POST_CONTACT_WITH_MOBILE)
+            .with_contact_preferences(r#"{"preferred_channels": ["Sms", "Post"]}"#)
+            .validated();
+        assert_eq!(
+            review_information.client_contact_information().available_channels(),
+            vec![ContactChannel::Post, ContactChannel::Phone, ContactChannel::Sms],
+        );
+
+        let plan = plan_annual_review_invites(&review_information, reply_deadline(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()).unwrap();
+
+        assert_eq!(plan.decision(), &ChannelDecision::Use(ContactChannel::Sms));
+        let [AnnualReviewInvite::SendSmsAnnualReviewInvite(invite)] = plan.invites().as_slice() else {
+            panic!("Expected one SMS invite");
+        };
+        assert_eq!(invite.phone_number().value(), "+447700900123");
+        assert_eq!(names(invite.recipient_names()), vec!["Siân"]);
+    }
+
+    #[test]
+    fn test_plan_adjusts_for_vulnerable_client() {
+        let review_information = ReviewInformationFixture::new(JOINT_INDIVIDUALS_POST_CONTACT)
+            .with_vulnerability(r#"{
+                "indicators": [{"driver": "Health", "notes": "Sight loss", "review_date": "01/04/2024"}],
+                "trusted_third_party": {"name": "Carol", "relationship": "Daughter", "email_address": "carol@example.com"}
+            }"#)
+            .validated();
+
+        let plan = plan_annual_review_invites(&review_information, reply_deadline(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()).unwrap();
+
+        assert_eq!(plan.decision(), &ChannelDecision::Use(ContactChannel::Post));
+        assert_eq!(plan.reply_deadline(), NaiveDate::from_ymd_opt(2024, 6, 14).unwrap());
+        let [AnnualReviewInvite::SendPostAnnualReviewInvite(invite)] = plan.invites().as_slice() else {
+            panic!("Expected one postal invite");
+        };
+        assert_eq!(invite.reply_deadline(), plan.reply_deadline());
+        assert!(plan.phone_call_before_letter());
+        assert_eq!(plan.trusted_third_party().unwrap().email_address().unwrap().value(), "carol@example.com");
+        let alert = plan.adviser_alert().unwrap();
+        assert_eq!(alert.adviser_name(), review_information.adviser_name());
+        assert_eq!(alert.reviews_overdue(), &vec![VulnerabilityDriver::Health]);
+
+        let plan = plan_annual_review_invites(
+            &ReviewInformationFixture::new(JOINT_INDIVIDUALS_POST_CONTACT).validated(),
+            reply_deadline(),
+            NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+        ).unwrap();
+        assert_eq!(plan.reply_deadline(), reply_deadline());
+        assert!(!plan.phone_call_before_letter());
+        assert!(plan.adviser_alert().is_none());
+    }
+
+    #[test]
+    fn test_plan_routes_invites_to_attorneys() {
+        let review_information = ReviewInformationFixture::new(JOINT_INDIVIDUALS_ELECTRONIC_CONTACT)
+            .with_authorised_contacts(r#"[
+                {
+                    "attorney_type": "PropertyAndFinancialAffairsLpa", "first_name": "Carol", "client_first_name": "Sarah",
+                    "registration_reference": "7000-1234-5678", "registered_on": "12/03/2021",
+                    "scope": "ActsForClient", "email_address": "carol@example.com"
+                },
+                {
+                    "attorney_type": "PropertyAndFinancialAffairsLpa", "first_name": "David", "client_first_name": "James",
+                    "registration_reference": "7000-8765-4321", "registered_on": "02/09/2022",
+                    "scope": "CopiedIn", "email_address": "david@example.com"
+                }
+            ]"#)
+            .validated();
+
+        let plan = plan_annual_review_invites(&review_information, reply_deadline(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()).unwrap();
+
+        let summary: Vec<(InviteContentReference, &str, Vec<&str>, Vec<&str>)> = plan.invites().iter().map(|invite| match invite {
+            AnnualReviewInvite::SendElectronicAnnualReviewInvite(invite) => (
+                invite.content_reference(),
+                invite.email_address().value(),
+                invite.recipient_names().iter().map(|name| name.value()).collect(),
+                invite.on_behalf_of().iter().map(|name| name.value()).collect(),
+            ),
+            _ => panic!("Expected electronic invites"),
+        }).collect();
+        assert_eq!(summary, vec![
+            (InviteContentReference::JointIndividualsElectronicInvite, "james@example.com", vec!["James"], vec![]),
+            (InviteContentReference::AttorneyElectronicInvite, "carol@example.com", vec!["Carol"], vec!["Sarah"]),
+            (InviteContentReference::AttorneyElectronicInvite, "david@example.com", vec!["David"], vec!["James"]),
+        ]);
+    }
+
+    #[test]
+    fn test_plan_rejects_authority_not_in_force() {
+        let review_information = ReviewInformationFixture::new(SINGLE_INDIVIDUAL_POST_CONTACT_WITH_MOBILE)
+            .with_authorised_contacts(r#"[{
+                "attorney_type": "Deputy", "first_name": "Carol", "client_first_name": "Siân",
+                "registration_reference": "13258834", "registered_on": "12/03/2021", "ends_on": "30/04/2024",
+                "scope": "ActsForClient", "email_address": "carol@example.com"
+            }]"#)
+            .validated();
+        assert!(matches!(
+            plan_annual_review_invites(&review_information, reply_deadline(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()),
+            Err(ValidationError::InvalidInput(_)),
+        ));
+
+        let unregistered = ReviewInformationFixture::new(SINGLE_INDIVIDUAL_POST_CONTACT_WITH_MOBILE)
+            .with_authorised_contacts(r#"[{
+                "attorney_type": "PropertyAndFinancialAffairsLpa", "first_name": "Carol", "client_first_name": "Siân",
+                "scope": "ActsForClient", "email_address": "carol@example.com"
+            }]"#)
+            .unvalidated();
+        assert!(matches!(ValidatedAnnualReviewInformation::validate(unregistered), Err(ValidationError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_plan_reroutes_to_surviving_joint_client() {
+        let review_information = ReviewInformationFixture::new(JOINT_INDIVIDUALS_POST_CONTACT)
+            .with_authorised_contacts(r#"[{
+                "attorney_type": "PropertyAndFinancialAffairsLpa", "first_name": "Carol", "client_first_name": "James",
+                "registration_reference": "7000-1234-5678", "registered_on": "12/03/2021", "ends_on": "14/04/2024",
+                "scope": "ActsForClient", "email_address": "carol@example.com"
+            }]"#)
+            .with_deceased(r#"[{
+                "first_name": "James",
+                "date_of_death": "14/04/2024",
+                "executors": [{"first_name": "Emma", "email_address": "emma@probate-solicitors.co.uk"}]
+            }]"#)
+            .validated();
+
+        let plan = plan_annual_review_invites(&review_information, reply_deadline(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()).unwrap();
+
+        let [AnnualReviewInvite::SendPostAnnualReviewInvite(invite)] = plan.invites().as_slice() else {
+            panic!("Expected one postal invite");
+        };
+        assert_eq!(names(invite.recipient_names()), vec!["Sarah"]);
+        assert_eq!(invite.content_reference(), InviteContentReference::BereavedIndividualPostInvite);
+        assert_eq!(plan.executors()[0].first_name().value(), "Emma");
+    }
+
+    #[test]
+    fn test_plan_suppresses_invites_for_deceased_client() {
+        let deceased = |date_of_death: &str| ReviewInformationFixture::new(SINGLE_INDIVIDUAL_POST_CONTACT_WITH_MOBILE)
+            .with_deceased(&format!(r#"[{{"first_name": "Siân", "date_of_death": "{}"}}]"#, date_of_death))
+            .validated();
+
+        let plan = plan_annual_review_invites(&deceased("14/04/2024"), reply_deadline(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()).unwrap();
+        assert!(plan.invites().is_empty());
+        assert!(plan.executors().is_empty());
+
+        assert!(matches!(
+            plan_annual_review_invites(&deceased("02/05/2024"), reply_deadline(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()),
+            Err(ValidationError::InvalidDate(_)),
+        ));
+    }
+
+    #[test]
+    fn test_plan_without_written_invite() {
+        let review_information = ReviewInformationFixture::new(JOINT_INDIVIDUALS_POST_CONTACT)
+            .with_contact_preferences(r#"{"preferred_channels": ["Email"], "do_not_contact": true}"#)
+            .validated();
+        let plan = plan_annual_review_invites(&review_information, reply_deadline(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()).unwrap();
+        assert_eq!(plan.decision(), &ChannelDecision::DoNotContact);
+        assert!(plan.invites().is_empty());
+
+        let review_information = ReviewInformationFixture::new(JOINT_INDIVIDUALS_POST_CONTACT)
+            .with_contact_preferences(r#"{"preferred_channels": ["Email"]}"#)
+            .validated();
+        let plan = plan_annual_review_invites(&review_information, reply_deadline(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()).unwrap();
+        assert_eq!(plan.decision(), &ChannelDecision::NoUsableChannel);
+    }
+
+    #[test]
+    fn test_reply_deadline_after_due_date_is_rejected() {
+        let result = create_annual_review_invites(
+            &validated_contact(JOINT_INDIVIDUALS_POST_CONTACT),
+            &due_date(),
+            NaiveDate::from_ymd_opt(2024, 7, 1).unwrap(),
+        );
+        assert!(matches!(result, Err(ValidationError::InvalidDate(_))));
+    }
+}
@@ -0,0 +1,251 @@
+use chrono::NaiveDate;
+
+use crate::{
+    pdf::{wrap_text, Font, PdfDocument, PdfPage, POINTS_PER_MM},
+    simple_types::ConstrainedIndividualNameString100,
+};
+use super::{
+    contact_preferences::AccessibleFormat,
+    invites::SendPostAnnualReviewInvite,
+    localisation::{format_date, message},
+    templates::{join_names, RenderedLetter},
+};
+
+const LEFT_MARGIN_MM: f32 = 25.0;
+const RIGHT_MARGIN_MM: f32 = 20.0;
+const BOTTOM_MARGIN_MM: f32 = 25.0;
+
+/// The address block for a DL envelope with a 90 x 35 mm window 20 mm from
+/// the left and 15 mm from the bottom, inset so that the address stays inside
+/// the window when the letter moves in the envelope.
+const ADDRESS_TOP_MM: f32 = 52.0;
+const ADDRESS_HEIGHT_MM: f32 = 31.0;
+const ADDRESS_MAX_FONT_SIZE: f32 = 10.0;
+
+const DATE_TOP_MM: f32 = 95.0;
+const BODY_TOP_MM: f32 = 108.0;
+const BODY_FONT_SIZE: f32 = 11.0;
+const BODY_LEADING: f32 = 15.0;
+/// Large print follows the RNIB clear print guidance of at least 16 point.
+const LARGE_PRINT_FONT_SIZE: f32 = 16.0;
+const LARGE_PRINT_LEADING: f32 = 22.0;
+const SIGNATURE_SPACE_MM: f32 = 18.0;
+
+/// The company details printed at the top of every letter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Letterhead {
+    name: String,
+    lines: Vec<String>,
+}
+
+impl Letterhead {
+    pub fn new(name: &str, lines: Vec<String>) -> Self {
+        Self { name: name.to_string(), lines }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn lines(&self) -> &Vec<String> {
+        &self.lines
+    }
+}
+
+/// A postal invite with its rendered content, ready to lay out.
+#[derive(Debug, Clone, Copy)]
+pub struct PostalLetter<'a> {
+    invite: &'a SendPostAnnualReviewInvite,
+    content: &'a RenderedLetter,
+}
+
+impl<'a> PostalLetter<'a> {
+    pub fn new(invite: &'a SendPostAnnualReviewInvite, content: &'a RenderedLetter) -> Self {
+        Self { invite, content }
+    }
+}
+
+/// Renders postal invites as A4 PDF letters for folding into DL window envelopes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LetterRenderer {
+    letterhead: Letterhead,
+}
+
+impl LetterRenderer {
+    pub fn new(letterhead: Letterhead) -> Self {
+        Self { letterhead }
+    }
+
+    /// One PDF with each letter starting on a new page, signed by the adviser and dated `letter_date`.
+    pub fn render(&self, letters: &[PostalLetter], adviser_name: &ConstrainedIndividualNameString100, letter_date: NaiveDate) -> Vec<u8> {
+        let mut document = PdfDocument::new(&format!("Annual review invitation from {}", self.letterhead.name));
+        for letter in letters {
+            for page in self.letter_pages(letter, adviser_name, letter_date) {
+                document = document.with_page(page);
+            }
+        }
+
+        document.to_bytes()
+    }
+
+    fn letter_pages(&self, letter: &PostalLetter, adviser_name: &ConstrainedIndividualNameString100, letter_date: NaiveDate) -> Vec<PdfPage> {
+        let mut page = PdfPage::a4();
+        let left = LEFT_MARGIN_MM * POINTS_PER_MM;
+        let right = page.width() - RIGHT_MARGIN_MM * POINTS_PER_MM;
+        let body_width = right - left;
+        let from_top = |page: &PdfPage, mm: f32| page.height() - mm * POINTS_PER_MM;
+
+        let (font_size, leading) = match letter.invite.format().accessible_format() {
+            AccessibleFormat::LargePrint => (LARGE_PRINT_FONT_SIZE, LARGE_PRINT_LEADING),
+            AccessibleFormat::Standard | AccessibleFormat::Braille | AccessibleFormat::Audio => (BODY_FONT_SIZE, BODY_LEADING),
+        };
+
+        let language = letter.invite.format().language();
+
+        self.draw_letterhead(&mut page, right);
+
+        let address_block = address_block(letter.invite);
+        let address_font_size = ADDRESS_MAX_FONT_SIZE.min(ADDRESS_HEIGHT_MM * POINTS_PER_MM / (address_block.len() as f32 * 1.2));
+        let mut y = from_top(&page, ADDRESS_TOP_MM) - address_font_size;
+        for line in &address_block {
+            page.text(left, y, Font::Helvetica, address_font_size, line);
+            y -= address_font_size * 1.2;
+        }
+
+        page.text_right_aligned(right, from_top(&page, DATE_TOP_MM), Font::Helvetica, font_size, &format_date(language, letter_date));
+
+        let mut y = from_top(&page, BODY_TOP_MM);
+        page.text(left, y, Font::Helvetica, font_size, &format!("{},", message(language, "letter.greeting", &[letter.content.salutation()])));
+        y -= leading * 2.0;
+
+        let mut pages = Vec::new();
+        let mut body_lines: Vec<String> = Vec::new();
+        for paragraph in letter.content.body().split("\n\n") {
+            body_lines.extend(wrap_text(paragraph, Font::Helvetica, font_size, body_width));
+            body_lines.push(String::new());
+        }
+        body_lines.push(message(language, "letter.sign_off", &[]));
+
+        for line in body_lines {
+            if y < BOTTOM_MARGIN_MM * POINTS_PER_MM {
+                pages.push(std::mem::replace(&mut page, PdfPage::a4()));
+                y = from_top(&page, 20.0);
+            }
+            if !line.is_empty() {
+                page.text(left, y, Font::Helvetica, font_size, &line);
+            }
+            y -= leading;
+        }
+
+        y -= SIGNATURE_SPACE_MM * POINTS_PER_MM;
+        if y < BOTTOM_MARGIN_MM * POINTS_PER_MM {
+            pages.push(std::mem::replace(&mut page, PdfPage::a4()));
+            y = from_top(&page, 20.0 + SIGNATURE_SPACE_MM);
+        }
+        page.text(left, y, Font::HelveticaBold, font_size, adviser_name.value());
+        page.text(left, y - leading, Font::Helvetica, font_size, &self.letterhead.name);
+
+        pages.push(page);
+        pages
+    }
+
+    fn draw_letterhead(&self, page: &mut PdfPage, right: f32) {
+        let mut y = page.height() - 20.0 * POINTS_PER_MM;
+        page.text_right_aligned(right, y, Font::HelveticaBold, 16.0, &self.letterhead.name);
+
+        for line in &self.letterhead.lines {
+            y -= 11.0;
+            page.text_right_aligned(right, y, Font::Helvetica, 8.5, line);
+        }
+
+        y -= 8.0;
+        page.line((LEFT_MARGIN_MM * POINTS_PER_MM, y), (right, y), 0.5);
+    }
+}
+
+/// The recipients, organisation and address shown in the envelope window.
+fn address_block(invite: &SendPostAnnualReviewInvite) -> Vec<String> {
+    let mut lines = vec![join_names(invite.recipient_names(), invite.format().language())];
+    lines.extend(invite.organisation_name().map(|name| name.value().to_string()));
+    lines.extend(invite.postal_address().address_lines());
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::test_utils::shared::{assert_snapshot, ReviewInformationFixture, ADVISER_NAME, JOINT_INDIVIDUALS_POST_CONTACT, MULTIPLE_TRUSTEES_POST_CONTACT};
+    use super::super::{contact_preferences::{CommunicationFormat, Language}, invites::AnnualReviewInvite, templates::InviteTemplates};
+    use super::*;
+
+    fn letterhead() -> Letterhead {
+        Letterhead::new("Grafton Court Wealth Management", vec!["reviews@gcwm.co.uk".to_string()])
+    }
+
+    fn adviser_name() -> ConstrainedIndividualNameString100 {
+        ConstrainedIndividualNameString100::try_from(ADVISER_NAME.to_string()).unwrap()
+    }
+
+    fn letter_date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()
+    }
+
+    fn render(json: &str) -> String {
+        let invites = ReviewInformationFixture::new(json).post_invites();
+        let templates = InviteTemplates::bundled().unwrap();
+        let contents: Vec<RenderedLetter> = invites.iter().map(|invite| templates.render_letter(invite, &adviser_name()).unwrap()).collect();
+        let letters: Vec<PostalLetter> = invites.iter().zip(&contents).map(|(invite, content)| PostalLetter::new(invite, content)).collect();
+
+        String::from_utf8(LetterRenderer::new(letterhead()).render(&letters, &adviser_name(), letter_date())).unwrap()
+    }
+
+    #[test]
+    fn test_joint_individuals_letter_snapshot() {
+        assert_snapshot("book_review_meeting/joint_individuals_letter.pdf", &render(JOINT_INDIVIDUALS_POST_CONTACT));
+    }
+
+    #[test]
+    fn test_trustee_letters_snapshot() {
+        let pdf = render(MULTIPLE_TRUSTEES_POST_CONTACT);
+
+        assert!(pdf.contains("/Count 2"));
+        assert!(pdf.contains("(Alice and Bob)"));
+        assert!(pdf.contains("(Dear Carol,)"));
+        assert_snapshot("book_review_meeting/multiple_trustees_letters.pdf", &pdf);
+    }
+
+    #[test]
+    fn test_rendering_is_deterministic() {
+        assert_eq!(render(MULTIPLE_TRUSTEES_POST_CONTACT), render(MULTIPLE_TRUSTEES_POST_CONTACT));
+    }
+
+    #[test]
+    fn test_large_print_letter() {
+        let templates = InviteTemplates::bundled().unwrap();
+        let invites: Vec<SendPostAnnualReviewInvite> = ReviewInformationFixture::new(JOINT_INDIVIDUALS_POST_CONTACT).post_invites().into_iter().map(|invite| {
+            match AnnualReviewInvite::SendPostAnnualReviewInvite(invite).with_format(CommunicationFormat::new(Language::English, AccessibleFormat::LargePrint)) {
+                AnnualReviewInvite::SendPostAnnualReviewInvite(invite) => invite,
+                _ => panic!("Expected a post invite"),
+            }
+        }).collect();
+        let content = templates.render_letter(&invites[0], &adviser_name()).unwrap();
+
+        let pdf = String::from_utf8(LetterRenderer::new(letterhead()).render(&[PostalLetter::new(&invites[0], &content)], &adviser_name(), letter_date())).unwrap();
+        let salutation = pdf.lines().find(|line| line.ends_with("(Dear James and Sarah,) Tj ET")).unwrap();
+        assert!(salutation.starts_with("BT /F1 16 Tf"), "{}", salutation);
+        assert!(pdf.contains("Td (Your annual review with Mark Ashworth"));
+    }
+
+    #[test]
+    fn test_address_block_sits_in_dl_window() {
+        let pdf = render(JOINT_INDIVIDUALS_POST_CONTACT);
+        let window_top = PdfPage::a4().height() - ADDRESS_TOP_MM * POINTS_PER_MM;
+        let window_bottom = window_top - ADDRESS_HEIGHT_MM * POINTS_PER_MM;
+
+        let address_lines = ["(James and Sarah)", "(12 High Street)", "(GUILDFORD)", "(Surrey)", "(GU1 3AA)"];
+        for address_line in address_lines {
+            let operation = pdf.lines().find(|line| line.ends_with(&format!("{} Tj ET", address_line))).unwrap();
+            let y: f32 = operation.split_whitespace().nth(5).unwrap().parse().unwrap();
+            assert!(y < window_top && y > window_bottom, "{} at {} is outside the window", address_line, y);
+        }
+    }
+}
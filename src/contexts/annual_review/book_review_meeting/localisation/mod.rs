@@ -0,0 +1,168 @@
+//! Messages in each language a client can ask for correspondence in.
+//!
+//! The messages for a language are bundled as a `.messages` file of
+//! `key = message` lines, where `{0}`, `{1}` and so on are replaced by the
+//! message's arguments. Lines starting with `#` are comments. Every key must be
+//! in every language, which the tests check.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use chrono::{Datelike, NaiveDate};
+
+use super::contact_preferences::Language;
+
+const ENGLISH_MESSAGES: &str = include_str!("en.messages");
+const WELSH_MESSAGES: &str = include_str!("cy.messages");
+
+type Catalogue = HashMap<&'static str, &'static str>;
+
+fn parse_catalogue(source: &'static str) -> Catalogue {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(" = "))
+        .map(|(key, message)| (key.trim(), message.trim()))
+        .collect()
+}
+
+fn catalogue(language: Language) -> &'static Catalogue {
+    static ENGLISH: OnceLock<Catalogue> = OnceLock::new();
+    static WELSH: OnceLock<Catalogue> = OnceLock::new();
+
+    match language {
+        Language::English => ENGLISH.get_or_init(|| parse_catalogue(ENGLISH_MESSAGES)),
+        Language::Welsh => WELSH.get_or_init(|| parse_catalogue(WELSH_MESSAGES)),
+    }
+}
+
+/// The message for `key` in `language` with its arguments filled in. A key
+/// missing from the language falls back to English, then to the key itself,
+/// so a missing translation never stops a message being sent.
+pub fn message(language: Language, key: &str, arguments: &[&str]) -> String {
+    let template = catalogue(language)
+        .get(key)
+        .or_else(|| catalogue(Language::English).get(key))
+        .copied()
+        .unwrap_or(key);
+
+    // One pass, so an argument that happens to contain `{1}` is left as it is.
+    let mut message = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        message.push_str(&rest[..open]);
+        rest = &rest[open + 1..];
+        let argument = rest.split_once('}').and_then(|(index, after)| Some((arguments.get(index.parse::<usize>().ok()?)?, after)));
+        match argument {
+            Some((argument, after)) => {
+                message.push_str(argument);
+                rest = after;
+            }
+            None => message.push('{'),
+        }
+    }
+    message.push_str(rest);
+    message
+}
+
+/// A date written out in full, e.g. "30 June 2024" or "30 Mehefin 2024".
+pub fn format_date(language: Language, date: NaiveDate) -> String {
+    format!("{} {} {}", date.day(), message(language, &format!("date.month.{}", date.month()), &[]), date.year())
+}
+
+/// Names joined for a greeting, e.g. "Alice, Bob and Carol" or "Siân ac Owain".
+pub fn join_list(language: Language, items: &[&str]) -> String {
+    match items.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => {
+            let key = if last.starts_with(['A', 'E', 'I', 'O', 'U', 'W', 'Y', 'a', 'e', 'i', 'o', 'u', 'w', 'y']) {
+                "list.and_before_vowel"
+            } else {
+                "list.and"
+            };
+            message(language, key, &[&rest.join(", "), last])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_types::{ConstrainedIndividualNameString100, SimpleTypeError};
+    use super::super::types::{ValidationDetail, ValidationError};
+
+    const LANGUAGES: [Language; 2] = [Language::English, Language::Welsh];
+
+    #[test]
+    fn test_every_message_key_exists_in_every_language() {
+        for language in LANGUAGES {
+            for other in LANGUAGES {
+                let mut missing: Vec<&&str> = catalogue(other).keys().filter(|key| !catalogue(language).contains_key(*key)).collect();
+                missing.sort();
+                assert!(missing.is_empty(), "{:?} is missing {:?}", language, missing);
+            }
+            assert!(catalogue(language).values().all(|message| !message.is_empty()), "{:?} has an empty message", language);
+        }
+    }
+
+    #[test]
+    fn test_message_arguments() {
+        assert_eq!(message(Language::English, "letter.greeting", &["Siân"]), "Dear Siân");
+        assert_eq!(message(Language::Welsh, "letter.greeting", &["Siân"]), "Annwyl Siân");
+        assert_eq!(message(Language::Welsh, "no.such.key", &[]), "no.such.key");
+        assert_eq!(message(Language::English, "list.and", &["{1}", "Bob"]), "{1} and Bob");
+    }
+
+    #[test]
+    fn test_dates_and_lists() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 30).unwrap();
+        assert_eq!(format_date(Language::English, date), "30 June 2024");
+        assert_eq!(format_date(Language::Welsh, date), "30 Mehefin 2024");
+
+        assert_eq!(join_list(Language::English, &["Alice", "Bob", "Owain"]), "Alice, Bob and Owain");
+        assert_eq!(join_list(Language::Welsh, &["Alice", "Bob", "Carys"]), "Alice, Bob a Carys");
+        assert_eq!(join_list(Language::Welsh, &["Siân", "Owain"]), "Siân ac Owain");
+        assert_eq!(join_list(Language::Welsh, &["Siân"]), "Siân");
+    }
+
+    #[test]
+    fn test_validation_error_messages() {
+        let error = ValidationError::InvalidEmail(ValidationDetail::new("validation.address_needed", &["Siân"]));
+        assert_eq!(error.to_string(), "Invalid email: An email or postal address is needed for Siân.");
+        assert_eq!(error.message(Language::Welsh), "E-bost annilys: Mae angen cyfeiriad e-bost neu gyfeiriad post ar gyfer Siân.");
+
+        let name_error = ConstrainedIndividualNameString100::try_from("Siân 2".to_string()).unwrap_err();
+        let error = ValidationError::InvalidName(name_error.into());
+        assert_eq!(error.to_string(), "Invalid name: Constrained name string 100 must not have any numbers in it.");
+        assert_eq!(error.message(Language::Welsh), "Enw annilys: Ni chaiff enw gynnwys rhifau.");
+    }
+
+    #[test]
+    fn test_simple_type_errors_match_their_english_messages() {
+        let errors = [
+            SimpleTypeError::InvalidClientId,
+            SimpleTypeError::InvalidReviewId,
+            SimpleTypeError::AddressTooLong,
+            SimpleTypeError::AddressHasUnusualCharacters,
+            SimpleTypeError::NameEmpty,
+            SimpleTypeError::NameTooLong,
+            SimpleTypeError::NameHasNumbers,
+            SimpleTypeError::NameHasUnusualCharacters,
+            SimpleTypeError::TrustOrCompanyNameHasUnusualCharacters,
+            SimpleTypeError::InvalidEmailAddress,
+            SimpleTypeError::PostcodeEmpty,
+            SimpleTypeError::InvalidPostcode,
+            SimpleTypeError::InvalidHouseNumber,
+            SimpleTypeError::PhoneNumberPrefix,
+            SimpleTypeError::PhoneNumberCharacters,
+            SimpleTypeError::InternationalPhoneNumberLength,
+            SimpleTypeError::UkPhoneNumberPrefix,
+            SimpleTypeError::UkPhoneNumberLength,
+        ];
+
+        for error in errors {
+            assert_eq!(message(Language::English, error.key(), &[]), error.to_string());
+        }
+    }
+}
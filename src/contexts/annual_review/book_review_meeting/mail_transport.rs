@@ -0,0 +1,266 @@
+use chrono::NaiveDateTime;
+use serde::{Serialize, Deserialize};
+
+use crate::{simple_types::{EmailAddress, ReviewId}, smtp::{SmtpClient, SmtpConfig, SmtpError, SmtpReply}};
+use super::invite_email::InviteEmail;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DeliveryFailureKind {
+    /// Sending again later may succeed, e.g. the server was unreachable.
+    Transient,
+    /// Sending again unchanged will fail, e.g. the mailbox does not exist.
+    Permanent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeliveryFailure {
+    kind: DeliveryFailureKind,
+    code: Option<u16>,
+    reason: String,
+}
+
+impl DeliveryFailure {
+    pub fn new(kind: DeliveryFailureKind, code: Option<u16>, reason: &str) -> Self {
+        Self { kind, code, reason: reason.to_string() }
+    }
+
+    pub fn kind(&self) -> DeliveryFailureKind {
+        self.kind
+    }
+
+    pub fn code(&self) -> Option<u16> {
+        self.code
+    }
+
+    pub fn reason(&self) -> &String {
+        &self.reason
+    }
+
+    pub fn is_permanent(&self) -> bool {
+        self.kind == DeliveryFailureKind::Permanent
+    }
+}
+
+/// What the transport did with each recipient of a message it handed on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransportReport {
+    accepted: Vec<EmailAddress>,
+    rejected: Vec<(EmailAddress, DeliveryFailure)>,
+}
+
+impl TransportReport {
+    pub fn new(accepted: Vec<EmailAddress>, rejected: Vec<(EmailAddress, DeliveryFailure)>) -> Self {
+        Self { accepted, rejected }
+    }
+
+    pub fn accepted(&self) -> &Vec<EmailAddress> {
+        &self.accepted
+    }
+
+    pub fn rejected(&self) -> &Vec<(EmailAddress, DeliveryFailure)> {
+        &self.rejected
+    }
+}
+
+/// Port for handing a raw RFC 5322 message to a mail server.
+pub trait MailTransport {
+    /// Returns an error only when the message was not accepted for any recipient.
+    fn send(&self, return_path: &EmailAddress, recipients: &[EmailAddress], raw_message: &str) -> Result<TransportReport, DeliveryFailure>;
+}
+
+pub struct SmtpMailTransport {
+    client: SmtpClient,
+}
+
+impl SmtpMailTransport {
+    pub fn new(config: SmtpConfig) -> Self {
+        Self { client: SmtpClient::new(config) }
+    }
+}
+
+impl MailTransport for SmtpMailTransport {
+    fn send(&self, return_path: &EmailAddress, recipients: &[EmailAddress], raw_message: &str) -> Result<TransportReport, DeliveryFailure> {
+        let addresses: Vec<&str> = recipients.iter().map(|recipient| recipient.value()).collect();
+        let report = self.client.send(return_path.value(), &addresses, raw_message).map_err(|error| failure_from_error(&error))?;
+
+        let recipient = |address: &String| recipients.iter().find(|recipient| recipient.value() == address).cloned();
+        Ok(TransportReport {
+            accepted: report.accepted().iter().filter_map(recipient).collect(),
+            rejected: report.rejected().iter()
+                .filter_map(|(address, reply)| recipient(address).map(|recipient| (recipient, failure_from_reply(reply))))
+                .collect(),
+        })
+    }
+}
+
+fn failure_from_reply(reply: &SmtpReply) -> DeliveryFailure {
+    let kind = if reply.is_permanent_failure() { DeliveryFailureKind::Permanent } else { DeliveryFailureKind::Transient };
+    DeliveryFailure::new(kind, Some(reply.code()), reply.message())
+}
+
+fn failure_from_error(error: &SmtpError) -> DeliveryFailure {
+    match error {
+        SmtpError::Reply(reply) => failure_from_reply(reply),
+        error if error.is_permanent() => DeliveryFailure::new(DeliveryFailureKind::Permanent, None, &error.to_string()),
+        error => DeliveryFailure::new(DeliveryFailureKind::Transient, None, &error.to_string()),
+    }
+}
+
+/// Domain events recorded for every invite email send so that a failure is
+/// always visible to the workflow.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum InviteDeliveryEvent {
+    DeliveryAttempted { review_id: ReviewId, message_id: String, attempt: u32, attempted_at: NaiveDateTime },
+    RecipientAccepted { review_id: ReviewId, message_id: String, recipient: EmailAddress },
+    RecipientRejected { review_id: ReviewId, message_id: String, recipient: EmailAddress, failure: DeliveryFailure },
+    DeliveryFailed { review_id: ReviewId, message_id: String, failure: DeliveryFailure },
+}
+
+impl InviteDeliveryEvent {
+    pub fn message_id(&self) -> &String {
+        match self {
+            Self::DeliveryAttempted { message_id, .. }
+            | Self::RecipientAccepted { message_id, .. }
+            | Self::RecipientRejected { message_id, .. }
+            | Self::DeliveryFailed { message_id, .. } => message_id,
+        }
+    }
+}
+
+pub struct InviteDelivery<'a> {
+    transport: &'a dyn MailTransport,
+    return_path: EmailAddress,
+}
+
+impl<'a> InviteDelivery<'a> {
+    /// `return_path` receives bounces for messages that fail after being accepted.
+    pub fn new(transport: &'a dyn MailTransport, return_path: EmailAddress) -> Self {
+        Self { transport, return_path }
+    }
+
+    /// Sends the email and returns the events describing what happened, starting
+    /// with `DeliveryAttempted`. Recipients the transport neither accepted nor
+    /// rejected are reported as transient rejections.
+    pub fn deliver(&self, review_id: &ReviewId, email: &InviteEmail, attempt: u32, attempted_at: NaiveDateTime) -> Vec<InviteDeliveryEvent> {
+        let message_id = email.message_id().clone();
+        let mut events = vec![InviteDeliveryEvent::DeliveryAttempted {
+            review_id: review_id.clone(),
+            message_id: message_id.clone(),
+            attempt,
+            attempted_at,
+        }];
+
+        let recipients = [email.recipient().clone()];
+        match self.transport.send(&self.return_path, &recipients, &email.to_rfc5322_string()) {
+            Err(failure) => events.push(InviteDeliveryEvent::DeliveryFailed { review_id: review_id.clone(), message_id, failure }),
+            Ok(report) => {
+                for recipient in &recipients {
+                    let event = if report.accepted().contains(recipient) {
+                        InviteDeliveryEvent::RecipientAccepted { review_id: review_id.clone(), message_id: message_id.clone(), recipient: recipient.clone() }
+                    } else {
+                        let failure = report.rejected().iter()
+                            .find(|(rejected, _)| rejected == recipient)
+                            .map(|(_, failure)| failure.clone())
+                            .unwrap_or_else(|| DeliveryFailure::new(DeliveryFailureKind::Transient, None, "Recipient not reported by the mail transport"));
+                        InviteDeliveryEvent::RecipientRejected { review_id: review_id.clone(), message_id: message_id.clone(), recipient: recipient.clone(), failure }
+                    };
+                    events.push(event);
+                }
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::NaiveDate;
+
+    use crate::{
+        mime::Mailbox,
+        smtp::fake_server::FakeSmtpServer,
+        tests::test_utils::shared::{ReviewInformationFixture, ADMINISTRATOR_EMAIL, REVIEW_ID, SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT},
+    };
+    use super::super::{invite_email::InviteEmailBuilder, templates::InviteTemplates};
+    use super::*;
+
+    fn attempted_at() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(8, 30, 0).unwrap()
+    }
+
+    fn review_id() -> ReviewId {
+        ReviewId::try_from(REVIEW_ID.to_string()).unwrap()
+    }
+
+    fn invite_email() -> InviteEmail {
+        let fixture = ReviewInformationFixture::new(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT);
+        let (review_information, invite) = (fixture.validated(), fixture.electronic_invites().remove(0));
+
+        let content = InviteTemplates::bundled().unwrap().render_email(&invite, review_information.adviser_name()).unwrap();
+        let sender = Mailbox::new(None, EmailAddress::try_from("invites@gcwm.co.uk".to_string()).unwrap());
+        let review_id = review_id();
+        InviteEmailBuilder::new(sender, &review_id, &review_information).build(&invite, &content, attempted_at())
+    }
+
+    fn return_path() -> EmailAddress {
+        EmailAddress::try_from(ADMINISTRATOR_EMAIL.to_string()).unwrap()
+    }
+
+    fn smtp_transport(port: u16) -> SmtpMailTransport {
+        SmtpMailTransport::new(SmtpConfig::new("127.0.0.1", port, "gcwm.co.uk").with_timeout(Duration::from_secs(5)))
+    }
+
+    #[test]
+    fn test_delivery_through_smtp_is_recorded() {
+        let server = FakeSmtpServer::new().start().unwrap();
+        let transport = smtp_transport(server.port());
+        let email = invite_email();
+
+        let events = InviteDelivery::new(&transport, return_path()).deliver(&review_id(), &email, 1, attempted_at());
+
+        assert_eq!(events, vec![
+            InviteDeliveryEvent::DeliveryAttempted { review_id: review_id(), message_id: email.message_id().clone(), attempt: 1, attempted_at: attempted_at() },
+            InviteDeliveryEvent::RecipientAccepted { review_id: review_id(), message_id: email.message_id().clone(), recipient: email.recipient().clone() },
+        ]);
+
+        let messages = server.received_messages();
+        assert_eq!(messages[0].sender(), ADMINISTRATOR_EMAIL);
+        assert_eq!(messages[0].data(), &email.to_rfc5322_string());
+    }
+
+    #[test]
+    fn test_rejected_recipient_is_permanent_failure() {
+        let server = FakeSmtpServer::new()
+            .with_recipient_reply("sian@example.com", SmtpReply::new(550, "Mailbox unavailable"))
+            .start()
+            .unwrap();
+        let transport = smtp_transport(server.port());
+
+        let events = InviteDelivery::new(&transport, return_path()).deliver(&review_id(), &invite_email(), 1, attempted_at());
+
+        let InviteDeliveryEvent::RecipientRejected { failure, .. } = &events[1] else {
+            panic!("Expected a rejected recipient, got {:?}", events);
+        };
+        assert!(failure.is_permanent());
+        assert_eq!(failure.code(), Some(550));
+        assert!(server.received_messages().is_empty());
+    }
+
+    #[test]
+    fn test_server_failure_is_transient_failure() {
+        let server = FakeSmtpServer::new().with_data_reply(SmtpReply::new(421, "Service not available")).start().unwrap();
+        let transport = smtp_transport(server.port());
+
+        let events = InviteDelivery::new(&transport, return_path()).deliver(&review_id(), &invite_email(), 2, attempted_at());
+
+        assert_eq!(events.len(), 2);
+        let InviteDeliveryEvent::DeliveryFailed { failure, .. } = &events[1] else {
+            panic!("Expected a failed delivery, got {:?}", events);
+        };
+        assert_eq!(failure.kind(), DeliveryFailureKind::Transient);
+        assert_eq!(failure.reason(), "Service not available");
+    }
+}
@@ -1,3 +1,4 @@
+/// ```text
 /// workflow "Book Review Meeting" = 
 ///     input:
 ///         UnvalidatedAnnualReviewInformation
@@ -7,15 +8,8 @@
 ///     Output:
 ///         SendElectronicAnnualReviewInvite
 ///         SendPostAnnualReviewInvite
-
-
-
-
-
-
-
-
-
+///         SendSmsAnnualReviewInvite
+///
 /// Workflow: Book Review Meeting
 /// Triggered by: 
 ///     2 month prior to annual review due date scheduler - 
@@ -38,8 +32,7 @@
 /// return:
 ///     AnnualReviewWorkflowTriggered Event
 ///     AnnualReviewScheduleMeetingMethod Event
- 
-
+///
 /// ClientContactInformation
 ///     JointIndividualsElectronicContact
 ///     SingleIndividualElectronicContact
@@ -53,20 +46,19 @@
 ///     PrimaryDirectorElectronicContact
 ///     MutlipleDirectorsPostContact
 ///     PrimaryDirectorPostContact    
-
+///
 /// AnnualReviewDueDate
 ///     
-
-
-
-
-
+///
 ///
 /// 
 /// 
 /// Other input: 
 ///     Clients preferred contact method
-///     Advisers calender
+///     Clients vulnerability profile
+///     Clients attorneys and deputies (AuthorisedContact)
+///     Deceased clients and their executors (DeceasedClient)
+///     Advisers calender (AdviserCalendar)
 /// Output events: 
 ///     'Review Meeting Booked' Event
 ///     'Review Meeting Declined In Tax Year' Event
@@ -74,5 +66,27 @@
 /// Side effects:
 ///     An acknowledgement of the outcome is sent
 ///     via the clients preferred contact method
-
-mod types;
+/// ```
+pub mod types;
+pub mod adviser_calendar;
+pub mod slot_finder;
+pub mod calendar_invite;
+pub mod invites;
+pub mod templates;
+pub mod localisation;
+pub mod invite_email;
+pub mod mail_transport;
+pub mod print_export;
+pub mod letter_pdf;
+pub mod delivery_tracking;
+pub mod reminders;
+pub mod responses;
+pub mod booking_links;
+pub mod contact_preferences;
+pub mod vulnerability;
+pub mod authorised_contacts;
+pub mod bereavement;
+pub mod invite_sms;
+pub mod sms_gateway;
+pub mod audited_commands;
+pub mod personal_data;
\ No newline at end of file
@@ -0,0 +1,328 @@
+//! The reminder timer table and the invite delivery tracker as
+//! [`PersonalDataSource`]s for subject access and erasure.
+//!
+//! Both are kept by review rather than by client, so each source is given the
+//! client's reviews through [`ClientReviews`]. Only reminders carry an invite;
+//! escalation and No Response timers hold nothing about the client and are
+//! left out. Erasing a timer or an outbound invite removes it, as neither can
+//! be sent without its recipient. What happened stays on the review's
+//! compliance file as reminder and delivery events.
+
+use chrono::{NaiveDateTime, NaiveTime};
+use serde_json::json;
+
+use crate::{
+    contexts::data_protection::subject_rights::{DataSubjectError, Erasure, PersonalDataSource, SubjectRecord},
+    simple_types::{ClientId, ReviewId},
+};
+use super::{
+    delivery_tracking::InviteDeliveryTracker,
+    invites::AnnualReviewInvite,
+    reminders::{ReminderAction, ReminderError, ReminderTimer, ReminderTimerTable},
+};
+
+const REMINDER_TIMERS: &str = "annual_review.reminder_timers";
+const OUTBOUND_INVITES: &str = "annual_review.outbound_invites";
+
+/// Port for looking up the reviews held for a client.
+pub trait ClientReviews {
+    fn reviews_for(&self, client_id: &ClientId) -> Vec<ReviewId>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryClientReviews {
+    reviews: Vec<(ClientId, ReviewId)>,
+}
+
+impl InMemoryClientReviews {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_review(mut self, client_id: &ClientId, review_id: &ReviewId) -> Self {
+        self.reviews.push((client_id.clone(), review_id.clone()));
+        self
+    }
+}
+
+impl ClientReviews for InMemoryClientReviews {
+    fn reviews_for(&self, client_id: &ClientId) -> Vec<ReviewId> {
+        self.reviews.iter().filter(|(review_client_id, _)| review_client_id == client_id).map(|(_, review_id)| review_id.clone()).collect()
+    }
+}
+
+/// The client's pending reminders, referenced `{invite reference}-reminder-{number}`
+/// and dated by the day they fall due.
+pub struct ReminderTimerSource<'a> {
+    timers: &'a mut dyn ReminderTimerTable,
+    reviews: &'a dyn ClientReviews,
+}
+
+impl<'a> ReminderTimerSource<'a> {
+    pub fn new(timers: &'a mut dyn ReminderTimerTable, reviews: &'a dyn ClientReviews) -> Self {
+        Self { timers, reviews }
+    }
+}
+
+impl PersonalDataSource for ReminderTimerSource<'_> {
+    fn name(&self) -> &str {
+        REMINDER_TIMERS
+    }
+
+    fn records_for(&self, client_id: &ClientId) -> Result<Vec<SubjectRecord>, DataSubjectError> {
+        let mut records = Vec::new();
+        for review_id in self.reviews.reviews_for(client_id) {
+            for timer in self.timers.timers_for(&review_id).map_err(timer_table_unavailable)? {
+                let (Some(record_id), ReminderAction::SendReminder { number, invite_reference, invite }) = (reminder_record_id(&timer), timer.action()) else {
+                    continue;
+                };
+                records.push(SubjectRecord::new(
+                    REMINDER_TIMERS,
+                    "reminder_timer",
+                    &record_id,
+                    timer.due_on().and_time(NaiveTime::MIN),
+                    json!({ "review_id": review_id, "invite_reference": invite_reference, "number": number, "due_on": timer.due_on() }),
+                    Some(invite_personal_data(invite)),
+                ));
+            }
+        }
+        Ok(records)
+    }
+
+    fn erase(&mut self, client_id: &ClientId, record_ids: &[String], _erased_at: NaiveDateTime) -> Result<Erasure, DataSubjectError> {
+        let mut erased = 0;
+        for review_id in self.reviews.reviews_for(client_id) {
+            let (removed, kept): (Vec<ReminderTimer>, Vec<ReminderTimer>) = self
+                .timers
+                .timers_for(&review_id)
+                .map_err(timer_table_unavailable)?
+                .into_iter()
+                .partition(|timer| reminder_record_id(timer).is_some_and(|record_id| record_ids.contains(&record_id)));
+            if !removed.is_empty() {
+                self.timers.replace_review(&review_id, kept).map_err(timer_table_unavailable)?;
+                erased += removed.len();
+            }
+        }
+        Ok(Erasure::new(erased))
+    }
+}
+
+/// The client's invites being delivered, referenced as in the tracker and
+/// dated by when they were queued.
+pub struct DeliveryTrackerSource<'a> {
+    tracker: &'a mut InviteDeliveryTracker,
+    reviews: &'a dyn ClientReviews,
+}
+
+impl<'a> DeliveryTrackerSource<'a> {
+    pub fn new(tracker: &'a mut InviteDeliveryTracker, reviews: &'a dyn ClientReviews) -> Self {
+        Self { tracker, reviews }
+    }
+}
+
+impl PersonalDataSource for DeliveryTrackerSource<'_> {
+    fn name(&self) -> &str {
+        OUTBOUND_INVITES
+    }
+
+    fn records_for(&self, client_id: &ClientId) -> Result<Vec<SubjectRecord>, DataSubjectError> {
+        let review_ids = self.reviews.reviews_for(client_id);
+        Ok(self
+            .tracker
+            .invites()
+            .iter()
+            .filter(|invite| review_ids.contains(invite.review_id()))
+            .map(|invite| SubjectRecord::new(
+                OUTBOUND_INVITES,
+                "outbound_invite",
+                invite.reference(),
+                invite.queued_at(),
+                json!({ "review_id": invite.review_id(), "channel": invite.channel(), "state": invite.state(), "fallback_for": invite.fallback_for() }),
+                Some(invite_personal_data(invite.invite())),
+            ))
+            .collect())
+    }
+
+    fn erase(&mut self, client_id: &ClientId, record_ids: &[String], _erased_at: NaiveDateTime) -> Result<Erasure, DataSubjectError> {
+        let review_ids = self.reviews.reviews_for(client_id);
+        let references: Vec<String> = self
+            .tracker
+            .invites()
+            .iter()
+            .filter(|invite| review_ids.contains(invite.review_id()) && record_ids.contains(invite.reference()))
+            .map(|invite| invite.reference().clone())
+            .collect();
+
+        Ok(Erasure::new(references.iter().filter_map(|reference| self.tracker.remove(reference)).count()))
+    }
+}
+
+fn reminder_record_id(timer: &ReminderTimer) -> Option<String> {
+    match timer.action() {
+        ReminderAction::SendReminder { number, invite_reference, .. } => Some(format!("{}-reminder-{}", invite_reference, number)),
+        ReminderAction::EscalateToAdviser { .. } | ReminderAction::RecordNoResponse { .. } => None,
+    }
+}
+
+/// Who the invite is to and where it goes, in the clear for the export.
+fn invite_personal_data(invite: &AnnualReviewInvite) -> serde_json::Value {
+    match invite {
+        AnnualReviewInvite::SendElectronicAnnualReviewInvite(electronic) => json!({
+            "recipient_names": electronic.recipient_names(),
+            "on_behalf_of": electronic.on_behalf_of(),
+            "organisation_name": electronic.organisation_name(),
+            "email_address": electronic.email_address(),
+        }),
+        AnnualReviewInvite::SendPostAnnualReviewInvite(post) => json!({
+            "recipient_names": post.recipient_names(),
+            "on_behalf_of": post.on_behalf_of(),
+            "organisation_name": post.organisation_name(),
+            "postal_address": post.postal_address(),
+        }),
+        AnnualReviewInvite::SendSmsAnnualReviewInvite(sms) => json!({
+            "recipient_names": sms.recipient_names(),
+            "organisation_name": sms.organisation_name(),
+            "phone_number": sms.phone_number(),
+        }),
+    }
+}
+
+fn timer_table_unavailable(error: ReminderError) -> DataSubjectError {
+    DataSubjectError::SourceUnavailable(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::{
+        contexts::data_protection::{
+            retention::{InMemoryRetentionHolds, RetentionPolicy},
+            subject_rights::{DataSubjectEvent, DataSubjectService},
+        },
+        tests::test_utils::shared::{ReviewInformationFixture, JOINT_INDIVIDUALS_POST_CONTACT, REVIEW_ID, SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT},
+    };
+    use super::super::{
+        reminders::InMemoryReminderTimerTable,
+        types::TaxYear,
+    };
+    use super::*;
+
+    const CLIENT_ID: &str = "a1b2c3d4-e5f6-4a5b-8c9d-0e1f2a3b4c5d";
+    const OTHER_CLIENT_ID: &str = "f0e1d2c3-b4a5-4968-8776-655443322110";
+    const OTHER_REVIEW_ID: &str = "0b1c2d3e-4f5a-4b6c-8d7e-9f0a1b2c3d4e";
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn client_id(id: &str) -> ClientId {
+        ClientId::try_from(id.to_string()).unwrap()
+    }
+
+    fn review_id(id: &str) -> ReviewId {
+        ReviewId::try_from(id.to_string()).unwrap()
+    }
+
+    fn client_reviews() -> InMemoryClientReviews {
+        InMemoryClientReviews::new()
+            .with_review(&client_id(CLIENT_ID), &review_id(REVIEW_ID))
+            .with_review(&client_id(OTHER_CLIENT_ID), &review_id(OTHER_REVIEW_ID))
+    }
+
+    fn invite(client_contact_information: &str) -> AnnualReviewInvite {
+        ReviewInformationFixture::new(client_contact_information).invites().remove(0)
+    }
+
+    fn reminder(review: &str, number: u8, client_contact_information: &str) -> ReminderTimer {
+        ReminderTimer::new(review_id(review), date(5, 8), ReminderAction::SendReminder {
+            number,
+            invite_reference: format!("{}-1", review),
+            invite: Box::new(invite(client_contact_information)),
+        })
+    }
+
+    fn timer_table() -> InMemoryReminderTimerTable {
+        let mut timers = InMemoryReminderTimerTable::new();
+        timers.insert(vec![
+            reminder(REVIEW_ID, 1, SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT),
+            ReminderTimer::new(review_id(REVIEW_ID), date(5, 29), ReminderAction::RecordNoResponse { tax_year: TaxYear::containing(date(6, 30)) }),
+            reminder(OTHER_REVIEW_ID, 1, JOINT_INDIVIDUALS_POST_CONTACT),
+        ]).unwrap();
+        timers
+    }
+
+    fn tracker() -> InviteDeliveryTracker {
+        let queued_at = date(5, 1).and_hms_opt(8, 0, 0).unwrap();
+        let mut tracker = InviteDeliveryTracker::new();
+        tracker.queue("email-1", &review_id(REVIEW_ID), invite(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT), queued_at).unwrap();
+        tracker.queue("letter-2", &review_id(OTHER_REVIEW_ID), invite(JOINT_INDIVIDUALS_POST_CONTACT), queued_at).unwrap();
+        tracker
+    }
+
+    #[test]
+    fn test_reminders_are_found_through_the_clients_reviews() {
+        let (mut timers, reviews) = (timer_table(), client_reviews());
+        let source = ReminderTimerSource::new(&mut timers, &reviews);
+
+        let records = source.records_for(&client_id(CLIENT_ID)).unwrap();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].record_id(), &format!("{}-1-reminder-1", REVIEW_ID));
+        assert_eq!(records[0].facts()["number"], 1);
+        assert_eq!(records[0].personal_data().unwrap()["email_address"], "sian@example.com");
+        assert_eq!(records[0].personal_data().unwrap()["recipient_names"][0], "Siân");
+    }
+
+    #[test]
+    fn test_erasing_reminders_keeps_other_timers() {
+        let (mut timers, reviews) = (timer_table(), client_reviews());
+        let mut source = ReminderTimerSource::new(&mut timers, &reviews);
+        let record_ids = vec![format!("{}-1-reminder-1", REVIEW_ID)];
+
+        let erasure = source.erase(&client_id(CLIENT_ID), &record_ids, date(6, 1).and_hms_opt(9, 0, 0).unwrap()).unwrap();
+
+        assert_eq!(erasure, Erasure::new(1));
+        assert!(source.records_for(&client_id(CLIENT_ID)).unwrap().is_empty());
+        assert_eq!(source.erase(&client_id(OTHER_CLIENT_ID), &record_ids, date(6, 1).and_hms_opt(9, 0, 0).unwrap()).unwrap(), Erasure::new(0));
+        assert_eq!(timers.timers_for(&review_id(REVIEW_ID)).unwrap().len(), 1);
+        assert_eq!(timers.timers_for(&review_id(OTHER_REVIEW_ID)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_outbound_invites_are_exported_and_removed_on_erasure() {
+        let (mut tracker, reviews) = (tracker(), client_reviews());
+        let mut source = DeliveryTrackerSource::new(&mut tracker, &reviews);
+
+        let records = source.records_for(&client_id(OTHER_CLIENT_ID)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].record_id(), "letter-2");
+        assert_eq!(records[0].facts()["state"], "Queued");
+        assert!(records[0].personal_data().unwrap()["postal_address"].is_object());
+
+        let erasure = source.erase(&client_id(OTHER_CLIENT_ID), &["letter-2".to_string(), "email-1".to_string()], date(6, 1).and_hms_opt(9, 0, 0).unwrap()).unwrap();
+        assert_eq!(erasure, Erasure::new(1));
+        assert!(tracker.invite("letter-2").is_none());
+        assert!(tracker.invite("email-1").is_some());
+    }
+
+    #[test]
+    fn test_erasure_reaches_reminders_and_outbound_invites() {
+        let (mut timers, mut tracker, reviews) = (timer_table(), tracker(), client_reviews());
+        let (policy, holds) = (RetentionPolicy::annual_review(), InMemoryRetentionHolds::new());
+        let (mut timer_source, mut tracker_source) = (ReminderTimerSource::new(&mut timers, &reviews), DeliveryTrackerSource::new(&mut tracker, &reviews));
+        let mut service = DataSubjectService::new(&policy, &holds).with_source(&mut timer_source).with_source(&mut tracker_source);
+        let requested_at = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+
+        let events = service.erase(&client_id(CLIENT_ID), requested_at).unwrap();
+
+        assert_eq!(events, vec![
+            DataSubjectEvent::PersonalDataErased { client_id: client_id(CLIENT_ID), source: REMINDER_TIMERS.to_string(), records: 1, erased_at: requested_at },
+            DataSubjectEvent::PersonalDataErased { client_id: client_id(CLIENT_ID), source: OUTBOUND_INVITES.to_string(), records: 1, erased_at: requested_at },
+        ]);
+        let (export, _) = service.subject_access_export(&client_id(CLIENT_ID), requested_at).unwrap();
+        assert!(export.records().is_empty());
+        let (other_export, _) = service.subject_access_export(&client_id(OTHER_CLIENT_ID), requested_at).unwrap();
+        assert_eq!(other_export.records().len(), 2);
+    }
+}
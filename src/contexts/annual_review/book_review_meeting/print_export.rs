@@ -0,0 +1,533 @@
+//! Batch export of postal invites for the print vendor and import of the
+//! vendor's status file.
+//!
+//! Each letter has a reference of the form `{review_id}-{letter number}` which
+//! the vendor returns in the status file. Letters are routed to the vendor's
+//! production line for the client's format, and a batch can be split into a
+//! manifest per line.
+
+use chrono::NaiveDateTime;
+use serde::{Serialize, Deserialize};
+
+use crate::{redaction::mask_postcode, simple_types::ReviewId};
+use super::{
+    contact_preferences::{AccessibleFormat, Language},
+    invites::{AnnualReviewInvite, SendPostAnnualReviewInvite},
+    localisation::message,
+    templates::join_names,
+};
+
+/// Columns reserved for the name and address blocks in the CSV manifest.
+const NAME_COLUMNS: usize = 2;
+const ADDRESS_COLUMNS: usize = 9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrintImportError {
+    InvalidHeader(String),
+    InvalidRow(String),
+    UnknownReference(String),
+}
+
+impl std::fmt::Display for PrintImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            PrintImportError::InvalidHeader(ref desc) => write!(f, "Invalid header: {}", desc),
+            PrintImportError::InvalidRow(ref desc) => write!(f, "Invalid row: {}", desc),
+            PrintImportError::UnknownReference(ref desc) => write!(f, "Unknown reference: {}", desc),
+        }
+    }
+}
+
+impl std::error::Error for PrintImportError {}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd)]
+pub enum PrintStatus {
+    Pending,
+    Printed,
+    Posted,
+}
+
+/// Where the vendor produces a letter.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ProductionLine {
+    Standard,
+    LargePrint,
+    /// Transcribed into braille from the standard letter.
+    Braille,
+    /// Recorded from the standard letter and sent on CD or USB stick.
+    Audio,
+}
+
+impl ProductionLine {
+    pub fn for_format(accessible_format: AccessibleFormat) -> Self {
+        match accessible_format {
+            AccessibleFormat::Standard => Self::Standard,
+            AccessibleFormat::LargePrint => Self::LargePrint,
+            AccessibleFormat::Braille => Self::Braille,
+            AccessibleFormat::Audio => Self::Audio,
+        }
+    }
+
+    pub fn value(&self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::LargePrint => "large_print",
+            Self::Braille => "braille",
+            Self::Audio => "audio",
+        }
+    }
+}
+
+/// One letter in a print batch.
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrintManifestLetter {
+    reference: String,
+    review_id: ReviewId,
+    template_id: String,
+    name_block: Vec<String>,
+    /// The address without the postcode, which has a field of its own.
+    address_lines: Vec<String>,
+    postcode: String,
+    salutation: String,
+    production_line: ProductionLine,
+    language: Language,
+    #[serde(skip)]
+    status: Option<PrintStatus>,
+}
+
+/// The names, address and salutation are left out and the postcode is masked.
+impl std::fmt::Debug for PrintManifestLetter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrintManifestLetter")
+            .field("reference", &self.reference)
+            .field("review_id", &self.review_id)
+            .field("template_id", &self.template_id)
+            .field("postcode", &mask_postcode(&self.postcode))
+            .field("production_line", &self.production_line)
+            .field("language", &self.language)
+            .field("status", &self.status)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PrintManifestLetter {
+    pub fn reference(&self) -> &String {
+        &self.reference
+    }
+
+    pub fn review_id(&self) -> &ReviewId {
+        &self.review_id
+    }
+
+    pub fn template_id(&self) -> &String {
+        &self.template_id
+    }
+
+    pub fn name_block(&self) -> &Vec<String> {
+        &self.name_block
+    }
+
+    pub fn address_lines(&self) -> &Vec<String> {
+        &self.address_lines
+    }
+
+    pub fn postcode(&self) -> &String {
+        &self.postcode
+    }
+
+    /// The greeting line of the letter, e.g. "Dear Alice and Bob".
+    pub fn salutation(&self) -> &String {
+        &self.salutation
+    }
+
+    pub fn production_line(&self) -> ProductionLine {
+        self.production_line
+    }
+
+    /// The language the letter is written in, which braille and audio are produced in.
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn status(&self) -> PrintStatus {
+        self.status.unwrap_or(PrintStatus::Pending)
+    }
+}
+
+/// A batch of letters for the print vendor, kept in postcode order so that the
+/// vendor can claim the postage discount for sorted mail.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrintManifest {
+    batch_id: String,
+    created_at: NaiveDateTime,
+    letters: Vec<PrintManifestLetter>,
+}
+
+impl PrintManifest {
+    pub fn new(batch_id: &str, created_at: NaiveDateTime) -> Self {
+        Self { batch_id: batch_id.to_string(), created_at, letters: Vec::new() }
+    }
+
+    pub fn batch_id(&self) -> &String {
+        &self.batch_id
+    }
+
+    pub fn letters(&self) -> &Vec<PrintManifestLetter> {
+        &self.letters
+    }
+
+    /// Adds a letter for each postal invite of the review, referenced `{review_id}-{letter number}`.
+    /// Electronic and SMS invites are ignored.
+    pub fn add_review(&mut self, review_id: &ReviewId, invites: &[AnnualReviewInvite]) {
+        let post_invites = invites.iter().filter_map(|invite| match invite {
+            AnnualReviewInvite::SendPostAnnualReviewInvite(invite) => Some(invite),
+            AnnualReviewInvite::SendElectronicAnnualReviewInvite(_) | AnnualReviewInvite::SendSmsAnnualReviewInvite(_) => None,
+        });
+
+        for (index, invite) in post_invites.enumerate() {
+            self.add_letter(&format!("{}-{:02}", review_id.value(), index + 1), review_id, invite);
+        }
+    }
+
+    /// Adds a single letter with a reference chosen by the caller, e.g. a postal fallback.
+    pub fn add_letter(&mut self, reference: &str, review_id: &ReviewId, invite: &SendPostAnnualReviewInvite) {
+        let language = invite.format().language();
+        let mut name_block = vec![join_names(invite.recipient_names(), language)];
+        name_block.extend(invite.organisation_name().map(|name| name.value().to_string()));
+
+        self.letters.push(PrintManifestLetter {
+            reference: reference.to_string(),
+            review_id: review_id.clone(),
+            template_id: invite.content_reference().value().to_string(),
+            name_block,
+            address_lines: invite.postal_address().address_lines_without_postcode(),
+            postcode: invite.postal_address().postcode().value().to_string(),
+            salutation: message(language, "letter.greeting", &[&join_names(invite.recipient_names(), language)]),
+            production_line: ProductionLine::for_format(invite.format().accessible_format()),
+            language,
+            status: None,
+        });
+
+        self.letters.sort_by(|a, b| (sort_key(&a.postcode), &a.reference).cmp(&(sort_key(&b.postcode), &b.reference)));
+    }
+
+    /// The production lines with letters in this batch.
+    pub fn production_lines(&self) -> Vec<ProductionLine> {
+        let mut production_lines: Vec<ProductionLine> = Vec::new();
+        for letter in &self.letters {
+            if !production_lines.contains(&letter.production_line) {
+                production_lines.push(letter.production_line);
+            }
+        }
+        production_lines
+    }
+
+    /// The letters for one production line as a batch of their own, with the
+    /// batch id `{batch_id}-{production line}`. References are unchanged, so
+    /// status files from each line apply to this batch too.
+    pub fn for_production_line(&self, production_line: ProductionLine) -> PrintManifest {
+        PrintManifest {
+            batch_id: format!("{}-{}", self.batch_id, production_line.value()),
+            created_at: self.created_at,
+            letters: self.letters.iter().filter(|letter| letter.production_line == production_line).cloned().collect(),
+        }
+    }
+
+    /// The manifest as CSV with a header row and a fixed number of name and address columns.
+    pub fn to_csv(&self) -> String {
+        let mut header = vec!["reference".to_string(), "template_id".to_string()];
+        header.extend((1..=NAME_COLUMNS).map(|n| format!("name_line_{}", n)));
+        header.extend((1..=ADDRESS_COLUMNS).map(|n| format!("address_line_{}", n)));
+        header.extend(["postcode".to_string(), "salutation".to_string(), "production_line".to_string(), "language".to_string()]);
+
+        let mut rows = vec![csv_row(&header)];
+        for letter in &self.letters {
+            let mut row = vec![letter.reference.clone(), letter.template_id.clone()];
+            row.extend(padded(&letter.name_block, NAME_COLUMNS));
+            row.extend(padded(&letter.address_lines, ADDRESS_COLUMNS));
+            row.extend([
+                letter.postcode.clone(),
+                letter.salutation.clone(),
+                letter.production_line.value().to_string(),
+                letter.language.value().to_string(),
+            ]);
+            rows.push(csv_row(&row));
+        }
+
+        rows.concat()
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Records the statuses from an imported status file on the letters.
+    pub fn apply_status_events(&mut self, events: &[PrintStatusEvent]) {
+        for event in events {
+            if let Some(letter) = self.letters.iter_mut().find(|letter| &letter.reference == event.reference()) {
+                if letter.status() < event.status() {
+                    letter.status = Some(event.status());
+                }
+            }
+        }
+    }
+
+    /// The least advanced status of the review's letters, or `None` if it has no letters in the batch.
+    pub fn review_status(&self, review_id: &ReviewId) -> Option<PrintStatus> {
+        self.letters.iter()
+            .filter(|letter| &letter.review_id == review_id)
+            .map(PrintManifestLetter::status)
+            .reduce(|least, status| if status < least { status } else { least })
+    }
+
+    /// Reads the vendor status file, a CSV with the header `reference,status,timestamp`
+    /// where status is `printed` or `posted`. Every reference must be in this batch.
+    pub fn import_status_file(&self, status_file: &str) -> Result<Vec<PrintStatusEvent>, PrintImportError> {
+        let mut lines = status_file.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+
+        let header = lines.next().map(|(_, line)| parse_csv_row(line)).transpose()?.unwrap_or_default();
+        let header: Vec<String> = header.iter().map(|column| column.trim().to_lowercase()).collect();
+        if header != ["reference", "status", "timestamp"] {
+            return Err(PrintImportError::InvalidHeader(format!("Expected reference,status,timestamp but found {}", header.join(","))));
+        }
+
+        let mut events = Vec::new();
+        for (index, line) in lines {
+            let line_number = index + 1;
+            let columns = parse_csv_row(line)?;
+            let [reference, status, timestamp] = columns.as_slice() else {
+                return Err(PrintImportError::InvalidRow(format!("Line {} has {} columns, expected 3", line_number, columns.len())));
+            };
+
+            let letter = self.letters.iter().find(|letter| letter.reference == reference.trim())
+                .ok_or_else(|| PrintImportError::UnknownReference(format!("{} on line {}", reference, line_number)))?;
+            let review_id = letter.review_id.clone();
+            let at = parse_timestamp(timestamp.trim())
+                .ok_or_else(|| PrintImportError::InvalidRow(format!("Line {} has an invalid timestamp '{}'", line_number, timestamp)))?;
+
+            let reference = letter.reference.clone();
+            let event = match status.trim().to_lowercase().as_str() {
+                "printed" => PrintStatusEvent::LetterPrinted { review_id, reference, printed_at: at },
+                "posted" => PrintStatusEvent::LetterPosted { review_id, reference, posted_at: at },
+                _ => return Err(PrintImportError::InvalidRow(format!("Line {} has an unknown status '{}'", line_number, status))),
+            };
+            events.push(event);
+        }
+
+        Ok(events)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum PrintStatusEvent {
+    LetterPrinted { review_id: ReviewId, reference: String, printed_at: NaiveDateTime },
+    LetterPosted { review_id: ReviewId, reference: String, posted_at: NaiveDateTime },
+}
+
+impl PrintStatusEvent {
+    pub fn review_id(&self) -> &ReviewId {
+        match self {
+            Self::LetterPrinted { review_id, .. } | Self::LetterPosted { review_id, .. } => review_id,
+        }
+    }
+
+    pub fn reference(&self) -> &String {
+        match self {
+            Self::LetterPrinted { reference, .. } | Self::LetterPosted { reference, .. } => reference,
+        }
+    }
+
+    pub fn status(&self) -> PrintStatus {
+        match self {
+            Self::LetterPrinted { .. } => PrintStatus::Printed,
+            Self::LetterPosted { .. } => PrintStatus::Posted,
+        }
+    }
+}
+
+fn sort_key(postcode: &str) -> String {
+    postcode.replace(' ', "").to_uppercase()
+}
+
+fn padded(values: &[String], columns: usize) -> Vec<String> {
+    let mut padded: Vec<String> = values.iter().take(columns).cloned().collect();
+    padded.resize(columns, String::new());
+    padded
+}
+
+fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+}
+
+/// A CSV record (RFC 4180) with CRLF line ending, quoting fields where needed.
+fn csv_row(fields: &[String]) -> String {
+    let fields: Vec<String> = fields.iter().map(|field| {
+        if field.contains([',', '"', '\r', '\n']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.clone()
+        }
+    }).collect();
+
+    format!("{}\r\n", fields.join(","))
+}
+
+fn parse_csv_row(line: &str) -> Result<Vec<String>, PrintImportError> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut characters = line.trim_end_matches('\r').chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match (character, in_quotes) {
+            ('"', true) if characters.peek() == Some(&'"') => {
+                field.push('"');
+                characters.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            (character, _) => field.push(character),
+        }
+    }
+
+    if in_quotes {
+        return Err(PrintImportError::InvalidRow(format!("Unterminated quoted field in '{}'", line)));
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::tests::test_utils::shared::{ReviewInformationFixture, JOINT_INDIVIDUALS_POST_CONTACT, MULTIPLE_TRUSTEES_POST_CONTACT, REVIEW_ID};
+    use super::super::contact_preferences::CommunicationFormat;
+    use super::*;
+
+    const SECOND_REVIEW_ID: &str = "16fd2706-8baf-433b-82eb-8c7fada847da";
+
+    fn created_at() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(9, 0, 0).unwrap()
+    }
+
+    fn manifest() -> PrintManifest {
+        let mut manifest = PrintManifest::new("batch-2024-05-01", created_at());
+        manifest.add_review(&ReviewId::try_from(REVIEW_ID.to_string()).unwrap(), &ReviewInformationFixture::new(MULTIPLE_TRUSTEES_POST_CONTACT).invites());
+        manifest.add_review(&ReviewId::try_from(SECOND_REVIEW_ID.to_string()).unwrap(), &ReviewInformationFixture::new(JOINT_INDIVIDUALS_POST_CONTACT).invites());
+        manifest
+    }
+
+    #[test]
+    fn test_letters_are_sorted_by_postcode() {
+        let manifest = manifest();
+        let letters: Vec<(&str, &str)> = manifest.letters().iter().map(|letter| (letter.postcode().as_str(), letter.reference().as_str())).collect();
+
+        assert_eq!(letters, vec![
+            ("GU1 3AA", "16fd2706-8baf-433b-82eb-8c7fada847da-01"),
+            ("GU1 3AA", "7c9e6679-7425-40de-944b-e07fc1f90ae7-01"),
+            ("GU5 9HF", "7c9e6679-7425-40de-944b-e07fc1f90ae7-02"),
+        ]);
+    }
+
+    #[test]
+    fn test_trustee_letter_fields() {
+        let manifest = manifest();
+        let letter = &manifest.letters()[1];
+
+        assert_eq!(letter.template_id(), "multiple_trustees_post_invite");
+        assert_eq!(letter.name_block(), &vec!["Alice and Bob".to_string(), "May Family Trust".to_string()]);
+        assert_eq!(letter.address_lines(), &vec!["12 High Street".to_string(), "GUILDFORD".to_string(), "Surrey".to_string()]);
+        assert_eq!(letter.salutation(), "Dear Alice and Bob");
+
+        let debug = format!("{:?}", letter);
+        assert!(debug.contains("postcode: \"GU1 ***\""), "{}", debug);
+        for personal_data in ["Alice", "May Family Trust", "High Street"] {
+            assert!(!debug.contains(personal_data), "'{}' found in {}", personal_data, debug);
+        }
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let csv = manifest().to_csv();
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+
+        assert_eq!(lines[0], "reference,template_id,name_line_1,name_line_2,address_line_1,address_line_2,address_line_3,address_line_4,address_line_5,address_line_6,address_line_7,address_line_8,address_line_9,postcode,salutation,production_line,language");
+        assert_eq!(lines[3], "7c9e6679-7425-40de-944b-e07fc1f90ae7-02,multiple_trustees_post_invite,Carol,May Family Trust,Rose Cottage,Church Lane,Shere,GUILDFORD,Surrey,,,,,GU5 9HF,Dear Carol,standard,english");
+        assert_eq!(parse_csv_row(lines[1]).unwrap().len(), 17);
+    }
+
+    #[test]
+    fn test_json_export() {
+        let json: serde_json::Value = serde_json::from_str(&manifest().to_json().unwrap()).unwrap();
+
+        assert_eq!(json["batch_id"], "batch-2024-05-01");
+        assert_eq!(json["letters"].as_array().unwrap().len(), 3);
+        assert_eq!(json["letters"][2]["name_block"][0], "Carol");
+    }
+
+    #[test]
+    fn test_letters_are_routed_to_production_lines() {
+        let mut manifest = manifest();
+        let braille = ReviewInformationFixture::new(JOINT_INDIVIDUALS_POST_CONTACT).invites().into_iter()
+            .map(|invite| invite.with_format(CommunicationFormat::new(Language::Welsh, AccessibleFormat::Braille)))
+            .collect::<Vec<_>>();
+        manifest.add_review(&ReviewId::try_from("9b2f6c1e-4d3a-4f8e-a5b7-2c1d0e9f8a7b".to_string()).unwrap(), &braille);
+
+        assert_eq!(manifest.production_lines(), vec![ProductionLine::Standard, ProductionLine::Braille]);
+
+        let braille_batch = manifest.for_production_line(ProductionLine::Braille);
+        assert_eq!(braille_batch.batch_id(), "batch-2024-05-01-braille");
+        let [letter] = braille_batch.letters().as_slice() else {
+            panic!("Expected one braille letter");
+        };
+        assert_eq!(letter.reference(), "9b2f6c1e-4d3a-4f8e-a5b7-2c1d0e9f8a7b-01");
+        assert_eq!(letter.language(), Language::Welsh);
+        assert!(braille_batch.to_csv().lines().nth(1).unwrap().ends_with(",braille,welsh"));
+        assert_eq!(manifest.for_production_line(ProductionLine::Standard).letters().len(), 3);
+        assert!(manifest.for_production_line(ProductionLine::Audio).letters().is_empty());
+    }
+
+    #[test]
+    fn test_status_import_updates_reviews() {
+        let mut manifest = manifest();
+        let review_id = ReviewId::try_from(REVIEW_ID.to_string()).unwrap();
+        let status_file = "reference,status,timestamp\r\n\
+            7c9e6679-7425-40de-944b-e07fc1f90ae7-01,printed,2024-05-02T10:00:00\r\n\
+            7c9e6679-7425-40de-944b-e07fc1f90ae7-02,posted,2024-05-02 16:30:00\r\n";
+
+        let events = manifest.import_status_file(status_file).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].review_id(), &review_id);
+        assert_eq!(events[1].status(), PrintStatus::Posted);
+
+        manifest.apply_status_events(&events);
+        assert_eq!(manifest.review_status(&review_id), Some(PrintStatus::Printed));
+        assert_eq!(manifest.review_status(&ReviewId::try_from(SECOND_REVIEW_ID.to_string()).unwrap()), Some(PrintStatus::Pending));
+    }
+
+    #[test]
+    fn test_status_import_rejects_unknown_references_and_statuses() {
+        let manifest = manifest();
+
+        let unknown = "reference,status,timestamp\nunknown-01,posted,2024-05-02T10:00:00\n";
+        assert!(matches!(manifest.import_status_file(unknown), Err(PrintImportError::UnknownReference(_))));
+
+        let bad_status = "reference,status,timestamp\n7c9e6679-7425-40de-944b-e07fc1f90ae7-01,lost,2024-05-02T10:00:00\n";
+        assert!(matches!(manifest.import_status_file(bad_status), Err(PrintImportError::InvalidRow(_))));
+
+        assert!(matches!(manifest.import_status_file("ref,state\n"), Err(PrintImportError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn test_csv_quoting_round_trips() {
+        let fields = vec!["May, James".to_string(), "\"The Lodge\"".to_string(), String::new()];
+        let row = csv_row(&fields);
+
+        assert_eq!(row, "\"May, James\",\"\"\"The Lodge\"\"\",\r\n");
+        assert_eq!(parse_csv_row(row.trim_end()).unwrap(), fields);
+    }
+}
@@ -0,0 +1,830 @@
+//! Reminders and chase-ups for invites the client has not answered.
+//!
+//! When the invites are sent a timer is written for each step of the reminder
+//! policy: a first reminder to each recipient on the original channel, a second
+//! reminder on an alternative channel, escalation to the adviser and finally a
+//! No Response outcome for the review. Timers are kept in a [`ReminderTimerTable`] so that they survive
+//! restarts, and are removed as soon as the client books or declines. When a
+//! client dies their reminders are cancelled, and a surviving joint client is
+//! reminded with the bereaved invite instead.
+
+use std::{fs, path::PathBuf};
+
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    holiday_calendar::HolidayCalendar,
+    simple_types::{ConstrainedIndividualNameString100, ReviewId},
+};
+use super::{
+    invites::AnnualReviewInvite,
+    types::{TaxYear, ValidatedAnnualReviewInformation},
+};
+
+#[derive(Debug)]
+pub enum ReminderError {
+    InvalidPolicy(String),
+    AlreadyScheduled(String),
+    TimerTableUnavailable(String),
+}
+
+impl std::fmt::Display for ReminderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            ReminderError::InvalidPolicy(ref desc) => write!(f, "Invalid reminder policy: {}", desc),
+            ReminderError::AlreadyScheduled(ref desc) => write!(f, "Reminders already scheduled: {}", desc),
+            ReminderError::TimerTableUnavailable(ref desc) => write!(f, "Reminder timer table unavailable: {}", desc),
+        }
+    }
+}
+
+impl std::error::Error for ReminderError {}
+
+/// When each step happens, in business days after the invite was sent.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ReminderPolicy {
+    first_reminder_after: u32,
+    second_reminder_after: u32,
+    escalation_after: u32,
+    final_deadline_after: u32,
+}
+
+impl ReminderPolicy {
+    pub fn new(
+        first_reminder_after: u32,
+        second_reminder_after: u32,
+        escalation_after: u32,
+        final_deadline_after: u32,
+    ) -> Result<Self, ReminderError> {
+        let steps = [first_reminder_after, second_reminder_after, escalation_after, final_deadline_after];
+        if first_reminder_after == 0 || steps.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(ReminderError::InvalidPolicy(format!(
+                "Steps must be at least one business day apart and in order, got {:?}", steps,
+            )));
+        }
+
+        Ok(Self { first_reminder_after, second_reminder_after, escalation_after, final_deadline_after })
+    }
+
+    pub fn first_reminder_after(&self) -> u32 {
+        self.first_reminder_after
+    }
+
+    pub fn second_reminder_after(&self) -> u32 {
+        self.second_reminder_after
+    }
+
+    pub fn escalation_after(&self) -> u32 {
+        self.escalation_after
+    }
+
+    pub fn final_deadline_after(&self) -> u32 {
+        self.final_deadline_after
+    }
+}
+
+impl Default for ReminderPolicy {
+    fn default() -> Self {
+        Self { first_reminder_after: 5, second_reminder_after: 10, escalation_after: 15, final_deadline_after: 20 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ReminderAction {
+    /// A reminder for the invite referenced `{review_id}-{invite number}`.
+    SendReminder { number: u8, invite_reference: String, invite: Box<AnnualReviewInvite> },
+    EscalateToAdviser { adviser_name: ConstrainedIndividualNameString100 },
+    RecordNoResponse { tax_year: TaxYear },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReminderTimer {
+    review_id: ReviewId,
+    due_on: NaiveDate,
+    action: ReminderAction,
+}
+
+impl ReminderTimer {
+    pub fn new(review_id: ReviewId, due_on: NaiveDate, action: ReminderAction) -> Self {
+        Self { review_id, due_on, action }
+    }
+
+    pub fn review_id(&self) -> &ReviewId {
+        &self.review_id
+    }
+
+    pub fn due_on(&self) -> NaiveDate {
+        self.due_on
+    }
+
+    pub fn action(&self) -> &ReminderAction {
+        &self.action
+    }
+}
+
+/// Port for storing reminder timers durably.
+pub trait ReminderTimerTable {
+    fn insert(&mut self, timers: Vec<ReminderTimer>) -> Result<(), ReminderError>;
+
+    fn timers_for(&self, review_id: &ReviewId) -> Result<Vec<ReminderTimer>, ReminderError>;
+
+    /// The timers due on or before `on`, earliest first. They stay in the
+    /// table until acknowledged, so a crash before then fires them again.
+    fn due(&self, on: NaiveDate) -> Result<Vec<ReminderTimer>, ReminderError>;
+
+    /// Removes timers that have fired. Timers no longer in the table are
+    /// ignored, so an acknowledgement can be repeated.
+    fn acknowledge(&mut self, timers: &[ReminderTimer]) -> Result<(), ReminderError>;
+
+    /// Removes and returns all the timers for the review.
+    fn remove_review(&mut self, review_id: &ReviewId) -> Result<Vec<ReminderTimer>, ReminderError>;
+
+    /// Replaces all the timers for the review at once.
+    fn replace_review(&mut self, review_id: &ReviewId, timers: Vec<ReminderTimer>) -> Result<(), ReminderError>;
+
+    fn all(&self) -> Result<Vec<ReminderTimer>, ReminderError>;
+
+    /// Replaces every timer in the table at once.
+    fn replace_all(&mut self, timers: Vec<ReminderTimer>) -> Result<(), ReminderError>;
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryReminderTimerTable {
+    timers: Vec<ReminderTimer>,
+}
+
+impl InMemoryReminderTimerTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A copy of `table` to make changes on, which [`Self::write_to`] saves.
+    pub fn copy_of(table: &dyn ReminderTimerTable) -> Result<Self, ReminderError> {
+        Ok(Self { timers: table.all()? })
+    }
+
+    pub fn write_to(self, table: &mut dyn ReminderTimerTable) -> Result<(), ReminderError> {
+        table.replace_all(self.timers)
+    }
+}
+
+impl ReminderTimerTable for InMemoryReminderTimerTable {
+    fn insert(&mut self, timers: Vec<ReminderTimer>) -> Result<(), ReminderError> {
+        self.timers.extend(timers);
+        Ok(())
+    }
+
+    fn timers_for(&self, review_id: &ReviewId) -> Result<Vec<ReminderTimer>, ReminderError> {
+        Ok(self.timers.iter().filter(|timer| &timer.review_id == review_id).cloned().collect())
+    }
+
+    fn due(&self, on: NaiveDate) -> Result<Vec<ReminderTimer>, ReminderError> {
+        let mut due: Vec<ReminderTimer> = self.timers.iter().filter(|timer| timer.due_on <= on).cloned().collect();
+        due.sort_by_key(|timer| timer.due_on);
+        Ok(due)
+    }
+
+    fn acknowledge(&mut self, timers: &[ReminderTimer]) -> Result<(), ReminderError> {
+        for fired in timers {
+            if let Some(index) = self.timers.iter().position(|timer| timer == fired) {
+                self.timers.remove(index);
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_review(&mut self, review_id: &ReviewId) -> Result<Vec<ReminderTimer>, ReminderError> {
+        let (removed, kept) = std::mem::take(&mut self.timers).into_iter().partition(|timer| &timer.review_id == review_id);
+        self.timers = kept;
+        Ok(removed)
+    }
+
+    fn replace_review(&mut self, review_id: &ReviewId, timers: Vec<ReminderTimer>) -> Result<(), ReminderError> {
+        self.timers.retain(|timer| &timer.review_id != review_id);
+        self.timers.extend(timers);
+        Ok(())
+    }
+
+    fn all(&self) -> Result<Vec<ReminderTimer>, ReminderError> {
+        Ok(self.timers.clone())
+    }
+
+    fn replace_all(&mut self, timers: Vec<ReminderTimer>) -> Result<(), ReminderError> {
+        self.timers = timers;
+        Ok(())
+    }
+}
+
+/// Timer table kept in a JSON file. Every change rewrites a temporary file
+/// which then replaces the original, so a crash leaves the old or new table.
+/// The invites' personal data is encrypted, so the table must be used inside
+/// [`crate::field_encryption::with_key_provider`].
+#[derive(Debug)]
+pub struct JsonFileReminderTimerTable {
+    path: PathBuf,
+}
+
+impl JsonFileReminderTimerTable {
+    /// The file is created on the first change if it does not exist.
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn load(&self) -> Result<InMemoryReminderTimerTable, ReminderError> {
+        if !self.path.exists() {
+            return Ok(InMemoryReminderTimerTable::new());
+        }
+
+        let content = fs::read_to_string(&self.path).map_err(|e| self.unavailable(e))?;
+        let timers = serde_json::from_str(&content).map_err(|e| self.unavailable(e))?;
+        Ok(InMemoryReminderTimerTable { timers })
+    }
+
+    fn save(&self, table: &InMemoryReminderTimerTable) -> Result<(), ReminderError> {
+        let content = serde_json::to_string_pretty(&table.timers).map_err(|e| self.unavailable(e))?;
+        let temporary_path = self.path.with_extension("tmp");
+        fs::write(&temporary_path, content).map_err(|e| self.unavailable(e))?;
+        fs::rename(&temporary_path, &self.path).map_err(|e| self.unavailable(e))
+    }
+
+    fn update<T>(&mut self, change: impl FnOnce(&mut InMemoryReminderTimerTable) -> Result<T, ReminderError>) -> Result<T, ReminderError> {
+        let mut table = self.load()?;
+        let result = change(&mut table)?;
+        self.save(&table)?;
+        Ok(result)
+    }
+
+    fn unavailable(&self, error: impl std::fmt::Display) -> ReminderError {
+        ReminderError::TimerTableUnavailable(format!("{}: {}", self.path.display(), error))
+    }
+}
+
+impl ReminderTimerTable for JsonFileReminderTimerTable {
+    fn insert(&mut self, timers: Vec<ReminderTimer>) -> Result<(), ReminderError> {
+        self.update(|table| table.insert(timers))
+    }
+
+    fn timers_for(&self, review_id: &ReviewId) -> Result<Vec<ReminderTimer>, ReminderError> {
+        self.load()?.timers_for(review_id)
+    }
+
+    fn due(&self, on: NaiveDate) -> Result<Vec<ReminderTimer>, ReminderError> {
+        self.load()?.due(on)
+    }
+
+    fn acknowledge(&mut self, timers: &[ReminderTimer]) -> Result<(), ReminderError> {
+        self.update(|table| table.acknowledge(timers))
+    }
+
+    fn remove_review(&mut self, review_id: &ReviewId) -> Result<Vec<ReminderTimer>, ReminderError> {
+        self.update(|table| table.remove_review(review_id))
+    }
+
+    fn replace_review(&mut self, review_id: &ReviewId, timers: Vec<ReminderTimer>) -> Result<(), ReminderError> {
+        self.update(|table| table.replace_review(review_id, timers))
+    }
+
+    fn all(&self) -> Result<Vec<ReminderTimer>, ReminderError> {
+        Ok(self.load()?.timers)
+    }
+
+    fn replace_all(&mut self, timers: Vec<ReminderTimer>) -> Result<(), ReminderError> {
+        self.save(&InMemoryReminderTimerTable { timers })
+    }
+}
+
+/// A reply from the client that ends the chase-up.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ReviewResponse {
+    Booked,
+    Declined,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ReminderEvent {
+    RemindersScheduled { review_id: ReviewId, sent_on: NaiveDate, no_response_on: NaiveDate },
+    ReminderDue { review_id: ReviewId, invite_reference: String, number: u8, invite: Box<AnnualReviewInvite>, due_on: NaiveDate },
+    EscalatedToAdviser { review_id: ReviewId, adviser_name: ConstrainedIndividualNameString100, due_on: NaiveDate },
+    NoResponse { review_id: ReviewId, tax_year: TaxYear, recorded_on: NaiveDate },
+    RemindersStopped { review_id: ReviewId, response: ReviewResponse, cancelled_timers: usize, stopped_on: NaiveDate },
+    RemindersCancelled { review_id: ReviewId, cancelled_timers: usize, rerouted_timers: usize, cancelled_on: NaiveDate },
+}
+
+pub struct ReminderScheduler<'a> {
+    policy: ReminderPolicy,
+    holiday_calendar: &'a HolidayCalendar,
+}
+
+impl<'a> ReminderScheduler<'a> {
+    pub fn new(policy: ReminderPolicy, holiday_calendar: &'a HolidayCalendar) -> Self {
+        Self { policy, holiday_calendar }
+    }
+
+    /// Writes the timers for the review's invites sent on `sent_on`. Every
+    /// recipient is reminded, each invite being referenced `{review_id}-{invite
+    /// number}` in the order given. The second reminder goes by post when an
+    /// emailed client has a postal address on file.
+    pub fn schedule(
+        &self,
+        timers: &mut dyn ReminderTimerTable,
+        review_id: &ReviewId,
+        invites: &[AnnualReviewInvite],
+        review_information: &ValidatedAnnualReviewInformation,
+        sent_on: NaiveDate,
+    ) -> Result<Vec<ReminderEvent>, ReminderError> {
+        if !timers.timers_for(review_id)?.is_empty() {
+            return Err(ReminderError::AlreadyScheduled(review_id.value().to_string()));
+        }
+
+        let after = |business_days: u32| self.holiday_calendar.add_business_days(sent_on, business_days);
+        let no_response_on = after(self.policy.final_deadline_after);
+        let timer = |due_on: NaiveDate, action: ReminderAction| ReminderTimer::new(review_id.clone(), due_on, action);
+
+        let mut reminders = Vec::new();
+        for (index, invite) in invites.iter().enumerate() {
+            let invite_reference = format!("{}-{:02}", review_id.value(), index + 1);
+            reminders.push(timer(after(self.policy.first_reminder_after), ReminderAction::SendReminder {
+                number: 1,
+                invite_reference: invite_reference.clone(),
+                invite: Box::new(invite.clone()),
+            }));
+            reminders.push(timer(after(self.policy.second_reminder_after), ReminderAction::SendReminder {
+                number: 2,
+                invite_reference,
+                invite: Box::new(alternative_channel(invite, review_information)),
+            }));
+        }
+
+        reminders.extend([
+            timer(after(self.policy.escalation_after), ReminderAction::EscalateToAdviser {
+                adviser_name: review_information.adviser_name().clone(),
+            }),
+            timer(no_response_on, ReminderAction::RecordNoResponse {
+                tax_year: review_information.annual_review_due_date().tax_year(),
+            }),
+        ]);
+        timers.insert(reminders)?;
+
+        Ok(vec![ReminderEvent::RemindersScheduled { review_id: review_id.clone(), sent_on, no_response_on }])
+    }
+
+    /// Fires every timer due on or before `today`. Run at least once a business
+    /// day. The timers fire again on the next run until the events are passed
+    /// to [`Self::acknowledge`], so act on the events before acknowledging them.
+    pub fn run_due(&self, timers: &dyn ReminderTimerTable, today: NaiveDate) -> Result<Vec<ReminderEvent>, ReminderError> {
+        let events = timers.due(today)?.into_iter().map(|timer| {
+            let review_id = timer.review_id;
+            match timer.action {
+                ReminderAction::SendReminder { number, invite_reference, invite } => ReminderEvent::ReminderDue { review_id, invite_reference, number, invite, due_on: timer.due_on },
+                ReminderAction::EscalateToAdviser { adviser_name } => ReminderEvent::EscalatedToAdviser { review_id, adviser_name, due_on: timer.due_on },
+                ReminderAction::RecordNoResponse { tax_year } => ReminderEvent::NoResponse { review_id, tax_year, recorded_on: timer.due_on },
+            }
+        });
+
+        Ok(events.collect())
+    }
+
+    /// Removes the timers behind events from [`Self::run_due`] once they have been acted on.
+    pub fn acknowledge(&self, timers: &mut dyn ReminderTimerTable, fired: &[ReminderEvent]) -> Result<(), ReminderError> {
+        timers.acknowledge(&fired.iter().filter_map(fired_timer).collect::<Vec<ReminderTimer>>())
+    }
+
+    /// Cancels the outstanding timers once the client has booked or declined.
+    pub fn stop(
+        &self,
+        timers: &mut dyn ReminderTimerTable,
+        review_id: &ReviewId,
+        response: ReviewResponse,
+        stopped_on: NaiveDate,
+    ) -> Result<Vec<ReminderEvent>, ReminderError> {
+        let cancelled_timers = timers.remove_review(review_id)?.len();
+
+        Ok(vec![ReminderEvent::RemindersStopped { review_id: review_id.clone(), response, cancelled_timers, stopped_on }])
+    }
+
+    /// Cancels the reminders to the clients recorded as deceased in
+    /// `review_information`, and to attorneys acting only for them. Reminders
+    /// to a surviving joint client are rerouted to them alone with the bereaved
+    /// invite. Once no client is living, every timer for the review is cancelled.
+    pub fn cancel(
+        &self,
+        timers: &mut dyn ReminderTimerTable,
+        review_id: &ReviewId,
+        review_information: &ValidatedAnnualReviewInformation,
+        cancelled_on: NaiveDate,
+    ) -> Result<Vec<ReminderEvent>, ReminderError> {
+        let outstanding = timers.timers_for(review_id)?;
+        let outstanding_timers = outstanding.len();
+
+        let mut kept = Vec::new();
+        let mut rerouted_timers = 0;
+        if review_information.has_living_client() {
+            for timer in outstanding {
+                let ReminderAction::SendReminder { number, invite_reference, invite } = timer.action else {
+                    kept.push(timer);
+                    continue;
+                };
+                if let Some(rerouted) = invite.clone().after_deaths(review_information.deceased_clients()) {
+                    rerouted_timers += usize::from(rerouted != *invite);
+                    kept.push(ReminderTimer::new(timer.review_id, timer.due_on, ReminderAction::SendReminder {
+                        number,
+                        invite_reference,
+                        invite: Box::new(rerouted),
+                    }));
+                }
+            }
+        }
+
+        let cancelled_timers = outstanding_timers - kept.len();
+        timers.replace_review(review_id, kept)?;
+
+        Ok(vec![ReminderEvent::RemindersCancelled { review_id: review_id.clone(), cancelled_timers, rerouted_timers, cancelled_on }])
+    }
+}
+
+/// The timer an event from [`ReminderScheduler::run_due`] fired from.
+fn fired_timer(event: &ReminderEvent) -> Option<ReminderTimer> {
+    let (review_id, due_on, action) = match event.clone() {
+        ReminderEvent::ReminderDue { review_id, invite_reference, number, invite, due_on } => {
+            (review_id, due_on, ReminderAction::SendReminder { number, invite_reference, invite })
+        }
+        ReminderEvent::EscalatedToAdviser { review_id, adviser_name, due_on } => (review_id, due_on, ReminderAction::EscalateToAdviser { adviser_name }),
+        ReminderEvent::NoResponse { review_id, tax_year, recorded_on } => (review_id, recorded_on, ReminderAction::RecordNoResponse { tax_year }),
+        _ => return None,
+    };
+    Some(ReminderTimer::new(review_id, due_on, action))
+}
+
+/// Emails are followed up by post where an address is on file, and otherwise
+/// by text message to a mobile number on file, as are letters.
+fn alternative_channel(invite: &AnnualReviewInvite, review_information: &ValidatedAnnualReviewInformation) -> AnnualReviewInvite {
+    let client_contact_information = review_information.client_contact_information();
+    let sms_invite = || client_contact_information
+        .phone_numbers()
+        .into_iter()
+        .find(|(_, phone_number)| phone_number.is_mobile())
+        .map(|(_, phone_number)| AnnualReviewInvite::SendSmsAnnualReviewInvite(invite.to_sms_invite(phone_number.clone())));
+
+    match invite {
+        AnnualReviewInvite::SendElectronicAnnualReviewInvite(electronic) => client_contact_information
+            .postal_address_on_file(electronic.email_address())
+            .map(|postal_address| AnnualReviewInvite::SendPostAnnualReviewInvite(electronic.to_post_invite(postal_address.clone())))
+            .or_else(sms_invite)
+            .unwrap_or_else(|| invite.clone()),
+        AnnualReviewInvite::SendPostAnnualReviewInvite(_) => sms_invite().unwrap_or_else(|| invite.clone()),
+        // Texts are only sent to clients who chose SMS, so they are texted again.
+        AnnualReviewInvite::SendSmsAnnualReviewInvite(_) => invite.clone(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ReviewOutcome {
+    Booked,
+    Declined,
+    NoResponse,
+}
+
+/// The outcome of each annual review due in a tax year.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaxYearReviewRecord {
+    tax_year: TaxYear,
+    outcomes: Vec<(ReviewId, ReviewOutcome)>,
+}
+
+impl TaxYearReviewRecord {
+    pub fn new(tax_year: TaxYear) -> Self {
+        Self { tax_year, outcomes: Vec::new() }
+    }
+
+    pub fn tax_year(&self) -> TaxYear {
+        self.tax_year
+    }
+
+    /// Records the outcome for the review, replacing any earlier outcome.
+    pub fn record(&mut self, review_id: &ReviewId, outcome: ReviewOutcome) {
+        self.outcomes.retain(|(recorded, _)| recorded != review_id);
+        self.outcomes.push((review_id.clone(), outcome));
+    }
+
+    /// Records the No Response outcomes in `events` that belong to this tax year.
+    pub fn apply(&mut self, events: &[ReminderEvent]) {
+        for event in events {
+            if let ReminderEvent::NoResponse { review_id, tax_year, .. } = event {
+                if *tax_year == self.tax_year {
+                    self.record(review_id, ReviewOutcome::NoResponse);
+                }
+            }
+        }
+    }
+
+    pub fn outcome(&self, review_id: &ReviewId) -> Option<ReviewOutcome> {
+        self.outcomes.iter().find(|(recorded, _)| recorded == review_id).map(|(_, outcome)| *outcome)
+    }
+
+    pub fn count(&self, outcome: ReviewOutcome) -> usize {
+        self.outcomes.iter().filter(|(_, recorded)| *recorded == outcome).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        field_encryption::{with_key_provider, EncryptionKey, InMemoryKeyProvider},
+        holiday_calendar::Jurisdiction,
+        tests::test_utils::shared::{
+            ReviewInformationFixture,
+            JOINT_INDIVIDUALS_ELECTRONIC_CONTACT, JOINT_INDIVIDUALS_POST_CONTACT, MULTIPLE_TRUSTEES_POST_CONTACT, REVIEW_ID, SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT,
+        },
+    };
+    use super::super::{delivery_tracking::DeliveryChannel, invites::InviteContentReference};
+    use super::*;
+
+    const SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT_WITH_ADDRESS: &str = r#"{
+        "type": "SingleIndividualElectronicContact",
+        "first_name": "Siân",
+        "email_address": "sian@example.com",
+        "postal_address": {
+            "house_name": "",
+            "house_number": "4",
+            "address_line_one": "Mill Road",
+            "address_line_two": "",
+            "address_line_three": "",
+            "address_line_four": "",
+            "city": "Guildford",
+            "county": "Surrey",
+            "postcode": "GU1 2AB",
+            "country": ""
+        }
+    }"#;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn review_id() -> ReviewId {
+        ReviewId::try_from(REVIEW_ID.to_string()).unwrap()
+    }
+
+    /// Schedules reminders for the invites sent on Wednesday 1 May 2024, with
+    /// the bank holidays on Monday 6 and Monday 27 May.
+    fn scheduled(contact_json: &str, timers: &mut dyn ReminderTimerTable) -> Vec<ReminderEvent> {
+        let fixture = ReviewInformationFixture::new(contact_json);
+        let review_information = fixture.validated();
+        let invites = fixture.invites();
+
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        ReminderScheduler::new(ReminderPolicy::default(), &calendar)
+            .schedule(timers, &review_id(), &invites, &review_information, date(5, 1))
+            .unwrap()
+    }
+
+    fn run_due(timers: &mut dyn ReminderTimerTable, today: NaiveDate) -> Vec<ReminderEvent> {
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        let scheduler = ReminderScheduler::new(ReminderPolicy::default(), &calendar);
+        let events = scheduler.run_due(timers, today).unwrap();
+        scheduler.acknowledge(timers, &events).unwrap();
+        events
+    }
+
+    fn reminder(event: &ReminderEvent) -> (u8, DeliveryChannel, NaiveDate) {
+        let ReminderEvent::ReminderDue { number, invite, due_on, .. } = event else {
+            panic!("Expected a reminder, got {:?}", event);
+        };
+        let channel = match **invite {
+            AnnualReviewInvite::SendElectronicAnnualReviewInvite(_) => DeliveryChannel::Email,
+            AnnualReviewInvite::SendPostAnnualReviewInvite(_) => DeliveryChannel::Post,
+            AnnualReviewInvite::SendSmsAnnualReviewInvite(_) => DeliveryChannel::Sms,
+        };
+        (*number, channel, *due_on)
+    }
+
+    #[test]
+    fn test_policy_steps_must_be_in_order() {
+        assert!(ReminderPolicy::new(5, 10, 15, 20).is_ok());
+        assert!(matches!(ReminderPolicy::new(5, 5, 15, 20), Err(ReminderError::InvalidPolicy(_))));
+        assert!(matches!(ReminderPolicy::new(0, 10, 15, 20), Err(ReminderError::InvalidPolicy(_))));
+        assert!(matches!(ReminderPolicy::new(5, 10, 25, 20), Err(ReminderError::InvalidPolicy(_))));
+    }
+
+    #[test]
+    fn test_tax_year() {
+        assert_eq!(TaxYear::containing(date(4, 5)).to_string(), "2023/24");
+        assert_eq!(TaxYear::containing(date(4, 6)).to_string(), "2024/25");
+        assert_eq!(TaxYear::containing(date(4, 6)).last_day(), NaiveDate::from_ymd_opt(2025, 4, 5).unwrap());
+    }
+
+    #[test]
+    fn test_full_cadence_without_response() {
+        let mut timers = InMemoryReminderTimerTable::new();
+
+        let events = scheduled(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT_WITH_ADDRESS, &mut timers);
+        assert_eq!(events, vec![ReminderEvent::RemindersScheduled { review_id: review_id(), sent_on: date(5, 1), no_response_on: date(5, 31) }]);
+
+        assert!(run_due(&mut timers, date(5, 8)).is_empty());
+
+        let events = run_due(&mut timers, date(5, 9));
+        assert_eq!(events.len(), 1);
+        assert_eq!(reminder(&events[0]), (1, DeliveryChannel::Email, date(5, 9)));
+
+        let events = run_due(&mut timers, date(5, 16));
+        assert_eq!(events.len(), 1);
+        assert_eq!(reminder(&events[0]), (2, DeliveryChannel::Post, date(5, 16)));
+
+        let events = run_due(&mut timers, date(5, 23));
+        assert!(matches!(&events[..], [ReminderEvent::EscalatedToAdviser { .. }]));
+
+        let events = run_due(&mut timers, date(6, 3));
+        assert_eq!(events, vec![ReminderEvent::NoResponse { review_id: review_id(), tax_year: TaxYear::containing(date(6, 30)), recorded_on: date(5, 31) }]);
+        assert!(timers.timers_for(&review_id()).unwrap().is_empty());
+
+        let mut record = TaxYearReviewRecord::new(TaxYear::containing(date(6, 30)));
+        record.apply(&events);
+        assert_eq!(record.outcome(&review_id()), Some(ReviewOutcome::NoResponse));
+        assert_eq!(record.count(ReviewOutcome::NoResponse), 1);
+    }
+
+    #[test]
+    fn test_due_reminders_fire_until_acknowledged() {
+        let mut timers = InMemoryReminderTimerTable::new();
+        scheduled(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, &mut timers);
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        let scheduler = ReminderScheduler::new(ReminderPolicy::default(), &calendar);
+
+        let fired = scheduler.run_due(&timers, date(5, 9)).unwrap();
+        assert_eq!(scheduler.run_due(&timers, date(5, 10)).unwrap(), fired);
+
+        scheduler.acknowledge(&mut timers, &fired).unwrap();
+        scheduler.acknowledge(&mut timers, &fired).unwrap();
+        assert!(scheduler.run_due(&timers, date(5, 10)).unwrap().is_empty());
+        assert_eq!(timers.timers_for(&review_id()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_every_trustee_is_reminded() {
+        let mut timers = InMemoryReminderTimerTable::new();
+        scheduled(MULTIPLE_TRUSTEES_POST_CONTACT, &mut timers);
+
+        let reminded = |events: Vec<ReminderEvent>| events.into_iter().map(|event| match event {
+            ReminderEvent::ReminderDue { invite_reference, number, invite, .. } => {
+                let AnnualReviewInvite::SendPostAnnualReviewInvite(letter) = *invite else {
+                    panic!("Expected a letter");
+                };
+                let names: Vec<String> = letter.recipient_names().iter().map(|name| name.value().to_string()).collect();
+                (invite_reference, number, names.join(" and "))
+            }
+            other => panic!("Expected a reminder, got {:?}", other),
+        }).collect::<Vec<_>>();
+
+        assert_eq!(reminded(run_due(&mut timers, date(5, 9))), vec![
+            (format!("{}-01", REVIEW_ID), 1, "Alice and Bob".to_string()),
+            (format!("{}-02", REVIEW_ID), 1, "Carol".to_string()),
+        ]);
+        assert_eq!(reminded(run_due(&mut timers, date(5, 16))), vec![
+            (format!("{}-01", REVIEW_ID), 2, "Alice and Bob".to_string()),
+            (format!("{}-02", REVIEW_ID), 2, "Carol".to_string()),
+        ]);
+        assert!(matches!(&run_due(&mut timers, date(5, 31))[..], [ReminderEvent::EscalatedToAdviser { .. }, ReminderEvent::NoResponse { .. }]));
+    }
+
+    #[test]
+    fn test_second_reminder_stays_on_channel_without_alternative() {
+        let mut timers = InMemoryReminderTimerTable::new();
+        scheduled(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, &mut timers);
+
+        let events = run_due(&mut timers, date(5, 16));
+        assert_eq!(reminder(&events[1]), (2, DeliveryChannel::Email, date(5, 16)));
+
+        let mut timers = InMemoryReminderTimerTable::new();
+        scheduled(JOINT_INDIVIDUALS_POST_CONTACT, &mut timers);
+
+        let events = run_due(&mut timers, date(5, 16));
+        assert_eq!(reminder(&events[1]), (2, DeliveryChannel::Post, date(5, 16)));
+    }
+
+    #[test]
+    fn test_response_stops_reminders() {
+        let mut timers = InMemoryReminderTimerTable::new();
+        scheduled(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, &mut timers);
+        run_due(&mut timers, date(5, 9));
+
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        let events = ReminderScheduler::new(ReminderPolicy::default(), &calendar)
+            .stop(&mut timers, &review_id(), ReviewResponse::Booked, date(5, 10))
+            .unwrap();
+
+        assert_eq!(events, vec![ReminderEvent::RemindersStopped { review_id: review_id(), response: ReviewResponse::Booked, cancelled_timers: 3, stopped_on: date(5, 10) }]);
+        assert!(run_due(&mut timers, date(6, 30)).is_empty());
+    }
+
+    fn cancelled_after_death(contact_json: &str, deceased_clients: &str, timers: &mut dyn ReminderTimerTable) -> Vec<ReminderEvent> {
+        let review_information = ReviewInformationFixture::new(contact_json).with_deceased(deceased_clients).validated();
+
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        ReminderScheduler::new(ReminderPolicy::default(), &calendar)
+            .cancel(timers, &review_id(), &review_information, date(5, 3))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_client_death_cancels_reminders() {
+        let mut timers = InMemoryReminderTimerTable::new();
+        scheduled(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, &mut timers);
+
+        let events = cancelled_after_death(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, r#"[{"first_name": "Siân", "date_of_death": "02/05/2024"}]"#, &mut timers);
+
+        assert_eq!(events, vec![ReminderEvent::RemindersCancelled { review_id: review_id(), cancelled_timers: 4, rerouted_timers: 0, cancelled_on: date(5, 3) }]);
+        assert!(run_due(&mut timers, date(6, 30)).is_empty());
+    }
+
+    #[test]
+    fn test_cancelling_leaves_other_reviews_timers() {
+        let other_review_id = ReviewId::try_from("0e5b7c1a-8f4d-4a5e-9b6c-2d3e4f5a6b7c".to_string()).unwrap();
+        let other_timer = ReminderTimer::new(other_review_id.clone(), date(5, 31), ReminderAction::RecordNoResponse { tax_year: TaxYear::containing(date(6, 30)) });
+        let mut timers = InMemoryReminderTimerTable::new();
+        timers.insert(vec![other_timer.clone()]).unwrap();
+        scheduled(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, &mut timers);
+
+        cancelled_after_death(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, r#"[{"first_name": "Siân", "date_of_death": "02/05/2024"}]"#, &mut timers);
+
+        assert_eq!(timers.all().unwrap(), vec![other_timer]);
+    }
+
+    #[test]
+    fn test_surviving_joint_client_is_still_reminded() {
+        let deceased = r#"[{"first_name": "James", "date_of_death": "02/05/2024"}]"#;
+        let reminded = |events: Vec<ReminderEvent>| events.into_iter().filter_map(|event| match event {
+            ReminderEvent::ReminderDue { invite, .. } => match *invite {
+                AnnualReviewInvite::SendElectronicAnnualReviewInvite(email) => Some((email.recipient_names()[0].value().to_string(), email.content_reference())),
+                AnnualReviewInvite::SendPostAnnualReviewInvite(letter) => Some((letter.recipient_names()[0].value().to_string(), letter.content_reference())),
+                AnnualReviewInvite::SendSmsAnnualReviewInvite(_) => None,
+            },
+            _ => None,
+        }).collect::<Vec<_>>();
+
+        let mut timers = InMemoryReminderTimerTable::new();
+        scheduled(JOINT_INDIVIDUALS_ELECTRONIC_CONTACT, &mut timers);
+        let events = cancelled_after_death(JOINT_INDIVIDUALS_ELECTRONIC_CONTACT, deceased, &mut timers);
+
+        assert_eq!(events, vec![ReminderEvent::RemindersCancelled { review_id: review_id(), cancelled_timers: 2, rerouted_timers: 2, cancelled_on: date(5, 3) }]);
+        assert_eq!(reminded(run_due(&mut timers, date(5, 9))), vec![("Sarah".to_string(), InviteContentReference::BereavedIndividualElectronicInvite)]);
+        assert_eq!(timers.timers_for(&review_id()).unwrap().len(), 3);
+
+        let mut timers = InMemoryReminderTimerTable::new();
+        scheduled(JOINT_INDIVIDUALS_POST_CONTACT, &mut timers);
+        cancelled_after_death(JOINT_INDIVIDUALS_POST_CONTACT, deceased, &mut timers);
+
+        let events = run_due(&mut timers, date(5, 16));
+        assert_eq!(reminded(events.clone()), vec![
+            ("Sarah".to_string(), InviteContentReference::BereavedIndividualPostInvite),
+            ("Sarah".to_string(), InviteContentReference::BereavedIndividualPostInvite),
+        ]);
+        let ReminderEvent::ReminderDue { invite, .. } = &events[0] else {
+            panic!("Expected a reminder");
+        };
+        let AnnualReviewInvite::SendPostAnnualReviewInvite(letter) = &**invite else {
+            panic!("Expected a letter");
+        };
+        assert_eq!(letter.recipient_names().len(), 1);
+    }
+
+    #[test]
+    fn test_reminders_are_scheduled_once() {
+        let mut timers = InMemoryReminderTimerTable::new();
+        scheduled(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, &mut timers);
+
+        let fixture = ReviewInformationFixture::new(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT);
+        let review_information = fixture.validated();
+        let invites = fixture.invites();
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        let result = ReminderScheduler::new(ReminderPolicy::default(), &calendar).schedule(&mut timers, &review_id(), &invites, &review_information, date(5, 2));
+
+        assert!(matches!(result, Err(ReminderError::AlreadyScheduled(_))));
+    }
+
+    #[test]
+    fn test_json_file_timer_table_survives_restart() {
+        let path = std::env::temp_dir().join(format!("gcwm_domain_{}_test_json_file_timer_table_survives_restart.json", std::process::id()));
+        let keys = InMemoryKeyProvider::new(EncryptionKey::new("k1", &[1; 32]).unwrap());
+
+        with_key_provider(&keys, || scheduled(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT_WITH_ADDRESS, &mut JsonFileReminderTimerTable::new(path.clone())));
+        let saved = fs::read_to_string(&path).unwrap();
+        let due = with_key_provider(&keys, || run_due(&mut JsonFileReminderTimerTable::new(path.clone()), date(5, 9)));
+        let remaining = with_key_provider(&keys, || JsonFileReminderTimerTable::new(path.clone()).timers_for(&review_id()).unwrap());
+        fs::remove_file(path).unwrap();
+
+        for plaintext in ["Siân", "sian@example.com", "Mill Road", "GU1 2AB"] {
+            assert!(!saved.contains(plaintext), "'{}' found in {}", plaintext, saved);
+        }
+        assert_eq!(reminder(&due[0]), (1, DeliveryChannel::Email, date(5, 9)));
+        assert_eq!(remaining.len(), 3);
+        assert_eq!(remaining[0].due_on(), date(5, 16));
+        let ReminderAction::SendReminder { invite, .. } = remaining[0].action() else {
+            panic!("Expected a reminder");
+        };
+        let AnnualReviewInvite::SendPostAnnualReviewInvite(letter) = &**invite else {
+            panic!("Expected a letter");
+        };
+        assert_eq!(letter.postal_address().postcode().value(), "GU1 2AB");
+    }
+}
@@ -0,0 +1,375 @@
+//! Commands received from clients in reply to an invite, and the meeting
+//! outcome events they lead to.
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Serialize, Deserialize};
+
+use crate::simple_types::ReviewId;
+use super::{
+    reminders::{ReviewOutcome, ReviewResponse},
+    slot_finder::MeetingSlot,
+    types::{TaxYear, ValidatedAnnualReviewDueDate},
+};
+
+const DATE_TIME_FORMAT: &str = "%d/%m/%Y %H:%M";
+const MAX_NOTE_LENGTH: usize = 1000;
+
+#[derive(Debug, PartialEq)]
+pub enum ResponseError {
+    InvalidCommand(String),
+    UnknownInvite(String),
+    InviteExpired(String),
+    AlreadyBooked(String),
+    AlreadyDeclined(String),
+    UnknownSlot(String),
+}
+
+impl std::fmt::Display for ResponseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            ResponseError::InvalidCommand(ref desc) => write!(f, "Invalid response command: {}", desc),
+            ResponseError::UnknownInvite(ref desc) => write!(f, "Unknown invite: {}", desc),
+            ResponseError::InviteExpired(ref desc) => write!(f, "Invite expired: {}", desc),
+            ResponseError::AlreadyBooked(ref desc) => write!(f, "Review meeting already booked: {}", desc),
+            ResponseError::AlreadyDeclined(ref desc) => write!(f, "Review meeting already declined: {}", desc),
+            ResponseError::UnknownSlot(ref desc) => write!(f, "Slot was not offered: {}", desc),
+        }
+    }
+}
+
+impl std::error::Error for ResponseError {}
+
+// Unvalidated client responses received as JSON from the booking page, inbox or phone log.
+// Date times use the format "dd/mm/yyyy hh:mm" in UK local time.
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum UnvalidatedClientResponse {
+    AcceptSlot { review_id: String, slot_start: String },
+    ProposeTime { review_id: String, proposed_start: String, #[serde(default)] note: Option<String> },
+    Decline { review_id: String, reason: String },
+    DeclineForTaxYear { review_id: String, #[serde(default)] reason: Option<String> },
+    RequestCallBack { review_id: String, #[serde(default)] preferred_time: Option<String> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ClientResponseCommand {
+    AcceptSlot { review_id: ReviewId, slot_start: NaiveDateTime },
+    ProposeTime { review_id: ReviewId, proposed_start: NaiveDateTime, note: Option<String> },
+    Decline { review_id: ReviewId, reason: String },
+    /// Declines this year's review but not future ones.
+    DeclineForTaxYear { review_id: ReviewId, reason: Option<String> },
+    RequestCallBack { review_id: ReviewId, preferred_time: Option<String> },
+}
+
+impl ClientResponseCommand {
+    pub fn validate(unvalidated: UnvalidatedClientResponse) -> Result<Self, ResponseError> {
+        match unvalidated {
+            UnvalidatedClientResponse::AcceptSlot { review_id, slot_start } => Ok(Self::AcceptSlot {
+                review_id: parse_review_id(review_id)?,
+                slot_start: parse_date_time(&slot_start)?,
+            }),
+            UnvalidatedClientResponse::ProposeTime { review_id, proposed_start, note } => Ok(Self::ProposeTime {
+                review_id: parse_review_id(review_id)?,
+                proposed_start: parse_date_time(&proposed_start)?,
+                note: optional_text(note)?,
+            }),
+            UnvalidatedClientResponse::Decline { review_id, reason } => Ok(Self::Decline {
+                review_id: parse_review_id(review_id)?,
+                reason: optional_text(Some(reason))?
+                    .ok_or_else(|| ResponseError::InvalidCommand("A reason is needed to decline.".to_string()))?,
+            }),
+            UnvalidatedClientResponse::DeclineForTaxYear { review_id, reason } => Ok(Self::DeclineForTaxYear {
+                review_id: parse_review_id(review_id)?,
+                reason: optional_text(reason)?,
+            }),
+            UnvalidatedClientResponse::RequestCallBack { review_id, preferred_time } => Ok(Self::RequestCallBack {
+                review_id: parse_review_id(review_id)?,
+                preferred_time: optional_text(preferred_time)?,
+            }),
+        }
+    }
+
+    pub fn review_id(&self) -> &ReviewId {
+        match self {
+            Self::AcceptSlot { review_id, .. }
+            | Self::ProposeTime { review_id, .. }
+            | Self::Decline { review_id, .. }
+            | Self::DeclineForTaxYear { review_id, .. }
+            | Self::RequestCallBack { review_id, .. } => review_id,
+        }
+    }
+}
+
+fn parse_review_id(review_id: String) -> Result<ReviewId, ResponseError> {
+    ReviewId::try_from(review_id).map_err(|e| ResponseError::InvalidCommand(e.to_string()))
+}
+
+fn parse_date_time(date_time: &str) -> Result<NaiveDateTime, ResponseError> {
+    NaiveDateTime::parse_from_str(date_time.trim(), DATE_TIME_FORMAT)
+        .map_err(|e| ResponseError::InvalidCommand(format!("'{}': {}", date_time, e)))
+}
+
+/// Trims free text from the client, treating blank text as missing.
+fn optional_text(text: Option<String>) -> Result<Option<String>, ResponseError> {
+    match text.map(|text| text.trim().to_string()).filter(|text| !text.is_empty()) {
+        Some(text) if text.chars().count() > MAX_NOTE_LENGTH => Err(ResponseError::InvalidCommand(format!(
+            "Text must be no longer than {} characters.", MAX_NOTE_LENGTH,
+        ))),
+        text => Ok(text),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum PendingInviteStatus {
+    AwaitingResponse,
+    Booked(MeetingSlot),
+    Declined,
+}
+
+/// An invite that has been sent and the slots it offered.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PendingInvite {
+    review_id: ReviewId,
+    tax_year: TaxYear,
+    reply_deadline: NaiveDate,
+    offered_slots: Vec<MeetingSlot>,
+    status: PendingInviteStatus,
+}
+
+impl PendingInvite {
+    pub fn new(
+        review_id: ReviewId,
+        annual_review_due_date: &ValidatedAnnualReviewDueDate,
+        reply_deadline: NaiveDate,
+        offered_slots: Vec<MeetingSlot>,
+    ) -> Self {
+        Self {
+            review_id,
+            tax_year: annual_review_due_date.tax_year(),
+            reply_deadline,
+            offered_slots,
+            status: PendingInviteStatus::AwaitingResponse,
+        }
+    }
+
+    pub fn review_id(&self) -> &ReviewId {
+        &self.review_id
+    }
+
+    pub fn reply_deadline(&self) -> NaiveDate {
+        self.reply_deadline
+    }
+
+    pub fn offered_slots(&self) -> &Vec<MeetingSlot> {
+        &self.offered_slots
+    }
+
+    pub fn status(&self) -> &PendingInviteStatus {
+        &self.status
+    }
+
+    /// Checks the command against the invite and returns the outcome event.
+    /// Booking or declining closes the invite to further responses.
+    pub fn handle(&mut self, command: ClientResponseCommand, received_at: NaiveDateTime) -> Result<MeetingOutcomeEvent, ResponseError> {
+        if command.review_id() != &self.review_id {
+            return Err(ResponseError::UnknownInvite(command.review_id().value().to_string()));
+        }
+        match self.status {
+            PendingInviteStatus::Booked(ref slot) => return Err(ResponseError::AlreadyBooked(format!("{} at {}", self.review_id.value(), slot.start()))),
+            PendingInviteStatus::Declined => return Err(ResponseError::AlreadyDeclined(self.review_id.value().to_string())),
+            PendingInviteStatus::AwaitingResponse => {}
+        }
+        if received_at.date() > self.reply_deadline {
+            return Err(ResponseError::InviteExpired(format!("Replies were due by {}", self.reply_deadline.format("%d/%m/%Y"))));
+        }
+
+        let review_id = self.review_id.clone();
+        let event = match command {
+            ClientResponseCommand::AcceptSlot { slot_start, .. } => {
+                let slot = self.offered_slots.iter().find(|slot| slot.start() == slot_start).cloned()
+                    .ok_or_else(|| ResponseError::UnknownSlot(slot_start.format(DATE_TIME_FORMAT).to_string()))?;
+                if slot.start() <= received_at {
+                    return Err(ResponseError::InvalidCommand("The slot has already started.".to_string()));
+                }
+                self.status = PendingInviteStatus::Booked(slot.clone());
+                MeetingOutcomeEvent::ReviewMeetingBooked { review_id, slot, booked_at: received_at }
+            }
+            ClientResponseCommand::ProposeTime { proposed_start, note, .. } => {
+                if proposed_start <= received_at {
+                    return Err(ResponseError::InvalidCommand("The proposed time must be in the future.".to_string()));
+                }
+                MeetingOutcomeEvent::AlternativeTimeProposed { review_id, proposed_start, note, received_at }
+            }
+            ClientResponseCommand::Decline { reason, .. } => {
+                self.status = PendingInviteStatus::Declined;
+                MeetingOutcomeEvent::ReviewMeetingDeclined { review_id, reason, declined_at: received_at }
+            }
+            ClientResponseCommand::DeclineForTaxYear { reason, .. } => {
+                self.status = PendingInviteStatus::Declined;
+                MeetingOutcomeEvent::ReviewMeetingDeclinedInTaxYear { review_id, tax_year: self.tax_year, reason, declined_at: received_at }
+            }
+            ClientResponseCommand::RequestCallBack { preferred_time, .. } => {
+                MeetingOutcomeEvent::CallBackRequested { review_id, preferred_time, received_at }
+            }
+        };
+
+        Ok(event)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum MeetingOutcomeEvent {
+    ReviewMeetingBooked { review_id: ReviewId, slot: MeetingSlot, booked_at: NaiveDateTime },
+    AlternativeTimeProposed { review_id: ReviewId, proposed_start: NaiveDateTime, note: Option<String>, received_at: NaiveDateTime },
+    ReviewMeetingDeclined { review_id: ReviewId, reason: String, declined_at: NaiveDateTime },
+    ReviewMeetingDeclinedInTaxYear { review_id: ReviewId, tax_year: TaxYear, reason: Option<String>, declined_at: NaiveDateTime },
+    CallBackRequested { review_id: ReviewId, preferred_time: Option<String>, received_at: NaiveDateTime },
+}
+
+impl MeetingOutcomeEvent {
+    /// The response that stops reminders, if the event closes the invite.
+    pub fn review_response(&self) -> Option<ReviewResponse> {
+        match self {
+            Self::ReviewMeetingBooked { .. } => Some(ReviewResponse::Booked),
+            Self::ReviewMeetingDeclined { .. } | Self::ReviewMeetingDeclinedInTaxYear { .. } => Some(ReviewResponse::Declined),
+            Self::AlternativeTimeProposed { .. } | Self::CallBackRequested { .. } => None,
+        }
+    }
+
+    /// The outcome recorded against the tax year, if the event settles the review.
+    pub fn review_outcome(&self) -> Option<ReviewOutcome> {
+        self.review_response().map(|response| match response {
+            ReviewResponse::Booked => ReviewOutcome::Booked,
+            ReviewResponse::Declined => ReviewOutcome::Declined,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::test_utils::shared::{due_date, reply_deadline, REVIEW_ID};
+    use super::super::adviser_calendar::MeetingLocation;
+    use super::*;
+
+    fn date_time(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn review_id() -> ReviewId {
+        ReviewId::try_from(REVIEW_ID.to_string()).unwrap()
+    }
+
+    fn slot(day: u32) -> MeetingSlot {
+        MeetingSlot::new(date_time(day, 10), date_time(day, 11), MeetingLocation::VideoCall("https://meet.example.com/review".to_string())).unwrap()
+    }
+
+    fn pending_invite() -> PendingInvite {
+        PendingInvite::new(review_id(), &due_date(), reply_deadline(), vec![slot(14), slot(15)])
+    }
+
+    fn command(json: &str) -> ClientResponseCommand {
+        ClientResponseCommand::validate(serde_json::from_str(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_accept_offered_slot() {
+        let mut invite = pending_invite();
+        let accept = command(r#"{"type": "AcceptSlot", "review_id": "7c9e6679-7425-40de-944b-e07fc1f90ae7", "slot_start": "15/05/2024 10:00"}"#);
+
+        let event = invite.handle(accept.clone(), date_time(2, 9)).unwrap();
+
+        assert_eq!(event, MeetingOutcomeEvent::ReviewMeetingBooked { review_id: review_id(), slot: slot(15), booked_at: date_time(2, 9) });
+        assert_eq!(event.review_response(), Some(ReviewResponse::Booked));
+        assert_eq!(invite.status(), &PendingInviteStatus::Booked(slot(15)));
+        assert!(matches!(invite.handle(accept, date_time(3, 9)), Err(ResponseError::AlreadyBooked(_))));
+    }
+
+    #[test]
+    fn test_accept_unknown_slot() {
+        let mut invite = pending_invite();
+        let accept = ClientResponseCommand::AcceptSlot { review_id: review_id(), slot_start: date_time(16, 10) };
+
+        assert!(matches!(invite.handle(accept, date_time(2, 9)), Err(ResponseError::UnknownSlot(_))));
+        assert_eq!(invite.status(), &PendingInviteStatus::AwaitingResponse);
+    }
+
+    #[test]
+    fn test_accept_started_slot() {
+        let mut invite = pending_invite();
+        let accept = ClientResponseCommand::AcceptSlot { review_id: review_id(), slot_start: date_time(14, 10) };
+
+        assert!(matches!(invite.handle(accept.clone(), date_time(14, 10)), Err(ResponseError::InvalidCommand(_))));
+        assert!(matches!(invite.handle(accept, date_time(15, 9)), Err(ResponseError::InvalidCommand(_))));
+        assert_eq!(invite.status(), &PendingInviteStatus::AwaitingResponse);
+    }
+
+    #[test]
+    fn test_propose_time_and_call_back_keep_invite_open() {
+        let mut invite = pending_invite();
+
+        let propose = command(r#"{"type": "ProposeTime", "review_id": "7c9e6679-7425-40de-944b-e07fc1f90ae7", "proposed_start": "20/05/2024 15:00", "note": " After school pickup "}"#);
+        let event = invite.handle(propose, date_time(2, 9)).unwrap();
+        assert_eq!(event, MeetingOutcomeEvent::AlternativeTimeProposed {
+            review_id: review_id(),
+            proposed_start: date_time(20, 15),
+            note: Some("After school pickup".to_string()),
+            received_at: date_time(2, 9),
+        });
+        assert_eq!(event.review_response(), None);
+
+        let call_back = command(r#"{"type": "RequestCallBack", "review_id": "7c9e6679-7425-40de-944b-e07fc1f90ae7"}"#);
+        assert!(matches!(invite.handle(call_back, date_time(2, 10)), Ok(MeetingOutcomeEvent::CallBackRequested { preferred_time: None, .. })));
+        assert_eq!(invite.status(), &PendingInviteStatus::AwaitingResponse);
+
+        let past = ClientResponseCommand::ProposeTime { review_id: review_id(), proposed_start: date_time(1, 15), note: None };
+        assert!(matches!(invite.handle(past, date_time(2, 9)), Err(ResponseError::InvalidCommand(_))));
+    }
+
+    #[test]
+    fn test_declines() {
+        let mut invite = pending_invite();
+        let decline = command(r#"{"type": "Decline", "review_id": "7c9e6679-7425-40de-944b-e07fc1f90ae7", "reason": "Moving to another adviser"}"#);
+        let event = invite.handle(decline, date_time(2, 9)).unwrap();
+        assert_eq!(event.review_outcome(), Some(ReviewOutcome::Declined));
+
+        let mut invite = pending_invite();
+        let decline = command(r#"{"type": "DeclineForTaxYear", "review_id": "7c9e6679-7425-40de-944b-e07fc1f90ae7"}"#);
+        let event = invite.handle(decline.clone(), date_time(2, 9)).unwrap();
+        assert_eq!(event, MeetingOutcomeEvent::ReviewMeetingDeclinedInTaxYear {
+            review_id: review_id(),
+            tax_year: TaxYear::containing(NaiveDate::from_ymd_opt(2024, 6, 30).unwrap()),
+            reason: None,
+            declined_at: date_time(2, 9),
+        });
+        assert!(matches!(invite.handle(decline, date_time(3, 9)), Err(ResponseError::AlreadyDeclined(_))));
+    }
+
+    #[test]
+    fn test_expired_invite() {
+        let mut invite = pending_invite();
+        let call_back = ClientResponseCommand::RequestCallBack { review_id: review_id(), preferred_time: None };
+
+        assert!(invite.handle(call_back.clone(), date_time(31, 23)).is_ok());
+        let late = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        assert!(matches!(invite.handle(call_back, late), Err(ResponseError::InviteExpired(_))));
+    }
+
+    #[test]
+    fn test_command_for_another_invite() {
+        let mut invite = pending_invite();
+        let other = ReviewId::try_from("00000000-0000-4000-8000-000000000000".to_string()).unwrap();
+        let decline = ClientResponseCommand::DeclineForTaxYear { review_id: other, reason: None };
+
+        assert!(matches!(invite.handle(decline, date_time(2, 9)), Err(ResponseError::UnknownInvite(_))));
+    }
+
+    #[test]
+    fn test_invalid_commands() {
+        let validate = |json: &str| ClientResponseCommand::validate(serde_json::from_str(json).unwrap());
+
+        assert!(matches!(validate(r#"{"type": "Decline", "review_id": "7c9e6679-7425-40de-944b-e07fc1f90ae7", "reason": "  "}"#), Err(ResponseError::InvalidCommand(_))));
+        assert!(matches!(validate(r#"{"type": "AcceptSlot", "review_id": "7c9e6679-7425-40de-944b-e07fc1f90ae7", "slot_start": "2024-05-15T10:00"}"#), Err(ResponseError::InvalidCommand(_))));
+        assert!(matches!(validate(r#"{"type": "RequestCallBack", "review_id": "not-a-review"}"#), Err(ResponseError::InvalidCommand(_))));
+    }
+}
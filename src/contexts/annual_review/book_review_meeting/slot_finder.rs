@@ -0,0 +1,325 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Serialize, Deserialize};
+
+use crate::{holiday_calendar::HolidayCalendar, simple_types::ConstrainedIndividualNameString100};
+use super::adviser_calendar::{AdviserCalendar, CalendarError, MeetingLocation};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MeetingSlot {
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    location: MeetingLocation,
+}
+
+impl MeetingSlot {
+    pub fn new(start: NaiveDateTime, end: NaiveDateTime, location: MeetingLocation) -> Result<Self, CalendarError> {
+        if end <= start {
+            Err(CalendarError::InvalidSearch("Meeting slot must end after it starts.".to_string()))
+        } else {
+            Ok(Self { start, end, location })
+        }
+    }
+
+    pub fn start(&self) -> NaiveDateTime {
+        self.start
+    }
+
+    pub fn end(&self) -> NaiveDateTime {
+        self.end
+    }
+
+    pub fn location(&self) -> &MeetingLocation {
+        &self.location
+    }
+}
+
+/// The dates, inclusive, within which the review meeting must take place.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ReviewWindow {
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+}
+
+impl ReviewWindow {
+    pub fn new(first_day: NaiveDate, last_day: NaiveDate) -> Result<Self, CalendarError> {
+        if last_day < first_day {
+            Err(CalendarError::InvalidSearch("Review window must not end before it starts.".to_string()))
+        } else {
+            Ok(Self { first_day, last_day })
+        }
+    }
+
+    pub fn first_day(&self) -> NaiveDate {
+        self.first_day
+    }
+
+    pub fn last_day(&self) -> NaiveDate {
+        self.last_day
+    }
+
+    pub fn contains(&self, date_time: NaiveDateTime) -> bool {
+        date_time.date() >= self.first_day && date_time.date() <= self.last_day
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkingHours {
+    working_days: Vec<Weekday>,
+    day_start: NaiveTime,
+    day_end: NaiveTime,
+    lunch_break: Option<(NaiveTime, NaiveTime)>,
+}
+
+impl WorkingHours {
+    pub fn new(
+        working_days: Vec<Weekday>,
+        day_start: NaiveTime,
+        day_end: NaiveTime,
+        lunch_break: Option<(NaiveTime, NaiveTime)>,
+    ) -> Result<Self, CalendarError> {
+        if day_end <= day_start {
+            return Err(CalendarError::InvalidSearch("Working day must end after it starts.".to_string()));
+        }
+        if let Some((lunch_start, lunch_end)) = lunch_break {
+            if lunch_end <= lunch_start || lunch_start < day_start || lunch_end > day_end {
+                return Err(CalendarError::InvalidSearch("Lunch break must fall within the working day.".to_string()));
+            }
+        }
+
+        Ok(Self { working_days, day_start, day_end, lunch_break })
+    }
+}
+
+impl Default for WorkingHours {
+    /// Monday to Friday, 09:00 to 17:00 with lunch from 12:30 to 13:30.
+    fn default() -> Self {
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).expect("Default working hours must be valid");
+        Self {
+            working_days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            day_start: time(9, 0),
+            day_end: time(17, 0),
+            lunch_break: Some((time(12, 30), time(13, 30))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotSearch {
+    window: ReviewWindow,
+    meeting_length: Duration,
+    location: MeetingLocation,
+    number_of_slots: usize,
+    working_hours: WorkingHours,
+    travel_buffer: Duration,
+    slot_interval: Duration,
+    max_slots_per_day: usize,
+}
+
+impl SlotSearch {
+    /// Search for the number of slots of the meeting length within the window, using
+    /// default working hours, a 30 minute travel buffer, slots starting on the half hour
+    /// and at most one slot offered per day.
+    pub fn new(
+        window: ReviewWindow,
+        meeting_length: Duration,
+        location: MeetingLocation,
+        number_of_slots: usize,
+    ) -> Result<Self, CalendarError> {
+        if meeting_length <= Duration::zero() {
+            return Err(CalendarError::InvalidSearch("Meeting length must be positive.".to_string()));
+        }
+
+        Ok(Self {
+            window,
+            meeting_length,
+            location,
+            number_of_slots,
+            working_hours: WorkingHours::default(),
+            travel_buffer: Duration::minutes(30),
+            slot_interval: Duration::minutes(30),
+            max_slots_per_day: 1,
+        })
+    }
+
+    pub fn with_working_hours(mut self, working_hours: WorkingHours) -> Self {
+        self.working_hours = working_hours;
+        self
+    }
+
+    /// Time kept clear either side of a meeting when either it or the
+    /// neighbouring booking is in person.
+    pub fn with_travel_buffer(mut self, travel_buffer: Duration) -> Self {
+        self.travel_buffer = travel_buffer;
+        self
+    }
+
+    pub fn with_slot_interval(mut self, slot_interval: Duration) -> Self {
+        self.slot_interval = slot_interval.max(Duration::minutes(5));
+        self
+    }
+
+    pub fn with_max_slots_per_day(mut self, max_slots_per_day: usize) -> Self {
+        self.max_slots_per_day = max_slots_per_day.max(1);
+        self
+    }
+}
+
+/// Offers candidate review meeting slots from an adviser's free time.
+pub struct SlotFinder<'a, C: AdviserCalendar> {
+    adviser_calendar: &'a C,
+    holiday_calendar: &'a HolidayCalendar,
+}
+
+impl<'a, C: AdviserCalendar> SlotFinder<'a, C> {
+    pub fn new(adviser_calendar: &'a C, holiday_calendar: &'a HolidayCalendar) -> Self {
+        Self { adviser_calendar, holiday_calendar }
+    }
+
+    /// The earliest free slots in the window, in date order, skipping bank
+    /// holidays, non-working days, lunch and time around existing bookings.
+    pub fn find_slots(
+        &self,
+        adviser_name: &ConstrainedIndividualNameString100,
+        search: &SlotSearch,
+    ) -> Result<Vec<MeetingSlot>, CalendarError> {
+        let window_start = search.window.first_day.and_time(NaiveTime::MIN);
+        let window_end = (search.window.last_day + Duration::days(1)).and_time(NaiveTime::MIN);
+        let entries = self.adviser_calendar.entries_between(
+            adviser_name,
+            window_start - search.travel_buffer,
+            window_end + search.travel_buffer,
+        )?;
+
+        // Busy periods including any travel time needed around each booking
+        let busy: Vec<(NaiveDateTime, NaiveDateTime)> = entries
+            .iter()
+            .map(|entry| {
+                let buffer = if entry.requires_travel() || search.location.requires_travel() {
+                    search.travel_buffer
+                } else {
+                    Duration::zero()
+                };
+                (entry.start() - buffer, entry.end() + buffer)
+            })
+            .collect();
+
+        let mut slots = Vec::new();
+        let mut day = search.window.first_day;
+
+        while day <= search.window.last_day && slots.len() < search.number_of_slots {
+            if search.working_hours.working_days.contains(&day.weekday()) && self.holiday_calendar.is_business_day(day) {
+                let mut slots_today = 0;
+                let mut start = day.and_time(search.working_hours.day_start);
+                let day_end = day.and_time(search.working_hours.day_end);
+
+                while start + search.meeting_length <= day_end
+                    && slots_today < search.max_slots_per_day
+                    && slots.len() < search.number_of_slots
+                {
+                    let end = start + search.meeting_length;
+                    let during_lunch = search.working_hours.lunch_break.is_some_and(|(lunch_start, lunch_end)| {
+                        start < day.and_time(lunch_end) && end > day.and_time(lunch_start)
+                    });
+                    let clashes = busy.iter().any(|(busy_start, busy_end)| start < *busy_end && end > *busy_start);
+
+                    if !during_lunch && !clashes {
+                        slots.push(MeetingSlot { start, end, location: search.location.clone() });
+                        slots_today += 1;
+                        start = end;
+                    } else {
+                        start += search.slot_interval;
+                    }
+                }
+            }
+            day += Duration::days(1);
+        }
+
+        Ok(slots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::holiday_calendar::Jurisdiction;
+    use super::super::adviser_calendar::{CalendarEntry, InMemoryAdviserCalendar};
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn date_time(month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        date(month, day).and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn adviser() -> ConstrainedIndividualNameString100 {
+        ConstrainedIndividualNameString100::try_from("Mark Ashworth".to_string()).unwrap()
+    }
+
+    fn in_person() -> MeetingLocation {
+        MeetingLocation::InPerson("Client home".to_string())
+    }
+
+    fn starts(slots: &[MeetingSlot]) -> Vec<NaiveDateTime> {
+        slots.iter().map(|slot| slot.start()).collect()
+    }
+
+    #[test]
+    fn test_finds_one_slot_per_working_day_skipping_weekends_and_bank_holidays() {
+        let adviser_calendar = InMemoryAdviserCalendar::new();
+        let holiday_calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        let search = SlotSearch::new(ReviewWindow::new(date(5, 24), date(5, 31)).unwrap(), Duration::hours(1), MeetingLocation::Telephone, 3).unwrap();
+
+        let slots = SlotFinder::new(&adviser_calendar, &holiday_calendar).find_slots(&adviser(), &search).unwrap();
+
+        assert_eq!(starts(&slots), vec![date_time(5, 24, 9, 0), date_time(5, 28, 9, 0), date_time(5, 29, 9, 0)]);
+    }
+
+    #[test]
+    fn test_slots_avoid_bookings_travel_buffers_and_lunch() {
+        let mut adviser_calendar = InMemoryAdviserCalendar::new();
+        adviser_calendar.add_entry(&adviser(), CalendarEntry::new(date_time(6, 12, 9, 0), date_time(6, 12, 11, 0), "Review".to_string(), Some(in_person())).unwrap());
+        adviser_calendar.add_entry(&adviser(), CalendarEntry::new(date_time(6, 12, 13, 30), date_time(6, 12, 15, 30), "Review".to_string(), None).unwrap());
+        let holiday_calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        let search = SlotSearch::new(ReviewWindow::new(date(6, 12), date(6, 12)).unwrap(), Duration::hours(1), in_person(), 5)
+            .unwrap()
+            .with_max_slots_per_day(5);
+
+        let slots = SlotFinder::new(&adviser_calendar, &holiday_calendar).find_slots(&adviser(), &search).unwrap();
+
+        // 11:00-11:30 is travel time, 12:30-13:30 is lunch and the afternoon booking needs a buffer either side
+        assert_eq!(starts(&slots), vec![date_time(6, 12, 11, 30), date_time(6, 12, 16, 0)]);
+    }
+
+    #[test]
+    fn test_no_travel_buffer_between_remote_meetings() {
+        let mut adviser_calendar = InMemoryAdviserCalendar::new();
+        adviser_calendar.add_entry(&adviser(), CalendarEntry::new(date_time(6, 12, 9, 0), date_time(6, 12, 10, 0), "Call".to_string(), Some(MeetingLocation::Telephone)).unwrap());
+        let holiday_calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        let search = SlotSearch::new(ReviewWindow::new(date(6, 12), date(6, 12)).unwrap(), Duration::hours(1), MeetingLocation::Telephone, 1).unwrap();
+
+        let slots = SlotFinder::new(&adviser_calendar, &holiday_calendar).find_slots(&adviser(), &search).unwrap();
+
+        assert_eq!(starts(&slots), vec![date_time(6, 12, 10, 0)]);
+    }
+
+    #[test]
+    fn test_fewer_slots_returned_when_window_is_full() {
+        let mut adviser_calendar = InMemoryAdviserCalendar::new();
+        adviser_calendar.add_entry(&adviser(), CalendarEntry::new(date_time(6, 12, 8, 0), date_time(6, 12, 18, 0), "Training".to_string(), None).unwrap());
+        let holiday_calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        let search = SlotSearch::new(ReviewWindow::new(date(6, 12), date(6, 13)).unwrap(), Duration::hours(1), MeetingLocation::Telephone, 3).unwrap();
+
+        let slots = SlotFinder::new(&adviser_calendar, &holiday_calendar).find_slots(&adviser(), &search).unwrap();
+
+        assert_eq!(starts(&slots), vec![date_time(6, 13, 9, 0)]);
+    }
+
+    #[test]
+    fn test_invalid_search_is_rejected() {
+        assert!(ReviewWindow::new(date(6, 13), date(6, 12)).is_err());
+        assert!(SlotSearch::new(ReviewWindow::new(date(6, 12), date(6, 13)).unwrap(), Duration::zero(), MeetingLocation::Telephone, 1).is_err());
+        let time = |hour| NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+        assert!(WorkingHours::new(vec![Weekday::Mon], time(9), time(17), Some((time(8), time(9)))).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use std::cell::RefCell;
+
+use serde::{Serialize, Deserialize};
+
+use crate::simple_types::PhoneNumber;
+use super::invite_sms::SmsMessage;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmsGatewayError {
+    /// Sending again unchanged will fail, e.g. the number cannot receive texts.
+    Rejected(String),
+    /// Sending again later may succeed.
+    Unavailable(String),
+}
+
+impl std::fmt::Display for SmsGatewayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            SmsGatewayError::Rejected(ref desc) => write!(f, "Text message rejected: {}", desc),
+            SmsGatewayError::Unavailable(ref desc) => write!(f, "SMS gateway unavailable: {}", desc),
+        }
+    }
+}
+
+impl std::error::Error for SmsGatewayError {}
+
+/// The gateway's acknowledgement of a message it accepted for delivery.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SmsReceipt {
+    reference: String,
+    segments: usize,
+}
+
+impl SmsReceipt {
+    pub fn new(reference: &str, segments: usize) -> Self {
+        Self { reference: reference.to_string(), segments }
+    }
+
+    /// The gateway's reference for the message, used to match delivery reports.
+    pub fn reference(&self) -> &String {
+        &self.reference
+    }
+
+    /// The segments charged for the message.
+    pub fn segments(&self) -> usize {
+        self.segments
+    }
+}
+
+/// Port for handing a text message to an SMS provider.
+pub trait SmsGateway {
+    fn send(&self, message: &SmsMessage) -> Result<SmsReceipt, SmsGatewayError>;
+}
+
+/// Records messages instead of sending them, for tests and local running.
+#[derive(Debug, Default)]
+pub struct FakeSmsGateway {
+    sent: RefCell<Vec<SmsMessage>>,
+    rejected_numbers: Vec<PhoneNumber>,
+    unavailable: bool,
+}
+
+impl FakeSmsGateway {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Messages to this number are rejected.
+    pub fn with_rejected_number(mut self, phone_number: PhoneNumber) -> Self {
+        self.rejected_numbers.push(phone_number);
+        self
+    }
+
+    /// Every message fails as though the provider could not be reached.
+    pub fn unavailable(mut self) -> Self {
+        self.unavailable = true;
+        self
+    }
+
+    pub fn sent(&self) -> Vec<SmsMessage> {
+        self.sent.borrow().clone()
+    }
+}
+
+impl SmsGateway for FakeSmsGateway {
+    fn send(&self, message: &SmsMessage) -> Result<SmsReceipt, SmsGatewayError> {
+        if self.unavailable {
+            return Err(SmsGatewayError::Unavailable("Fake gateway is unavailable".to_string()));
+        }
+        if self.rejected_numbers.contains(message.recipient()) {
+            return Err(SmsGatewayError::Rejected(format!("{} cannot receive text messages", message.recipient())));
+        }
+
+        let mut sent = self.sent.borrow_mut();
+        sent.push(message.clone());
+        Ok(SmsReceipt::new(&format!("fake-sms-{}", sent.len()), message.length().segments()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phone_number(number: &str) -> PhoneNumber {
+        PhoneNumber::try_from(number.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_fake_gateway_records_sent_messages() {
+        let gateway = FakeSmsGateway::new();
+        let message = SmsMessage::new(phone_number("07700 900123"), &"a".repeat(200));
+
+        let receipt = gateway.send(&message).unwrap();
+
+        assert_eq!(receipt, SmsReceipt::new("fake-sms-1", 2));
+        assert_eq!(gateway.sent(), vec![message]);
+    }
+
+    #[test]
+    fn test_fake_gateway_rejects_and_fails() {
+        let gateway = FakeSmsGateway::new().with_rejected_number(phone_number("07700 900456"));
+        let result = gateway.send(&SmsMessage::new(phone_number("+447700900456"), "Hello"));
+        assert!(matches!(result, Err(SmsGatewayError::Rejected(_))));
+        assert!(gateway.sent().is_empty());
+
+        let result = FakeSmsGateway::new().unavailable().send(&SmsMessage::new(phone_number("07700 900123"), "Hello"));
+        assert!(matches!(result, Err(SmsGatewayError::Unavailable(_))));
+    }
+}
@@ -0,0 +1,773 @@
+//! Templates for the annual review invite emails and letters.
+//!
+//! A template file is split into sections, each introduced by a `--- name`
+//! line. Email templates have `subject`, `text` and `html` sections and letter
+//! templates have a single `body` section. Placeholders are written as
+//! `{{name}}` and are checked against [`Placeholder`] when the template is
+//! loaded, so a misspelt placeholder fails on load rather than when an invite
+//! is sent.
+//!
+//! Each template may have variants for the client's language and, for letters,
+//! large print. English and Welsh versions of every template are bundled.
+//! Dates and lists of names are written in the invite's language. Braille and
+//! audio are transcribed from the standard letter by their production lines, so
+//! they need no template of their own. Rendering an invite whose variant has no
+//! template fails rather than falling back to standard English.
+//!
+//! Invites to an attorney or deputy are written about the clients they act
+//! for, who only these templates can name with `{{client_names}}`. A joint
+//! client whose partner has died is written to with the bereaved templates.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+
+use crate::simple_types::{ConstainedTrustOrCompanyNameString200, ConstrainedIndividualNameString100};
+use super::{
+    contact_preferences::{AccessibleFormat, CommunicationFormat, Language},
+    localisation::{format_date, join_list},
+    invites::{InviteContentReference, SendElectronicAnnualReviewInvite, SendPostAnnualReviewInvite},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    MissingTemplate(String),
+    MissingSection(String),
+    UnknownSection(String),
+    UnknownPlaceholder(String),
+    UnavailablePlaceholder(String),
+    MalformedPlaceholder(String),
+    UnsupportedFormat(String),
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            TemplateError::MissingTemplate(ref desc) => write!(f, "Missing template: {}", desc),
+            TemplateError::MissingSection(ref desc) => write!(f, "Missing template section: {}", desc),
+            TemplateError::UnknownSection(ref desc) => write!(f, "Unknown template section: {}", desc),
+            TemplateError::UnknownPlaceholder(ref desc) => write!(f, "Unknown placeholder: {}", desc),
+            TemplateError::UnavailablePlaceholder(ref desc) => write!(f, "Unavailable placeholder: {}", desc),
+            TemplateError::MalformedPlaceholder(ref desc) => write!(f, "Malformed placeholder: {}", desc),
+            TemplateError::UnsupportedFormat(ref desc) => write!(f, "Unsupported format: {}", desc),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// The values a template can refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Placeholder {
+    Salutation,
+    OrganisationName,
+    AdviserName,
+    AnnualReviewDueDate,
+    ReplyDeadline,
+    ClientNames,
+}
+
+impl TryFrom<&str> for Placeholder {
+    type Error = TemplateError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "salutation" => Ok(Self::Salutation),
+            "organisation_name" => Ok(Self::OrganisationName),
+            "adviser_name" => Ok(Self::AdviserName),
+            "annual_review_due_date" => Ok(Self::AnnualReviewDueDate),
+            "reply_deadline" => Ok(Self::ReplyDeadline),
+            "client_names" => Ok(Self::ClientNames),
+            _ => Err(TemplateError::UnknownPlaceholder(format!("{{{{{}}}}}", value))),
+        }
+    }
+}
+
+/// The values substituted into a template for one invite.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InviteTemplateContext {
+    recipient_names: Vec<ConstrainedIndividualNameString100>,
+    organisation_name: Option<ConstainedTrustOrCompanyNameString200>,
+    adviser_name: ConstrainedIndividualNameString100,
+    annual_review_due_date: NaiveDate,
+    reply_deadline: NaiveDate,
+    language: Language,
+    client_names: Vec<ConstrainedIndividualNameString100>,
+}
+
+impl InviteTemplateContext {
+    pub fn for_electronic_invite(invite: &SendElectronicAnnualReviewInvite, adviser_name: &ConstrainedIndividualNameString100) -> Self {
+        Self {
+            recipient_names: invite.recipient_names().clone(),
+            organisation_name: invite.organisation_name().cloned(),
+            adviser_name: adviser_name.clone(),
+            annual_review_due_date: invite.annual_review_due_date().date(),
+            reply_deadline: invite.reply_deadline(),
+            language: invite.format().language(),
+            client_names: invite.on_behalf_of().clone(),
+        }
+    }
+
+    pub fn for_post_invite(invite: &SendPostAnnualReviewInvite, adviser_name: &ConstrainedIndividualNameString100) -> Self {
+        Self {
+            recipient_names: invite.recipient_names().clone(),
+            organisation_name: invite.organisation_name().cloned(),
+            adviser_name: adviser_name.clone(),
+            annual_review_due_date: invite.annual_review_due_date().date(),
+            reply_deadline: invite.reply_deadline(),
+            language: invite.format().language(),
+            client_names: invite.on_behalf_of().clone(),
+        }
+    }
+
+    pub fn salutation(&self) -> String {
+        join_names(&self.recipient_names, self.language)
+    }
+
+    pub fn adviser_name(&self) -> &ConstrainedIndividualNameString100 {
+        &self.adviser_name
+    }
+
+    pub fn value(&self, placeholder: Placeholder) -> String {
+        match placeholder {
+            Placeholder::Salutation => self.salutation(),
+            Placeholder::OrganisationName => self.organisation_name.as_ref().map(|name| name.value().to_string()).unwrap_or_default(),
+            Placeholder::AdviserName => self.adviser_name.value().to_string(),
+            Placeholder::AnnualReviewDueDate => format_date(self.language, self.annual_review_due_date),
+            Placeholder::ReplyDeadline => format_date(self.language, self.reply_deadline),
+            Placeholder::ClientNames => join_names(&self.client_names, self.language),
+        }
+    }
+}
+
+/// Names joined for a greeting, e.g. "Alice, Bob and Carol".
+pub fn join_names(names: &[ConstrainedIndividualNameString100], language: Language) -> String {
+    let names: Vec<&str> = names.iter().map(|name| name.value()).collect();
+    join_list(language, &names)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+/// A single section of a template with its placeholders resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateSection {
+    segments: Vec<Segment>,
+}
+
+impl TemplateSection {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut rest = source;
+
+        while let Some(open) = rest.find("{{") {
+            if open > 0 {
+                segments.push(Segment::Text(rest[..open].to_string()));
+            }
+
+            let after_open = &rest[open + 2..];
+            let close = after_open.find("}}")
+                .ok_or_else(|| TemplateError::MalformedPlaceholder(format!("Unclosed placeholder at '{}'", truncate(&rest[open..]))))?;
+            let name = after_open[..close].trim();
+            if name.is_empty() || name.contains("{{") {
+                return Err(TemplateError::MalformedPlaceholder(format!("Invalid placeholder at '{}'", truncate(&rest[open..]))));
+            }
+
+            segments.push(Segment::Placeholder(Placeholder::try_from(name)?));
+            rest = &after_open[close + 2..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+
+        Ok(Self { segments })
+    }
+
+    pub fn placeholders(&self) -> impl Iterator<Item = Placeholder> + '_ {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Placeholder(placeholder) => Some(*placeholder),
+            Segment::Text(_) => None,
+        })
+    }
+
+    pub fn render(&self, context: &InviteTemplateContext) -> String {
+        self.render_with(context, |value| value)
+    }
+
+    /// Renders the section for an HTML document, escaping the substituted values.
+    pub fn render_html(&self, context: &InviteTemplateContext) -> String {
+        self.render_with(context, escape_html)
+    }
+
+    fn render_with(&self, context: &InviteTemplateContext, escape: impl Fn(String) -> String) -> String {
+        self.segments.iter().map(|segment| match segment {
+            Segment::Text(text) => text.clone(),
+            Segment::Placeholder(placeholder) => escape(context.value(*placeholder)),
+        }).collect()
+    }
+}
+
+fn truncate(value: &str) -> String {
+    value.chars().take(30).collect()
+}
+
+fn escape_html(value: String) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[derive(Clone, PartialEq)]
+pub struct RenderedEmail {
+    subject: String,
+    text_body: String,
+    html_body: String,
+}
+
+/// Every part is left out as each can name the client.
+impl std::fmt::Debug for RenderedEmail {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenderedEmail").finish_non_exhaustive()
+    }
+}
+
+impl RenderedEmail {
+    pub fn subject(&self) -> &String {
+        &self.subject
+    }
+
+    pub fn text_body(&self) -> &String {
+        &self.text_body
+    }
+
+    pub fn html_body(&self) -> &String {
+        &self.html_body
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct RenderedLetter {
+    salutation: String,
+    body: String,
+}
+
+/// The salutation and body are left out as both name the client.
+impl std::fmt::Debug for RenderedLetter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenderedLetter").finish_non_exhaustive()
+    }
+}
+
+impl RenderedLetter {
+    pub fn salutation(&self) -> &String {
+        &self.salutation
+    }
+
+    /// The body of the letter with paragraphs separated by blank lines.
+    pub fn body(&self) -> &String {
+        &self.body
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InviteTemplate {
+    Email { subject: TemplateSection, text: TemplateSection, html: TemplateSection },
+    Letter { body: TemplateSection },
+}
+
+impl InviteTemplate {
+    /// Parses the template for `content_reference`, checking it has the sections
+    /// for its channel and only uses placeholders the invite can provide.
+    pub fn load(content_reference: InviteContentReference, source: &str) -> Result<Self, TemplateError> {
+        let mut sections = split_sections(content_reference, source)?;
+        let mut take = |name: &str| sections.remove(name)
+            .ok_or_else(|| TemplateError::MissingSection(format!("'{}' in {}", name, content_reference.value())));
+
+        let template = if is_electronic(content_reference) {
+            Self::Email { subject: take("subject")?, text: take("text")?, html: take("html")? }
+        } else {
+            Self::Letter { body: take("body")? }
+        };
+
+        if let Some(name) = sections.keys().next() {
+            return Err(TemplateError::UnknownSection(format!("'{}' in {}", name, content_reference.value())));
+        }
+
+        if !has_organisation(content_reference) && template.placeholders().any(|placeholder| placeholder == Placeholder::OrganisationName) {
+            return Err(TemplateError::UnavailablePlaceholder(format!("{{{{organisation_name}}}} in {}", content_reference.value())));
+        }
+
+        if !content_reference.is_for_authorised_contact() && template.placeholders().any(|placeholder| placeholder == Placeholder::ClientNames) {
+            return Err(TemplateError::UnavailablePlaceholder(format!("{{{{client_names}}}} in {}", content_reference.value())));
+        }
+
+        Ok(template)
+    }
+
+    fn placeholders(&self) -> Box<dyn Iterator<Item = Placeholder> + '_> {
+        match self {
+            Self::Email { subject, text, html } => Box::new(subject.placeholders().chain(text.placeholders()).chain(html.placeholders())),
+            Self::Letter { body } => Box::new(body.placeholders()),
+        }
+    }
+}
+
+fn split_sections(content_reference: InviteContentReference, source: &str) -> Result<HashMap<String, TemplateSection>, TemplateError> {
+    let mut sections = HashMap::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+
+    for line in source.lines() {
+        if let Some(name) = line.strip_prefix("--- ") {
+            if let Some((name, lines)) = current.take() {
+                sections.insert(name, TemplateSection::parse(lines.join("\n").trim())?);
+            }
+            current = Some((name.trim().to_string(), Vec::new()));
+        } else if let Some((_, lines)) = current.as_mut() {
+            lines.push(line);
+        } else if !line.trim().is_empty() {
+            return Err(TemplateError::MissingSection(format!("Content before the first section in {}", content_reference.value())));
+        }
+    }
+
+    if let Some((name, lines)) = current {
+        sections.insert(name, TemplateSection::parse(lines.join("\n").trim())?);
+    }
+
+    Ok(sections)
+}
+
+fn is_electronic(content_reference: InviteContentReference) -> bool {
+    matches!(
+        content_reference,
+        InviteContentReference::JointIndividualsElectronicInvite
+            | InviteContentReference::SingleIndividualElectronicInvite
+            | InviteContentReference::MultipleTrusteesElectronicInvite
+            | InviteContentReference::PrimaryTrusteeElectronicInvite
+            | InviteContentReference::MultipleDirectorsElectronicInvite
+            | InviteContentReference::PrimaryDirectorElectronicInvite
+            | InviteContentReference::AttorneyElectronicInvite
+            | InviteContentReference::BereavedIndividualElectronicInvite
+    )
+}
+
+fn has_organisation(content_reference: InviteContentReference) -> bool {
+    !matches!(
+        content_reference,
+        InviteContentReference::JointIndividualsElectronicInvite
+            | InviteContentReference::SingleIndividualElectronicInvite
+            | InviteContentReference::JointIndividualsPostInvite
+            | InviteContentReference::SingleIndividualPostInvite
+            | InviteContentReference::AttorneyElectronicInvite
+            | InviteContentReference::AttorneyPostInvite
+            | InviteContentReference::BereavedIndividualElectronicInvite
+            | InviteContentReference::BereavedIndividualPostInvite
+    )
+}
+
+/// The version of a template an invite is rendered from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TemplateVariant {
+    language: Language,
+    large_print: bool,
+}
+
+impl TemplateVariant {
+    pub fn standard(language: Language) -> Self {
+        Self { language, large_print: false }
+    }
+
+    pub fn large_print(language: Language) -> Self {
+        Self { language, large_print: true }
+    }
+
+    /// Large print letters have their own template; braille and audio are
+    /// produced from the standard one.
+    pub fn for_letter(format: CommunicationFormat) -> Self {
+        Self { language: format.language(), large_print: format.accessible_format() == AccessibleFormat::LargePrint }
+    }
+
+    /// Emails are enlarged on the client's screen, so only the language matters.
+    pub fn for_email(format: CommunicationFormat) -> Self {
+        Self::standard(format.language())
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn is_large_print(&self) -> bool {
+        self.large_print
+    }
+
+    fn describe(&self) -> String {
+        format!("{:?}{}", self.language, if self.large_print { " large print" } else { "" })
+    }
+}
+
+const BUNDLED_TEMPLATES: [(InviteContentReference, &str); 16] = [
+    (InviteContentReference::JointIndividualsElectronicInvite, include_str!("defaults/joint_individuals_electronic_invite.tmpl")),
+    (InviteContentReference::SingleIndividualElectronicInvite, include_str!("defaults/single_individual_electronic_invite.tmpl")),
+    (InviteContentReference::MultipleTrusteesElectronicInvite, include_str!("defaults/multiple_trustees_electronic_invite.tmpl")),
+    (InviteContentReference::PrimaryTrusteeElectronicInvite, include_str!("defaults/primary_trustee_electronic_invite.tmpl")),
+    (InviteContentReference::MultipleDirectorsElectronicInvite, include_str!("defaults/multiple_directors_electronic_invite.tmpl")),
+    (InviteContentReference::PrimaryDirectorElectronicInvite, include_str!("defaults/primary_director_electronic_invite.tmpl")),
+    (InviteContentReference::JointIndividualsPostInvite, include_str!("defaults/joint_individuals_post_invite.tmpl")),
+    (InviteContentReference::SingleIndividualPostInvite, include_str!("defaults/single_individual_post_invite.tmpl")),
+    (InviteContentReference::MultipleTrusteesPostInvite, include_str!("defaults/multiple_trustees_post_invite.tmpl")),
+    (InviteContentReference::PrimaryTrusteePostInvite, include_str!("defaults/primary_trustee_post_invite.tmpl")),
+    (InviteContentReference::MultipleDirectorsPostInvite, include_str!("defaults/multiple_directors_post_invite.tmpl")),
+    (InviteContentReference::PrimaryDirectorPostInvite, include_str!("defaults/primary_director_post_invite.tmpl")),
+    (InviteContentReference::AttorneyElectronicInvite, include_str!("defaults/attorney_electronic_invite.tmpl")),
+    (InviteContentReference::AttorneyPostInvite, include_str!("defaults/attorney_post_invite.tmpl")),
+    (InviteContentReference::BereavedIndividualElectronicInvite, include_str!("defaults/bereaved_individual_electronic_invite.tmpl")),
+    (InviteContentReference::BereavedIndividualPostInvite, include_str!("defaults/bereaved_individual_post_invite.tmpl")),
+];
+
+const BUNDLED_LARGE_PRINT_TEMPLATES: [(InviteContentReference, &str); 8] = [
+    (InviteContentReference::JointIndividualsPostInvite, include_str!("defaults/large_print/joint_individuals_post_invite.tmpl")),
+    (InviteContentReference::SingleIndividualPostInvite, include_str!("defaults/large_print/single_individual_post_invite.tmpl")),
+    (InviteContentReference::MultipleTrusteesPostInvite, include_str!("defaults/large_print/multiple_trustees_post_invite.tmpl")),
+    (InviteContentReference::PrimaryTrusteePostInvite, include_str!("defaults/large_print/primary_trustee_post_invite.tmpl")),
+    (InviteContentReference::MultipleDirectorsPostInvite, include_str!("defaults/large_print/multiple_directors_post_invite.tmpl")),
+    (InviteContentReference::PrimaryDirectorPostInvite, include_str!("defaults/large_print/primary_director_post_invite.tmpl")),
+    (InviteContentReference::AttorneyPostInvite, include_str!("defaults/large_print/attorney_post_invite.tmpl")),
+    (InviteContentReference::BereavedIndividualPostInvite, include_str!("defaults/large_print/bereaved_individual_post_invite.tmpl")),
+];
+
+const BUNDLED_WELSH_TEMPLATES: [(InviteContentReference, &str); 16] = [
+    (InviteContentReference::JointIndividualsElectronicInvite, include_str!("defaults/cy/joint_individuals_electronic_invite.tmpl")),
+    (InviteContentReference::SingleIndividualElectronicInvite, include_str!("defaults/cy/single_individual_electronic_invite.tmpl")),
+    (InviteContentReference::MultipleTrusteesElectronicInvite, include_str!("defaults/cy/multiple_trustees_electronic_invite.tmpl")),
+    (InviteContentReference::PrimaryTrusteeElectronicInvite, include_str!("defaults/cy/primary_trustee_electronic_invite.tmpl")),
+    (InviteContentReference::MultipleDirectorsElectronicInvite, include_str!("defaults/cy/multiple_directors_electronic_invite.tmpl")),
+    (InviteContentReference::PrimaryDirectorElectronicInvite, include_str!("defaults/cy/primary_director_electronic_invite.tmpl")),
+    (InviteContentReference::JointIndividualsPostInvite, include_str!("defaults/cy/joint_individuals_post_invite.tmpl")),
+    (InviteContentReference::SingleIndividualPostInvite, include_str!("defaults/cy/single_individual_post_invite.tmpl")),
+    (InviteContentReference::MultipleTrusteesPostInvite, include_str!("defaults/cy/multiple_trustees_post_invite.tmpl")),
+    (InviteContentReference::PrimaryTrusteePostInvite, include_str!("defaults/cy/primary_trustee_post_invite.tmpl")),
+    (InviteContentReference::MultipleDirectorsPostInvite, include_str!("defaults/cy/multiple_directors_post_invite.tmpl")),
+    (InviteContentReference::PrimaryDirectorPostInvite, include_str!("defaults/cy/primary_director_post_invite.tmpl")),
+    (InviteContentReference::AttorneyElectronicInvite, include_str!("defaults/cy/attorney_electronic_invite.tmpl")),
+    (InviteContentReference::AttorneyPostInvite, include_str!("defaults/cy/attorney_post_invite.tmpl")),
+    (InviteContentReference::BereavedIndividualElectronicInvite, include_str!("defaults/cy/bereaved_individual_electronic_invite.tmpl")),
+    (InviteContentReference::BereavedIndividualPostInvite, include_str!("defaults/cy/bereaved_individual_post_invite.tmpl")),
+];
+
+const BUNDLED_WELSH_LARGE_PRINT_TEMPLATES: [(InviteContentReference, &str); 8] = [
+    (InviteContentReference::JointIndividualsPostInvite, include_str!("defaults/cy/large_print/joint_individuals_post_invite.tmpl")),
+    (InviteContentReference::SingleIndividualPostInvite, include_str!("defaults/cy/large_print/single_individual_post_invite.tmpl")),
+    (InviteContentReference::MultipleTrusteesPostInvite, include_str!("defaults/cy/large_print/multiple_trustees_post_invite.tmpl")),
+    (InviteContentReference::PrimaryTrusteePostInvite, include_str!("defaults/cy/large_print/primary_trustee_post_invite.tmpl")),
+    (InviteContentReference::MultipleDirectorsPostInvite, include_str!("defaults/cy/large_print/multiple_directors_post_invite.tmpl")),
+    (InviteContentReference::PrimaryDirectorPostInvite, include_str!("defaults/cy/large_print/primary_director_post_invite.tmpl")),
+    (InviteContentReference::AttorneyPostInvite, include_str!("defaults/cy/large_print/attorney_post_invite.tmpl")),
+    (InviteContentReference::BereavedIndividualPostInvite, include_str!("defaults/cy/large_print/bereaved_individual_post_invite.tmpl")),
+];
+
+/// The loaded templates, one per invite content reference and variant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InviteTemplates {
+    templates: HashMap<(InviteContentReference, TemplateVariant), InviteTemplate>,
+}
+
+impl InviteTemplates {
+    /// The default templates bundled with the crate.
+    pub fn bundled() -> Result<Self, TemplateError> {
+        Self::load(BUNDLED_TEMPLATES.iter().copied())?
+            .with_variant(TemplateVariant::large_print(Language::English), BUNDLED_LARGE_PRINT_TEMPLATES.iter().copied())?
+            .with_variant(TemplateVariant::standard(Language::Welsh), BUNDLED_WELSH_TEMPLATES.iter().copied())?
+            .with_variant(TemplateVariant::large_print(Language::Welsh), BUNDLED_WELSH_LARGE_PRINT_TEMPLATES.iter().copied())
+    }
+
+    /// Loads the standard English templates.
+    pub fn load<'a>(sources: impl IntoIterator<Item = (InviteContentReference, &'a str)>) -> Result<Self, TemplateError> {
+        Self { templates: HashMap::new() }.with_variant(TemplateVariant::standard(Language::English), sources)
+    }
+
+    /// Adds the templates for a variant. Large print templates are for letters only.
+    pub fn with_variant<'a>(
+        mut self,
+        variant: TemplateVariant,
+        sources: impl IntoIterator<Item = (InviteContentReference, &'a str)>,
+    ) -> Result<Self, TemplateError> {
+        for (content_reference, source) in sources {
+            if variant.large_print && is_electronic(content_reference) {
+                return Err(TemplateError::UnsupportedFormat(format!("{} has no large print version", content_reference.value())));
+            }
+            self.templates.insert((content_reference, variant), InviteTemplate::load(content_reference, source)?);
+        }
+
+        Ok(self)
+    }
+
+    /// The standard English template.
+    pub fn template(&self, content_reference: InviteContentReference) -> Result<&InviteTemplate, TemplateError> {
+        self.template_for(content_reference, TemplateVariant::standard(Language::English))
+    }
+
+    pub fn template_for(&self, content_reference: InviteContentReference, variant: TemplateVariant) -> Result<&InviteTemplate, TemplateError> {
+        self.templates.get(&(content_reference, variant)).ok_or_else(|| {
+            if variant == TemplateVariant::standard(Language::English) {
+                TemplateError::MissingTemplate(content_reference.value().to_string())
+            } else {
+                TemplateError::UnsupportedFormat(format!("No {} template for {}", variant.describe(), content_reference.value()))
+            }
+        })
+    }
+
+    pub fn render_email(&self, invite: &SendElectronicAnnualReviewInvite, adviser_name: &ConstrainedIndividualNameString100) -> Result<RenderedEmail, TemplateError> {
+        let context = InviteTemplateContext::for_electronic_invite(invite, adviser_name);
+
+        match self.template_for(invite.content_reference(), TemplateVariant::for_email(invite.format()))? {
+            InviteTemplate::Email { subject, text, html } => Ok(RenderedEmail {
+                subject: subject.render(&context),
+                text_body: text.render(&context),
+                html_body: html.render_html(&context),
+            }),
+            InviteTemplate::Letter { .. } => Err(TemplateError::MissingTemplate(format!("{} is not an email template", invite.content_reference().value()))),
+        }
+    }
+
+    pub fn render_letter(&self, invite: &SendPostAnnualReviewInvite, adviser_name: &ConstrainedIndividualNameString100) -> Result<RenderedLetter, TemplateError> {
+        let context = InviteTemplateContext::for_post_invite(invite, adviser_name);
+
+        match self.template_for(invite.content_reference(), TemplateVariant::for_letter(invite.format()))? {
+            InviteTemplate::Letter { body } => Ok(RenderedLetter {
+                salutation: context.salutation(),
+                body: body.render(&context),
+            }),
+            InviteTemplate::Email { .. } => Err(TemplateError::MissingTemplate(format!("{} is not a letter template", invite.content_reference().value()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::test_utils::shared::{due_date, reply_deadline, ReviewInformationFixture, ADVISER_NAME, JOINT_INDIVIDUALS_ELECTRONIC_CONTACT, MULTIPLE_TRUSTEES_POST_CONTACT, PRIMARY_DIRECTOR_ELECTRONIC_CONTACT};
+    use super::super::{
+        authorised_contacts::AuthorisedContact,
+        invites::{create_authorised_contact_invites, AnnualReviewInvite},
+    };
+    use super::*;
+
+    fn adviser_name() -> ConstrainedIndividualNameString100 {
+        ConstrainedIndividualNameString100::try_from(ADVISER_NAME.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_bundled_templates_load_for_every_contact_variant() {
+        let templates = InviteTemplates::bundled().unwrap();
+
+        for (content_reference, _) in BUNDLED_TEMPLATES {
+            assert!(templates.template(content_reference).is_ok(), "{}", content_reference.value());
+        }
+        for (content_reference, _) in BUNDLED_LARGE_PRINT_TEMPLATES {
+            assert!(templates.template_for(content_reference, TemplateVariant::large_print(Language::English)).is_ok(), "{}", content_reference.value());
+        }
+    }
+
+    #[test]
+    fn test_every_bundled_template_has_a_welsh_translation() {
+        let templates = InviteTemplates::bundled().unwrap();
+
+        for (content_reference, _) in BUNDLED_TEMPLATES {
+            assert!(templates.template_for(content_reference, TemplateVariant::standard(Language::Welsh)).is_ok(), "{}", content_reference.value());
+        }
+        for (content_reference, _) in BUNDLED_LARGE_PRINT_TEMPLATES {
+            assert!(templates.template_for(content_reference, TemplateVariant::large_print(Language::Welsh)).is_ok(), "{}", content_reference.value());
+        }
+    }
+
+    #[test]
+    fn test_render_attorney_email() {
+        let templates = InviteTemplates::bundled().unwrap();
+        let attorney = AuthorisedContact::validate(serde_json::from_str(r#"{
+            "attorney_type": "PropertyAndFinancialAffairsLpa", "first_name": "Carol", "client_first_name": "Sarah",
+            "registration_reference": "7000-1234-5678", "registered_on": "12/03/2021",
+            "scope": "ActsForClient", "email_address": "carol@example.com"
+        }"#).unwrap()).unwrap();
+
+        let invites = create_authorised_contact_invites(&[attorney], &due_date(), reply_deadline());
+        let [AnnualReviewInvite::SendElectronicAnnualReviewInvite(invite)] = invites.as_slice() else {
+            panic!("Expected one electronic invite");
+        };
+        let email = templates.render_email(invite, &adviser_name()).unwrap();
+        assert_eq!(email.subject(), "Annual review for Sarah with Mark Ashworth");
+        assert!(email.text_body().starts_with("Dear Carol,"));
+        assert!(email.text_body().contains("the affairs of Sarah. Their annual review"));
+
+        assert_eq!(
+            InviteTemplate::load(InviteContentReference::SingleIndividualPostInvite, "--- body\nOn behalf of {{client_names}}"),
+            Err(TemplateError::UnavailablePlaceholder("{{client_names}} in single_individual_post_invite".to_string())),
+        );
+    }
+
+    #[test]
+    fn test_render_welsh_email_and_letter() {
+        let templates = InviteTemplates::bundled().unwrap();
+        let welsh = CommunicationFormat::new(Language::Welsh, AccessibleFormat::Standard);
+
+        let AnnualReviewInvite::SendElectronicAnnualReviewInvite(invite) = ReviewInformationFixture::new(PRIMARY_DIRECTOR_ELECTRONIC_CONTACT).invites()[0].clone().with_format(welsh) else {
+            panic!("Expected an electronic invite");
+        };
+        let email = templates.render_email(&invite, &adviser_name()).unwrap();
+        assert_eq!(email.subject(), "Adolygiad blynyddol May Holdings Ltd gyda Mark Ashworth");
+        assert!(email.text_body().starts_with("Annwyl James,"));
+        assert!(email.text_body().contains("yn ddyledus erbyn 30 Mehefin 2024"));
+        assert!(email.html_body().contains("<strong>31 Mai 2024</strong>"));
+
+        let AnnualReviewInvite::SendPostAnnualReviewInvite(invite) = ReviewInformationFixture::new(MULTIPLE_TRUSTEES_POST_CONTACT).invites()[0].clone().with_format(welsh) else {
+            panic!("Expected a post invite");
+        };
+        let letter = templates.render_letter(&invite, &adviser_name()).unwrap();
+        assert_eq!(letter.salutation(), "Alice a Bob");
+        assert!(letter.body().contains("Rydym wedi ysgrifennu at bob ymddiriedolwr."));
+    }
+
+    #[test]
+    fn test_misspelt_placeholder_fails_on_load() {
+        let source = "--- body\nYour review with {{adviser_nmae}}.";
+
+        assert_eq!(
+            InviteTemplate::load(InviteContentReference::SingleIndividualPostInvite, source),
+            Err(TemplateError::UnknownPlaceholder("{{adviser_nmae}}".to_string())),
+        );
+    }
+
+    #[test]
+    fn test_template_must_match_its_channel() {
+        let letter = "--- body\nYour review with {{adviser_name}}.";
+        assert!(matches!(
+            InviteTemplate::load(InviteContentReference::SingleIndividualElectronicInvite, letter),
+            Err(TemplateError::MissingSection(_)),
+        ));
+
+        let unclosed = "--- body\nYour review with {{adviser_name.";
+        assert!(matches!(
+            InviteTemplate::load(InviteContentReference::SingleIndividualPostInvite, unclosed),
+            Err(TemplateError::MalformedPlaceholder(_)),
+        ));
+    }
+
+    #[test]
+    fn test_organisation_name_unavailable_for_individuals() {
+        let source = "--- body\nThe review of {{organisation_name}}.";
+
+        assert!(matches!(
+            InviteTemplate::load(InviteContentReference::JointIndividualsPostInvite, source),
+            Err(TemplateError::UnavailablePlaceholder(_)),
+        ));
+        assert!(InviteTemplate::load(InviteContentReference::MultipleTrusteesPostInvite, source).is_ok());
+    }
+
+    #[test]
+    fn test_render_email() {
+        let templates = InviteTemplates::bundled().unwrap();
+        let AnnualReviewInvite::SendElectronicAnnualReviewInvite(invite) = &ReviewInformationFixture::new(PRIMARY_DIRECTOR_ELECTRONIC_CONTACT).invites()[0] else {
+            panic!("Expected an electronic invite");
+        };
+
+        let email = templates.render_email(invite, &adviser_name()).unwrap();
+        assert_eq!(email.subject(), "Annual review for May Holdings Ltd with Mark Ashworth");
+        assert!(email.text_body().starts_with("Dear James,\n\nIt is nearly time for the annual review of May Holdings Ltd"));
+        assert!(email.text_body().contains("due by 30 June 2024"));
+        assert!(email.text_body().contains("reply to this email by 31 May 2024"));
+        assert!(email.html_body().contains("<strong>30 June 2024</strong>"));
+        assert!(!email.text_body().contains("{{"));
+        assert_eq!(format!("{:?}", email), "RenderedEmail { .. }");
+    }
+
+    #[test]
+    fn test_render_joint_email_greets_both_clients() {
+        let templates = InviteTemplates::bundled().unwrap();
+
+        for invite in ReviewInformationFixture::new(JOINT_INDIVIDUALS_ELECTRONIC_CONTACT).invites() {
+            let AnnualReviewInvite::SendElectronicAnnualReviewInvite(invite) = invite else {
+                panic!("Expected an electronic invite");
+            };
+            let email = templates.render_email(&invite, &adviser_name()).unwrap();
+            assert_eq!(email.subject(), "Your annual review with Mark Ashworth");
+            assert!(email.text_body().contains("separately"));
+        }
+    }
+
+    #[test]
+    fn test_render_letter_for_trustees_sharing_an_address() {
+        let templates = InviteTemplates::bundled().unwrap();
+        let AnnualReviewInvite::SendPostAnnualReviewInvite(invite) = &ReviewInformationFixture::new(MULTIPLE_TRUSTEES_POST_CONTACT).invites()[0] else {
+            panic!("Expected a post invite");
+        };
+
+        let letter = templates.render_letter(invite, &adviser_name()).unwrap();
+        assert_eq!(letter.salutation(), "Alice and Bob");
+        assert!(letter.body().starts_with("It is nearly time for the annual review of May Family Trust with Mark Ashworth."));
+        assert_eq!(format!("{:?}", letter), "RenderedLetter { .. }");
+    }
+
+    #[test]
+    fn test_letter_uses_template_for_clients_format() {
+        let templates = InviteTemplates::bundled().unwrap();
+        let AnnualReviewInvite::SendPostAnnualReviewInvite(invite) = ReviewInformationFixture::new(MULTIPLE_TRUSTEES_POST_CONTACT).invites()[0].clone()
+            .with_format(CommunicationFormat::new(Language::English, AccessibleFormat::LargePrint)) else {
+            panic!("Expected a post invite");
+        };
+        let letter = templates.render_letter(&invite, &adviser_name()).unwrap();
+        assert!(letter.body().starts_with("The annual review of May Family Trust with Mark Ashworth is due by 30 June 2024."));
+
+        // Braille is transcribed from the standard letter.
+        let AnnualReviewInvite::SendPostAnnualReviewInvite(invite) = ReviewInformationFixture::new(MULTIPLE_TRUSTEES_POST_CONTACT).invites()[0].clone()
+            .with_format(CommunicationFormat::new(Language::English, AccessibleFormat::Braille)) else {
+            panic!("Expected a post invite");
+        };
+        let letter = templates.render_letter(&invite, &adviser_name()).unwrap();
+        assert!(letter.body().starts_with("It is nearly time for the annual review of May Family Trust"));
+    }
+
+    #[test]
+    fn test_missing_format_template_is_an_error() {
+        let welsh = CommunicationFormat::new(Language::Welsh, AccessibleFormat::Standard);
+        let AnnualReviewInvite::SendElectronicAnnualReviewInvite(invite) = ReviewInformationFixture::new(PRIMARY_DIRECTOR_ELECTRONIC_CONTACT).invites()[0].clone().with_format(welsh) else {
+            panic!("Expected an electronic invite");
+        };
+
+        let templates = InviteTemplates::load(BUNDLED_TEMPLATES.iter().copied()).unwrap();
+        assert_eq!(
+            templates.render_email(&invite, &adviser_name()),
+            Err(TemplateError::UnsupportedFormat("No Welsh template for primary_director_electronic_invite".to_string())),
+        );
+
+        let source = "--- subject\nAdolygiad blynyddol\n--- text\nAnnwyl {{salutation}}\n--- html\n<p>Annwyl {{salutation}}</p>";
+        let templates = templates.with_variant(TemplateVariant::standard(Language::Welsh), [(InviteContentReference::PrimaryDirectorElectronicInvite, source)]).unwrap();
+        assert_eq!(templates.render_email(&invite, &adviser_name()).unwrap().text_body(), "Annwyl James");
+
+        assert!(matches!(
+            templates.with_variant(TemplateVariant::large_print(Language::English), [(InviteContentReference::PrimaryDirectorElectronicInvite, source)]),
+            Err(TemplateError::UnsupportedFormat(_)),
+        ));
+    }
+
+    #[test]
+    fn test_html_values_are_escaped() {
+        let section = TemplateSection::parse("<p>{{organisation_name}}</p>").unwrap();
+        let context = InviteTemplateContext {
+            recipient_names: vec![],
+            organisation_name: Some(ConstainedTrustOrCompanyNameString200::try_from("Smith & Sons Ltd".to_string()).unwrap()),
+            adviser_name: adviser_name(),
+            annual_review_due_date: NaiveDate::from_ymd_opt(2024, 6, 30).unwrap(),
+            reply_deadline: NaiveDate::from_ymd_opt(2024, 5, 31).unwrap(),
+            language: Language::English,
+            client_names: vec![],
+        };
+
+        assert_eq!(section.render_html(&context), "<p>Smith &amp; Sons Ltd</p>");
+        assert_eq!(section.render(&context), "<p>Smith & Sons Ltd</p>");
+    }
+}
@@ -1,27 +1,80 @@
-use crate::{contexts::annual_review, simple_types::{ClientId, ConstainedTrustOrCompanyNameString200, ConstrainedIndividualNameString100, EmailAddress, PostalAddress}};
+use crate::redaction::Sensitive;
+use crate::simple_types::{ConstainedTrustOrCompanyNameString200, ConstrainedIndividualNameString100, EmailAddress, PhoneNumber, PostalAddress, SimpleTypeError};
+use super::contact_preferences::{ChannelDecision, ContactChannel, ContactPreferences, Language, UnvalidatedContactPreferences};
+use super::localisation::message;
+use super::vulnerability::{UnvalidatedVulnerabilityProfile, VulnerabilityProfile};
+use super::authorised_contacts::{validate_authorised_contacts, AuthorisedContact, UnvalidatedAuthorisedContact};
+use super::bereavement::{validate_deceased_clients, ClientStatus, DeceasedClient, UnvalidatedDeceasedClient};
 use serde::{Serialize, Deserialize};
-use chrono::{NaiveDateTime};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+
+/// What was wrong with the input, as a message key and its arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationDetail {
+    key: &'static str,
+    arguments: Vec<String>,
+}
+
+impl ValidationDetail {
+    pub fn new(key: &'static str, arguments: &[&str]) -> Self {
+        Self { key, arguments: arguments.iter().map(|argument| argument.to_string()).collect() }
+    }
+
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    pub fn arguments(&self) -> &Vec<String> {
+        &self.arguments
+    }
+
+    pub fn message(&self, language: Language) -> String {
+        let arguments: Vec<&str> = self.arguments.iter().map(String::as_str).collect();
+        message(language, self.key, &arguments)
+    }
+}
+
+impl From<SimpleTypeError> for ValidationDetail {
+    fn from(error: SimpleTypeError) -> Self {
+        Self::new(error.key(), &[])
+    }
+}
+
+/// A date which does not parse as DD/MM/YYYY.
+pub(crate) fn invalid_date_format(date: &str) -> ValidationError {
+    ValidationError::InvalidDate(ValidationDetail::new("validation.date_format", &[date]))
+}
 
 #[derive(Debug)]
 pub enum ValidationError {
-    InvalidName(String),
-    InvalidEmail(String),
-    InvalidAddress(String),
-    InvalidDate(String),
-    EmptyInput(String),
-    InvalidInput(String)
+    InvalidName(ValidationDetail),
+    InvalidEmail(ValidationDetail),
+    InvalidAddress(ValidationDetail),
+    InvalidPhoneNumber(ValidationDetail),
+    InvalidDate(ValidationDetail),
+    EmptyInput(ValidationDetail),
+    InvalidInput(ValidationDetail)
+}
+
+impl ValidationError {
+    /// The error in `language`, including its detail.
+    pub fn message(&self, language: Language) -> String {
+        let (key, detail) = match *self {
+            ValidationError::InvalidName(ref detail) => ("validation_error.invalid_name", detail),
+            ValidationError::InvalidEmail(ref detail) => ("validation_error.invalid_email", detail),
+            ValidationError::InvalidAddress(ref detail) => ("validation_error.invalid_address", detail),
+            ValidationError::InvalidPhoneNumber(ref detail) => ("validation_error.invalid_phone_number", detail),
+            ValidationError::InvalidDate(ref detail) => ("validation_error.invalid_date", detail),
+            ValidationError::EmptyInput(ref detail) => ("validation_error.empty_input", detail),
+            ValidationError::InvalidInput(ref detail) => ("validation_error.invalid_input", detail)
+        };
+        message(language, key, &[&detail.message(language)])
+    }
 }
 
 impl std::fmt::Display for ValidationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match *self {
-            ValidationError::InvalidName(ref desc) => write!(f, "Invalid name: {}", desc),
-            ValidationError::InvalidEmail(ref desc) => write!(f, "Invalid email: {}", desc),
-            ValidationError::InvalidAddress(ref desc) => write!(f, "Invalid address: {}", desc),
-            ValidationError::InvalidDate(ref desc) => write!(f, "Invalid date: {}", desc),
-            ValidationError::EmptyInput(ref desc) => write!(f, "Empty Input: {}", desc),
-            ValidationError::InvalidInput(ref desc) => write!(f, "Invalid Input: {}", desc)
-        }
+        write!(f, "{}", self.message(Language::English))
     }
 }
 
@@ -35,7 +88,15 @@ pub struct UnvalidatedAnnualReviewInformation {
     last_annual_review_due_date: String,
     adviser_name: String,
     administrator_email: String,
-    client_contact_information: ClientContactInformation
+    client_contact_information: ClientContactInformation,
+    #[serde(default)]
+    contact_preferences: Option<UnvalidatedContactPreferences>,
+    #[serde(default)]
+    vulnerability_profile: Option<UnvalidatedVulnerabilityProfile>,
+    #[serde(default)]
+    authorised_contacts: Vec<UnvalidatedAuthorisedContact>,
+    #[serde(default)]
+    deceased_clients: Vec<UnvalidatedDeceasedClient>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -44,61 +105,183 @@ pub enum ClientContactInformation {
     JointIndividualsElectronicContact(JointIndividualsElectronicContact),
     SingleIndividualElectronicContact(SingleIndividualElectronicContact),
     JointIndividualsPostContact(JointIndividualsPostContact),
-    //SingleIndividualPostContact(SingleIndividualPostContact),
-    //MultipleTrusteesElectronicContact(MultipleTrusteesElectronicContact),
-    //PrimaryTrusteeElectronicContact(PrimaryTrusteeElectronicContact),
-    //MultipleTrusteesPostContact(MultipleTrusteesPostContact),
-    //PrimaryTrusteePostContact(PrimaryTrusteePostContact),
-    //MultipleDirectorsElectronicContact(MultipleDirectorsElectronicContact),
-    //PrimaryDirectorElectronicContact(PrimaryDirectorElectronicContact),
-    //MultipleDirectorsPostContact(MultipleDirectorsPostContact),
-    //PrimaryDirectorPostContact(PrimaryDirectorPostContact), 
+    SingleIndividualPostContact(SingleIndividualPostContact),
+    MultipleTrusteesElectronicContact(MultipleTrusteesElectronicContact),
+    PrimaryTrusteeElectronicContact(PrimaryTrusteeElectronicContact),
+    MultipleTrusteesPostContact(MultipleTrusteesPostContact),
+    PrimaryTrusteePostContact(PrimaryTrusteePostContact),
+    MultipleDirectorsElectronicContact(MultipleDirectorsElectronicContact),
+    PrimaryDirectorElectronicContact(PrimaryDirectorElectronicContact),
+    MultipleDirectorsPostContact(MultipleDirectorsPostContact),
+    PrimaryDirectorPostContact(PrimaryDirectorPostContact),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JointIndividualsElectronicContact {
-    primary_contact_first_name: String,
-    individual_two_first_name: String,
-    primary_contact_email_address: String,
-    individual_two_email_address: String,
+    primary_contact_first_name: Sensitive<String>,
+    individual_two_first_name: Sensitive<String>,
+    primary_contact_email_address: Sensitive<String>,
+    individual_two_email_address: Sensitive<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SingleIndividualElectronicContact {
-    first_name: String,
-    email_address: String,
+    first_name: Sensitive<String>,
+    email_address: Sensitive<String>,
+    #[serde(default)]
+    postal_address: Option<UnvalidatedPostalAddress>,
+    #[serde(default)]
+    phone_number: Option<Sensitive<String>>,
+}
+
+// Address fields are flattened so post contacts keep the address alongside the names
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnvalidatedPostalAddress {
+    house_name: Sensitive<String>,
+    house_number: Sensitive<String>,
+    address_line_one: Sensitive<String>,
+    address_line_two: Sensitive<String>,
+    address_line_three: Sensitive<String>,
+    address_line_four: Sensitive<String>,
+    city: Sensitive<String>,
+    county: Sensitive<String>,
+    postcode: Sensitive<String>,
+    country: Sensitive<String>,
+}
+
+impl UnvalidatedPostalAddress {
+    pub fn validate(self) -> Result<PostalAddress, ValidationError> {
+        let optional = |field: String| if field.is_empty() { None } else { Some(field) };
+
+        PostalAddress::new(
+            optional(self.house_name.into_inner()),
+            optional(self.house_number.into_inner()),
+            self.address_line_one.into_inner(),
+            optional(self.address_line_two.into_inner()),
+            optional(self.address_line_three.into_inner()),
+            optional(self.address_line_four.into_inner()),
+            self.city.into_inner(),
+            optional(self.county.into_inner()),
+            self.postcode.into_inner(),
+            optional(self.country.into_inner()),
+        ).map_err(|e| ValidationError::InvalidAddress(e.into()))
+    }
+}
+
+fn validate_phone_number(phone_number: String) -> Result<PhoneNumber, ValidationError> {
+    PhoneNumber::try_from(phone_number).map_err(|e| ValidationError::InvalidPhoneNumber(e.into()))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct JointIndividualsPostContact {
-    primary_contact_first_name: String,
-    individual_two_first_name: String,
-    house_name: String,
-    house_number: String,
-    address_line_one: String,
-    address_line_two: String,
-    address_line_three: String,
-    address_line_four: String,
-    city: String,
-    county: String,
-    postcode: String,
-    country: String
+    primary_contact_first_name: Sensitive<String>,
+    individual_two_first_name: Sensitive<String>,
+    #[serde(flatten)]
+    postal_address: UnvalidatedPostalAddress,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SingleIndividualPostContact {
+    first_name: Sensitive<String>,
+    #[serde(flatten)]
+    postal_address: UnvalidatedPostalAddress,
+    #[serde(default)]
+    phone_number: Option<Sensitive<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrusteeElectronicContact {
+    first_name: Sensitive<String>,
+    email_address: Sensitive<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MultipleTrusteesElectronicContact {
+    trust_name: Sensitive<String>,
+    trustees: Vec<TrusteeElectronicContact>,
+}
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrimaryTrusteeElectronicContact {
+    trust_name: Sensitive<String>,
+    primary_trustee_first_name: Sensitive<String>,
+    primary_trustee_email_address: Sensitive<String>,
+}
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrusteePostContact {
+    first_name: Sensitive<String>,
+    #[serde(flatten)]
+    postal_address: UnvalidatedPostalAddress,
+}
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MultipleTrusteesPostContact {
+    trust_name: Sensitive<String>,
+    trustees: Vec<TrusteePostContact>,
+}
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrimaryTrusteePostContact {
+    trust_name: Sensitive<String>,
+    primary_trustee_first_name: Sensitive<String>,
+    #[serde(flatten)]
+    primary_trustee_postal_address: UnvalidatedPostalAddress,
+}
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DirectorContact {
+    first_name: Sensitive<String>,
+    email_address: Sensitive<String>,
+}
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MultipleDirectorsElectronicContact {
+    company_name: Sensitive<String>,
+    directors: Vec<DirectorContact>,
+}
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrimaryDirectorElectronicContact {
+    company_name: Sensitive<String>,
+    primary_director_first_name: Sensitive<String>,
+    primary_director_email_address: Sensitive<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DirectorPostContact {
+    first_name: Sensitive<String>,
+    #[serde(flatten)]
+    postal_address: UnvalidatedPostalAddress,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MultipleDirectorsPostContact {
+    company_name: Sensitive<String>,
+    directors: Vec<DirectorPostContact>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrimaryDirectorPostContact {
+    company_name: Sensitive<String>,
+    primary_director_first_name: Sensitive<String>,
+    #[serde(flatten)]
+    primary_director_postal_address: UnvalidatedPostalAddress,
+}
 
 
 
 #[derive(Debug, Serialize)]
-struct ValidatedAnnualReviewInformation {
+pub struct ValidatedAnnualReviewInformation {
     annual_review_due_date: ValidatedAnnualReviewDueDate,
-    client_contact_information: ValidatedClientContactInformation
+    adviser_name: ConstrainedIndividualNameString100,
+    administrator_email: EmailAddress,
+    client_contact_information: ValidatedClientContactInformation,
+    contact_preferences: ContactPreferences,
+    vulnerability_profile: Option<VulnerabilityProfile>,
+    authorised_contacts: Vec<AuthorisedContact>,
+    deceased_clients: Vec<DeceasedClient>,
 }
 
 impl ValidatedAnnualReviewInformation {
@@ -108,41 +291,121 @@ impl ValidatedAnnualReviewInformation {
         let annual_review_due_date = ValidatedAnnualReviewDueDate::validate(
             unvalidated_annual_review_information.annual_review_due_date,
             unvalidated_annual_review_information.last_annual_review_due_date
-        ).map_err(|e| ValidationError::InvalidDate(e.to_string()))?;
+        )?;
 
-        let client_contact_information = match unvalidated_annual_review_information.client_contact_information {
-            ClientContactInformation::JointIndividualsElectronicContact(information) => {
-                ValidatedClientContactInformation::validate_joint_indivduals_electronic_contact(information)
-            }
-            ClientContactInformation::SingleIndividualElectronicContact(information) => {
-                ValidatedClientContactInformation::validate_single_indivdual_electronic_contact(information)
-            }
-            ClientContactInformation::JointIndividualsPostContact(information) => {
-                ValidatedClientContactInformation::validated_joint_individuals_post_contact(information)
-            }
+        let adviser_name = ConstrainedIndividualNameString100::try_from(unvalidated_annual_review_information.adviser_name)
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
+
+        let administrator_email = EmailAddress::try_from(unvalidated_annual_review_information.administrator_email)
+            .map_err(|e| ValidationError::InvalidEmail(e.into()))?;
+
+        let client_contact_information = ValidatedClientContactInformation::validate(
+            unvalidated_annual_review_information.client_contact_information
+        )?;
+
+        // Clients without stated preferences are contacted the way their contact details imply
+        let contact_preferences = match unvalidated_annual_review_information.contact_preferences {
+            Some(contact_preferences) => ContactPreferences::validate(contact_preferences)?,
+            None => ContactPreferences::default_for(&client_contact_information),
         };
 
-        match client_contact_information {
-            Ok(client_contact_information) => { Ok(Self{annual_review_due_date, client_contact_information})}
-            Err(error) => {Err(error)}
-        }
+        let vulnerability_profile = unvalidated_annual_review_information.vulnerability_profile
+            .map(VulnerabilityProfile::validate)
+            .transpose()?;
+
+        let authorised_contacts = validate_authorised_contacts(
+            unvalidated_annual_review_information.authorised_contacts,
+            &client_contact_information,
+        )?;
+
+        let deceased_clients = validate_deceased_clients(
+            unvalidated_annual_review_information.deceased_clients,
+            &client_contact_information,
+        )?;
+
+        Ok(Self{
+            annual_review_due_date,
+            adviser_name,
+            administrator_email,
+            client_contact_information,
+            contact_preferences,
+            vulnerability_profile,
+            authorised_contacts,
+            deceased_clients,
+        })
+    }
+
+    pub fn annual_review_due_date(&self) -> &ValidatedAnnualReviewDueDate {
+        &self.annual_review_due_date
+    }
+
+    pub fn adviser_name(&self) -> &ConstrainedIndividualNameString100 {
+        &self.adviser_name
+    }
+
+    pub fn administrator_email(&self) -> &EmailAddress {
+        &self.administrator_email
+    }
+
+    pub fn client_contact_information(&self) -> &ValidatedClientContactInformation {
+        &self.client_contact_information
+    }
+
+    pub fn contact_preferences(&self) -> &ContactPreferences {
+        &self.contact_preferences
+    }
+
+    /// Present only for clients with a recorded vulnerability.
+    pub fn vulnerability_profile(&self) -> Option<&VulnerabilityProfile> {
+        self.vulnerability_profile.as_ref()
+    }
+
+    /// Attorneys and deputies who act for the client or are copied in on their review.
+    pub fn authorised_contacts(&self) -> &Vec<AuthorisedContact> {
+        &self.authorised_contacts
+    }
+
+    /// Clients who have died, with the executors of their estates.
+    pub fn deceased_clients(&self) -> &Vec<DeceasedClient> {
+        &self.deceased_clients
+    }
+
+    pub fn client_status(&self, first_name: &ConstrainedIndividualNameString100) -> ClientStatus {
+        self.deceased_clients
+            .iter()
+            .find(|deceased| deceased.first_name() == first_name)
+            .map_or(ClientStatus::Living, DeceasedClient::status)
+    }
+
+    /// Whether any client is still living. Trustees and directors are contacts
+    /// of the organisation that is the client, so it always is.
+    pub fn has_living_client(&self) -> bool {
+        self.client_contact_information.electronic_recipients().into_iter().map(|(name, _)| name)
+            .chain(self.client_contact_information.postal_recipients().into_iter().map(|(name, _)| name))
+            .any(|name| self.client_status(name) == ClientStatus::Living)
+    }
+
+    /// The channel to contact the client on `date`, from their preferences and contact details.
+    pub fn choose_channel(&self, date: NaiveDate) -> ChannelDecision {
+        self.contact_preferences.choose_channel(&self.client_contact_information.available_channels(), date)
     }
 }
 
 
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct ValidatedAnnualReviewDueDate(NaiveDateTime);
+pub struct ValidatedAnnualReviewDueDate(NaiveDateTime);
 
 impl ValidatedAnnualReviewDueDate {
 
     pub fn validate(
         unvalidated_date_string: String,
-        last_annual_review_date_string: String,
+        _last_annual_review_date_string: String,
     ) -> Result<Self, ValidationError> {
-        
-        let datetime = NaiveDateTime::parse_from_str(&unvalidated_date_string, "%d/%m/%Y")
-            .map_err(|e| ValidationError::InvalidDate(e.to_string()))?;
+
+        let datetime = NaiveDate::parse_from_str(&unvalidated_date_string, "%d/%m/%Y")
+            .map(|date| date.and_time(NaiveTime::MIN))
+            .map_err(|_| invalid_date_format(&unvalidated_date_string))?;
 
         // Check the date is not in the past
 
@@ -153,36 +416,269 @@ impl ValidatedAnnualReviewDueDate {
         Ok(Self(datetime))
 
     }
+
+    pub fn value(&self) -> &NaiveDateTime {
+        &self.0
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.0.date()
+    }
+
+    pub fn tax_year(&self) -> TaxYear {
+        TaxYear::containing(self.date())
+    }
 }
 
+/// A UK tax year, running from 6 April to 5 April, identified by the year it starts in.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TaxYear(i32);
 
+impl TaxYear {
+    pub fn containing(date: NaiveDate) -> Self {
+        if (date.month(), date.day()) >= (4, 6) {
+            Self(date.year())
+        } else {
+            Self(date.year() - 1)
+        }
+    }
 
-#[derive(Debug, Serialize)]
-enum ValidatedClientContactInformation {
+    pub fn start_year(&self) -> i32 {
+        self.0
+    }
+
+    pub fn first_day(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.0, 4, 6).expect("6 April must exist")
+    }
+
+    pub fn last_day(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.0 + 1, 4, 5).expect("5 April must exist")
+    }
+}
+
+impl std::fmt::Display for TaxYear {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{:02}", self.0, (self.0 + 1) % 100)
+    }
+}
+
+
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub enum ValidatedClientContactInformation {
     ValidatedJointIndividualsElectronicContact(ValidatedJointIndividualsElectronicContact),
     ValidatedSingleIndividualElectronicContact(ValidatedSingleIndividualElectronicContact),
     ValidatedJointIndividualsPostContact(ValidatedJointIndividualsPostContact),
-    //SingleIndividualPostContact(SingleIndividualPostContact),
-    //MultipleTrusteesElectronicContact(MultipleTrusteesElectronicContact),
-    //PrimaryTrusteeElectronicContact(PrimaryTrusteeElectronicContact),
-    //MultipleTrusteesPostContact(MultipleTrusteesPostContact),
-    //PrimaryTrusteePostContact(PrimaryTrusteePostContact),
-    //MultipleDirectorsElectronicContact(MultipleDirectorsElectronicContact),
-    //PrimaryDirectorElectronicContact(PrimaryDirectorElectronicContact),
-    //MultipleDirectorsPostContact(MultipleDirectorsPostContact),
-    //PrimaryDirectorPostContact(PrimaryDirectorPostContact), 
+    ValidatedSingleIndividualPostContact(ValidatedSingleIndividualPostContact),
+    ValidatedMultipleTrusteesElectronicContact(ValidatedMultipleTrusteesElectronicContact),
+    ValidatedPrimaryTrusteeElectronicContact(ValidatedPrimaryTrusteeElectronicContact),
+    ValidatedMultipleTrusteesPostContact(ValidatedMultipleTrusteesPostContact),
+    ValidatedPrimaryTrusteePostContact(ValidatedPrimaryTrusteePostContact),
+    ValidatedMultipleDirectorsElectronicContact(ValidatedMultipleDirectorsElectronicContact),
+    ValidatedPrimaryDirectorElectronicContact(ValidatedPrimaryDirectorElectronicContact),
+    ValidatedMultipleDirectorsPostContact(ValidatedMultipleDirectorsPostContact),
+    ValidatedPrimaryDirectorPostContact(ValidatedPrimaryDirectorPostContact),
 }
 
 impl ValidatedClientContactInformation {
+    pub fn validate(
+        client_contact_information: ClientContactInformation
+    ) -> Result<Self, ValidationError> {
+        match client_contact_information {
+            ClientContactInformation::JointIndividualsElectronicContact(information) => {
+                Self::validate_joint_indivduals_electronic_contact(information)
+            }
+            ClientContactInformation::SingleIndividualElectronicContact(information) => {
+                Self::validate_single_indivdual_electronic_contact(information)
+            }
+            ClientContactInformation::JointIndividualsPostContact(information) => {
+                Self::validated_joint_individuals_post_contact(information)
+            }
+            ClientContactInformation::SingleIndividualPostContact(information) => {
+                Self::validate_single_individual_post_contact(information)
+            }
+            ClientContactInformation::MultipleTrusteesElectronicContact(information) => {
+                Self::validate_multiple_trustees_electronic_contact(information)
+            }
+            ClientContactInformation::PrimaryTrusteeElectronicContact(information) => {
+                Self::validate_primary_trustee_electronic_contact(information)
+            }
+            ClientContactInformation::MultipleTrusteesPostContact(information) => {
+                Self::validate_multiple_trustees_post_contact(information)
+            }
+            ClientContactInformation::PrimaryTrusteePostContact(information) => {
+                Self::validate_primary_trustee_post_contact(information)
+            }
+            ClientContactInformation::MultipleDirectorsElectronicContact(information) => {
+                Self::validate_multiple_directors_electronic_contact(information)
+            }
+            ClientContactInformation::PrimaryDirectorElectronicContact(information) => {
+                Self::validate_primary_director_electronic_contact(information)
+            }
+            ClientContactInformation::MultipleDirectorsPostContact(information) => {
+                Self::validate_multiple_directors_post_contact(information)
+            }
+            ClientContactInformation::PrimaryDirectorPostContact(information) => {
+                Self::validate_primary_director_post_contact(information)
+            }
+        }
+    }
+
+    /// Name and email address of each client reached electronically, in contact order.
+    /// Post contacts have no electronic recipients.
+    pub fn electronic_recipients(&self) -> Vec<(&ConstrainedIndividualNameString100, &EmailAddress)> {
+        match self {
+            Self::ValidatedJointIndividualsElectronicContact(contact) => vec![
+                (contact.primary_contact_first_name(), contact.primary_contact_email_address()),
+                (contact.individual_two_first_name(), contact.individual_two_email_address()),
+            ],
+            Self::ValidatedSingleIndividualElectronicContact(contact) => vec![
+                (contact.first_name(), contact.email_address()),
+            ],
+            Self::ValidatedMultipleTrusteesElectronicContact(contact) => contact
+                .trustees()
+                .iter()
+                .map(|trustee| (trustee.first_name(), trustee.email_address()))
+                .collect(),
+            Self::ValidatedPrimaryTrusteeElectronicContact(contact) => vec![
+                (contact.primary_trustee_first_name(), contact.primary_trustee_email_address()),
+            ],
+            Self::ValidatedMultipleDirectorsElectronicContact(contact) => contact
+                .directors()
+                .iter()
+                .map(|director| (director.first_name(), director.email_address()))
+                .collect(),
+            Self::ValidatedPrimaryDirectorElectronicContact(contact) => vec![
+                (contact.primary_director_first_name(), contact.primary_director_email_address()),
+            ],
+            Self::ValidatedJointIndividualsPostContact(_)
+            | Self::ValidatedSingleIndividualPostContact(_)
+            | Self::ValidatedMultipleTrusteesPostContact(_)
+            | Self::ValidatedPrimaryTrusteePostContact(_)
+            | Self::ValidatedMultipleDirectorsPostContact(_)
+            | Self::ValidatedPrimaryDirectorPostContact(_) => vec![],
+        }
+    }
+
+    /// Name and postal address of each client reached by post, in contact order.
+    /// Electronic contacts have no postal recipients.
+    pub fn postal_recipients(&self) -> Vec<(&ConstrainedIndividualNameString100, &PostalAddress)> {
+        match self {
+            Self::ValidatedJointIndividualsPostContact(contact) => vec![
+                (contact.primary_contact_first_name(), contact.postal_address()),
+                (contact.individual_two_first_name(), contact.postal_address()),
+            ],
+            Self::ValidatedSingleIndividualPostContact(contact) => vec![
+                (contact.contact_first_name(), contact.postal_address()),
+            ],
+            Self::ValidatedMultipleTrusteesPostContact(contact) => contact
+                .trustees()
+                .iter()
+                .map(|trustee| (trustee.first_name(), trustee.postal_address()))
+                .collect(),
+            Self::ValidatedPrimaryTrusteePostContact(contact) => vec![
+                (contact.primary_trustee_first_name(), contact.primary_trustee_postal_address()),
+            ],
+            Self::ValidatedMultipleDirectorsPostContact(contact) => contact
+                .directors()
+                .iter()
+                .map(|director| (director.first_name(), director.postal_address()))
+                .collect(),
+            Self::ValidatedPrimaryDirectorPostContact(contact) => vec![
+                (contact.primary_director_first_name(), contact.primary_director_postal_address()),
+            ],
+            Self::ValidatedJointIndividualsElectronicContact(_)
+            | Self::ValidatedSingleIndividualElectronicContact(_)
+            | Self::ValidatedMultipleTrusteesElectronicContact(_)
+            | Self::ValidatedPrimaryTrusteeElectronicContact(_)
+            | Self::ValidatedMultipleDirectorsElectronicContact(_)
+            | Self::ValidatedPrimaryDirectorElectronicContact(_) => vec![],
+        }
+    }
+
+    /// The trust or company the contacts act for, if the client is not an individual.
+    pub fn organisation_name(&self) -> Option<&ConstainedTrustOrCompanyNameString200> {
+        match self {
+            Self::ValidatedMultipleTrusteesElectronicContact(contact) => Some(contact.trust_name()),
+            Self::ValidatedPrimaryTrusteeElectronicContact(contact) => Some(contact.trust_name()),
+            Self::ValidatedMultipleTrusteesPostContact(contact) => Some(contact.trust_name()),
+            Self::ValidatedPrimaryTrusteePostContact(contact) => Some(contact.trust_name()),
+            Self::ValidatedMultipleDirectorsElectronicContact(contact) => Some(contact.company_name()),
+            Self::ValidatedPrimaryDirectorElectronicContact(contact) => Some(contact.company_name()),
+            Self::ValidatedMultipleDirectorsPostContact(contact) => Some(contact.company_name()),
+            Self::ValidatedPrimaryDirectorPostContact(contact) => Some(contact.company_name()),
+            Self::ValidatedJointIndividualsElectronicContact(_)
+            | Self::ValidatedSingleIndividualElectronicContact(_)
+            | Self::ValidatedJointIndividualsPostContact(_)
+            | Self::ValidatedSingleIndividualPostContact(_) => None,
+        }
+    }
+
+    pub fn is_electronic(&self) -> bool {
+        !self.electronic_recipients().is_empty()
+    }
+
+    /// The postal address on file for the holder of an email address, used when
+    /// an electronic invite cannot be delivered.
+    pub fn postal_address_on_file(&self, email_address: &EmailAddress) -> Option<&PostalAddress> {
+        match self {
+            Self::ValidatedSingleIndividualElectronicContact(contact)
+                if contact.email_address().value().eq_ignore_ascii_case(email_address.value()) => contact.postal_address(),
+            _ => None,
+        }
+    }
+
+    /// The channels the contact details can reach the client on. Secure portal
+    /// accounts are opened with an email address, so any client with one can be
+    /// sent portal messages.
+    pub fn available_channels(&self) -> Vec<ContactChannel> {
+        let electronic_recipients = self.electronic_recipients();
+        let mut channels = Vec::new();
+
+        if !electronic_recipients.is_empty() {
+            channels.push(ContactChannel::Email);
+            channels.push(ContactChannel::SecurePortalMessage);
+        }
+        let has_postal_address = !self.postal_recipients().is_empty()
+            || electronic_recipients.iter().any(|(_, email_address)| self.postal_address_on_file(email_address).is_some());
+        if has_postal_address {
+            channels.push(ContactChannel::Post);
+        }
+        let phone_numbers = self.phone_numbers();
+        if !phone_numbers.is_empty() {
+            channels.push(ContactChannel::Phone);
+        }
+        if phone_numbers.iter().any(|(_, phone_number)| phone_number.is_mobile()) {
+            channels.push(ContactChannel::Sms);
+        }
+
+        channels
+    }
+
+    /// Name and phone number of each client with a number on file, in contact order.
+    pub fn phone_numbers(&self) -> Vec<(&ConstrainedIndividualNameString100, &PhoneNumber)> {
+        match self {
+            Self::ValidatedSingleIndividualElectronicContact(contact) => contact
+                .phone_number()
+                .map(|phone_number| vec![(contact.first_name(), phone_number)])
+                .unwrap_or_default(),
+            Self::ValidatedSingleIndividualPostContact(contact) => contact
+                .phone_number()
+                .map(|phone_number| vec![(contact.contact_first_name(), phone_number)])
+                .unwrap_or_default(),
+            _ => vec![],
+        }
+    }
+
     pub fn validate_joint_indivduals_electronic_contact(
         joint_individuals_electronic_contact: JointIndividualsElectronicContact
     ) -> Result<Self, ValidationError> {
-        
+
         let validated_joint_individuals_electronic_contact = ValidatedJointIndividualsElectronicContact::validate(
-            joint_individuals_electronic_contact.primary_contact_first_name, 
-            joint_individuals_electronic_contact.individual_two_first_name, 
-            joint_individuals_electronic_contact.primary_contact_email_address, 
-            joint_individuals_electronic_contact.individual_two_email_address
+            joint_individuals_electronic_contact.primary_contact_first_name.into_inner(),
+            joint_individuals_electronic_contact.individual_two_first_name.into_inner(),
+            joint_individuals_electronic_contact.primary_contact_email_address.into_inner(),
+            joint_individuals_electronic_contact.individual_two_email_address.into_inner()
         );
 
         match validated_joint_individuals_electronic_contact {
@@ -197,9 +693,15 @@ impl ValidatedClientContactInformation {
     ) -> Result<Self, ValidationError> {
 
         let validated_single_individual_electronic_contact = ValidatedSingleIndividualElectronicContact::validate(
-            single_individual_electronic_contact.first_name, 
-            single_individual_electronic_contact.email_address
-        );
+            single_individual_electronic_contact.first_name.into_inner(),
+            single_individual_electronic_contact.email_address.into_inner()
+        ).and_then(|contact| match single_individual_electronic_contact.postal_address {
+            Some(postal_address) => Ok(contact.with_postal_address(postal_address.validate()?)),
+            None => Ok(contact),
+        }).and_then(|contact| match single_individual_electronic_contact.phone_number {
+            Some(phone_number) => Ok(contact.with_phone_number(validate_phone_number(phone_number.into_inner())?)),
+            None => Ok(contact),
+        });
 
         match validated_single_individual_electronic_contact {
             Ok(valid) => { Ok(Self::ValidatedSingleIndividualElectronicContact(valid))}
@@ -212,18 +714,9 @@ impl ValidatedClientContactInformation {
     ) -> Result<Self, ValidationError> {
 
         let validated_joint_individuals_post_contact = ValidatedJointIndividualsPostContact::validate(
-            joint_individuals_post_contact.primary_contact_first_name, 
-            joint_individuals_post_contact.individual_two_first_name, 
-            joint_individuals_post_contact.house_name,
-            joint_individuals_post_contact.house_number,
-            joint_individuals_post_contact.address_line_one,
-            joint_individuals_post_contact.address_line_two,
-            joint_individuals_post_contact.address_line_three,
-            joint_individuals_post_contact.address_line_four,
-            joint_individuals_post_contact.city,
-            joint_individuals_post_contact.county,
-            joint_individuals_post_contact.postcode,
-            joint_individuals_post_contact.country
+            joint_individuals_post_contact.primary_contact_first_name.into_inner(),
+            joint_individuals_post_contact.individual_two_first_name.into_inner(),
+            joint_individuals_post_contact.postal_address.validate()?
         );
 
         match validated_joint_individuals_post_contact {
@@ -231,13 +724,155 @@ impl ValidatedClientContactInformation {
             Err(error) => Err(error)
         }
     }
+
+    pub fn validate_single_individual_post_contact(
+        single_individual_post_contact: SingleIndividualPostContact
+    ) -> Result<Self, ValidationError> {
+
+        let mut validated_single_individual_post_contact = ValidatedSingleIndividualPostContact::validate(
+            single_individual_post_contact.first_name.into_inner(),
+            single_individual_post_contact.postal_address.validate()?
+        )?;
+        if let Some(phone_number) = single_individual_post_contact.phone_number {
+            validated_single_individual_post_contact = validated_single_individual_post_contact.with_phone_number(validate_phone_number(phone_number.into_inner())?);
+        }
+
+        Ok(Self::ValidatedSingleIndividualPostContact(validated_single_individual_post_contact))
+    }
+
+    pub fn validate_multiple_trustees_electronic_contact(
+        multiple_trustees_electronic_contact: MultipleTrusteesElectronicContact
+    ) -> Result<Self, ValidationError> {
+
+        let trustees = multiple_trustees_electronic_contact.trustees
+            .into_iter()
+            .map(|trustee| ValidatedTrusteeElectronicContact::validate(trustee.first_name.into_inner(), trustee.email_address.into_inner()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let validated_multiple_trustees_electronic_contact = ValidatedMultipleTrusteesElectronicContact::validate(
+            multiple_trustees_electronic_contact.trust_name.into_inner(),
+            trustees
+        )?;
+
+        Ok(Self::ValidatedMultipleTrusteesElectronicContact(validated_multiple_trustees_electronic_contact))
+    }
+
+    pub fn validate_primary_trustee_electronic_contact(
+        primary_trustee_electronic_contact: PrimaryTrusteeElectronicContact
+    ) -> Result<Self, ValidationError> {
+
+        let validated_primary_trustee_electronic_contact = ValidatedPrimaryTrusteeElectronicContact::validate(
+            primary_trustee_electronic_contact.trust_name.into_inner(),
+            primary_trustee_electronic_contact.primary_trustee_first_name.into_inner(),
+            primary_trustee_electronic_contact.primary_trustee_email_address.into_inner()
+        )?;
+
+        Ok(Self::ValidatedPrimaryTrusteeElectronicContact(validated_primary_trustee_electronic_contact))
+    }
+
+    pub fn validate_multiple_trustees_post_contact(
+        multiple_trustees_post_contact: MultipleTrusteesPostContact
+    ) -> Result<Self, ValidationError> {
+
+        let trustees = multiple_trustees_post_contact.trustees
+            .into_iter()
+            .map(|trustee| ValidatedTrusteePostContact::validate(trustee.first_name.into_inner(), trustee.postal_address.validate()?))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let validated_multiple_trustees_post_contact = ValidatedMultipleTrusteesPostContact::validate(
+            multiple_trustees_post_contact.trust_name.into_inner(),
+            trustees
+        )?;
+
+        Ok(Self::ValidatedMultipleTrusteesPostContact(validated_multiple_trustees_post_contact))
+    }
+
+    pub fn validate_primary_trustee_post_contact(
+        primary_trustee_post_contact: PrimaryTrusteePostContact
+    ) -> Result<Self, ValidationError> {
+
+        let validated_primary_trustee_post_contact = ValidatedPrimaryTrusteePostContact::validate(
+            primary_trustee_post_contact.trust_name.into_inner(),
+            primary_trustee_post_contact.primary_trustee_first_name.into_inner(),
+            primary_trustee_post_contact.primary_trustee_postal_address.validate()?
+        )?;
+
+        Ok(Self::ValidatedPrimaryTrusteePostContact(validated_primary_trustee_post_contact))
+    }
+
+    pub fn validate_multiple_directors_electronic_contact(
+        multiple_directors_electronic_contact: MultipleDirectorsElectronicContact
+    ) -> Result<Self, ValidationError> {
+
+        let directors = multiple_directors_electronic_contact.directors
+            .into_iter()
+            .map(|director| ValidatedDirectorContact::validate(director.first_name.into_inner(), director.email_address.into_inner()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let validated_multiple_directors_electronic_contact = ValidatedMultipleDirectorsElectronicContact::validate(
+            multiple_directors_electronic_contact.company_name.into_inner(),
+            directors
+        )?;
+
+        Ok(Self::ValidatedMultipleDirectorsElectronicContact(validated_multiple_directors_electronic_contact))
+    }
+
+    pub fn validate_primary_director_electronic_contact(
+        primary_director_electronic_contact: PrimaryDirectorElectronicContact
+    ) -> Result<Self, ValidationError> {
+
+        let validated_primary_director_electronic_contact = ValidatedPrimaryDirectorElectronicContact::validate(
+            primary_director_electronic_contact.company_name.into_inner(),
+            primary_director_electronic_contact.primary_director_first_name.into_inner(),
+            primary_director_electronic_contact.primary_director_email_address.into_inner()
+        )?;
+
+        Ok(Self::ValidatedPrimaryDirectorElectronicContact(validated_primary_director_electronic_contact))
+    }
+
+    pub fn validate_multiple_directors_post_contact(
+        multiple_directors_post_contact: MultipleDirectorsPostContact
+    ) -> Result<Self, ValidationError> {
+
+        let directors = multiple_directors_post_contact.directors
+            .into_iter()
+            .map(|director| ValidatedDirectorPostContact::validate(director.first_name.into_inner(), director.postal_address.validate()?))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let validated_multiple_directors_post_contact = ValidatedMultipleDirectorsPostContact::validate(
+            multiple_directors_post_contact.company_name.into_inner(),
+            directors
+        )?;
+
+        Ok(Self::ValidatedMultipleDirectorsPostContact(validated_multiple_directors_post_contact))
+    }
+
+    pub fn validate_primary_director_post_contact(
+        primary_director_post_contact: PrimaryDirectorPostContact
+    ) -> Result<Self, ValidationError> {
+
+        let validated_primary_director_post_contact = ValidatedPrimaryDirectorPostContact::validate(
+            primary_director_post_contact.company_name.into_inner(),
+            primary_director_post_contact.primary_director_first_name.into_inner(),
+            primary_director_post_contact.primary_director_postal_address.validate()?
+        )?;
+
+        Ok(Self::ValidatedPrimaryDirectorPostContact(validated_primary_director_post_contact))
+    }
 }
 
+// Names, email addresses, postal addresses and phone numbers of validated contacts are
+// encrypted when serialised, see `crate::field_encryption`.
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct ValidatedJointIndividualsElectronicContact {
+pub struct ValidatedJointIndividualsElectronicContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     primary_contact_first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     individual_two_first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     primary_contact_email_address: EmailAddress,
+    #[serde(with = "crate::field_encryption::encrypted")]
     individual_two_email_address: EmailAddress,
 }
 
@@ -249,13 +884,13 @@ impl ValidatedJointIndividualsElectronicContact {
         individual_two_email_address: String,
     ) -> Result<Self, ValidationError> {
         let primary_contact_first_name = ConstrainedIndividualNameString100::try_from(primary_contact_first_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
         let individual_two_first_name = ConstrainedIndividualNameString100::try_from(individual_two_first_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
         let primary_contact_email_address = EmailAddress::try_from(primary_contact_email_address)
-            .map_err(|e| ValidationError::InvalidEmail(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidEmail(e.into()))?;
         let individual_two_email_address = EmailAddress::try_from(individual_two_email_address)
-            .map_err(|e| ValidationError::InvalidEmail(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidEmail(e.into()))?;
 
         Ok(Self {
             primary_contact_first_name,
@@ -278,25 +913,42 @@ impl ValidatedJointIndividualsElectronicContact {
     }
 
     pub fn individual_two_email_address(&self) -> &EmailAddress {
-        &self.primary_contact_email_address
+        &self.individual_two_email_address
     }
 }
 
 // SingleIndividualElectronicContact
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct ValidatedSingleIndividualElectronicContact {
+pub struct ValidatedSingleIndividualElectronicContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     email_address: EmailAddress,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    postal_address: Option<PostalAddress>,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    phone_number: Option<PhoneNumber>,
 }
 
 impl ValidatedSingleIndividualElectronicContact {
     pub fn validate(first_name: String, email_address: String) -> Result<Self, ValidationError> {
         let first_name = ConstrainedIndividualNameString100::try_from(first_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
         let email_address = EmailAddress::try_from(email_address)
-            .map_err(|e| ValidationError::InvalidEmail(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidEmail(e.into()))?;
+
+        Ok(Self { first_name, email_address, postal_address: None, phone_number: None })
+    }
+
+    /// A postal address kept on file for when email cannot reach the client.
+    pub fn with_postal_address(mut self, postal_address: PostalAddress) -> Self {
+        self.postal_address = Some(postal_address);
+        self
+    }
 
-        Ok(Self { first_name, email_address })
+    pub fn with_phone_number(mut self, phone_number: PhoneNumber) -> Self {
+        self.phone_number = Some(phone_number);
+        self
     }
 
     pub fn first_name(&self) -> &ConstrainedIndividualNameString100 {
@@ -306,12 +958,23 @@ impl ValidatedSingleIndividualElectronicContact {
     pub fn email_address(&self) -> &EmailAddress {
         &self.email_address
     }
+
+    pub fn postal_address(&self) -> Option<&PostalAddress> {
+        self.postal_address.as_ref()
+    }
+
+    pub fn phone_number(&self) -> Option<&PhoneNumber> {
+        self.phone_number.as_ref()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct ValidatedJointIndividualsPostContact {
+pub struct ValidatedJointIndividualsPostContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     primary_contact_first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     individual_two_first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     postal_address: PostalAddress,
 }
 
@@ -319,33 +982,12 @@ impl ValidatedJointIndividualsPostContact {
     pub fn validate(
         primary_contact_first_name: String,
         individual_two_first_name: String,
-        house_name: String,
-        house_number: String,
-        address_line_one: String,
-        address_line_two: String,
-        address_line_three: String,
-        address_line_four: String,
-        city: String,
-        county: String,
-        postcode: String,
-        country: String,
+        postal_address: PostalAddress,
     ) -> Result<Self, ValidationError> {
         let primary_contact_first_name = ConstrainedIndividualNameString100::try_from(primary_contact_first_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
         let individual_two_first_name = ConstrainedIndividualNameString100::try_from(individual_two_first_name)
-            .map_err(|e| ValidationError::InvalidEmail(e.to_string()))?;
-        let postal_address = PostalAddress::new(
-            if house_name.is_empty() { None } else { Some(house_name) },
-            if house_number.is_empty() { None } else { Some(house_number) },
-            address_line_one,
-            if address_line_two.is_empty() { None } else { Some(address_line_two) },
-            if address_line_three.is_empty() { None } else { Some(address_line_three)},
-            if address_line_four.is_empty() { None } else { Some(address_line_four)},
-            city,
-            if county.is_empty() { None } else { Some(county)},
-            postcode,
-            if country.is_empty() { None } else { Some(country)},
-        ).map_err(|e|ValidationError::InvalidInput(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
 
         Ok(Self {
             primary_contact_first_name,
@@ -368,25 +1010,35 @@ impl ValidatedJointIndividualsPostContact {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct SingleIndividualPostContact {
+pub struct ValidatedSingleIndividualPostContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     contact_first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     postal_address: PostalAddress,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    phone_number: Option<PhoneNumber>,
 }
 
-impl SingleIndividualPostContact {
+impl ValidatedSingleIndividualPostContact {
     pub fn validate(
         contact_first_name: String,
         postal_address: PostalAddress,
     ) -> Result<Self, ValidationError> {
         let contact_first_name = ConstrainedIndividualNameString100::try_from(contact_first_name)
-            .map_err(|e| ValidationError::InvalidAddress(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
 
         Ok(Self {
             contact_first_name,
             postal_address,
+            phone_number: None,
         })
     }
 
+    pub fn with_phone_number(mut self, phone_number: PhoneNumber) -> Self {
+        self.phone_number = Some(phone_number);
+        self
+    }
+
     pub fn contact_first_name(&self) -> &ConstrainedIndividualNameString100 {
         &self.contact_first_name
     }
@@ -394,23 +1046,29 @@ impl SingleIndividualPostContact {
     pub fn postal_address(&self) -> &PostalAddress {
         &self.postal_address
     }
+
+    pub fn phone_number(&self) -> Option<&PhoneNumber> {
+        self.phone_number.as_ref()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct TrusteeElectronicContact {
+pub struct ValidatedTrusteeElectronicContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     email_address: EmailAddress,
 }
 
-impl TrusteeElectronicContact {
+impl ValidatedTrusteeElectronicContact {
     pub fn validate(
         first_name: String,
         email_address: String,
     ) -> Result<Self, ValidationError> {
         let first_name = ConstrainedIndividualNameString100::try_from(first_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
         let email_address = EmailAddress::try_from(email_address)
-            .map_err(|e| ValidationError::InvalidEmail(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidEmail(e.into()))?;
 
         Ok(Self {
             first_name,
@@ -425,33 +1083,38 @@ impl TrusteeElectronicContact {
     pub fn email_address(&self) -> &EmailAddress {
         &self.email_address
     }
-    
+
 }
 
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct MultipleTrusteesElectronicContact {
+pub struct ValidatedMultipleTrusteesElectronicContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     trust_name: ConstainedTrustOrCompanyNameString200,
-    trustees: Vec<TrusteeElectronicContact>,
+    trustees: Vec<ValidatedTrusteeElectronicContact>,
 }
 
-impl MultipleTrusteesElectronicContact {
-    pub fn validate(trust_name: ConstainedTrustOrCompanyNameString200, trustees: Vec<TrusteeElectronicContact>) -> Result<Self, ValidationError> {
-        
+impl ValidatedMultipleTrusteesElectronicContact {
+    pub fn validate(trust_name: String, trustees: Vec<ValidatedTrusteeElectronicContact>) -> Result<Self, ValidationError> {
+
         let trust_name = ConstainedTrustOrCompanyNameString200::try_from(trust_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
-        
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
+
         if trustees.len() < 2 {
-            Err(ValidationError::EmptyInput("There must be at least two trustees.".to_string()))
+            Err(ValidationError::EmptyInput(ValidationDetail::new("validation.too_few_trustees", &[])))
         } else {
-            Ok(Self { 
+            Ok(Self {
                 trust_name,
-                trustees 
+                trustees
             })
         }
     }
 
-    pub fn trustees(&self) -> &Vec<TrusteeElectronicContact> {
+    pub fn trust_name(&self) -> &ConstainedTrustOrCompanyNameString200 {
+        &self.trust_name
+    }
+
+    pub fn trustees(&self) -> &Vec<ValidatedTrusteeElectronicContact> {
         &self.trustees
     }
 }
@@ -459,24 +1122,27 @@ impl MultipleTrusteesElectronicContact {
 
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct PrimaryTrusteeElectronicContact {
+pub struct ValidatedPrimaryTrusteeElectronicContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     trust_name: ConstainedTrustOrCompanyNameString200,
+    #[serde(with = "crate::field_encryption::encrypted")]
     primary_trustee_first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     primary_trustee_email_address: EmailAddress,
 }
 
-impl PrimaryTrusteeElectronicContact {
+impl ValidatedPrimaryTrusteeElectronicContact {
     pub fn validate(
         trust_name: String,
         primary_trustee_first_name: String,
         primary_trustee_email_address: String,
     ) -> Result<Self, ValidationError> {
         let trust_name = ConstainedTrustOrCompanyNameString200::try_from(trust_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
         let primary_trustee_first_name = ConstrainedIndividualNameString100::try_from(primary_trustee_first_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
         let primary_trustee_email_address = EmailAddress::try_from(primary_trustee_email_address)
-            .map_err(|e| ValidationError::InvalidEmail(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidEmail(e.into()))?;
 
         Ok(Self {
             trust_name,
@@ -485,6 +1151,10 @@ impl PrimaryTrusteeElectronicContact {
         })
     }
 
+    pub fn trust_name(&self) -> &ConstainedTrustOrCompanyNameString200 {
+        &self.trust_name
+    }
+
     pub fn primary_trustee_first_name(&self) -> &ConstrainedIndividualNameString100 {
         &self.primary_trustee_first_name
     }
@@ -493,73 +1163,91 @@ impl PrimaryTrusteeElectronicContact {
         &self.primary_trustee_email_address
     }
 }
-    
+
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct TrusteePostContact {
+pub struct ValidatedTrusteePostContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     postal_address: PostalAddress,
 }
 
-impl TrusteePostContact {
+impl ValidatedTrusteePostContact {
     pub fn validate(
         first_name: String,
         postal_address: PostalAddress,
     ) -> Result<Self, ValidationError> {
         let first_name = ConstrainedIndividualNameString100::try_from(first_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
 
         Ok(Self {
             first_name,
             postal_address,
         })
     }
+
+    pub fn first_name(&self) -> &ConstrainedIndividualNameString100 {
+        &self.first_name
+    }
+
+    pub fn postal_address(&self) -> &PostalAddress {
+        &self.postal_address
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct MultipleTrusteesPostContact {
+pub struct ValidatedMultipleTrusteesPostContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     trust_name: ConstainedTrustOrCompanyNameString200,
-    trustees: Vec<TrusteePostContact>,
+    trustees: Vec<ValidatedTrusteePostContact>,
 }
 
-impl MultipleTrusteesPostContact {
-    pub fn validate(trust_name: String, trustees: Vec<TrusteePostContact>) -> Result<Self, ValidationError> {
+impl ValidatedMultipleTrusteesPostContact {
+    pub fn validate(trust_name: String, trustees: Vec<ValidatedTrusteePostContact>) -> Result<Self, ValidationError> {
         let trust_name = ConstainedTrustOrCompanyNameString200::try_from(trust_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
-        
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
+
         if trustees.len() < 2 {
-            Err(ValidationError::EmptyInput("There must be at least two trustees.".to_string()))
+            Err(ValidationError::EmptyInput(ValidationDetail::new("validation.too_few_trustees", &[])))
         } else {
-            Ok(Self { 
+            Ok(Self {
                 trust_name,
-                trustees 
+                trustees
             })
         }
     }
 
-    pub fn trustees(&self) -> &Vec<TrusteePostContact> {
+    pub fn trust_name(&self) -> &ConstainedTrustOrCompanyNameString200 {
+        &self.trust_name
+    }
+
+    pub fn trustees(&self) -> &Vec<ValidatedTrusteePostContact> {
         &self.trustees
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct PrimaryTrusteePostContact {
+pub struct ValidatedPrimaryTrusteePostContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     trust_name: ConstainedTrustOrCompanyNameString200,
+    #[serde(with = "crate::field_encryption::encrypted")]
     primary_trustee_first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     primary_trustee_postal_address: PostalAddress,
 }
 
-impl PrimaryTrusteePostContact {
+impl ValidatedPrimaryTrusteePostContact {
     pub fn validate(
         trust_name: String,
         primary_trustee_first_name: String,
         primary_trustee_postal_address: PostalAddress,
     ) -> Result<Self, ValidationError> {
         let trust_name = ConstainedTrustOrCompanyNameString200::try_from(trust_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
-        
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
+
         let primary_trustee_first_name = ConstrainedIndividualNameString100::try_from(primary_trustee_first_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
 
         Ok(Self {
             trust_name,
@@ -568,6 +1256,10 @@ impl PrimaryTrusteePostContact {
         })
     }
 
+    pub fn trust_name(&self) -> &ConstainedTrustOrCompanyNameString200 {
+        &self.trust_name
+    }
+
     pub fn primary_trustee_first_name(&self) -> &ConstrainedIndividualNameString100 {
         &self.primary_trustee_first_name
     }
@@ -578,20 +1270,22 @@ impl PrimaryTrusteePostContact {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct DirectorContact {
+pub struct ValidatedDirectorContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     email_address: EmailAddress,
 }
 
-impl DirectorContact {
+impl ValidatedDirectorContact {
     pub fn validate(
         first_name: String,
         email_address: String,
     ) -> Result<Self, ValidationError> {
         let first_name = ConstrainedIndividualNameString100::try_from(first_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
         let email_address = EmailAddress::try_from(email_address)
-            .map_err(|e| ValidationError::InvalidEmail(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidEmail(e.into()))?;
 
         Ok(Self {
             first_name,
@@ -609,51 +1303,59 @@ impl DirectorContact {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct MultipleDirectorsElectronicContact {
+pub struct ValidatedMultipleDirectorsElectronicContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     company_name: ConstainedTrustOrCompanyNameString200,
-    directors: Vec<DirectorContact>,
+    directors: Vec<ValidatedDirectorContact>,
 }
 
-impl MultipleDirectorsElectronicContact {
-    pub fn validate(company_name: String, directors: Vec<DirectorContact>) -> Result<Self, ValidationError> {
+impl ValidatedMultipleDirectorsElectronicContact {
+    pub fn validate(company_name: String, directors: Vec<ValidatedDirectorContact>) -> Result<Self, ValidationError> {
 
         let company_name = ConstainedTrustOrCompanyNameString200::try_from(company_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
 
         if directors.len() < 2 {
-            Err(ValidationError::EmptyInput("There must be at least two directors.".to_string()))
+            Err(ValidationError::EmptyInput(ValidationDetail::new("validation.too_few_directors", &[])))
         } else {
-            Ok(Self { 
+            Ok(Self {
                 company_name,
                 directors }
             )
         }
     }
 
-    pub fn directors(&self) -> &Vec<DirectorContact> {
+    pub fn company_name(&self) -> &ConstainedTrustOrCompanyNameString200 {
+        &self.company_name
+    }
+
+    pub fn directors(&self) -> &Vec<ValidatedDirectorContact> {
         &self.directors
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct PrimaryDirectorElectronicContact {
+pub struct ValidatedPrimaryDirectorElectronicContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     company_name: ConstainedTrustOrCompanyNameString200,
+    #[serde(with = "crate::field_encryption::encrypted")]
     primary_director_first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     primary_director_email_address: EmailAddress,
 }
 
-impl PrimaryDirectorElectronicContact {
+impl ValidatedPrimaryDirectorElectronicContact {
     pub fn validate(
         company_name: String,
         primary_director_first_name: String,
         primary_director_email_address: String,
     ) -> Result<Self, ValidationError> {
         let company_name = ConstainedTrustOrCompanyNameString200::try_from(company_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
         let primary_director_first_name = ConstrainedIndividualNameString100::try_from(primary_director_first_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
         let primary_director_email_address = EmailAddress::try_from(primary_director_email_address)
-            .map_err(|e| ValidationError::InvalidEmail(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidEmail(e.into()))?;
 
         Ok(Self {
             company_name,
@@ -662,6 +1364,10 @@ impl PrimaryDirectorElectronicContact {
         })
     }
 
+    pub fn company_name(&self) -> &ConstainedTrustOrCompanyNameString200 {
+        &self.company_name
+    }
+
     pub fn primary_director_first_name(&self) -> &ConstrainedIndividualNameString100 {
         &self.primary_director_first_name
     }
@@ -672,18 +1378,20 @@ impl PrimaryDirectorElectronicContact {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct DirectorPostContact {
+pub struct ValidatedDirectorPostContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     postal_address: PostalAddress,
 }
 
-impl DirectorPostContact {
+impl ValidatedDirectorPostContact {
     pub fn validate(
         first_name: String,
         postal_address: PostalAddress,
     ) -> Result<Self, ValidationError> {
         let first_name = ConstrainedIndividualNameString100::try_from(first_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
 
         Ok(Self {
             first_name,
@@ -701,50 +1409,67 @@ impl DirectorPostContact {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct MultipleDirectorsPostContact {
+pub struct ValidatedMultipleDirectorsPostContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
     company_name: ConstainedTrustOrCompanyNameString200,
-    directors: Vec<DirectorPostContact>,
+    directors: Vec<ValidatedDirectorPostContact>,
 }
 
-impl MultipleDirectorsPostContact {
-    pub fn validate(company_name: String, directors: Vec<DirectorPostContact>) -> Result<Self, ValidationError> {
+impl ValidatedMultipleDirectorsPostContact {
+    pub fn validate(company_name: String, directors: Vec<ValidatedDirectorPostContact>) -> Result<Self, ValidationError> {
         let company_name = ConstainedTrustOrCompanyNameString200::try_from(company_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
         if directors.len() < 2 {
-            Err(ValidationError::EmptyInput("There must be at least two directors.".to_string()))
+            Err(ValidationError::EmptyInput(ValidationDetail::new("validation.too_few_directors", &[])))
         } else {
-            Ok(Self { 
+            Ok(Self {
                 company_name,
-                directors 
+                directors
             })
         }
     }
 
-    pub fn directors(&self) -> &Vec<DirectorPostContact> {
+    pub fn company_name(&self) -> &ConstainedTrustOrCompanyNameString200 {
+        &self.company_name
+    }
+
+    pub fn directors(&self) -> &Vec<ValidatedDirectorPostContact> {
         &self.directors
     }
 }
 
-#[derive(Debug, Serialize)]
-struct PrimaryDirectorPostContact {
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ValidatedPrimaryDirectorPostContact {
+    #[serde(with = "crate::field_encryption::encrypted")]
+    company_name: ConstainedTrustOrCompanyNameString200,
+    #[serde(with = "crate::field_encryption::encrypted")]
     primary_director_first_name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
     primary_director_postal_address: PostalAddress,
 }
 
-impl PrimaryDirectorPostContact {
+impl ValidatedPrimaryDirectorPostContact {
     pub fn validate(
+        company_name: String,
         primary_director_first_name: String,
         primary_director_postal_address: PostalAddress,
     ) -> Result<Self, ValidationError> {
+        let company_name = ConstainedTrustOrCompanyNameString200::try_from(company_name)
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
         let primary_director_first_name = ConstrainedIndividualNameString100::try_from(primary_director_first_name)
-            .map_err(|e| ValidationError::InvalidName(e.to_string()))?;
+            .map_err(|e| ValidationError::InvalidName(e.into()))?;
 
         Ok( Self {
+            company_name,
             primary_director_first_name,
             primary_director_postal_address,
         })
     }
 
+    pub fn company_name(&self) -> &ConstainedTrustOrCompanyNameString200 {
+        &self.company_name
+    }
+
     pub fn primary_director_first_name(&self) -> &ConstrainedIndividualNameString100 {
         &self.primary_director_first_name
     }
@@ -752,4 +1477,4 @@ impl PrimaryDirectorPostContact {
     pub fn primary_director_postal_address(&self) -> &PostalAddress {
         &self.primary_director_postal_address
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,348 @@
+//! Vulnerable client flags, and how they change the way a review is run.
+//!
+//! The FCA's guidance on the fair treatment of vulnerable customers (FG21/1)
+//! groups the drivers of vulnerability as health, life events, resilience and
+//! capability. A profile records an indicator for each driver that applies,
+//! with notes and the date the adviser must next review it. The notes are
+//! special category data, so they are masked in logs and encrypted at rest.
+//!
+//! For a vulnerable client the reply deadline is extended, the adviser is
+//! alerted, a trusted third party may be contacted when the client has named
+//! one, and a client with a health or capability indicator is phoned before
+//! any letter is sent.
+
+use chrono::{Days, NaiveDate};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    redaction::Sensitive,
+    simple_types::{ConstrainedIndividualNameString100, EmailAddress, PhoneNumber},
+};
+use super::types::{invalid_date_format, ValidationDetail, ValidationError};
+
+/// Days added to the reply deadline for a vulnerable client.
+pub const EXTRA_REPLY_DAYS: u64 = 14;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum VulnerabilityDriver {
+    /// Conditions or illnesses that affect the ability to carry out day to day tasks.
+    Health,
+    /// Life events such as bereavement, job loss or relationship breakdown.
+    LifeEvents,
+    /// Low ability to withstand financial or emotional shocks.
+    Resilience,
+    /// Low knowledge of financial matters or low confidence managing money.
+    Capability,
+}
+
+impl TryFrom<&str> for VulnerabilityDriver {
+    type Error = ValidationError;
+
+    fn try_from(driver: &str) -> Result<Self, Self::Error> {
+        match driver {
+            "Health" => Ok(VulnerabilityDriver::Health),
+            "LifeEvents" => Ok(VulnerabilityDriver::LifeEvents),
+            "Resilience" => Ok(VulnerabilityDriver::Resilience),
+            "Capability" => Ok(VulnerabilityDriver::Capability),
+            _ => Err(ValidationError::InvalidInput(ValidationDetail::new("validation.unknown_vulnerability_driver", &[driver]))),
+        }
+    }
+}
+
+// Unvalidated vulnerability profile received with the annual review information.
+// Review dates use the format "dd/mm/yyyy".
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnvalidatedVulnerabilityProfile {
+    indicators: Vec<UnvalidatedVulnerabilityIndicator>,
+    #[serde(default)]
+    trusted_third_party: Option<UnvalidatedTrustedThirdParty>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnvalidatedVulnerabilityIndicator {
+    driver: String,
+    notes: Sensitive<String>,
+    review_date: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnvalidatedTrustedThirdParty {
+    name: Sensitive<String>,
+    relationship: Sensitive<String>,
+    #[serde(default)]
+    phone_number: Option<Sensitive<String>>,
+    #[serde(default)]
+    email_address: Option<Sensitive<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VulnerabilityIndicator {
+    driver: VulnerabilityDriver,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    notes: Sensitive<String>,
+    review_date: NaiveDate,
+}
+
+impl VulnerabilityIndicator {
+    pub fn new(driver: VulnerabilityDriver, notes: &str, review_date: NaiveDate) -> Result<Self, ValidationError> {
+        if notes.trim().is_empty() {
+            return Err(ValidationError::EmptyInput(ValidationDetail::new("validation.empty_vulnerability_notes", &[])));
+        }
+
+        Ok(Self { driver, notes: Sensitive::new(notes.trim().to_string()), review_date })
+    }
+
+    pub fn driver(&self) -> VulnerabilityDriver {
+        self.driver
+    }
+
+    pub fn notes(&self) -> &String {
+        self.notes.expose()
+    }
+
+    /// The date by which the adviser must review the indicator.
+    pub fn review_date(&self) -> NaiveDate {
+        self.review_date
+    }
+
+    pub fn is_review_overdue(&self, on: NaiveDate) -> bool {
+        self.review_date < on
+    }
+}
+
+/// Someone the client has agreed may be contacted about their review, such
+/// as a family member who helps them with their affairs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrustedThirdParty {
+    #[serde(with = "crate::field_encryption::encrypted")]
+    name: ConstrainedIndividualNameString100,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    relationship: Sensitive<String>,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    phone_number: Option<PhoneNumber>,
+    #[serde(with = "crate::field_encryption::encrypted")]
+    email_address: Option<EmailAddress>,
+}
+
+impl TrustedThirdParty {
+    /// A phone number or an email address is needed to reach them.
+    pub fn new(
+        name: ConstrainedIndividualNameString100,
+        relationship: &str,
+        phone_number: Option<PhoneNumber>,
+        email_address: Option<EmailAddress>,
+    ) -> Result<Self, ValidationError> {
+        if relationship.trim().is_empty() {
+            return Err(ValidationError::EmptyInput(ValidationDetail::new("validation.trusted_third_party_relationship_needed", &[])));
+        }
+        if phone_number.is_none() && email_address.is_none() {
+            return Err(ValidationError::EmptyInput(ValidationDetail::new("validation.trusted_third_party_contact_needed", &[])));
+        }
+
+        Ok(Self { name, relationship: Sensitive::new(relationship.trim().to_string()), phone_number, email_address })
+    }
+
+    pub fn name(&self) -> &ConstrainedIndividualNameString100 {
+        &self.name
+    }
+
+    pub fn relationship(&self) -> &String {
+        self.relationship.expose()
+    }
+
+    pub fn phone_number(&self) -> Option<&PhoneNumber> {
+        self.phone_number.as_ref()
+    }
+
+    pub fn email_address(&self) -> Option<&EmailAddress> {
+        self.email_address.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VulnerabilityProfile {
+    indicators: Vec<VulnerabilityIndicator>,
+    trusted_third_party: Option<TrustedThirdParty>,
+}
+
+impl VulnerabilityProfile {
+    /// At most one indicator per driver.
+    pub fn new(indicators: Vec<VulnerabilityIndicator>) -> Result<Self, ValidationError> {
+        if indicators.is_empty() {
+            return Err(ValidationError::EmptyInput(ValidationDetail::new("validation.no_vulnerability_indicators", &[])));
+        }
+        if indicators.iter().enumerate().any(|(index, indicator)| indicators[..index].iter().any(|earlier| earlier.driver == indicator.driver)) {
+            return Err(ValidationError::InvalidInput(ValidationDetail::new("validation.repeated_vulnerability_drivers", &[])));
+        }
+
+        Ok(Self { indicators, trusted_third_party: None })
+    }
+
+    pub fn validate(unvalidated: UnvalidatedVulnerabilityProfile) -> Result<Self, ValidationError> {
+        let parse_date = |date: &str| NaiveDate::parse_from_str(date, "%d/%m/%Y").map_err(|_| invalid_date_format(date));
+
+        let indicators = unvalidated.indicators.into_iter().map(|indicator| {
+            VulnerabilityIndicator::new(VulnerabilityDriver::try_from(indicator.driver.as_str())?, indicator.notes.expose(), parse_date(&indicator.review_date)?)
+        }).collect::<Result<Vec<_>, _>>()?;
+        let mut profile = Self::new(indicators)?;
+
+        if let Some(third_party) = unvalidated.trusted_third_party {
+            let name = ConstrainedIndividualNameString100::try_from(third_party.name.into_inner())
+                .map_err(|e| ValidationError::InvalidName(e.into()))?;
+            let phone_number = third_party.phone_number
+                .map(|phone_number| PhoneNumber::try_from(phone_number.into_inner()))
+                .transpose()
+                .map_err(|e| ValidationError::InvalidPhoneNumber(e.into()))?;
+            let email_address = third_party.email_address
+                .map(|email_address| EmailAddress::try_from(email_address.into_inner()))
+                .transpose()
+                .map_err(|e| ValidationError::InvalidEmail(e.into()))?;
+            profile = profile.with_trusted_third_party(TrustedThirdParty::new(name, third_party.relationship.expose(), phone_number, email_address)?);
+        }
+
+        Ok(profile)
+    }
+
+    pub fn with_trusted_third_party(mut self, trusted_third_party: TrustedThirdParty) -> Self {
+        self.trusted_third_party = Some(trusted_third_party);
+        self
+    }
+
+    pub fn indicators(&self) -> &Vec<VulnerabilityIndicator> {
+        &self.indicators
+    }
+
+    pub fn trusted_third_party(&self) -> Option<&TrustedThirdParty> {
+        self.trusted_third_party.as_ref()
+    }
+
+    pub fn drivers(&self) -> Vec<VulnerabilityDriver> {
+        self.indicators.iter().map(|indicator| indicator.driver).collect()
+    }
+
+    pub fn has_driver(&self, driver: VulnerabilityDriver) -> bool {
+        self.indicators.iter().any(|indicator| indicator.driver == driver)
+    }
+
+    /// Health and capability needs are best understood by talking to the
+    /// client, so they are phoned before a letter is sent.
+    pub fn requires_phone_call_before_letter(&self) -> bool {
+        self.has_driver(VulnerabilityDriver::Health) || self.has_driver(VulnerabilityDriver::Capability)
+    }
+
+    /// The reply deadline with [`EXTRA_REPLY_DAYS`] added, but no later than `latest`.
+    pub fn extend_reply_deadline(&self, reply_deadline: NaiveDate, latest: NaiveDate) -> NaiveDate {
+        reply_deadline.checked_add_days(Days::new(EXTRA_REPLY_DAYS)).unwrap_or(latest).min(latest).max(reply_deadline)
+    }
+
+    /// The alert for the adviser before a review is run on `on`.
+    pub fn adviser_alert(&self, adviser_name: &ConstrainedIndividualNameString100, on: NaiveDate) -> VulnerableClientAlert {
+        VulnerableClientAlert {
+            adviser_name: adviser_name.clone(),
+            drivers: self.drivers(),
+            reviews_overdue: self.indicators.iter().filter(|indicator| indicator.is_review_overdue(on)).map(|indicator| indicator.driver).collect(),
+            trusted_third_party_named: self.trusted_third_party.is_some(),
+        }
+    }
+}
+
+/// Tells the adviser a vulnerable client's review is under way, and which
+/// indicators are past their review date.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VulnerableClientAlert {
+    adviser_name: ConstrainedIndividualNameString100,
+    drivers: Vec<VulnerabilityDriver>,
+    reviews_overdue: Vec<VulnerabilityDriver>,
+    trusted_third_party_named: bool,
+}
+
+impl VulnerableClientAlert {
+    pub fn adviser_name(&self) -> &ConstrainedIndividualNameString100 {
+        &self.adviser_name
+    }
+
+    pub fn drivers(&self) -> &Vec<VulnerabilityDriver> {
+        &self.drivers
+    }
+
+    pub fn reviews_overdue(&self) -> &Vec<VulnerabilityDriver> {
+        &self.reviews_overdue
+    }
+
+    pub fn trusted_third_party_named(&self) -> bool {
+        self.trusted_third_party_named
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn profile(json: &str) -> Result<VulnerabilityProfile, ValidationError> {
+        VulnerabilityProfile::validate(serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn test_validate_profile_with_trusted_third_party() {
+        let profile = profile(r#"{
+            "indicators": [
+                {"driver": "Health", "notes": " Hearing loss, prefers large print ", "review_date": "01/09/2024"},
+                {"driver": "LifeEvents", "notes": "Recently bereaved", "review_date": "01/04/2024"}
+            ],
+            "trusted_third_party": {"name": "Carol", "relationship": "Daughter", "phone_number": "07700 900456"}
+        }"#).unwrap();
+
+        assert_eq!(profile.drivers(), vec![VulnerabilityDriver::Health, VulnerabilityDriver::LifeEvents]);
+        assert_eq!(profile.indicators()[0].notes(), "Hearing loss, prefers large print");
+        assert!(profile.requires_phone_call_before_letter());
+        let third_party = profile.trusted_third_party().unwrap();
+        assert_eq!(third_party.name().value(), "Carol");
+        assert_eq!(third_party.phone_number().unwrap().value(), "+447700900456");
+        assert!(!format!("{:?}", profile).contains("bereaved"));
+    }
+
+    #[test]
+    fn test_invalid_profiles() {
+        assert!(matches!(profile(r#"{"indicators": []}"#), Err(ValidationError::EmptyInput(_))));
+        assert!(matches!(
+            profile(r#"{"indicators": [{"driver": "Mood", "notes": "Low", "review_date": "01/09/2024"}]}"#),
+            Err(ValidationError::InvalidInput(_)),
+        ));
+        assert!(matches!(
+            profile(r#"{"indicators": [
+                {"driver": "Resilience", "notes": "Redundancy", "review_date": "01/09/2024"},
+                {"driver": "Resilience", "notes": "Debt", "review_date": "01/09/2024"}
+            ]}"#),
+            Err(ValidationError::InvalidInput(_)),
+        ));
+        assert!(matches!(
+            profile(r#"{
+                "indicators": [{"driver": "Capability", "notes": "Low confidence online", "review_date": "01/09/2024"}],
+                "trusted_third_party": {"name": "Carol", "relationship": "Daughter"}
+            }"#),
+            Err(ValidationError::EmptyInput(_)),
+        ));
+    }
+
+    #[test]
+    fn test_adjustments() {
+        let resilience = VulnerabilityProfile::new(vec![
+            VulnerabilityIndicator::new(VulnerabilityDriver::Resilience, "Recently made redundant", date(4, 1)).unwrap(),
+        ]).unwrap();
+
+        assert!(!resilience.requires_phone_call_before_letter());
+        assert_eq!(resilience.extend_reply_deadline(date(5, 31), date(6, 30)), date(6, 14));
+        assert_eq!(resilience.extend_reply_deadline(date(6, 20), date(6, 30)), date(6, 30));
+
+        let adviser_name = ConstrainedIndividualNameString100::try_from("Mark Ashworth".to_string()).unwrap();
+        let alert = resilience.adviser_alert(&adviser_name, date(5, 1));
+        assert_eq!(alert.drivers(), &vec![VulnerabilityDriver::Resilience]);
+        assert_eq!(alert.reviews_overdue(), &vec![VulnerabilityDriver::Resilience]);
+        assert!(!alert.trusted_third_party_named());
+    }
+}
@@ -0,0 +1,48 @@
+/// ```text
+/// workflow "Data Subject Request" =
+///     input:
+///         ClientId
+///         RetentionPolicy
+///         RetentionHolds
+///     Output:
+///         SubjectAccessExport
+///         PersonalDataErased
+///         PersonalDataRetained
+///         ErasureRefused
+///
+/// Workflow: Data Subject Request
+/// Triggered by:
+///     A subject access or erasure request from a client (UK GDPR Articles 15 and 17)
+///
+/// Step 1
+/// do SubjectAccessExport
+///     gather every record and event held for the client from each PersonalDataSource
+///
+/// Step 2
+/// do Erasure
+///     if an active RetentionHold exists then:
+///         return ErasureRefused Event
+///     keep records whose RetentionClass period has not ended
+///         return PersonalDataRetained Event per record
+///     tombstone personal data in record stores
+///     shred the events' keys for encrypted event streams
+///     return PersonalDataErased Event per source
+///
+/// workflow "Purge Expired Records" =
+///     input:
+///         ClientIds
+///         RetentionPolicy
+///     Output:
+///         PurgePlan
+///         PersonalDataErased
+///
+/// Step 1
+/// do PurgePlan
+///     decide for each record whether its retention has ended
+///
+/// Step 2
+/// do Purge
+///     erase the records the plan marks for purging
+/// ```
+pub mod retention;
+pub mod subject_rights;
@@ -0,0 +1,386 @@
+//! Record retention under the FCA record keeping rules.
+//!
+//! Every record type is assigned a [`RetentionClass`], which sets how long
+//! records of that type are kept after they are made. Review and suitability
+//! records are kept for five years. Pension transfer, conversion and opt-out
+//! records are kept indefinitely. A record type the policy does not know is
+//! never purged until it has been classified.
+//!
+//! A [`RetentionHold`], such as a complaint or litigation hold, stops any of
+//! a client's records being purged while it is active.
+
+use std::collections::HashMap;
+
+use chrono::{Months, NaiveDate};
+use serde::{Serialize, Deserialize};
+
+use crate::simple_types::ClientId;
+use super::subject_rights::SubjectRecord;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RetentionPeriod {
+    Years(u32),
+    Indefinite,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RetentionClass {
+    /// Review meetings, outcomes and attempts to contact the client.
+    ReviewRecord,
+    /// Suitability reports and the advice behind them.
+    SuitabilityRecord,
+    /// Advice on pension transfers, conversions and opt-outs.
+    PensionTransferRecord,
+    /// Contact details and message delivery, with no regulatory period.
+    OperationalRecord,
+}
+
+impl RetentionClass {
+    pub fn period(&self) -> RetentionPeriod {
+        match self {
+            RetentionClass::ReviewRecord => RetentionPeriod::Years(5),
+            RetentionClass::SuitabilityRecord => RetentionPeriod::Years(5),
+            RetentionClass::PensionTransferRecord => RetentionPeriod::Indefinite,
+            RetentionClass::OperationalRecord => RetentionPeriod::Years(1),
+        }
+    }
+}
+
+/// A reason personal data must be kept, e.g. an open complaint or litigation.
+/// With no end date the hold lasts until it is lifted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RetentionHold {
+    reason: String,
+    until: Option<NaiveDate>,
+}
+
+impl RetentionHold {
+    pub fn new(reason: &str, until: Option<NaiveDate>) -> Self {
+        Self { reason: reason.to_string(), until }
+    }
+
+    pub fn reason(&self) -> &String {
+        &self.reason
+    }
+
+    pub fn until(&self) -> Option<NaiveDate> {
+        self.until
+    }
+
+    /// Holds run to the end of their `until` date.
+    pub fn is_active_on(&self, date: NaiveDate) -> bool {
+        match self.until {
+            Some(until) => date <= until,
+            None => true,
+        }
+    }
+}
+
+/// Port for the retention holds placed on clients.
+pub trait RetentionHolds {
+    fn holds_for(&self, client_id: &ClientId) -> Vec<RetentionHold>;
+
+    fn active_holds(&self, client_id: &ClientId, on: NaiveDate) -> Vec<RetentionHold> {
+        self.holds_for(client_id).into_iter().filter(|hold| hold.is_active_on(on)).collect()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryRetentionHolds {
+    holds: Vec<(ClientId, RetentionHold)>,
+}
+
+impl InMemoryRetentionHolds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_hold(mut self, client_id: &ClientId, hold: RetentionHold) -> Self {
+        self.holds.push((client_id.clone(), hold));
+        self
+    }
+}
+
+impl RetentionHolds for InMemoryRetentionHolds {
+    fn holds_for(&self, client_id: &ClientId) -> Vec<RetentionHold> {
+        self.holds.iter().filter(|(held_client_id, _)| held_client_id == client_id).map(|(_, hold)| hold.clone()).collect()
+    }
+}
+
+/// Whether a record may be purged on a given date.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum RetentionDecision {
+    Purge,
+    RetainUntil(NaiveDate),
+    RetainIndefinitely,
+    RetainUnderHold(String),
+    /// The record type has no retention class, so it is kept until it has one.
+    Unclassified,
+    /// The source could not erase the record on its own, for the reason given.
+    RetainedBySource(String),
+}
+
+impl RetentionDecision {
+    pub fn is_purge(&self) -> bool {
+        *self == RetentionDecision::Purge
+    }
+}
+
+/// The retention class of each record type.
+#[derive(Debug, Clone, Default)]
+pub struct RetentionPolicy {
+    classes: HashMap<String, RetentionClass>,
+}
+
+impl RetentionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The record types of the annual review context.
+    pub fn annual_review() -> Self {
+        let classes = [
+            ("annual_review_information", RetentionClass::ReviewRecord),
+            ("ReviewMeetingBooked", RetentionClass::ReviewRecord),
+            ("AlternativeTimeProposed", RetentionClass::ReviewRecord),
+            ("ReviewMeetingDeclined", RetentionClass::ReviewRecord),
+            ("ReviewMeetingDeclinedInTaxYear", RetentionClass::ReviewRecord),
+            ("CallBackRequested", RetentionClass::ReviewRecord),
+            ("NoResponse", RetentionClass::ReviewRecord),
+            ("ClientUncontactable", RetentionClass::ReviewRecord),
+            ("EscalatedToAdviser", RetentionClass::ReviewRecord),
+            ("vulnerability_profile", RetentionClass::ReviewRecord),
+            ("authorised_contacts", RetentionClass::ReviewRecord),
+            ("deceased_clients", RetentionClass::ReviewRecord),
+            ("suitability_report", RetentionClass::SuitabilityRecord),
+            ("pension_transfer_advice", RetentionClass::PensionTransferRecord),
+            ("contact_information", RetentionClass::OperationalRecord),
+            ("contact_preferences", RetentionClass::OperationalRecord),
+            ("InviteQueued", RetentionClass::OperationalRecord),
+            ("DeliveryStateChanged", RetentionClass::OperationalRecord),
+            ("DeliveryDeferred", RetentionClass::OperationalRecord),
+            ("FellBackToPost", RetentionClass::OperationalRecord),
+            ("LetterPrinted", RetentionClass::OperationalRecord),
+            ("LetterPosted", RetentionClass::OperationalRecord),
+            ("RemindersScheduled", RetentionClass::OperationalRecord),
+            ("RemindersStopped", RetentionClass::OperationalRecord),
+            ("RemindersCancelled", RetentionClass::OperationalRecord),
+            ("reminder_timer", RetentionClass::OperationalRecord),
+            ("outbound_invite", RetentionClass::OperationalRecord),
+        ];
+
+        classes.into_iter().fold(Self::new(), |policy, (record_type, class)| policy.with_record_type(record_type, class))
+    }
+
+    pub fn with_record_type(mut self, record_type: &str, class: RetentionClass) -> Self {
+        self.classes.insert(record_type.to_string(), class);
+        self
+    }
+
+    pub fn class_for(&self, record_type: &str) -> Option<RetentionClass> {
+        self.classes.get(record_type).copied()
+    }
+
+    /// The first day the record may be purged, or `None` if it is kept
+    /// indefinitely or is unclassified.
+    pub fn earliest_purge_date(&self, record: &SubjectRecord) -> Option<NaiveDate> {
+        match self.class_for(record.record_type())?.period() {
+            RetentionPeriod::Years(years) => record.recorded_at().date().checked_add_months(Months::new(years * 12)),
+            RetentionPeriod::Indefinite => None,
+        }
+    }
+
+    pub fn decide(&self, record: &SubjectRecord, active_holds: &[RetentionHold], on: NaiveDate) -> RetentionDecision {
+        if let Some(hold) = active_holds.first() {
+            return RetentionDecision::RetainUnderHold(hold.reason().clone());
+        }
+
+        let Some(class) = self.class_for(record.record_type()) else {
+            return RetentionDecision::Unclassified;
+        };
+        match (class.period(), self.earliest_purge_date(record)) {
+            (RetentionPeriod::Years(_), Some(purge_date)) if purge_date <= on => RetentionDecision::Purge,
+            (RetentionPeriod::Years(_), Some(purge_date)) => RetentionDecision::RetainUntil(purge_date),
+            _ => RetentionDecision::RetainIndefinitely,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PurgePlanEntry {
+    client_id: ClientId,
+    source: String,
+    record_type: String,
+    record_id: String,
+    class: Option<RetentionClass>,
+    earliest_purge_date: Option<NaiveDate>,
+    decision: RetentionDecision,
+}
+
+impl PurgePlanEntry {
+    pub fn client_id(&self) -> &ClientId {
+        &self.client_id
+    }
+
+    pub fn source(&self) -> &String {
+        &self.source
+    }
+
+    pub fn record_type(&self) -> &String {
+        &self.record_type
+    }
+
+    pub fn record_id(&self) -> &String {
+        &self.record_id
+    }
+
+    pub fn class(&self) -> Option<RetentionClass> {
+        self.class
+    }
+
+    pub fn earliest_purge_date(&self) -> Option<NaiveDate> {
+        self.earliest_purge_date
+    }
+
+    pub fn decision(&self) -> &RetentionDecision {
+        &self.decision
+    }
+}
+
+/// The retention decision for every record of the clients planned, for
+/// review before the purge is carried out.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PurgePlan {
+    planned_on: NaiveDate,
+    entries: Vec<PurgePlanEntry>,
+}
+
+impl PurgePlan {
+    pub fn new(planned_on: NaiveDate) -> Self {
+        Self { planned_on, entries: Vec::new() }
+    }
+
+    pub fn planned_on(&self) -> NaiveDate {
+        self.planned_on
+    }
+
+    pub fn entries(&self) -> &Vec<PurgePlanEntry> {
+        &self.entries
+    }
+
+    /// Adds the client's records from one source. Records already erased are left out.
+    pub fn add_records(&mut self, policy: &RetentionPolicy, client_id: &ClientId, records: &[SubjectRecord], active_holds: &[RetentionHold]) {
+        for record in records.iter().filter(|record| record.personal_data().is_some()) {
+            self.entries.push(PurgePlanEntry {
+                client_id: client_id.clone(),
+                source: record.source().clone(),
+                record_type: record.record_type().clone(),
+                record_id: record.record_id().clone(),
+                class: policy.class_for(record.record_type()),
+                earliest_purge_date: policy.earliest_purge_date(record),
+                decision: policy.decide(record, active_holds, self.planned_on),
+            });
+        }
+    }
+
+    pub fn to_purge(&self) -> impl Iterator<Item = &PurgePlanEntry> {
+        self.entries.iter().filter(|entry| entry.decision.is_purge())
+    }
+
+    pub fn to_retain(&self) -> impl Iterator<Item = &PurgePlanEntry> {
+        self.entries.iter().filter(|entry| !entry.decision.is_purge())
+    }
+
+    /// The ids of the client's records in `source` that may be purged.
+    pub fn record_ids_to_purge(&self, client_id: &ClientId, source: &str) -> Vec<String> {
+        self.to_purge()
+            .filter(|entry| &entry.client_id == client_id && entry.source == source)
+            .map(|entry| entry.record_id.clone())
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+    use serde_json::json;
+
+    use super::*;
+
+    const CLIENT_ID: &str = "a1b2c3d4-e5f6-4a5b-8c9d-0e1f2a3b4c5d";
+
+    fn client_id() -> ClientId {
+        ClientId::try_from(CLIENT_ID.to_string()).unwrap()
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn recorded_at(year: i32, month: u32, day: u32) -> NaiveDateTime {
+        date(year, month, day).and_hms_opt(9, 0, 0).unwrap()
+    }
+
+    fn record(record_type: &str, record_id: &str, recorded_at: NaiveDateTime) -> SubjectRecord {
+        SubjectRecord::new("annual_review.events", record_type, record_id, recorded_at, json!({}), Some(json!({ "name": "Siân" })))
+    }
+
+    #[test]
+    fn test_earliest_purge_dates() {
+        let policy = RetentionPolicy::annual_review();
+
+        assert_eq!(policy.earliest_purge_date(&record("ReviewMeetingBooked", "1", recorded_at(2024, 6, 3))), Some(date(2029, 6, 3)));
+        assert_eq!(policy.earliest_purge_date(&record("suitability_report", "2", recorded_at(2024, 2, 29))), Some(date(2029, 2, 28)));
+        assert_eq!(policy.earliest_purge_date(&record("InviteQueued", "3", recorded_at(2024, 6, 3))), Some(date(2025, 6, 3)));
+        assert_eq!(policy.earliest_purge_date(&record("pension_transfer_advice", "4", recorded_at(2024, 6, 3))), None);
+        assert_eq!(policy.earliest_purge_date(&record("ComplaintLogged", "5", recorded_at(2024, 6, 3))), None);
+    }
+
+    #[test]
+    fn test_retention_decisions() {
+        let policy = RetentionPolicy::annual_review();
+        let booked = record("ReviewMeetingBooked", "1", recorded_at(2024, 6, 3));
+
+        assert_eq!(policy.decide(&booked, &[], date(2029, 6, 2)), RetentionDecision::RetainUntil(date(2029, 6, 3)));
+        assert_eq!(policy.decide(&booked, &[], date(2029, 6, 3)), RetentionDecision::Purge);
+        assert_eq!(
+            policy.decide(&booked, &[RetentionHold::new("Complaint under investigation", None)], date(2030, 1, 1)),
+            RetentionDecision::RetainUnderHold("Complaint under investigation".to_string()),
+        );
+        assert_eq!(policy.decide(&record("pension_transfer_advice", "2", recorded_at(2010, 1, 4)), &[], date(2030, 1, 1)), RetentionDecision::RetainIndefinitely);
+        assert_eq!(policy.decide(&record("ComplaintLogged", "3", recorded_at(2010, 1, 4)), &[], date(2030, 1, 1)), RetentionDecision::Unclassified);
+    }
+
+    #[test]
+    fn test_purge_plan_splits_records_to_purge_and_retain() {
+        let policy = RetentionPolicy::annual_review();
+        let erased = SubjectRecord::new("annual_review.events", "InviteQueued", "4", recorded_at(2019, 6, 3), json!({}), None);
+        let records = vec![
+            record("InviteQueued", "1", recorded_at(2024, 6, 3)),
+            record("ReviewMeetingBooked", "2", recorded_at(2024, 6, 3)),
+            record("pension_transfer_advice", "3", recorded_at(2019, 6, 3)),
+            erased,
+        ];
+
+        let mut plan = PurgePlan::new(date(2026, 1, 1));
+        plan.add_records(&policy, &client_id(), &records, &[]);
+
+        assert_eq!(plan.entries().len(), 3);
+        assert_eq!(plan.record_ids_to_purge(&client_id(), "annual_review.events"), vec!["1"]);
+        let retained: Vec<(&str, &RetentionDecision)> = plan.to_retain().map(|entry| (entry.record_id().as_str(), entry.decision())).collect();
+        assert_eq!(retained, vec![("2", &RetentionDecision::RetainUntil(date(2029, 6, 3))), ("3", &RetentionDecision::RetainIndefinitely)]);
+        assert!(!plan.to_json().unwrap().contains("Siân"));
+    }
+
+    #[test]
+    fn test_retention_hold_ends_after_until_date() {
+        let hold = RetentionHold::new("Record keeping period", Some(date(2024, 6, 10)));
+
+        assert!(hold.is_active_on(date(2024, 6, 10)));
+        assert!(!hold.is_active_on(date(2024, 6, 11)));
+    }
+}
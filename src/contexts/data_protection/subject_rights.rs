@@ -0,0 +1,776 @@
+//! Subject access exports and erasure of a client's personal data.
+//!
+//! Each store holding data about clients is plugged in as a
+//! [`PersonalDataSource`]. Every record keeps its personal data apart from the
+//! non-personal facts, such as what happened and when, that must be kept for
+//! regulatory records after the personal data has gone.
+//!
+//! Record stores erase by tombstoning: the personal data is removed in place
+//! and the facts stay. Event streams are append only, so each event's personal
+//! data is sealed under a key of its own and erased by destroying the key
+//! (crypto-shredding), which also covers copies in backups. Keys are per event
+//! rather than per client so that events past retention can go while others
+//! of the same client are kept.
+//!
+//! Erasure leaves records still under retention (see [`super::retention`])
+//! and is refused while the client has an active [`RetentionHold`], e.g. a
+//! complaint under investigation. A source which cannot erase a record it was
+//! asked to holds it back and the record is reported as retained.
+//!
+//! The reminder timer table and the invite delivery tracker are sources through
+//! [`crate::contexts::annual_review::book_review_meeting::personal_data`].
+//! The audit log is not: it identifies invites and contacts by reference and
+//! its entries cannot change without breaking the hash chain.
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    field_encryption::{self, EncryptionKey, FieldEncryptionError, InMemoryKeyProvider},
+    simple_types::ClientId,
+};
+use super::retention::{PurgePlan, RetentionDecision, RetentionHold, RetentionHolds, RetentionPolicy};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataSubjectError {
+    SourceUnavailable(String),
+    Encryption(String),
+    PurgePlanNotDue(String),
+}
+
+impl std::fmt::Display for DataSubjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            DataSubjectError::SourceUnavailable(ref desc) => write!(f, "Personal data source unavailable: {}", desc),
+            DataSubjectError::Encryption(ref desc) => write!(f, "Personal data encryption failed: {}", desc),
+            DataSubjectError::PurgePlanNotDue(ref desc) => write!(f, "Purge plan not due: {}", desc),
+        }
+    }
+}
+
+impl std::error::Error for DataSubjectError {}
+
+/// One record or event held about a client.
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+pub struct SubjectRecord {
+    source: String,
+    record_type: String,
+    record_id: String,
+    recorded_at: NaiveDateTime,
+    facts: serde_json::Value,
+    personal_data: Option<serde_json::Value>,
+}
+
+impl SubjectRecord {
+    pub fn new(
+        source: &str,
+        record_type: &str,
+        record_id: &str,
+        recorded_at: NaiveDateTime,
+        facts: serde_json::Value,
+        personal_data: Option<serde_json::Value>,
+    ) -> Self {
+        Self {
+            source: source.to_string(),
+            record_type: record_type.to_string(),
+            record_id: record_id.to_string(),
+            recorded_at,
+            facts,
+            personal_data,
+        }
+    }
+
+    /// The name of the [`PersonalDataSource`] holding the record.
+    pub fn source(&self) -> &String {
+        &self.source
+    }
+
+    pub fn record_type(&self) -> &String {
+        &self.record_type
+    }
+
+    pub fn record_id(&self) -> &String {
+        &self.record_id
+    }
+
+    pub fn recorded_at(&self) -> NaiveDateTime {
+        self.recorded_at
+    }
+
+    /// Non-personal facts kept for regulatory records after erasure.
+    pub fn facts(&self) -> &serde_json::Value {
+        &self.facts
+    }
+
+    /// `None` once erased.
+    pub fn personal_data(&self) -> Option<&serde_json::Value> {
+        self.personal_data.as_ref()
+    }
+}
+
+impl std::fmt::Debug for SubjectRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubjectRecord")
+            .field("source", &self.source)
+            .field("record_type", &self.record_type)
+            .field("record_id", &self.record_id)
+            .field("erased", &self.personal_data.is_none())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Everything held about a client, in answer to a subject access request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SubjectAccessExport {
+    client_id: ClientId,
+    generated_at: NaiveDateTime,
+    records: Vec<SubjectRecord>,
+}
+
+impl SubjectAccessExport {
+    pub fn client_id(&self) -> &ClientId {
+        &self.client_id
+    }
+
+    pub fn generated_at(&self) -> NaiveDateTime {
+        self.generated_at
+    }
+
+    pub fn records(&self) -> &Vec<SubjectRecord> {
+        &self.records
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// Port for a store holding personal data about clients.
+pub trait PersonalDataSource {
+    /// The name records from this source are exported under.
+    fn name(&self) -> &str;
+
+    fn records_for(&self, client_id: &ClientId) -> Result<Vec<SubjectRecord>, DataSubjectError>;
+
+    /// Erases the personal data of the client's records listed, keeping the
+    /// facts.
+    fn erase(&mut self, client_id: &ClientId, record_ids: &[String], erased_at: NaiveDateTime) -> Result<Erasure, DataSubjectError>;
+}
+
+/// What a source erased, and the records it was asked to erase but kept.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Erasure {
+    erased: usize,
+    held_back: Vec<(String, String)>,
+}
+
+impl Erasure {
+    pub fn new(erased: usize) -> Self {
+        Self { erased, held_back: Vec::new() }
+    }
+
+    pub fn with_held_back(mut self, record_id: &str, reason: &str) -> Self {
+        self.held_back.push((record_id.to_string(), reason.to_string()));
+        self
+    }
+
+    pub fn erased(&self) -> usize {
+        self.erased
+    }
+
+    /// The record ids kept, each with the reason.
+    pub fn held_back(&self) -> &Vec<(String, String)> {
+        &self.held_back
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct StoredRecord {
+    client_id: ClientId,
+    record: SubjectRecord,
+    erased_at: Option<NaiveDateTime>,
+}
+
+/// Records held in memory, erased by tombstoning.
+#[derive(Debug, Clone)]
+pub struct InMemoryRecordStore {
+    name: String,
+    records: Vec<StoredRecord>,
+}
+
+impl InMemoryRecordStore {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), records: Vec::new() }
+    }
+
+    pub fn insert(
+        &mut self,
+        client_id: &ClientId,
+        record_type: &str,
+        record_id: &str,
+        recorded_at: NaiveDateTime,
+        facts: serde_json::Value,
+        personal_data: serde_json::Value,
+    ) {
+        let record = SubjectRecord::new(&self.name, record_type, record_id, recorded_at, facts, Some(personal_data));
+        self.records.push(StoredRecord { client_id: client_id.clone(), record, erased_at: None });
+    }
+
+    /// When the record's personal data was erased, if it has been.
+    pub fn erased_at(&self, record_id: &str) -> Option<NaiveDateTime> {
+        self.records.iter().find(|stored| stored.record.record_id == record_id).and_then(|stored| stored.erased_at)
+    }
+}
+
+impl PersonalDataSource for InMemoryRecordStore {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn records_for(&self, client_id: &ClientId) -> Result<Vec<SubjectRecord>, DataSubjectError> {
+        Ok(self.records.iter().filter(|stored| &stored.client_id == client_id).map(|stored| stored.record.clone()).collect())
+    }
+
+    fn erase(&mut self, client_id: &ClientId, record_ids: &[String], erased_at: NaiveDateTime) -> Result<Erasure, DataSubjectError> {
+        let mut erased = 0;
+        for stored in self.records.iter_mut().filter(|stored| {
+            &stored.client_id == client_id && stored.erased_at.is_none() && record_ids.contains(&stored.record.record_id)
+        }) {
+            stored.record.personal_data = None;
+            stored.erased_at = Some(erased_at);
+            erased += 1;
+        }
+        Ok(Erasure::new(erased))
+    }
+}
+
+/// Port for the keys client events are sealed under, e.g. in a key
+/// management service.
+pub trait ClientKeyStore {
+    /// A new key for one of the client's events.
+    fn new_key(&mut self, client_id: &ClientId) -> Result<EncryptionKey, DataSubjectError>;
+
+    /// The key with this id, or `None` if it was never created or has been shredded.
+    fn existing_key(&self, key_id: &str) -> Option<EncryptionKey>;
+
+    /// Destroys the key. Returns `false` if there was none.
+    fn shred(&mut self, key_id: &str) -> bool;
+}
+
+/// Keys held in memory. Every key gets a new id, so a key created after
+/// shredding never appears to open what the shredded key sealed.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryClientKeyStore {
+    keys: HashMap<String, EncryptionKey>,
+    keys_created: usize,
+}
+
+impl InMemoryClientKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ClientKeyStore for InMemoryClientKeyStore {
+    fn new_key(&mut self, client_id: &ClientId) -> Result<EncryptionKey, DataSubjectError> {
+        self.keys_created += 1;
+        let key = EncryptionKey::generate(&format!("client-key-{}-{}", client_id.value(), self.keys_created)).map_err(encryption_error)?;
+        self.keys.insert(key.id().to_string(), key.clone());
+        Ok(key)
+    }
+
+    fn existing_key(&self, key_id: &str) -> Option<EncryptionKey> {
+        self.keys.get(key_id).cloned()
+    }
+
+    fn shred(&mut self, key_id: &str) -> bool {
+        self.keys.remove(key_id).is_some()
+    }
+}
+
+/// An event in a client's stream. The facts are in the clear and the
+/// personal data is sealed under the event's own key.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClientEvent {
+    client_id: ClientId,
+    sequence: u64,
+    event_type: String,
+    occurred_at: NaiveDateTime,
+    facts: serde_json::Value,
+    sealed_personal_data: String,
+}
+
+impl ClientEvent {
+    pub fn client_id(&self) -> &ClientId {
+        &self.client_id
+    }
+
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn event_type(&self) -> &String {
+        &self.event_type
+    }
+
+    pub fn occurred_at(&self) -> NaiveDateTime {
+        self.occurred_at
+    }
+
+    pub fn facts(&self) -> &serde_json::Value {
+        &self.facts
+    }
+
+    pub fn sealed_personal_data(&self) -> &String {
+        &self.sealed_personal_data
+    }
+}
+
+/// An append only event stream held in memory, erased by crypto-shredding.
+#[derive(Debug, Clone)]
+pub struct EncryptedEventStream<K: ClientKeyStore> {
+    name: String,
+    keys: K,
+    events: Vec<ClientEvent>,
+}
+
+impl<K: ClientKeyStore> EncryptedEventStream<K> {
+    pub fn new(name: &str, keys: K) -> Self {
+        Self { name: name.to_string(), keys, events: Vec::new() }
+    }
+
+    /// Appends an event, sealing `personal_data` under a new key. Returns its sequence number.
+    pub fn append(
+        &mut self,
+        client_id: &ClientId,
+        event_type: &str,
+        occurred_at: NaiveDateTime,
+        facts: serde_json::Value,
+        personal_data: &serde_json::Value,
+    ) -> Result<u64, DataSubjectError> {
+        let key = self.keys.new_key(client_id)?;
+        let plaintext = serde_json::to_vec(personal_data).map_err(|e| DataSubjectError::Encryption(e.to_string()))?;
+        let sealed_personal_data = field_encryption::encrypt(&InMemoryKeyProvider::new(key), &plaintext).map_err(encryption_error)?;
+
+        let sequence = self.events.len() as u64 + 1;
+        self.events.push(ClientEvent {
+            client_id: client_id.clone(),
+            sequence,
+            event_type: event_type.to_string(),
+            occurred_at,
+            facts,
+            sealed_personal_data,
+        });
+        Ok(sequence)
+    }
+
+    pub fn events(&self) -> &Vec<ClientEvent> {
+        &self.events
+    }
+
+    /// The event's personal data, or `None` if its key has been shredded.
+    fn open(&self, event: &ClientEvent) -> Result<Option<serde_json::Value>, DataSubjectError> {
+        let Some(key) = field_encryption::key_id(&event.sealed_personal_data).and_then(|key_id| self.keys.existing_key(key_id)) else {
+            return Ok(None);
+        };
+
+        match field_encryption::decrypt(&InMemoryKeyProvider::new(key), &event.sealed_personal_data) {
+            Ok(plaintext) => serde_json::from_slice(&plaintext).map(Some).map_err(|e| DataSubjectError::Encryption(e.to_string())),
+            Err(FieldEncryptionError::UnknownKey(_)) => Ok(None),
+            Err(e) => Err(encryption_error(e)),
+        }
+    }
+}
+
+impl<K: ClientKeyStore> PersonalDataSource for EncryptedEventStream<K> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn records_for(&self, client_id: &ClientId) -> Result<Vec<SubjectRecord>, DataSubjectError> {
+        self.events
+            .iter()
+            .filter(|event| &event.client_id == client_id)
+            .map(|event| {
+                Ok(SubjectRecord::new(
+                    &self.name,
+                    &event.event_type,
+                    &event.sequence.to_string(),
+                    event.occurred_at,
+                    event.facts.clone(),
+                    self.open(event)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Shreds the keys of the events listed. Events whose key has already
+    /// gone are not counted.
+    fn erase(&mut self, client_id: &ClientId, record_ids: &[String], _erased_at: NaiveDateTime) -> Result<Erasure, DataSubjectError> {
+        let key_ids: Vec<String> = self
+            .events
+            .iter()
+            .filter(|event| &event.client_id == client_id && record_ids.contains(&event.sequence.to_string()))
+            .filter_map(|event| field_encryption::key_id(&event.sealed_personal_data).map(str::to_string))
+            .collect();
+
+        Ok(Erasure::new(key_ids.iter().filter(|key_id| self.keys.shred(key_id)).count()))
+    }
+}
+
+/// Events recorded on the client's data protection file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DataSubjectEvent {
+    SubjectAccessExported { client_id: ClientId, records: usize, exported_at: NaiveDateTime },
+    PersonalDataErased { client_id: ClientId, source: String, records: usize, erased_at: NaiveDateTime },
+    /// The record was kept because its retention period has not ended.
+    PersonalDataRetained { client_id: ClientId, source: String, record_id: String, decision: RetentionDecision, retained_at: NaiveDateTime },
+    /// Nothing was erased because of the holds listed.
+    ErasureRefused { client_id: ClientId, holds: Vec<RetentionHold>, refused_at: NaiveDateTime },
+}
+
+/// Answers subject access and erasure requests across every registered
+/// source, and purges records whose retention has ended.
+pub struct DataSubjectService<'a> {
+    sources: Vec<&'a mut dyn PersonalDataSource>,
+    retention_policy: &'a RetentionPolicy,
+    retention_holds: &'a dyn RetentionHolds,
+}
+
+impl<'a> DataSubjectService<'a> {
+    pub fn new(retention_policy: &'a RetentionPolicy, retention_holds: &'a dyn RetentionHolds) -> Self {
+        Self { sources: Vec::new(), retention_policy, retention_holds }
+    }
+
+    pub fn with_source(mut self, source: &'a mut dyn PersonalDataSource) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    pub fn subject_access_export(
+        &self,
+        client_id: &ClientId,
+        generated_at: NaiveDateTime,
+    ) -> Result<(SubjectAccessExport, DataSubjectEvent), DataSubjectError> {
+        let mut records = Vec::new();
+        for source in &self.sources {
+            let mut source_records = source.records_for(client_id)?;
+            source_records.sort_by_key(|record| record.recorded_at);
+            records.extend(source_records);
+        }
+
+        let event = DataSubjectEvent::SubjectAccessExported { client_id: client_id.clone(), records: records.len(), exported_at: generated_at };
+        Ok((SubjectAccessExport { client_id: client_id.clone(), generated_at, records }, event))
+    }
+
+    /// The retention decision on `planned_on` for every record held about the clients.
+    pub fn purge_plan(&self, client_ids: &[ClientId], planned_on: NaiveDate) -> Result<PurgePlan, DataSubjectError> {
+        let mut plan = PurgePlan::new(planned_on);
+        for client_id in client_ids {
+            let active_holds = self.retention_holds.active_holds(client_id, planned_on);
+            for source in &self.sources {
+                plan.add_records(self.retention_policy, client_id, &source.records_for(client_id)?, &active_holds);
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Erases the records the plan marks for purging. Holds are checked again,
+    /// so a hold placed since the plan was made still protects the client.
+    /// Records a source holds back are reported as retained.
+    pub fn purge(&mut self, plan: &PurgePlan, purged_at: NaiveDateTime) -> Result<Vec<DataSubjectEvent>, DataSubjectError> {
+        if plan.planned_on() > purged_at.date() {
+            return Err(DataSubjectError::PurgePlanNotDue(format!("Plan is for {}, it cannot be carried out on {}.", plan.planned_on(), purged_at.date())));
+        }
+
+        let mut client_ids: Vec<&ClientId> = Vec::new();
+        for entry in plan.to_purge() {
+            if !client_ids.contains(&entry.client_id()) {
+                client_ids.push(entry.client_id());
+            }
+        }
+
+        let mut events = Vec::new();
+        for client_id in client_ids {
+            let active_holds = self.retention_holds.active_holds(client_id, purged_at.date());
+            if !active_holds.is_empty() {
+                events.push(DataSubjectEvent::ErasureRefused { client_id: client_id.clone(), holds: active_holds, refused_at: purged_at });
+                continue;
+            }
+
+            for source in self.sources.iter_mut() {
+                let record_ids = plan.record_ids_to_purge(client_id, source.name());
+                if record_ids.is_empty() {
+                    continue;
+                }
+                let erasure = source.erase(client_id, &record_ids, purged_at)?;
+                if erasure.erased() > 0 {
+                    events.push(DataSubjectEvent::PersonalDataErased {
+                        client_id: client_id.clone(),
+                        source: source.name().to_string(),
+                        records: erasure.erased(),
+                        erased_at: purged_at,
+                    });
+                }
+                events.extend(erasure.held_back().iter().map(|(record_id, reason)| DataSubjectEvent::PersonalDataRetained {
+                    client_id: client_id.clone(),
+                    source: source.name().to_string(),
+                    record_id: record_id.clone(),
+                    decision: RetentionDecision::RetainedBySource(reason.clone()),
+                    retained_at: purged_at,
+                }));
+            }
+        }
+        Ok(events)
+    }
+
+    /// Erases the client's personal data from every source, except records
+    /// still under retention. Nothing is erased while a retention hold is active.
+    pub fn erase(&mut self, client_id: &ClientId, requested_at: NaiveDateTime) -> Result<Vec<DataSubjectEvent>, DataSubjectError> {
+        let active_holds = self.retention_holds.active_holds(client_id, requested_at.date());
+        if !active_holds.is_empty() {
+            return Ok(vec![DataSubjectEvent::ErasureRefused { client_id: client_id.clone(), holds: active_holds, refused_at: requested_at }]);
+        }
+
+        let plan = self.purge_plan(std::slice::from_ref(client_id), requested_at.date())?;
+        let mut events = self.purge(&plan, requested_at)?;
+        events.extend(plan.to_retain().map(|entry| DataSubjectEvent::PersonalDataRetained {
+            client_id: client_id.clone(),
+            source: entry.source().clone(),
+            record_id: entry.record_id().clone(),
+            decision: entry.decision().clone(),
+            retained_at: requested_at,
+        }));
+        Ok(events)
+    }
+}
+
+fn encryption_error(error: FieldEncryptionError) -> DataSubjectError {
+    DataSubjectError::Encryption(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::super::retention::InMemoryRetentionHolds;
+    use super::*;
+
+    const CLIENT_ID: &str = "a1b2c3d4-e5f6-4a5b-8c9d-0e1f2a3b4c5d";
+    const OTHER_CLIENT_ID: &str = "f0e1d2c3-b4a5-4968-8776-655443322110";
+
+    fn client_id(id: &str) -> ClientId {
+        ClientId::try_from(id.to_string()).unwrap()
+    }
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    /// After the one year operational retention, within the five year review retention.
+    fn next_year() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 7, 1).unwrap().and_hms_opt(12, 0, 0).unwrap()
+    }
+
+    /// After every retention period of the records in the fixtures.
+    fn years_later() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2029, 7, 1).unwrap().and_hms_opt(12, 0, 0).unwrap()
+    }
+
+    fn record_ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn record_store() -> InMemoryRecordStore {
+        let mut store = InMemoryRecordStore::new("annual_review.contacts");
+        store.insert(
+            &client_id(CLIENT_ID),
+            "contact_information",
+            "contact-1",
+            at(1, 9),
+            json!({ "contact_type": "SingleIndividualPostContact" }),
+            json!({ "name": "Siân", "postcode": "GU1 3UY" }),
+        );
+        store.insert(
+            &client_id(OTHER_CLIENT_ID),
+            "contact_information",
+            "contact-2",
+            at(1, 9),
+            json!({ "contact_type": "SingleIndividualElectronicContact" }),
+            json!({ "name": "James", "email_address": "james@example.co.uk" }),
+        );
+        store
+    }
+
+    fn event_stream() -> EncryptedEventStream<InMemoryClientKeyStore> {
+        let mut stream = EncryptedEventStream::new("annual_review.events", InMemoryClientKeyStore::new());
+        stream.append(
+            &client_id(CLIENT_ID),
+            "ReviewMeetingBooked",
+            at(3, 10),
+            json!({ "review_id": "r-1", "slot_start": "2024-06-20T10:00:00" }),
+            &json!({ "notes": "Siân prefers mornings" }),
+        ).unwrap();
+        stream.append(
+            &client_id(CLIENT_ID),
+            "InviteQueued",
+            at(2, 10),
+            json!({ "review_id": "r-1", "channel": "Sms" }),
+            &json!({ "phone_number": "+447700900123" }),
+        ).unwrap();
+        stream.append(
+            &client_id(OTHER_CLIENT_ID),
+            "InviteQueued",
+            at(2, 11),
+            json!({ "review_id": "r-2", "channel": "Email" }),
+            &json!({ "email_address": "james@example.co.uk" }),
+        ).unwrap();
+        stream
+    }
+
+    #[test]
+    fn test_subject_access_export_gathers_records_and_events() {
+        let (mut records, mut events) = (record_store(), event_stream());
+        let (policy, holds) = (RetentionPolicy::annual_review(), InMemoryRetentionHolds::new());
+        let service = DataSubjectService::new(&policy, &holds).with_source(&mut records).with_source(&mut events);
+
+        let (export, event) = service.subject_access_export(&client_id(CLIENT_ID), at(10, 12)).unwrap();
+
+        let summary: Vec<(&str, &str, &str)> = export
+            .records()
+            .iter()
+            .map(|record| (record.source().as_str(), record.record_type().as_str(), record.record_id().as_str()))
+            .collect();
+        assert_eq!(summary, vec![
+            ("annual_review.contacts", "contact_information", "contact-1"),
+            ("annual_review.events", "InviteQueued", "2"),
+            ("annual_review.events", "ReviewMeetingBooked", "1"),
+        ]);
+        assert_eq!(export.records()[1].personal_data(), Some(&json!({ "phone_number": "+447700900123" })));
+        assert_eq!(event, DataSubjectEvent::SubjectAccessExported { client_id: client_id(CLIENT_ID), records: 3, exported_at: at(10, 12) });
+
+        let exported: serde_json::Value = serde_json::from_str(&export.to_json().unwrap()).unwrap();
+        assert_eq!(exported["client_id"], CLIENT_ID);
+        assert_eq!(exported["records"][0]["personal_data"]["name"], "Siân");
+        assert!(!export.to_json().unwrap().contains("James"));
+    }
+
+    #[test]
+    fn test_erasure_tombstones_records_and_shreds_events_keeping_facts() {
+        let (mut records, mut events) = (record_store(), event_stream());
+        let (policy, holds) = (RetentionPolicy::annual_review(), InMemoryRetentionHolds::new());
+        let mut service = DataSubjectService::new(&policy, &holds).with_source(&mut records).with_source(&mut events);
+
+        let erasure_events = service.erase(&client_id(CLIENT_ID), years_later()).unwrap();
+        assert_eq!(erasure_events, vec![
+            DataSubjectEvent::PersonalDataErased { client_id: client_id(CLIENT_ID), source: "annual_review.contacts".to_string(), records: 1, erased_at: years_later() },
+            DataSubjectEvent::PersonalDataErased { client_id: client_id(CLIENT_ID), source: "annual_review.events".to_string(), records: 2, erased_at: years_later() },
+        ]);
+
+        let (export, _) = service.subject_access_export(&client_id(CLIENT_ID), years_later()).unwrap();
+        assert_eq!(export.records().len(), 3);
+        assert!(export.records().iter().all(|record| record.personal_data().is_none()));
+        assert_eq!(export.records()[2].facts(), &json!({ "review_id": "r-1", "slot_start": "2024-06-20T10:00:00" }));
+        assert!(!export.to_json().unwrap().contains("Siân"));
+
+        let (other_export, _) = service.subject_access_export(&client_id(OTHER_CLIENT_ID), years_later()).unwrap();
+        assert!(other_export.records().iter().all(|record| record.personal_data().is_some()));
+
+        drop(service);
+        assert_eq!(records.erased_at("contact-1"), Some(years_later()));
+        assert_eq!(events.events().len(), 3);
+    }
+
+    #[test]
+    fn test_erasure_keeps_records_still_under_retention() {
+        let (mut records, mut events) = (record_store(), event_stream());
+        let (policy, holds) = (RetentionPolicy::annual_review(), InMemoryRetentionHolds::new());
+        let mut service = DataSubjectService::new(&policy, &holds).with_source(&mut records).with_source(&mut events);
+
+        let erasure_events = service.erase(&client_id(CLIENT_ID), next_year()).unwrap();
+
+        assert_eq!(erasure_events, vec![
+            DataSubjectEvent::PersonalDataErased { client_id: client_id(CLIENT_ID), source: "annual_review.contacts".to_string(), records: 1, erased_at: next_year() },
+            DataSubjectEvent::PersonalDataErased { client_id: client_id(CLIENT_ID), source: "annual_review.events".to_string(), records: 1, erased_at: next_year() },
+            DataSubjectEvent::PersonalDataRetained {
+                client_id: client_id(CLIENT_ID),
+                source: "annual_review.events".to_string(),
+                record_id: "1".to_string(),
+                decision: RetentionDecision::RetainUntil(NaiveDate::from_ymd_opt(2029, 6, 3).unwrap()),
+                retained_at: next_year(),
+            },
+        ]);
+        let (export, _) = service.subject_access_export(&client_id(CLIENT_ID), next_year()).unwrap();
+        let readable: Vec<&String> = export.records().iter().filter(|record| record.personal_data().is_some()).map(|record| record.record_type()).collect();
+        assert_eq!(readable, vec!["ReviewMeetingBooked"]);
+    }
+
+    #[test]
+    fn test_purge_plan_is_carried_out_for_every_client() {
+        let (mut records, mut events) = (record_store(), event_stream());
+        let (policy, holds) = (RetentionPolicy::annual_review(), InMemoryRetentionHolds::new());
+        let mut service = DataSubjectService::new(&policy, &holds).with_source(&mut records).with_source(&mut events);
+        let client_ids = [client_id(CLIENT_ID), client_id(OTHER_CLIENT_ID)];
+
+        let plan = service.purge_plan(&client_ids, next_year().date()).unwrap();
+        let to_purge: Vec<(&str, &str)> = plan.to_purge().map(|entry| (entry.source().as_str(), entry.record_id().as_str())).collect();
+        assert_eq!(to_purge, vec![
+            ("annual_review.contacts", "contact-1"),
+            ("annual_review.events", "2"),
+            ("annual_review.contacts", "contact-2"),
+            ("annual_review.events", "3"),
+        ]);
+
+        assert!(matches!(service.purge(&plan, at(10, 12)), Err(DataSubjectError::PurgePlanNotDue(_))));
+
+        let purge_events = service.purge(&plan, next_year()).unwrap();
+        assert_eq!(purge_events[3], DataSubjectEvent::PersonalDataErased {
+            client_id: client_id(OTHER_CLIENT_ID),
+            source: "annual_review.events".to_string(),
+            records: 1,
+            erased_at: next_year(),
+        });
+        let (other_export, _) = service.subject_access_export(&client_id(OTHER_CLIENT_ID), next_year()).unwrap();
+        assert!(other_export.records().iter().all(|record| record.personal_data().is_none()));
+    }
+
+    #[test]
+    fn test_events_sealed_before_shredding_stay_unreadable() {
+        let mut events = event_stream();
+        events.erase(&client_id(CLIENT_ID), &record_ids(&["1", "2"]), at(10, 12)).unwrap();
+        events.append(
+            &client_id(CLIENT_ID),
+            "ComplaintLogged",
+            at(11, 9),
+            json!({ "complaint_id": "c-1" }),
+            &json!({ "summary": "Post was sent after erasure" }),
+        ).unwrap();
+
+        let records = events.records_for(&client_id(CLIENT_ID)).unwrap();
+        let readable: Vec<&String> = records.iter().filter(|record| record.personal_data().is_some()).map(|record| record.record_type()).collect();
+        assert_eq!(readable, vec!["ComplaintLogged"]);
+        assert_eq!(events.erase(&client_id(CLIENT_ID), &record_ids(&["1"]), at(12, 9)).unwrap(), Erasure::new(0));
+        assert_eq!(events.erase(&client_id(CLIENT_ID), &record_ids(&["4"]), at(12, 9)).unwrap(), Erasure::new(1));
+    }
+
+    #[test]
+    fn test_active_retention_hold_refuses_erasure() {
+        let (mut records, mut events) = (record_store(), event_stream());
+        let complaint = RetentionHold::new("Complaint under investigation", None);
+        let holds = InMemoryRetentionHolds::new()
+            .with_hold(&client_id(CLIENT_ID), RetentionHold::new("Advice file review", NaiveDate::from_ymd_opt(2024, 5, 31)))
+            .with_hold(&client_id(CLIENT_ID), complaint.clone());
+        let policy = RetentionPolicy::annual_review();
+        let mut service = DataSubjectService::new(&policy, &holds).with_source(&mut records).with_source(&mut events);
+
+        let erasure_events = service.erase(&client_id(CLIENT_ID), years_later()).unwrap();
+        assert_eq!(erasure_events, vec![DataSubjectEvent::ErasureRefused { client_id: client_id(CLIENT_ID), holds: vec![complaint.clone()], refused_at: years_later() }]);
+
+        let plan = service.purge_plan(&[client_id(CLIENT_ID)], years_later().date()).unwrap();
+        assert_eq!(plan.to_purge().count(), 0);
+        let (export, _) = service.subject_access_export(&client_id(CLIENT_ID), years_later()).unwrap();
+        assert!(export.records().iter().all(|record| record.personal_data().is_some()));
+    }
+}
@@ -1 +1,2 @@
-pub mod annual_review;
\ No newline at end of file
+pub mod annual_review;
+pub mod data_protection;
\ No newline at end of file
@@ -0,0 +1,87 @@
+//! Byte encodings shared across the crate.
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL_ALPHABET: &[u8; 64] = &url_safe(BASE64_ALPHABET);
+
+/// The base64url alphabet (RFC 4648 section 5), which swaps `+` and `/` for `-` and `_`.
+const fn url_safe(alphabet: &[u8; 64]) -> [u8; 64] {
+    let mut url_safe = *alphabet;
+    url_safe[62] = b'-';
+    url_safe[63] = b'_';
+    url_safe
+}
+
+/// Base64 encoding (RFC 4648) without line breaks.
+pub fn base64(bytes: &[u8]) -> String {
+    encode(bytes, BASE64_ALPHABET, true)
+}
+
+/// Base64url without padding, as used in URLs.
+pub fn base64_url(bytes: &[u8]) -> String {
+    encode(bytes, BASE64_URL_ALPHABET, false)
+}
+
+fn encode(bytes: &[u8], alphabet: &[u8; 64], padded: bool) -> String {
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let buffer = chunk.iter().enumerate().fold(0u32, |buffer, (index, byte)| buffer | (*byte as u32) << (16 - index * 8));
+        for index in 0..=chunk.len() {
+            output.push(alphabet[(buffer >> (18 - index * 6)) as usize & 0x3f] as char);
+        }
+        if padded {
+            output.extend(std::iter::repeat_n('=', 3 - chunk.len()));
+        }
+    }
+
+    output
+}
+
+pub fn base64_url_decode(encoded: &str) -> Option<Vec<u8>> {
+    if encoded.len() % 4 == 1 {
+        return None;
+    }
+
+    let values = encoded.bytes()
+        .map(|byte| BASE64_URL_ALPHABET.iter().position(|candidate| *candidate == byte).map(|value| value as u32))
+        .collect::<Option<Vec<u32>>>()?;
+
+    let mut output = Vec::with_capacity(values.len() * 3 / 4);
+    for chunk in values.chunks(4) {
+        let buffer = chunk.iter().enumerate().fold(0u32, |buffer, (index, value)| buffer | value << (18 - index * 6));
+        for index in 0..chunk.len() - 1 {
+            output.push((buffer >> (16 - index * 8)) as u8);
+        }
+    }
+
+    // Rejects unused trailing bits so each value has one encoding, otherwise a
+    // re-encoded signature would look like a different, unused token.
+    (base64_url(&output) == encoded).then_some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0xfb, 0xff]), "+/8=");
+    }
+
+    #[test]
+    fn test_base64_url_round_trip() {
+        for input in ["", "f", "fo", "foo", "foob", "fooba", "foobar", "\u{ff}\u{fe}?>"] {
+            let encoded = base64_url(input.as_bytes());
+            assert!(!encoded.contains(['+', '/', '=']));
+            assert_eq!(base64_url_decode(&encoded).unwrap(), input.as_bytes());
+        }
+        assert_eq!(base64_url(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_url_decode("Zm9vYg"), Some(b"foob".to_vec()));
+        assert_eq!(base64_url_decode("Zm9vYh"), None);
+    }
+}
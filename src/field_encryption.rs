@@ -0,0 +1,371 @@
+//! Encryption of personal data fields at rest.
+//!
+//! Fields tagged `#[serde(with = "crate::field_encryption::encrypted")]` are
+//! written as `enc:v1:{key id}:{base64url nonce and ciphertext}`, sealed with
+//! AES-256-GCM under the current key of a [`KeyProvider`]. The key id is also
+//! authenticated, so a ciphertext cannot be relabelled with another key.
+//!
+//! Serde has no way to pass the keys in, so they are put in scope for the
+//! current thread with [`with_key_provider`]. Tagged fields fail to serialise
+//! when no provider is in scope rather than falling back to plaintext.
+//!
+//! ```text
+//! let json = with_key_provider(&keys, || serde_json::to_string(&contact))?;
+//! ```
+//!
+//! When a key is retired, [`reencrypt_json`] rewrites stored documents under
+//! the current key without needing to know their types.
+
+use std::{cell::RefCell, rc::Rc};
+
+use aes_gcm::{aead::{Aead, AeadCore, OsRng, Payload}, Aes256Gcm, KeyInit, Nonce};
+use serde::{de::{DeserializeOwned, Error as _}, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::encoding::{base64_url, base64_url_decode};
+
+const CIPHERTEXT_PREFIX: &str = "enc:v1:";
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldEncryptionError {
+    InvalidKey(String),
+    UnknownKey(String),
+    NoKeyProvider(String),
+    Malformed(String),
+    DecryptionFailed(String),
+}
+
+impl std::fmt::Display for FieldEncryptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            FieldEncryptionError::InvalidKey(ref desc) => write!(f, "Invalid encryption key: {}", desc),
+            FieldEncryptionError::UnknownKey(ref desc) => write!(f, "Unknown encryption key: {}", desc),
+            FieldEncryptionError::NoKeyProvider(ref desc) => write!(f, "No key provider: {}", desc),
+            FieldEncryptionError::Malformed(ref desc) => write!(f, "Malformed ciphertext: {}", desc),
+            FieldEncryptionError::DecryptionFailed(ref desc) => write!(f, "Decryption failed: {}", desc),
+        }
+    }
+}
+
+impl std::error::Error for FieldEncryptionError {}
+
+/// A 256 bit data encryption key and the id stored with everything it encrypts.
+#[derive(Clone, PartialEq)]
+pub struct EncryptionKey {
+    id: String,
+    key: [u8; KEY_LENGTH],
+}
+
+impl EncryptionKey {
+    /// `id` must be letters, digits, `-` or `_` so it can be read back from a ciphertext.
+    pub fn new(id: &str, key: &[u8]) -> Result<Self, FieldEncryptionError> {
+        if id.is_empty() || !id.chars().all(|character| character.is_ascii_alphanumeric() || character == '-' || character == '_') {
+            return Err(FieldEncryptionError::InvalidKey("Key id must be letters, digits, '-' or '_'.".to_string()));
+        }
+        let key = key.try_into().map_err(|_| FieldEncryptionError::InvalidKey(format!("Key {} must be {} bytes.", id, KEY_LENGTH)))?;
+
+        Ok(Self { id: id.to_string(), key })
+    }
+
+    /// A new random key from the operating system's generator.
+    pub fn generate(id: &str) -> Result<Self, FieldEncryptionError> {
+        Self::new(id, &Aes256Gcm::generate_key(&mut OsRng))
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptionKey").field("id", &self.id).finish_non_exhaustive()
+    }
+}
+
+/// Port for the keys personal data is encrypted with, e.g. a key management service.
+pub trait KeyProvider {
+    /// The key new ciphertexts are written with.
+    fn current_key(&self) -> Result<EncryptionKey, FieldEncryptionError>;
+
+    /// The key with this id, current or retired.
+    fn key(&self, key_id: &str) -> Result<EncryptionKey, FieldEncryptionError>;
+}
+
+/// Keys held in memory. Retired keys are kept only to read existing ciphertexts.
+#[derive(Debug, Clone)]
+pub struct InMemoryKeyProvider {
+    current: EncryptionKey,
+    retired: Vec<EncryptionKey>,
+}
+
+impl InMemoryKeyProvider {
+    pub fn new(current: EncryptionKey) -> Self {
+        Self { current, retired: Vec::new() }
+    }
+
+    pub fn with_retired_key(mut self, key: EncryptionKey) -> Self {
+        self.retired.push(key);
+        self
+    }
+}
+
+impl KeyProvider for InMemoryKeyProvider {
+    fn current_key(&self) -> Result<EncryptionKey, FieldEncryptionError> {
+        Ok(self.current.clone())
+    }
+
+    fn key(&self, key_id: &str) -> Result<EncryptionKey, FieldEncryptionError> {
+        std::iter::once(&self.current)
+            .chain(&self.retired)
+            .find(|key| key.id == key_id)
+            .cloned()
+            .ok_or_else(|| FieldEncryptionError::UnknownKey(key_id.to_string()))
+    }
+}
+
+/// Seals `plaintext` under the provider's current key.
+pub fn encrypt(provider: &dyn KeyProvider, plaintext: &[u8]) -> Result<String, FieldEncryptionError> {
+    let key = provider.current_key()?;
+    let header = format!("{}{}:", CIPHERTEXT_PREFIX, key.id);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher(&key)
+        .encrypt(&nonce, Payload { msg: plaintext, aad: header.as_bytes() })
+        .map_err(|_| FieldEncryptionError::InvalidKey(format!("Key {} could not encrypt", key.id)))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    Ok(format!("{}{}", header, base64_url(&sealed)))
+}
+
+/// Opens a ciphertext written by [`encrypt`] with the key named in it.
+pub fn decrypt(provider: &dyn KeyProvider, ciphertext: &str) -> Result<Vec<u8>, FieldEncryptionError> {
+    let key_id = key_id(ciphertext).ok_or_else(|| FieldEncryptionError::Malformed("Expected enc:v1:{key id}:{data}".to_string()))?;
+    let (header, encoded) = ciphertext.split_at(CIPHERTEXT_PREFIX.len() + key_id.len() + 1);
+    let sealed = base64_url_decode(encoded).ok_or_else(|| FieldEncryptionError::Malformed("Data is not base64url".to_string()))?;
+    if sealed.len() < NONCE_LENGTH {
+        return Err(FieldEncryptionError::Malformed("Data is too short".to_string()));
+    }
+
+    let key = provider.key(key_id)?;
+    let (nonce, sealed) = sealed.split_at(NONCE_LENGTH);
+    cipher(&key)
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: sealed, aad: header.as_bytes() })
+        .map_err(|_| FieldEncryptionError::DecryptionFailed(format!("Ciphertext under key {} has been altered", key_id)))
+}
+
+/// The id of the key a ciphertext was written with.
+pub fn key_id(ciphertext: &str) -> Option<&str> {
+    let (key_id, _) = ciphertext.strip_prefix(CIPHERTEXT_PREFIX)?.split_once(':')?;
+    Some(key_id).filter(|key_id| !key_id.is_empty())
+}
+
+fn is_ciphertext(value: &str) -> bool {
+    key_id(value).is_some()
+}
+
+fn cipher(key: &EncryptionKey) -> Aes256Gcm {
+    Aes256Gcm::new_from_slice(&key.key).expect("Keys are always 32 bytes")
+}
+
+/// Decrypts a ciphertext and encrypts it again under the current key. Ciphertexts
+/// already under the current key are returned unchanged.
+pub fn reencrypt(provider: &dyn KeyProvider, ciphertext: &str) -> Result<String, FieldEncryptionError> {
+    if key_id(ciphertext) == Some(provider.current_key()?.id()) {
+        return Ok(ciphertext.to_string());
+    }
+
+    encrypt(provider, &decrypt(provider, ciphertext)?)
+}
+
+/// Re-encrypts every ciphertext in a stored JSON document that is not under the
+/// current key, returning how many were rewritten.
+pub fn reencrypt_json(provider: &dyn KeyProvider, document: &mut serde_json::Value) -> Result<usize, FieldEncryptionError> {
+    let current_key_id = provider.current_key()?.id;
+
+    match document {
+        serde_json::Value::String(value) if is_ciphertext(value) && key_id(value) != Some(current_key_id.as_str()) => {
+            *value = reencrypt(provider, value)?;
+            Ok(1)
+        }
+        serde_json::Value::Array(values) => values.iter_mut().map(|value| reencrypt_json(provider, value)).sum(),
+        serde_json::Value::Object(fields) => fields.values_mut().map(|value| reencrypt_json(provider, value)).sum(),
+        _ => Ok(0),
+    }
+}
+
+thread_local! {
+    static KEY_PROVIDER: RefCell<Option<Rc<dyn KeyProvider>>> = RefCell::new(None);
+}
+
+/// Restores the provider that was in scope before, even if `f` panics.
+struct KeyProviderScope(Option<Rc<dyn KeyProvider>>);
+
+impl Drop for KeyProviderScope {
+    fn drop(&mut self) {
+        KEY_PROVIDER.with(|provider| *provider.borrow_mut() = self.0.take());
+    }
+}
+
+/// Runs `f` with `provider` used for every encrypted field serialised or
+/// deserialised on this thread.
+pub fn with_key_provider<P: KeyProvider + Clone + 'static, R>(provider: &P, f: impl FnOnce() -> R) -> R {
+    let previous = KEY_PROVIDER.with(|scoped| scoped.borrow_mut().replace(Rc::new(provider.clone())));
+    let _scope = KeyProviderScope(previous);
+    f()
+}
+
+fn scoped_key_provider() -> Result<Rc<dyn KeyProvider>, FieldEncryptionError> {
+    KEY_PROVIDER
+        .with(|provider| provider.borrow().clone())
+        .ok_or_else(|| FieldEncryptionError::NoKeyProvider("Encrypted fields must be serialised inside with_key_provider".to_string()))
+}
+
+/// Serde functions for `#[serde(with = "crate::field_encryption::encrypted")]`.
+/// The field is written as JSON and then encrypted, so any serialisable type works.
+pub mod encrypted {
+    use super::*;
+
+    pub fn serialize<T: Serialize, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        let provider = scoped_key_provider().map_err(S::Error::custom)?;
+        let plaintext = serde_json::to_vec(value).map_err(S::Error::custom)?;
+        serializer.serialize_str(&encrypt(provider.as_ref(), &plaintext).map_err(S::Error::custom)?)
+    }
+
+    pub fn deserialize<'de, T: DeserializeOwned, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        let ciphertext = String::deserialize(deserializer)?;
+        let provider = scoped_key_provider().map_err(D::Error::custom)?;
+        let plaintext = decrypt(provider.as_ref(), &ciphertext).map_err(D::Error::custom)?;
+        serde_json::from_slice(&plaintext).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        contexts::annual_review::book_review_meeting::types::{ValidatedClientContactInformation, ValidatedSingleIndividualElectronicContact},
+        tests::test_utils::shared::{
+            validated_contact, ReviewInformationFixture, JOINT_INDIVIDUALS_POST_CONTACT, SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, SINGLE_INDIVIDUAL_POST_CONTACT_WITH_MOBILE,
+        },
+    };
+    use super::*;
+
+    fn key(id: &str, byte: u8) -> EncryptionKey {
+        EncryptionKey::new(id, &[byte; KEY_LENGTH]).unwrap()
+    }
+
+    fn contact() -> ValidatedSingleIndividualElectronicContact {
+        let ValidatedClientContactInformation::ValidatedSingleIndividualElectronicContact(contact) = validated_contact(SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT) else {
+            panic!("Expected a single individual electronic contact");
+        };
+        contact
+    }
+
+    #[test]
+    fn test_tagged_fields_are_stored_encrypted() {
+        let keys = InMemoryKeyProvider::new(key("k1", 1));
+        let json = with_key_provider(&keys, || serde_json::to_string(&contact())).unwrap();
+
+        assert!(!json.contains("Siân") && !json.contains("sian@example.com"), "{}", json);
+        assert!(json.contains("\"enc:v1:k1:"), "{}", json);
+
+        let read_back: ValidatedSingleIndividualElectronicContact = with_key_provider(&keys, || serde_json::from_str(&json)).unwrap();
+        assert_eq!(read_back, contact());
+    }
+
+    #[test]
+    fn test_contacts_with_addresses_and_phone_numbers_are_stored_encrypted() {
+        let keys = InMemoryKeyProvider::new(key("k1", 1));
+        let json = with_key_provider(&keys, || serde_json::to_string(&validated_contact(SINGLE_INDIVIDUAL_POST_CONTACT_WITH_MOBILE))).unwrap();
+
+        for plaintext in ["Siân", "Quarry Street", "GU1 3UY", "447700900123"] {
+            assert!(!json.contains(plaintext), "'{}' found in {}", plaintext, json);
+        }
+    }
+
+    #[test]
+    fn test_authorised_contacts_are_stored_encrypted() {
+        let review_information = ReviewInformationFixture::new(JOINT_INDIVIDUALS_POST_CONTACT)
+            .with_authorised_contacts(r#"[{
+                "attorney_type": "PropertyAndFinancialAffairsLpa", "first_name": "Carol", "client_first_name": "Sarah",
+                "registration_reference": "7000-1234-5678", "registered_on": "12/03/2021",
+                "scope": "ActsForClient", "email_address": "carol@example.com"
+            }]"#)
+            .validated();
+        let keys = InMemoryKeyProvider::new(key("k1", 1));
+        let json = with_key_provider(&keys, || serde_json::to_string(&review_information)).unwrap();
+
+        for plaintext in ["Carol", "Sarah", "7000-1234-5678", "carol@example.com"] {
+            assert!(!json.contains(plaintext), "'{}' found in {}", plaintext, json);
+        }
+    }
+
+    #[test]
+    fn test_deceased_clients_are_stored_encrypted() {
+        let review_information = ReviewInformationFixture::new(JOINT_INDIVIDUALS_POST_CONTACT)
+            .with_deceased(r#"[{
+                "first_name": "James",
+                "date_of_death": "14/04/2024",
+                "executors": [{"first_name": "Emma", "email_address": "emma@probate-solicitors.co.uk"}]
+            }]"#)
+            .validated();
+        let keys = InMemoryKeyProvider::new(key("k1", 1));
+        let json = with_key_provider(&keys, || serde_json::to_string(&review_information)).unwrap();
+
+        for plaintext in ["James", "2024-04-14", "Emma", "emma@probate-solicitors.co.uk"] {
+            assert!(!json.contains(plaintext), "'{}' found in {}", plaintext, json);
+        }
+    }
+
+    #[test]
+    fn test_encrypted_fields_need_a_key_provider() {
+        assert!(serde_json::to_string(&contact()).is_err());
+
+        let keys = InMemoryKeyProvider::new(key("k1", 1));
+        let json = with_key_provider(&keys, || serde_json::to_string(&contact())).unwrap();
+        assert!(serde_json::from_str::<ValidatedSingleIndividualElectronicContact>(&json).is_err());
+    }
+
+    #[test]
+    fn test_altered_or_relabelled_ciphertext_is_rejected() {
+        let keys = InMemoryKeyProvider::new(key("k1", 1)).with_retired_key(key("k0", 1));
+        let ciphertext = encrypt(&keys, b"Sian").unwrap();
+        assert_eq!(decrypt(&keys, &ciphertext).unwrap(), b"Sian");
+
+        let mut altered = ciphertext.clone().into_bytes();
+        let last = altered.len() - 1;
+        altered[last] = if altered[last] == b'A' { b'B' } else { b'A' };
+        assert!(decrypt(&keys, &String::from_utf8(altered).unwrap()).is_err());
+
+        // k0 holds the same key material, but the key id is authenticated.
+        let relabelled = ciphertext.replacen("enc:v1:k1:", "enc:v1:k0:", 1);
+        assert!(matches!(decrypt(&keys, &relabelled), Err(FieldEncryptionError::DecryptionFailed(_))));
+
+        assert!(matches!(decrypt(&keys, "enc:v1:k9:AAAAAAAAAAAAAAAA"), Err(FieldEncryptionError::UnknownKey(_))));
+        assert!(matches!(decrypt(&keys, "Sian"), Err(FieldEncryptionError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_reencrypt_stored_document_under_new_key() {
+        let old_keys = InMemoryKeyProvider::new(key("k1", 1));
+        let mut document: serde_json::Value = with_key_provider(&old_keys, || serde_json::to_value(contact())).unwrap();
+
+        let new_keys = InMemoryKeyProvider::new(key("k2", 2)).with_retired_key(key("k1", 1));
+        assert_eq!(reencrypt_json(&new_keys, &mut document).unwrap(), 4);
+        assert_eq!(reencrypt_json(&new_keys, &mut document).unwrap(), 0);
+        assert!(document.to_string().contains("enc:v1:k2:") && !document.to_string().contains("enc:v1:k1:"));
+
+        let only_new_key = InMemoryKeyProvider::new(key("k2", 2));
+        let read_back: ValidatedSingleIndividualElectronicContact = with_key_provider(&only_new_key, || serde_json::from_value(document)).unwrap();
+        assert_eq!(read_back, contact());
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        assert!(EncryptionKey::new("k1", &[0; 16]).is_err());
+        assert!(EncryptionKey::new("k:1", &[0; KEY_LENGTH]).is_err());
+        assert_eq!(format!("{:?}", key("k1", 7)), "EncryptionKey { id: \"k1\", .. }");
+    }
+}
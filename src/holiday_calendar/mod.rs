@@ -0,0 +1,383 @@
+//! UK bank holiday calendar and business day arithmetic
+//!
+//! Holidays are worked out from the statutory rules for each jurisdiction
+//! (fixed dates with substitute days, Easter and the May and August Mondays),
+//! with one-off and moved holidays loaded from a bundled data file.
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use serde::{Serialize, Deserialize};
+
+const BUNDLED_ONE_OFF_HOLIDAYS: &str = include_str!("one_off_holidays.csv");
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Jurisdiction {
+    EnglandAndWales,
+    Scotland,
+    NorthernIreland,
+}
+
+impl Jurisdiction {
+    fn code(&self) -> &'static str {
+        match self {
+            Jurisdiction::EnglandAndWales => "EW",
+            Jurisdiction::Scotland => "SC",
+            Jurisdiction::NorthernIreland => "NI",
+        }
+    }
+}
+
+impl std::fmt::Display for Jurisdiction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl TryFrom<&str> for Jurisdiction {
+    type Error = String;
+
+    fn try_from(code: &str) -> Result<Self, Self::Error> {
+        match code.trim() {
+            "EW" => Ok(Jurisdiction::EnglandAndWales),
+            "SC" => Ok(Jurisdiction::Scotland),
+            "NI" => Ok(Jurisdiction::NorthernIreland),
+            other => Err(format!("Unknown jurisdiction code '{}'.", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BankHoliday {
+    date: NaiveDate,
+    name: String,
+}
+
+impl BankHoliday {
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct OneOffHoliday {
+    date: NaiveDate,
+    jurisdictions: Vec<Jurisdiction>,
+    name: String,
+    replaces: Option<NaiveDate>,
+}
+
+impl OneOffHoliday {
+    fn parse(line: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+
+        if fields.len() < 3 || fields.len() > 4 {
+            return Err(format!("One-off holiday '{}' must have 3 or 4 fields.", line));
+        }
+
+        let date = parse_date(fields[0])?;
+        let jurisdictions = fields[1]
+            .split(';')
+            .map(Jurisdiction::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let name = fields[2].to_string();
+        let replaces = fields.get(3).map(|replaces| parse_date(replaces)).transpose()?;
+
+        if name.is_empty() {
+            return Err(format!("One-off holiday on {} must have a name.", date));
+        }
+
+        Ok(Self { date, jurisdictions, name, replaces })
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| format!("Invalid holiday date '{}': {}", date, e))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HolidayCalendar {
+    jurisdiction: Jurisdiction,
+    one_off_holidays: Vec<OneOffHoliday>,
+}
+
+impl HolidayCalendar {
+    /// Calendar for the jurisdiction using the bundled one-off holiday data.
+    pub fn new(jurisdiction: Jurisdiction) -> Self {
+        Self::with_one_off_holidays(jurisdiction, BUNDLED_ONE_OFF_HOLIDAYS)
+            .expect("Bundled one-off holiday data must be valid")
+    }
+
+    /// Calendar for the jurisdiction using one-off holiday data in the bundled file format.
+    pub fn with_one_off_holidays(jurisdiction: Jurisdiction, data: &str) -> Result<Self, String> {
+        let one_off_holidays = data
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(OneOffHoliday::parse)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|holiday| holiday.jurisdictions.contains(&jurisdiction))
+            .collect();
+
+        Ok(Self { jurisdiction, one_off_holidays })
+    }
+
+    pub fn jurisdiction(&self) -> Jurisdiction {
+        self.jurisdiction
+    }
+
+    /// All bank holidays observed in the year, in date order.
+    pub fn holidays_in_year(&self, year: i32) -> Vec<BankHoliday> {
+        let mut holidays = rule_based_holidays(self.jurisdiction, year);
+
+        for one_off in self.one_off_holidays.iter().filter(|holiday| holiday.date.year() == year) {
+            if let Some(replaced) = one_off.replaces {
+                holidays.retain(|holiday| holiday.date != replaced);
+            }
+            holidays.push(BankHoliday { date: one_off.date, name: one_off.name.clone() });
+        }
+
+        holidays.sort_by_key(|holiday| holiday.date);
+        holidays
+    }
+
+    pub fn is_bank_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays_in_year(date.year()).iter().any(|holiday| holiday.date == date)
+    }
+
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !is_weekend(date) && !self.is_bank_holiday(date)
+    }
+
+    /// The first business day strictly after the date.
+    pub fn next_business_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut next = date + Days::new(1);
+        while !self.is_business_day(next) {
+            next = next + Days::new(1);
+        }
+        next
+    }
+
+    /// The date the given number of business days after the date. Adding zero
+    /// days to a non-business day rolls forward to the next business day.
+    pub fn add_business_days(&self, date: NaiveDate, business_days: u32) -> NaiveDate {
+        if business_days == 0 {
+            return if self.is_business_day(date) { date } else { self.next_business_day(date) };
+        }
+
+        (0..business_days).fold(date, |current, _| self.next_business_day(current))
+    }
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+fn rule_based_holidays(jurisdiction: Jurisdiction, year: i32) -> Vec<BankHoliday> {
+    let date = |month: u32, day: u32| NaiveDate::from_ymd_opt(year, month, day).expect("Fixed holiday date must exist");
+
+    let mut fixed_holidays = vec![(date(1, 1), "New Year's Day")];
+    if jurisdiction == Jurisdiction::Scotland {
+        fixed_holidays.push((date(1, 2), "2nd January"));
+    }
+    if jurisdiction == Jurisdiction::NorthernIreland {
+        fixed_holidays.push((date(3, 17), "St Patrick's Day"));
+        fixed_holidays.push((date(7, 12), "Battle of the Boyne (Orangemen's Day)"));
+    }
+    if jurisdiction == Jurisdiction::Scotland {
+        fixed_holidays.push((date(11, 30), "St Andrew's Day"));
+    }
+    fixed_holidays.push((date(12, 25), "Christmas Day"));
+    fixed_holidays.push((date(12, 26), "Boxing Day"));
+
+    let easter_sunday = easter_sunday(year);
+    let mut holidays = vec![
+        BankHoliday { date: easter_sunday - Days::new(2), name: "Good Friday".to_string() },
+        BankHoliday { date: first_weekday_of_month(year, 5, Weekday::Mon), name: "Early May bank holiday".to_string() },
+        BankHoliday { date: last_weekday_of_month(year, 5, Weekday::Mon), name: "Spring bank holiday".to_string() },
+    ];
+    if jurisdiction == Jurisdiction::Scotland {
+        holidays.push(BankHoliday { date: first_weekday_of_month(year, 8, Weekday::Mon), name: "Summer bank holiday".to_string() });
+    } else {
+        holidays.push(BankHoliday { date: easter_sunday + Days::new(1), name: "Easter Monday".to_string() });
+        holidays.push(BankHoliday { date: last_weekday_of_month(year, 8, Weekday::Mon), name: "Summer bank holiday".to_string() });
+    }
+
+    // Fixed date holidays falling on a weekday are observed on the day itself
+    holidays.extend(
+        fixed_holidays
+            .iter()
+            .filter(|(date, _)| !is_weekend(*date))
+            .map(|(date, name)| BankHoliday { date: *date, name: name.to_string() }),
+    );
+
+    // Those falling on a weekend move to the next weekday that is not already a holiday,
+    // so Christmas and Boxing Day on a weekend become the following Monday and Tuesday
+    for (date, name) in fixed_holidays.iter().filter(|(date, _)| is_weekend(*date)) {
+        let mut substitute = *date + Days::new(1);
+        while is_weekend(substitute) || holidays.iter().any(|holiday| holiday.date == substitute) {
+            substitute = substitute + Days::new(1);
+        }
+        holidays.push(BankHoliday { date: substitute, name: format!("{} (substitute day)", name) });
+    }
+
+    holidays.sort_by_key(|holiday| holiday.date);
+    holidays
+}
+
+/// Easter Sunday in the Gregorian calendar using the anonymous Gregorian algorithm.
+fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).expect("Easter date must exist")
+}
+
+fn first_weekday_of_month(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 1).expect("Every month has a first weekday")
+}
+
+fn last_weekday_of_month(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+        .unwrap_or_else(|| NaiveDate::from_weekday_of_month_opt(year, month, weekday, 4).expect("Every month has four of each weekday"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn holiday_dates(calendar: &HolidayCalendar, year: i32) -> Vec<NaiveDate> {
+        calendar.holidays_in_year(year).iter().map(|holiday| holiday.date()).collect()
+    }
+
+    #[test]
+    fn test_easter_sunday() {
+        assert_eq!(easter_sunday(2019), date(2019, 4, 21));
+        assert_eq!(easter_sunday(2024), date(2024, 3, 31));
+        assert_eq!(easter_sunday(2025), date(2025, 4, 20));
+        assert_eq!(easter_sunday(2038), date(2038, 4, 25));
+    }
+
+    #[test]
+    fn test_england_and_wales_holidays_2024() {
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        assert_eq!(holiday_dates(&calendar, 2024), vec![
+            date(2024, 1, 1),
+            date(2024, 3, 29),
+            date(2024, 4, 1),
+            date(2024, 5, 6),
+            date(2024, 5, 27),
+            date(2024, 8, 26),
+            date(2024, 12, 25),
+            date(2024, 12, 26),
+        ]);
+    }
+
+    #[test]
+    fn test_scotland_holidays_2022_include_substitutes_and_one_offs() {
+        let calendar = HolidayCalendar::new(Jurisdiction::Scotland);
+        assert_eq!(holiday_dates(&calendar, 2022), vec![
+            date(2022, 1, 3),
+            date(2022, 1, 4),
+            date(2022, 4, 15),
+            date(2022, 5, 2),
+            date(2022, 6, 2),
+            date(2022, 6, 3),
+            date(2022, 8, 1),
+            date(2022, 9, 19),
+            date(2022, 11, 30),
+            date(2022, 12, 26),
+            date(2022, 12, 27),
+        ]);
+    }
+
+    #[test]
+    fn test_northern_ireland_holidays_2023() {
+        let calendar = HolidayCalendar::new(Jurisdiction::NorthernIreland);
+        assert_eq!(holiday_dates(&calendar, 2023), vec![
+            date(2023, 1, 2),
+            date(2023, 3, 17),
+            date(2023, 4, 7),
+            date(2023, 4, 10),
+            date(2023, 5, 1),
+            date(2023, 5, 8),
+            date(2023, 5, 29),
+            date(2023, 7, 12),
+            date(2023, 8, 28),
+            date(2023, 12, 25),
+            date(2023, 12, 26),
+        ]);
+    }
+
+    #[test]
+    fn test_christmas_on_saturday_substitutes_monday_and_tuesday() {
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        assert!(calendar.is_bank_holiday(date(2021, 12, 27)));
+        assert!(calendar.is_bank_holiday(date(2021, 12, 28)));
+        assert!(!calendar.is_bank_holiday(date(2021, 12, 25)));
+    }
+
+    #[test]
+    fn test_moved_holiday_replaces_rule_based_date() {
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        assert!(!calendar.is_bank_holiday(date(2020, 5, 4)));
+        assert!(calendar.is_bank_holiday(date(2020, 5, 8)));
+    }
+
+    #[test]
+    fn test_is_business_day() {
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        assert!(calendar.is_business_day(date(2024, 4, 2)));
+        assert!(!calendar.is_business_day(date(2024, 4, 1)));
+        assert!(!calendar.is_business_day(date(2024, 4, 6)));
+    }
+
+    #[test]
+    fn test_next_business_day_skips_easter_weekend() {
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        assert_eq!(calendar.next_business_day(date(2024, 3, 28)), date(2024, 4, 2));
+    }
+
+    #[test]
+    fn test_next_business_day_differs_by_jurisdiction() {
+        let england = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        let scotland = HolidayCalendar::new(Jurisdiction::Scotland);
+        assert_eq!(england.next_business_day(date(2024, 3, 29)), date(2024, 4, 2));
+        assert_eq!(scotland.next_business_day(date(2024, 3, 29)), date(2024, 4, 1));
+    }
+
+    #[test]
+    fn test_add_business_days_over_christmas() {
+        let calendar = HolidayCalendar::new(Jurisdiction::EnglandAndWales);
+        assert_eq!(calendar.add_business_days(date(2024, 12, 23), 3), date(2024, 12, 30));
+        assert_eq!(calendar.add_business_days(date(2024, 12, 21), 0), date(2024, 12, 23));
+    }
+
+    #[test]
+    fn test_invalid_one_off_data_is_rejected() {
+        let result = HolidayCalendar::with_one_off_holidays(Jurisdiction::EnglandAndWales, "2024-13-01,EW,Bad date");
+        assert!(result.is_err());
+
+        let result = HolidayCalendar::with_one_off_holidays(Jurisdiction::EnglandAndWales, "2024-06-01,XX,Unknown");
+        assert_eq!(result.unwrap_err(), "Unknown jurisdiction code 'XX'.");
+    }
+}
@@ -0,0 +1,446 @@
+//! Minimal iCalendar (RFC 5545) support
+//!
+//! Parses and writes calendar content as components and properties, handling
+//! line folding, parameters and text escaping, and converts date-time values
+//! to and from UK local time.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+pub const LONDON_TIME_ZONE: &str = "Europe/London";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    name: String,
+    parameters: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    pub fn new(name: &str, value: &str) -> Self {
+        Self { name: name.to_ascii_uppercase(), parameters: Vec::new(), value: value.to_string() }
+    }
+
+    /// A TEXT property, escaping the value.
+    pub fn text_value(name: &str, text: &str) -> Self {
+        Self::new(name, &escape_text(text))
+    }
+
+    /// A DATE-TIME property in UTC.
+    pub fn utc_date_time(name: &str, utc: NaiveDateTime) -> Self {
+        Self::new(name, &utc.format("%Y%m%dT%H%M%SZ").to_string())
+    }
+
+    /// A DATE-TIME property in UK local time, referencing the Europe/London VTIMEZONE.
+    pub fn london_date_time(name: &str, local: NaiveDateTime) -> Self {
+        Self::new(name, &local.format("%Y%m%dT%H%M%S").to_string()).with_parameter("TZID", LONDON_TIME_ZONE)
+    }
+
+    pub fn with_parameter(mut self, name: &str, value: &str) -> Self {
+        self.parameters.push((name.to_ascii_uppercase(), value.to_string()));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(parameter, _)| parameter.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// The value with TEXT escaping removed.
+    pub fn text(&self) -> String {
+        unescape_text(&self.value)
+    }
+
+    /// The value as a UK local date-time. All-day dates start at midnight,
+    /// UTC values are converted to Europe/London and floating values are
+    /// taken to be local already.
+    pub fn local_date_time(&self) -> Result<NaiveDateTime, String> {
+        if self.parameter("VALUE").is_some_and(|value| value.eq_ignore_ascii_case("DATE")) {
+            let date = NaiveDate::parse_from_str(&self.value, "%Y%m%d")
+                .map_err(|e| format!("Invalid {} date '{}': {}", self.name, self.value, e))?;
+            return Ok(date.and_time(NaiveTime::MIN));
+        }
+
+        if let Some(utc) = self.value.strip_suffix('Z') {
+            let utc = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+                .map_err(|e| format!("Invalid {} date-time '{}': {}", self.name, self.value, e))?;
+            return Ok(london_from_utc(utc));
+        }
+
+        match self.parameter("TZID") {
+            None | Some(LONDON_TIME_ZONE) | Some("GMT Standard Time") => {
+                NaiveDateTime::parse_from_str(&self.value, "%Y%m%dT%H%M%S")
+                    .map_err(|e| format!("Invalid {} date-time '{}': {}", self.name, self.value, e))
+            }
+            Some(time_zone) => Err(format!("Unsupported time zone '{}' on {}.", time_zone, self.name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    name: String,
+    properties: Vec<Property>,
+    components: Vec<Component>,
+}
+
+impl Component {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_ascii_uppercase(), properties: Vec::new(), components: Vec::new() }
+    }
+
+    pub fn with_property(mut self, property: Property) -> Self {
+        self.properties.push(property);
+        self
+    }
+
+    pub fn with_component(mut self, component: Component) -> Self {
+        self.components.push(component);
+        self
+    }
+
+    /// Serialises the component as CRLF terminated content lines folded at 75 octets.
+    pub fn to_ics_string(&self) -> String {
+        let mut output = String::new();
+        self.write_to(&mut output);
+        output
+    }
+
+    fn write_to(&self, output: &mut String) {
+        write_content_line(output, &format!("BEGIN:{}", self.name));
+        for property in &self.properties {
+            let mut line = property.name.clone();
+            for (name, value) in &property.parameters {
+                if value.contains([':', ';', ',']) {
+                    line.push_str(&format!(";{}=\"{}\"", name, value));
+                } else {
+                    line.push_str(&format!(";{}={}", name, value));
+                }
+            }
+            line.push(':');
+            line.push_str(&property.value);
+            write_content_line(output, &line);
+        }
+        for component in &self.components {
+            component.write_to(output);
+        }
+        write_content_line(output, &format!("END:{}", self.name));
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn properties(&self) -> &Vec<Property> {
+        &self.properties
+    }
+
+    pub fn components(&self) -> &Vec<Component> {
+        &self.components
+    }
+
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|property| property.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn properties_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Property> {
+        self.properties.iter().filter(move |property| property.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Nested components with the given name, e.g. every VEVENT in a VCALENDAR.
+    pub fn components_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Component> {
+        self.components.iter().filter(move |component| component.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Parses iCalendar content into its top level components.
+pub fn parse(content: &str) -> Result<Vec<Component>, String> {
+    let mut stack: Vec<Component> = Vec::new();
+    let mut parsed = Vec::new();
+
+    for line in unfold(content) {
+        let property = parse_content_line(&line)?;
+
+        if property.name.eq_ignore_ascii_case("BEGIN") {
+            stack.push(Component {
+                name: property.value.to_ascii_uppercase(),
+                properties: Vec::new(),
+                components: Vec::new(),
+            });
+        } else if property.name.eq_ignore_ascii_case("END") {
+            let component = stack.pop().ok_or_else(|| format!("Unexpected END:{} without BEGIN.", property.value))?;
+            if !component.name.eq_ignore_ascii_case(&property.value) {
+                return Err(format!("Expected END:{} but found END:{}.", component.name, property.value));
+            }
+            match stack.last_mut() {
+                Some(parent) => parent.components.push(component),
+                None => parsed.push(component),
+            }
+        } else {
+            stack
+                .last_mut()
+                .ok_or_else(|| format!("Property {} found outside of a component.", property.name))?
+                .properties
+                .push(property);
+        }
+    }
+
+    match stack.pop() {
+        Some(component) => Err(format!("Component {} is missing its END line.", component.name)),
+        None => Ok(parsed),
+    }
+}
+
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in content.split('\n').map(|line| line.strip_suffix('\r').unwrap_or(line)) {
+        if let Some(continuation) = line.strip_prefix([' ', '\t']) {
+            if let Some(previous) = lines.last_mut() {
+                previous.push_str(continuation);
+                continue;
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+
+    lines
+}
+
+fn parse_content_line(line: &str) -> Result<Property, String> {
+    // The value starts at the first colon that is not inside a quoted parameter value
+    let mut in_quotes = false;
+    let value_start = line
+        .char_indices()
+        .find(|(_, c)| {
+            if *c == '"' {
+                in_quotes = !in_quotes;
+            }
+            *c == ':' && !in_quotes
+        })
+        .map(|(index, _)| index)
+        .ok_or_else(|| format!("Content line '{}' has no value.", line))?;
+
+    let (name_and_parameters, value) = (&line[..value_start], &line[value_start + 1..]);
+    let mut segments = split_unquoted(name_and_parameters, ';').into_iter();
+    let name = segments.next().unwrap_or_default().to_ascii_uppercase();
+
+    if name.is_empty() {
+        return Err(format!("Content line '{}' has no property name.", line));
+    }
+
+    let parameters = segments
+        .map(|parameter| {
+            parameter
+                .split_once('=')
+                .map(|(key, value)| (key.to_ascii_uppercase(), value.trim_matches('"').to_string()))
+                .ok_or_else(|| format!("Parameter '{}' on {} has no value.", parameter, name))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Property { name, parameters, value: value.to_string() })
+}
+
+fn split_unquoted(string: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut in_quotes = false;
+
+    for c in string.chars() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        if c == separator && !in_quotes {
+            parts.push(String::new());
+        } else if let Some(part) = parts.last_mut() {
+            part.push(c);
+        }
+    }
+
+    parts
+}
+
+fn write_content_line(output: &mut String, line: &str) {
+    let mut octets_on_line = 0;
+
+    for c in line.chars() {
+        if octets_on_line + c.len_utf8() > 75 {
+            output.push_str("\r\n ");
+            octets_on_line = 1;
+        }
+        output.push(c);
+        octets_on_line += c.len_utf8();
+    }
+
+    output.push_str("\r\n");
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => text.push('\n'),
+                Some(escaped) => text.push(escaped),
+                None => text.push('\\'),
+            }
+        } else {
+            text.push(c);
+        }
+    }
+
+    text
+}
+
+/// British Summer Time runs from 01:00 UTC on the last Sunday in March
+/// to 01:00 UTC on the last Sunday in October.
+fn british_summer_time_bounds_utc(year: i32) -> (NaiveDateTime, NaiveDateTime) {
+    let last_sunday = |month: u32| {
+        let last_day = NaiveDate::from_ymd_opt(year, month, 31).expect("March and October have 31 days");
+        last_day - Duration::days(last_day.weekday().num_days_from_sunday() as i64)
+    };
+    let one_am = NaiveTime::from_hms_opt(1, 0, 0).expect("01:00 is a valid time");
+
+    (last_sunday(3).and_time(one_am), last_sunday(10).and_time(one_am))
+}
+
+pub fn london_from_utc(utc: NaiveDateTime) -> NaiveDateTime {
+    let (start, end) = british_summer_time_bounds_utc(utc.year());
+    if utc >= start && utc < end { utc + Duration::hours(1) } else { utc }
+}
+
+/// Converts UK local time to UTC. Local times that are skipped or repeated
+/// when the clocks change are read as GMT.
+pub fn utc_from_london(local: NaiveDateTime) -> NaiveDateTime {
+    let (start, end) = british_summer_time_bounds_utc(local.year());
+    let as_bst = local - Duration::hours(1);
+    if as_bst >= start && as_bst < end - Duration::hours(1) { as_bst } else { local }
+}
+
+/// VTIMEZONE definition for Europe/London, required by any DATE-TIME
+/// that references it through TZID.
+pub fn london_time_zone() -> Component {
+    Component::new("VTIMEZONE")
+        .with_property(Property::new("TZID", LONDON_TIME_ZONE))
+        .with_component(
+            Component::new("DAYLIGHT")
+                .with_property(Property::new("TZOFFSETFROM", "+0000"))
+                .with_property(Property::new("TZOFFSETTO", "+0100"))
+                .with_property(Property::new("TZNAME", "BST"))
+                .with_property(Property::new("DTSTART", "19810329T010000"))
+                .with_property(Property::new("RRULE", "FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU")),
+        )
+        .with_component(
+            Component::new("STANDARD")
+                .with_property(Property::new("TZOFFSETFROM", "+0100"))
+                .with_property(Property::new("TZOFFSETTO", "+0000"))
+                .with_property(Property::new("TZNAME", "GMT"))
+                .with_property(Property::new("DTSTART", "19961027T020000"))
+                .with_property(Property::new("RRULE", "FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU")),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date_time(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_nested_components_with_folded_lines() {
+        let content = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nSUMMARY:Annual review with\r\n  Mr and Mrs May\r\nLOCATION:Grafton Court\\, Room 1\r\nDTSTART;TZID=Europe/London:20240612T100000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        let calendars = parse(content).unwrap();
+        assert_eq!(calendars.len(), 1);
+        assert_eq!(calendars[0].property("VERSION").unwrap().value(), "2.0");
+
+        let event = calendars[0].components_named("VEVENT").next().unwrap();
+        assert_eq!(event.property("SUMMARY").unwrap().text(), "Annual review with Mr and Mrs May");
+        assert_eq!(event.property("LOCATION").unwrap().text(), "Grafton Court, Room 1");
+        assert_eq!(event.property("DTSTART").unwrap().parameter("tzid"), Some("Europe/London"));
+    }
+
+    #[test]
+    fn test_parse_quoted_parameter_containing_colon() {
+        let calendars = parse("BEGIN:VEVENT\nATTENDEE;CN=\"May: James\";ROLE=REQ-PARTICIPANT:mailto:james@example.com\nEND:VEVENT").unwrap();
+        let attendee = calendars[0].property("ATTENDEE").unwrap();
+        assert_eq!(attendee.parameter("CN"), Some("May: James"));
+        assert_eq!(attendee.value(), "mailto:james@example.com");
+    }
+
+    #[test]
+    fn test_parse_rejects_mismatched_end() {
+        let result = parse("BEGIN:VCALENDAR\nBEGIN:VEVENT\nEND:VCALENDAR\n");
+        assert_eq!(result.unwrap_err(), "Expected END:VEVENT but found END:VCALENDAR.");
+    }
+
+    #[test]
+    fn test_parse_rejects_missing_end() {
+        assert!(parse("BEGIN:VCALENDAR\nVERSION:2.0\n").is_err());
+    }
+
+    #[test]
+    fn test_local_date_time_converts_utc_in_summer() {
+        let calendars = parse("BEGIN:VEVENT\nDTSTART:20240612T090000Z\nDTEND;VALUE=DATE:20241225\nEND:VEVENT").unwrap();
+        assert_eq!(calendars[0].property("DTSTART").unwrap().local_date_time().unwrap(), date_time(2024, 6, 12, 10, 0));
+        assert_eq!(calendars[0].property("DTEND").unwrap().local_date_time().unwrap(), date_time(2024, 12, 25, 0, 0));
+    }
+
+    #[test]
+    fn test_london_utc_conversion_around_clock_changes() {
+        assert_eq!(london_from_utc(date_time(2024, 3, 31, 0, 59)), date_time(2024, 3, 31, 0, 59));
+        assert_eq!(london_from_utc(date_time(2024, 3, 31, 1, 0)), date_time(2024, 3, 31, 2, 0));
+        assert_eq!(london_from_utc(date_time(2024, 10, 27, 1, 0)), date_time(2024, 10, 27, 1, 0));
+        assert_eq!(utc_from_london(date_time(2024, 6, 12, 10, 0)), date_time(2024, 6, 12, 9, 0));
+        assert_eq!(utc_from_london(date_time(2024, 12, 12, 10, 0)), date_time(2024, 12, 12, 10, 0));
+    }
+
+    #[test]
+    fn test_written_component_round_trips_through_parse() {
+        let event = Component::new("VEVENT")
+            .with_property(Property::text_value("SUMMARY", "Review; with Mr & Mrs O'Brien, Dún Laoghaire\nPlease bring statements"))
+            .with_property(Property::new("ATTENDEE", "mailto:james@example.com").with_parameter("CN", "May, James"))
+            .with_property(Property::london_date_time("DTSTART", date_time(2024, 6, 12, 10, 0)));
+        let calendar = Component::new("VCALENDAR").with_component(london_time_zone()).with_component(event);
+
+        let written = calendar.to_ics_string();
+        assert!(written.lines().all(|line| line.len() <= 75));
+        assert!(written.contains("ATTENDEE;CN=\"May, James\":mailto:james@example.com\r\n"));
+
+        let parsed = parse(&written).unwrap();
+        assert_eq!(parsed, vec![calendar]);
+        let summary = parsed[0].components_named("VEVENT").next().unwrap().property("SUMMARY").unwrap().text();
+        assert_eq!(summary, "Review; with Mr & Mrs O'Brien, Dún Laoghaire\nPlease bring statements");
+    }
+
+    #[test]
+    fn test_long_lines_are_folded_on_character_boundaries() {
+        let description = "é".repeat(100);
+        let written = Component::new("VEVENT").with_property(Property::text_value("DESCRIPTION", &description)).to_ics_string();
+
+        assert!(written.split("\r\n").all(|line| line.len() <= 75));
+        assert_eq!(parse(&written).unwrap()[0].property("DESCRIPTION").unwrap().text(), description);
+    }
+}
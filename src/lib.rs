@@ -12,7 +12,16 @@ gcwm-domain = {git="git@github.com:graftoncourt/gcwm-domain.git"}
 ```
 */
 
-mod simple_types;
+pub mod audit_log;
+mod encoding;
+pub mod field_encryption;
+pub mod holiday_calendar;
+pub mod icalendar;
+pub mod mime;
+pub mod pdf;
+pub mod redaction;
+pub mod smtp;
+pub mod simple_types;
 mod tests;
 
 pub mod contexts {
@@ -20,7 +29,11 @@ pub mod contexts {
     //! 
     //! This module contains all the bounded contexts available:
     //! - Annual Review
+    //! - Data Protection
     
     /// Module for the Annual Review bounded context
     pub mod annual_review;
+
+    /// Module for the Data Protection bounded context
+    pub mod data_protection;
 }
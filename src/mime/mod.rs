@@ -0,0 +1,341 @@
+//! Internet message formatting (RFC 5322) with MIME bodies (RFC 2045-2047).
+//!
+//! Messages are built from [`MimePart`]s and written with CRLF line endings.
+//! Text parts are quoted-printable, attachments are base64 and header text
+//! containing non-ASCII characters is written as RFC 2047 encoded words.
+
+use chrono::NaiveDateTime;
+
+use crate::{encoding::base64, redaction::Sensitive, simple_types::EmailAddress};
+
+const MAX_LINE_LENGTH: usize = 76;
+
+/// An address with an optional display name, e.g. `Mark Ashworth <mark@gcwm.co.uk>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mailbox {
+    display_name: Option<Sensitive<String>>,
+    address: EmailAddress,
+}
+
+impl Mailbox {
+    pub fn new(display_name: Option<&str>, address: EmailAddress) -> Self {
+        Self {
+            display_name: display_name.filter(|name| !name.trim().is_empty()).map(|name| Sensitive::new(name.to_string())),
+            address,
+        }
+    }
+
+    pub fn display_name(&self) -> Option<&str> {
+        self.display_name.as_ref().map(|name| name.expose().as_str())
+    }
+
+    pub fn address(&self) -> &EmailAddress {
+        &self.address
+    }
+
+    /// The mailbox as written in an address header.
+    pub fn to_header_value(&self) -> String {
+        match self.display_name() {
+            None => self.address.value().to_string(),
+            Some(name) if !name.is_ascii() => format!("{} <{}>", encode_word(name), self.address.value()),
+            Some(name) if name.chars().all(|c| c.is_ascii_alphanumeric() || " !#$%&'*+-/=?^_`{|}~".contains(c)) => {
+                format!("{} <{}>", name, self.address.value())
+            }
+            Some(name) => format!("\"{}\" <{}>", name.replace('\\', "\\\\").replace('"', "\\\""), self.address.value()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PartBody {
+    Encoded(String),
+    Multipart { boundary: String, parts: Vec<MimePart> },
+}
+
+/// A single MIME entity: a leaf with encoded content or a multipart container.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MimePart {
+    headers: Vec<(String, String)>,
+    body: PartBody,
+}
+
+impl MimePart {
+    /// A UTF-8 text part, e.g. `text("plain", ..)` or `text("html", ..)`.
+    pub fn text(subtype: &str, content: &str) -> Self {
+        Self {
+            headers: vec![
+                ("Content-Type".to_string(), format!("text/{}; charset=UTF-8", subtype)),
+                ("Content-Transfer-Encoding".to_string(), "quoted-printable".to_string()),
+            ],
+            body: PartBody::Encoded(quoted_printable(content)),
+        }
+    }
+
+    /// A base64 encoded part. `content_type` is written as given, parameters included.
+    pub fn binary(content_type: &str, content: &[u8]) -> Self {
+        Self {
+            headers: vec![
+                ("Content-Type".to_string(), content_type.to_string()),
+                ("Content-Transfer-Encoding".to_string(), "base64".to_string()),
+            ],
+            body: PartBody::Encoded(base64_lines(content)),
+        }
+    }
+
+    /// A base64 encoded attachment offered to the reader as `filename`.
+    pub fn attachment(content_type: &str, filename: &str, content: &[u8]) -> Self {
+        Self::binary(&format!("{}; name=\"{}\"", content_type, filename), content)
+            .with_header("Content-Disposition", &format!("attachment; filename=\"{}\"", filename))
+    }
+
+    /// A multipart container such as `multipart("alternative", ..)`. The boundary
+    /// must not occur in any of the parts; the `=_` prefix used by callers cannot
+    /// appear in quoted-printable or base64 content.
+    pub fn multipart(subtype: &str, boundary: &str, parts: Vec<MimePart>) -> Self {
+        Self {
+            headers: vec![("Content-Type".to_string(), format!("multipart/{}; boundary=\"{}\"", subtype, boundary))],
+            body: PartBody::Multipart { boundary: boundary.to_string(), parts },
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn parts(&self) -> &[MimePart] {
+        match &self.body {
+            PartBody::Encoded(_) => &[],
+            PartBody::Multipart { parts, .. } => parts,
+        }
+    }
+
+    fn write(&self, output: &mut String) {
+        for (name, value) in &self.headers {
+            write_header(output, name, value);
+        }
+        output.push_str("\r\n");
+
+        match &self.body {
+            PartBody::Encoded(content) => output.push_str(content),
+            PartBody::Multipart { boundary, parts } => {
+                output.push_str("This is a multi-part message in MIME format.\r\n");
+                for part in parts {
+                    output.push_str(&format!("\r\n--{}\r\n", boundary));
+                    part.write(output);
+                }
+                output.push_str(&format!("\r\n--{}--\r\n", boundary));
+            }
+        }
+    }
+}
+
+/// An RFC 5322 message. Headers are written in the order they were added,
+/// followed by `MIME-Version` and the body's own headers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmailMessage {
+    headers: Vec<(String, String)>,
+    body: MimePart,
+}
+
+impl EmailMessage {
+    pub fn new(body: MimePart) -> Self {
+        Self { headers: Vec::new(), body }
+    }
+
+    /// Adds an unstructured header such as `Subject`, encoding non-ASCII text.
+    pub fn with_text_header(self, name: &str, value: &str) -> Self {
+        let value = if value.is_ascii() { value.to_string() } else { encode_words(value) };
+        self.with_header(name, &value)
+    }
+
+    pub fn with_mailbox_header(self, name: &str, mailboxes: &[Mailbox]) -> Self {
+        let value = mailboxes.iter().map(Mailbox::to_header_value).collect::<Vec<String>>().join(", ");
+        self.with_header(name, &value)
+    }
+
+    /// Adds a `Date` header for a UTC time.
+    pub fn with_date(self, utc: NaiveDateTime) -> Self {
+        self.with_header("Date", &utc.format("%a, %d %b %Y %H:%M:%S +0000").to_string())
+    }
+
+    /// Adds a header written as given. The value must already be ASCII.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn body(&self) -> &MimePart {
+        &self.body
+    }
+
+    pub fn to_rfc5322_string(&self) -> String {
+        let mut output = String::new();
+        for (name, value) in &self.headers {
+            write_header(&mut output, name, value);
+        }
+        write_header(&mut output, "MIME-Version", "1.0");
+        self.body.write(&mut output);
+        output
+    }
+}
+
+/// Writes a header, folding at whitespace so lines stay within 78 characters where possible.
+fn write_header(output: &mut String, name: &str, value: &str) {
+    let mut line = format!("{}:", name);
+
+    for word in value.split(' ') {
+        if line.len() + 1 + word.len() > 78 && line.len() > name.len() + 1 {
+            output.push_str(&line);
+            output.push_str("\r\n");
+            line = String::new();
+        }
+        line.push(' ');
+        line.push_str(word);
+    }
+
+    output.push_str(&line);
+    output.push_str("\r\n");
+}
+
+/// Encodes text as one or more RFC 2047 `B` encoded words, splitting only
+/// between characters so that each word is at most 75 characters long.
+pub fn encode_words(text: &str) -> String {
+    // "=?UTF-8?B?" and "?=" take 12 of the 75 characters, leaving 63 for base64, or 45 bytes.
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+
+    for character in text.chars() {
+        if chunk.len() + character.len_utf8() > 45 {
+            words.push(encode_word(&chunk));
+            chunk.clear();
+        }
+        chunk.push(character);
+    }
+    if !chunk.is_empty() || words.is_empty() {
+        words.push(encode_word(&chunk));
+    }
+
+    words.join(" ")
+}
+
+fn encode_word(text: &str) -> String {
+    format!("=?UTF-8?B?{}?=", base64(text.as_bytes()))
+}
+
+/// Quoted-printable encoding (RFC 2045 section 6.7) of UTF-8 text with CRLF line
+/// breaks and soft breaks keeping encoded lines within 76 characters.
+pub fn quoted_printable(text: &str) -> String {
+    let normalised = text.replace("\r\n", "\n");
+    let mut output = Vec::new();
+
+    for line in normalised.split('\n') {
+        let mut encoded_line = String::new();
+        let bytes = line.as_bytes();
+
+        for (index, byte) in bytes.iter().enumerate() {
+            let is_trailing_whitespace = (*byte == b' ' || *byte == b'\t') && index == bytes.len() - 1;
+            let encoded = if (*byte == b'\t' || (b' '..=b'~').contains(byte)) && *byte != b'=' && !is_trailing_whitespace {
+                (*byte as char).to_string()
+            } else {
+                format!("={:02X}", byte)
+            };
+
+            if encoded_line.len() + encoded.len() > MAX_LINE_LENGTH - 1 {
+                output.push(format!("{}=", encoded_line));
+                encoded_line.clear();
+            }
+            encoded_line.push_str(&encoded);
+        }
+        output.push(encoded_line);
+    }
+
+    output.join("\r\n")
+}
+
+fn base64_lines(bytes: &[u8]) -> String {
+    let encoded = base64(bytes);
+    encoded.as_bytes()
+        .chunks(MAX_LINE_LENGTH)
+        .map(|line| String::from_utf8_lossy(line).into_owned())
+        .collect::<Vec<String>>()
+        .join("\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email_address(address: &str) -> EmailAddress {
+        EmailAddress::try_from(address.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_quoted_printable() {
+        assert_eq!(quoted_printable("Café = £5"), "Caf=C3=A9 =3D =C2=A35");
+        assert_eq!(quoted_printable("line one \nline two"), "line one=20\r\nline two");
+
+        let long_line = "a".repeat(100);
+        let encoded = quoted_printable(&long_line);
+        assert!(encoded.split("\r\n").all(|line| line.len() <= 76));
+        assert_eq!(encoded.replace("=\r\n", ""), long_line);
+    }
+
+    #[test]
+    fn test_encoded_words() {
+        assert_eq!(encode_words("Siân"), "=?UTF-8?B?U2nDom4=?=");
+
+        let long_name = "Siân ".repeat(20);
+        let encoded = encode_words(&long_name);
+        assert!(encoded.split(' ').all(|word| word.len() <= 75));
+        assert!(encoded.split(' ').count() > 1);
+    }
+
+    #[test]
+    fn test_mailbox_header_values() {
+        assert_eq!(Mailbox::new(None, email_address("james@example.com")).to_header_value(), "james@example.com");
+        assert_eq!(Mailbox::new(Some("James May"), email_address("james@example.com")).to_header_value(), "James May <james@example.com>");
+        assert_eq!(Mailbox::new(Some("May, James"), email_address("james@example.com")).to_header_value(), "\"May, James\" <james@example.com>");
+        assert_eq!(Mailbox::new(Some("Siân"), email_address("sian@example.com")).to_header_value(), "=?UTF-8?B?U2nDom4=?= <sian@example.com>");
+    }
+
+    #[test]
+    fn test_message_layout() {
+        let message = EmailMessage::new(MimePart::multipart("alternative", "=_boundary", vec![
+            MimePart::text("plain", "Hello"),
+            MimePart::text("html", "<p>Hello</p>"),
+        ]))
+            .with_mailbox_header("To", &[Mailbox::new(Some("James"), email_address("james@example.com"))])
+            .with_text_header("Subject", "Hello");
+
+        let raw = message.to_rfc5322_string();
+        assert!(raw.starts_with("To: James <james@example.com>\r\nSubject: Hello\r\nMIME-Version: 1.0\r\nContent-Type: multipart/alternative; boundary=\"=_boundary\"\r\n\r\n"));
+        assert!(raw.contains("\r\n--=_boundary\r\nContent-Type: text/plain; charset=UTF-8\r\nContent-Transfer-Encoding: quoted-printable\r\n\r\nHello\r\n"));
+        assert!(raw.ends_with("\r\n--=_boundary--\r\n"));
+        assert!(!raw.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn test_long_headers_are_folded() {
+        let message = EmailMessage::new(MimePart::text("plain", ""))
+            .with_text_header("Subject", &"Annual review ".repeat(10));
+
+        let raw = message.to_rfc5322_string();
+        let subject_lines: Vec<&str> = raw.split("\r\n").take_while(|line| !line.starts_with("MIME-Version")).collect();
+        assert!(subject_lines.len() > 1);
+        assert!(subject_lines.iter().all(|line| line.len() <= 78));
+        assert!(subject_lines[1..].iter().all(|line| line.starts_with(' ')));
+    }
+}
@@ -0,0 +1,275 @@
+//! A minimal PDF 1.4 writer for text documents.
+//!
+//! Only the standard Helvetica fonts are used so nothing is embedded, and text
+//! is written in WinAnsiEncoding. The output has no timestamps or random
+//! identifiers, so the same document always produces the same bytes.
+
+/// Points per millimetre.
+pub const POINTS_PER_MM: f32 = 72.0 / 25.4;
+pub const A4_WIDTH: f32 = 595.28;
+pub const A4_HEIGHT: f32 = 841.89;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Font {
+    Helvetica,
+    HelveticaBold,
+}
+
+impl Font {
+    fn resource_name(&self) -> &'static str {
+        match self {
+            Font::Helvetica => "F1",
+            Font::HelveticaBold => "F2",
+        }
+    }
+
+    /// The width of `text` in points when set at `size`.
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        let units: u32 = text.chars().map(|character| glyph_width(*self, character)).sum();
+        units as f32 * size / 1000.0
+    }
+}
+
+/// Helvetica advance widths for the printable ASCII characters, from the Adobe font metrics.
+const HELVETICA_WIDTHS: [u32; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Helvetica-Bold advance widths for the printable ASCII characters.
+const HELVETICA_BOLD_WIDTHS: [u32; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+fn glyph_width(font: Font, character: char) -> u32 {
+    let widths = match font {
+        Font::Helvetica => &HELVETICA_WIDTHS,
+        Font::HelveticaBold => &HELVETICA_BOLD_WIDTHS,
+    };
+
+    match character {
+        ' '..='~' => widths[character as usize - 32],
+        // Accented letters share the width of their base letter closely enough for line breaking.
+        _ => 556,
+    }
+}
+
+/// The WinAnsiEncoding byte for a character, or `?` when it cannot be encoded.
+fn win_ansi_byte(character: char) -> u8 {
+    match character {
+        ' '..='~' => character as u8,
+        '\u{a0}'..='\u{ff}' => character as u32 as u8,
+        '€' => 0x80,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        _ => b'?',
+    }
+}
+
+/// A PDF literal string, with non-ASCII bytes written as octal escapes so the
+/// file stays 7-bit.
+fn literal_string(text: &str) -> String {
+    let mut output = String::from("(");
+    for byte in text.chars().map(win_ansi_byte) {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                output.push('\\');
+                output.push(byte as char);
+            }
+            0x20..=0x7e => output.push(byte as char),
+            _ => output.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    output.push(')');
+    output
+}
+
+/// Breaks `text` into lines no wider than `width` points, keeping words whole.
+pub fn wrap_text(text: &str, font: Font, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+        if font.text_width(&candidate, size) > width && !line.is_empty() {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = candidate;
+        }
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// A page of drawing operations. Coordinates are in points from the bottom left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfPage {
+    width: f32,
+    height: f32,
+    content: String,
+}
+
+impl PdfPage {
+    pub fn a4() -> Self {
+        Self { width: A4_WIDTH, height: A4_HEIGHT, content: String::new() }
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Draws `text` with its baseline starting at `(x, y)`.
+    pub fn text(&mut self, x: f32, y: f32, font: Font, size: f32, text: &str) {
+        self.content.push_str(&format!(
+            "BT /{} {} Tf {} {} Td {} Tj ET\n",
+            font.resource_name(), number(size), number(x), number(y), literal_string(text),
+        ));
+    }
+
+    /// Draws `text` so that it ends at `right`.
+    pub fn text_right_aligned(&mut self, right: f32, y: f32, font: Font, size: f32, text: &str) {
+        self.text(right - font.text_width(text, size), y, font, size, text);
+    }
+
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32), thickness: f32) {
+        self.content.push_str(&format!(
+            "{} w {} {} m {} {} l S\n",
+            number(thickness), number(from.0), number(from.1), number(to.0), number(to.1),
+        ));
+    }
+}
+
+/// Numbers to two decimal places without trailing zeros.
+fn number(value: f32) -> String {
+    let formatted = format!("{:.2}", value);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfDocument {
+    title: String,
+    pages: Vec<PdfPage>,
+}
+
+impl PdfDocument {
+    pub fn new(title: &str) -> Self {
+        Self { title: title.to_string(), pages: Vec::new() }
+    }
+
+    pub fn with_page(mut self, page: PdfPage) -> Self {
+        self.pages.push(page);
+        self
+    }
+
+    pub fn pages(&self) -> &Vec<PdfPage> {
+        &self.pages
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // Objects: 1 catalog, 2 page tree, 3 info, 4 and 5 fonts, then a page and content stream per page.
+        let page_ids: Vec<usize> = (0..self.pages.len()).map(|index| 6 + index * 2).collect();
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<String>>().join(" "),
+                self.pages.len(),
+            ),
+            format!("<< /Title {} /Producer (gcwm-domain) >>", literal_string(&self.title)),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_string(),
+        ];
+
+        for (page, page_id) in self.pages.iter().zip(&page_ids) {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 4 0 R /F2 5 0 R >> >> /Contents {} 0 R >>",
+                number(page.width), number(page.height), page_id + 1,
+            ));
+            objects.push(format!("<< /Length {} >>\nstream\n{}endstream", page.content.len(), page.content));
+        }
+
+        let mut output = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(output.len());
+            output.push_str(&format!("{} 0 obj\n{}\nendobj\n", index + 1, object));
+        }
+
+        let xref_offset = output.len();
+        output.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+        for offset in offsets {
+            output.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        output.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R /Info 3 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1, xref_offset,
+        ));
+
+        output.into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_width() {
+        assert_eq!(Font::Helvetica.text_width("Hello", 10.0), (722 + 556 + 222 + 222 + 556) as f32 / 100.0);
+        assert!(Font::HelveticaBold.text_width("Hello", 10.0) > Font::Helvetica.text_width("Hello", 10.0));
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        let lines = wrap_text("It is nearly time for your annual review with Mark Ashworth.", Font::Helvetica, 11.0, 150.0);
+
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| Font::Helvetica.text_width(line, 11.0) <= 150.0));
+        assert_eq!(lines.join(" "), "It is nearly time for your annual review with Mark Ashworth.");
+    }
+
+    #[test]
+    fn test_literal_string_escapes() {
+        assert_eq!(literal_string("Siân (£5) \\"), "(Si\\342n \\(\\2435\\) \\\\)");
+        assert_eq!(literal_string("日"), "(?)");
+    }
+
+    #[test]
+    fn test_document_structure() {
+        let mut page = PdfPage::a4();
+        page.text(72.0, 720.0, Font::Helvetica, 11.0, "Hello");
+        let bytes = PdfDocument::new("Test").with_page(page).to_bytes();
+        let pdf = String::from_utf8(bytes).unwrap();
+
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("BT /F1 11 Tf 72 720 Td (Hello) Tj ET"));
+
+        let xref_offset: usize = pdf.lines().rev().nth(1).unwrap().parse().unwrap();
+        assert!(pdf[xref_offset..].starts_with("xref\n0 8\n"));
+
+        let page_offset: usize = pdf[xref_offset..].lines().nth(8).unwrap()[..10].parse().unwrap();
+        assert!(pdf[page_offset..].starts_with("6 0 obj\n<< /Type /Page "));
+    }
+}
@@ -0,0 +1,252 @@
+//! Masking of personal data in `Debug` and `Display` output.
+//!
+//! Client names, email addresses, postal addresses and phone numbers must not
+//! reach logs in full (UK GDPR data minimisation). Types holding them print a
+//! masked form instead, e.g. `j***@e***.co.uk` or a postcode's outward code only.
+//! The full value is only available through an explicit accessor: `value()` on
+//! the simple types, or [`Sensitive::expose`] for raw input.
+
+use serde::{Serialize, Deserialize};
+
+/// How a value holding personal data is shown in logs.
+pub trait Redact {
+    fn redacted(&self) -> String;
+}
+
+impl Redact for String {
+    fn redacted(&self) -> String {
+        mask_text(self)
+    }
+}
+
+impl<T: Redact> Redact for Option<T> {
+    fn redacted(&self) -> String {
+        match self {
+            Some(value) => value.redacted(),
+            None => "None".to_string(),
+        }
+    }
+}
+
+/// Raw personal data, such as unvalidated input, that prints masked. Serialises
+/// as the bare value.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct Sensitive<T>(T);
+
+impl<T> Sensitive<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// The unmasked value, for deliberate use such as validation or sending.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Sensitive<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Redact> std::fmt::Debug for Sensitive<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Sensitive({})", self.0.redacted())
+    }
+}
+
+impl<T: Redact> std::fmt::Display for Sensitive<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.redacted())
+    }
+}
+
+/// Implements `Debug` as `Type(masked)` and `Display` as the masked value from
+/// the type's [`Redact`] implementation.
+macro_rules! redacted_debug_and_display {
+    ($($type:ty),+ $(,)?) => {$(
+        impl std::fmt::Debug for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}({})", stringify!($type), $crate::redaction::Redact::redacted(self))
+            }
+        }
+
+        impl std::fmt::Display for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&$crate::redaction::Redact::redacted(self))
+            }
+        }
+    )+};
+}
+
+pub(crate) use redacted_debug_and_display;
+
+/// The first character followed by `***`, e.g. "S***" for "Siân".
+pub fn mask_text(text: &str) -> String {
+    match text.chars().next() {
+        Some(first) => format!("{}***", first),
+        None => String::new(),
+    }
+}
+
+/// Masks the mailbox and the domain name, keeping the top level domain, and
+/// the second level for domains such as `.co.uk`, e.g. "j***@e***.co.uk".
+pub fn mask_email(email_address: &str) -> String {
+    let Some((local_part, domain)) = email_address.rsplit_once('@') else {
+        return mask_text(email_address);
+    };
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    let suffix_labels = match labels.as_slice() {
+        [.., second_level, country] if labels.len() > 2 && country.len() == 2 && second_level.len() <= 3 => 2,
+        [_, ..] if labels.len() > 1 => 1,
+        _ => 0,
+    };
+    let (name_labels, suffix) = labels.split_at(labels.len() - suffix_labels);
+
+    let mut masked_domain = mask_text(&name_labels.join("."));
+    for label in suffix {
+        masked_domain.push('.');
+        masked_domain.push_str(label);
+    }
+
+    format!("{}@{}", mask_text(local_part), masked_domain)
+}
+
+/// The outward code only, e.g. "GU1 ***" for "GU1 3AA".
+pub fn mask_postcode(postcode: &str) -> String {
+    let compact: Vec<char> = postcode.chars().filter(|character| !character.is_whitespace()).collect();
+    if compact.len() <= 3 {
+        return "***".to_string();
+    }
+
+    format!("{} ***", compact[..compact.len() - 3].iter().collect::<String>())
+}
+
+/// All but the leading "+" and the last three digits masked, e.g. "+*********123".
+pub fn mask_phone_number(phone_number: &str) -> String {
+    let characters: Vec<char> = phone_number.chars().collect();
+    let visible_from = characters.len().saturating_sub(3);
+
+    characters
+        .iter()
+        .enumerate()
+        .map(|(index, character)| if index >= visible_from || *character == '+' { *character } else { '*' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::{
+        contexts::annual_review::book_review_meeting::{
+            contact_preferences::ContactChannel,
+            invite_sms::SmsMessage,
+            invites::create_annual_review_invites_for_channel,
+            types::ClientContactInformation,
+        },
+        simple_types::{EmailAddress, PhoneNumber},
+        tests::test_utils::shared::{
+            ReviewInformationFixture, JOINT_INDIVIDUALS_ELECTRONIC_CONTACT, JOINT_INDIVIDUALS_POST_CONTACT, MULTIPLE_TRUSTEES_POST_CONTACT,
+            PRIMARY_DIRECTOR_ELECTRONIC_CONTACT, SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT, SINGLE_INDIVIDUAL_POST_CONTACT_WITH_MOBILE,
+        },
+    };
+    use super::*;
+
+    /// Every name, address, email address and phone number in the shared fixtures.
+    const RAW_PII: [&str; 19] = [
+        "Siân", "James", "Sarah", "Alice", "Carol", "sian@", "james@", "sarah@", "example.com", "mayholdings",
+        "High Street", "Quarry Street", "Church Lane", "Rose Cottage", "GU1 3AA", "3UY", "May Family Trust", "May Holdings", "900123",
+    ];
+
+    fn assert_no_raw_pii(output: &str) {
+        for pii in RAW_PII {
+            assert!(!output.contains(pii), "'{}' found in {}", pii, output);
+        }
+    }
+
+    #[test]
+    fn test_mask_email() {
+        assert_eq!(mask_email("james@example.co.uk"), "j***@e***.co.uk");
+        assert_eq!(mask_email("sian@example.com"), "s***@e***.com");
+        assert_eq!(mask_email("anne.may@mail.mayholdings.org.uk"), "a***@m***.org.uk");
+        assert_eq!(mask_email("not an email"), "n***");
+    }
+
+    #[test]
+    fn test_mask_postcode_and_phone_number() {
+        assert_eq!(mask_postcode("GU1 3AA"), "GU1 ***");
+        assert_eq!(mask_postcode("SW1A1AA"), "SW1A ***");
+        assert_eq!(mask_phone_number("+447700900123"), "+*********123");
+        assert_eq!(mask_text("Siân"), "S***");
+    }
+
+    #[test]
+    fn test_sensitive_masks_debug_and_display_but_not_serialisation() {
+        let name = Sensitive::new("Siân".to_string());
+
+        assert_eq!(format!("{:?}", name), "Sensitive(S***)");
+        assert_eq!(name.to_string(), "S***");
+        assert_eq!(name.expose(), "Siân");
+        assert_eq!(serde_json::to_string(&name).unwrap(), r#""Siân""#);
+        assert_eq!(serde_json::from_str::<Sensitive<String>>(r#""Siân""#).unwrap(), name);
+    }
+
+    #[test]
+    fn test_simple_types_mask_debug_and_display() {
+        let email_address = EmailAddress::try_from("james@example.co.uk".to_string()).unwrap();
+        assert_eq!(format!("{:?}", email_address), "EmailAddress(j***@e***.co.uk)");
+        assert_eq!(email_address.to_string(), "j***@e***.co.uk");
+        assert_eq!(email_address.value(), "james@example.co.uk");
+
+        let phone_number = PhoneNumber::try_from("07700 900123".to_string()).unwrap();
+        assert_eq!(format!("{:?}", phone_number), "PhoneNumber(+*********123)");
+    }
+
+    #[test]
+    fn test_debug_output_of_client_data_never_contains_raw_pii() {
+        let contacts = [
+            JOINT_INDIVIDUALS_ELECTRONIC_CONTACT,
+            SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT,
+            JOINT_INDIVIDUALS_POST_CONTACT,
+            SINGLE_INDIVIDUAL_POST_CONTACT_WITH_MOBILE,
+            MULTIPLE_TRUSTEES_POST_CONTACT,
+            PRIMARY_DIRECTOR_ELECTRONIC_CONTACT,
+        ];
+
+        for contact in contacts {
+            let unvalidated: ClientContactInformation = serde_json::from_str(contact).unwrap();
+            assert_no_raw_pii(&format!("{:?}", unvalidated));
+
+            let review_information = ReviewInformationFixture::new(contact).validated();
+            assert_no_raw_pii(&format!("{:?}", review_information));
+            assert_no_raw_pii(&format!("{:#?}", review_information));
+
+            for channel in [ContactChannel::Email, ContactChannel::Post, ContactChannel::Sms] {
+                let invites = create_annual_review_invites_for_channel(
+                    review_information.client_contact_information(),
+                    review_information.annual_review_due_date(),
+                    NaiveDate::from_ymd_opt(2024, 5, 31).unwrap(),
+                    channel,
+                ).unwrap();
+                assert_no_raw_pii(&format!("{:?}", invites));
+            }
+        }
+    }
+
+    #[test]
+    fn test_debug_output_of_messages_never_contains_raw_pii() {
+        let phone_number = PhoneNumber::try_from("07700 900123".to_string()).unwrap();
+        let message = SmsMessage::new(phone_number, "Hi Siân, your annual review is due on 30 June 2024.");
+
+        assert_no_raw_pii(&format!("{:?}", message));
+        assert!(message.body().contains("Siân"));
+    }
+}
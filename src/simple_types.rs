@@ -1,7 +1,68 @@
 use regex::Regex;
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+use crate::contexts::annual_review::book_review_meeting::{contact_preferences::Language, localisation};
+use crate::redaction::{mask_email, mask_phone_number, mask_postcode, mask_text, redacted_debug_and_display, Redact};
+
+/// Why a value was rejected. Each error has a message key, so it can be
+/// written in the client's language as well as in English.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimpleTypeError {
+    InvalidClientId,
+    InvalidReviewId,
+    AddressTooLong,
+    AddressHasUnusualCharacters,
+    NameEmpty,
+    NameTooLong,
+    NameHasNumbers,
+    NameHasUnusualCharacters,
+    TrustOrCompanyNameHasUnusualCharacters,
+    InvalidEmailAddress,
+    PostcodeEmpty,
+    InvalidPostcode,
+    InvalidHouseNumber,
+    PhoneNumberPrefix,
+    PhoneNumberCharacters,
+    InternationalPhoneNumberLength,
+    UkPhoneNumberPrefix,
+    UkPhoneNumberLength,
+}
+
+impl SimpleTypeError {
+    /// The key of the error's message in each language's messages.
+    pub fn key(&self) -> &'static str {
+        match *self {
+            SimpleTypeError::InvalidClientId => "simple_types.invalid_client_id",
+            SimpleTypeError::InvalidReviewId => "simple_types.invalid_review_id",
+            SimpleTypeError::AddressTooLong => "simple_types.address_too_long",
+            SimpleTypeError::AddressHasUnusualCharacters => "simple_types.address_has_unusual_characters",
+            SimpleTypeError::NameEmpty => "simple_types.name_empty",
+            SimpleTypeError::NameTooLong => "simple_types.name_too_long",
+            SimpleTypeError::NameHasNumbers => "simple_types.name_has_numbers",
+            SimpleTypeError::NameHasUnusualCharacters => "simple_types.name_has_unusual_characters",
+            SimpleTypeError::TrustOrCompanyNameHasUnusualCharacters => "simple_types.trust_or_company_name_has_unusual_characters",
+            SimpleTypeError::InvalidEmailAddress => "simple_types.invalid_email_address",
+            SimpleTypeError::PostcodeEmpty => "simple_types.postcode_empty",
+            SimpleTypeError::InvalidPostcode => "simple_types.invalid_postcode",
+            SimpleTypeError::InvalidHouseNumber => "simple_types.invalid_house_number",
+            SimpleTypeError::PhoneNumberPrefix => "simple_types.phone_number_prefix",
+            SimpleTypeError::PhoneNumberCharacters => "simple_types.phone_number_characters",
+            SimpleTypeError::InternationalPhoneNumberLength => "simple_types.international_phone_number_length",
+            SimpleTypeError::UkPhoneNumberPrefix => "simple_types.uk_phone_number_prefix",
+            SimpleTypeError::UkPhoneNumberLength => "simple_types.uk_phone_number_length",
+        }
+    }
+}
+
+impl std::fmt::Display for SimpleTypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", localisation::message(Language::English, self.key(), &[]))
+    }
+}
+
+impl std::error::Error for SimpleTypeError {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct ClientId(String);
 
 impl ClientId {
@@ -11,20 +72,43 @@ impl ClientId {
 }
 
 impl TryFrom<String> for ClientId {
-    type Error = &'static str;
+    type Error = SimpleTypeError;
 
     fn try_from(client_id_string: String) -> Result<Self, Self::Error> {
         let regex_uuid = Regex::new(r"^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$").unwrap();
         
         if !regex_uuid.is_match(&client_id_string) {
-            Err("Client id does not include valid uuid string")
+            Err(SimpleTypeError::InvalidClientId)
         } else {
             Ok(Self(client_id_string))
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct ReviewId(String);
+
+impl ReviewId {
+    pub fn value(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for ReviewId {
+    type Error = SimpleTypeError;
+
+    fn try_from(review_id_string: String) -> Result<Self, Self::Error> {
+        let regex_uuid = Regex::new(r"^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$").unwrap();
+
+        if !regex_uuid.is_match(&review_id_string) {
+            Err(SimpleTypeError::InvalidReviewId)
+        } else {
+            Ok(Self(review_id_string))
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
 pub struct ConstrainedAddressString100(String);
 
 impl ConstrainedAddressString100 {
@@ -34,22 +118,22 @@ impl ConstrainedAddressString100 {
 }
 
 impl TryFrom<String> for ConstrainedAddressString100 {
-    type Error = &'static str;
+    type Error = SimpleTypeError;
 
     fn try_from(string: String) -> Result<Self, Self::Error> {
         let regex_unusual_characters = Regex::new(r"[!£$%^*{}\\/_]").unwrap();
 
         if string.len() > 100 {
-            Err("Constrained address string 100 must not have more than 100 characters.")
+            Err(SimpleTypeError::AddressTooLong)
         }  else if regex_unusual_characters.is_match(&string) {
-            Err("Constrained address string 100 must not have any unusual special characters such as ! £ $ % ^ * {} \\ / _ in it.")
+            Err(SimpleTypeError::AddressHasUnusualCharacters)
         } else {
             Ok(Self(string))
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
 pub struct ConstrainedIndividualNameString100(String);
 
 impl ConstrainedIndividualNameString100 {
@@ -59,27 +143,27 @@ impl ConstrainedIndividualNameString100 {
 }
 
 impl TryFrom<String> for ConstrainedIndividualNameString100 {
-    type Error = &'static str;
+    type Error = SimpleTypeError;
 
     fn try_from(name: String) -> Result<Self, Self::Error> {
         let regex_any_numbers = Regex::new(r"[\d]").unwrap();
         let regex_unusual_characters = Regex::new(r"[!£$%^&*(){}\\/+]").unwrap();
         
         if name.is_empty() {
-            Err("Constrained string 100 characters must have at least one character.")
+            Err(SimpleTypeError::NameEmpty)
         } else if name.len() > 100 {
-            Err("Constrained string 100 must not have more than 100 characters.")
+            Err(SimpleTypeError::NameTooLong)
         } else if regex_any_numbers.is_match(&name) {
-            Err("Constrained name string 100 must not have any numbers in it.")
+            Err(SimpleTypeError::NameHasNumbers)
         } else if regex_unusual_characters.is_match(&name) {
-            Err("Constrained name string 100 must not have any unusual special characters such as ! £ $ % ^ & * () {} \\ / _ + in it.")
+            Err(SimpleTypeError::NameHasUnusualCharacters)
         } else {
             Ok(Self(name))
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
 pub struct EmailAddress(String);
 
 impl EmailAddress {
@@ -89,21 +173,21 @@ impl EmailAddress {
 }
 
 impl TryFrom<String> for EmailAddress {
-    type Error = &'static str;
+    type Error = SimpleTypeError;
 
     fn try_from(email_address: String) -> Result<Self, Self::Error> {
-        let regex_email = Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9-]+(\.[a-zA-Z]{2,})$").unwrap();
+        let regex_email = Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9-]+(\.[a-zA-Z0-9-]+)*(\.[a-zA-Z]{2,})$").unwrap();
 
         if !regex_email.is_match(&email_address) {
-            Err("Invalid email address format.")
+            Err(SimpleTypeError::InvalidEmailAddress)
         } else {
             Ok(Self(email_address))
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-struct Postcode(String);
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+pub struct Postcode(String);
 
 impl Postcode {
     pub fn value(&self) -> &str {
@@ -113,13 +197,13 @@ impl Postcode {
 
 impl Postcode {
 
-    fn new(postcode: String) -> Result<Self, String> {
+    fn new(postcode: String) -> Result<Self, SimpleTypeError> {
         let regex_postcode = Regex::new(r"^[A-Z]{1,2}\d[A-Z\d]? ?\d[A-Z]{2}$").unwrap();
 
         if postcode.is_empty() {
-            Err("Postcode must not be empty.".to_string())
+            Err(SimpleTypeError::PostcodeEmpty)
         } else if !regex_postcode.is_match(&postcode) {
-            Err("Invalid UK postcode format.".to_string())
+            Err(SimpleTypeError::InvalidPostcode)
         } else {
             Ok(Self(postcode))
         }
@@ -140,10 +224,11 @@ struct PhysicalAddress{
     country: Option<ConstrainedAddressString100>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
 pub struct PostalAddress(PhysicalAddress);
 
 impl PostalAddress {
+    #[allow(clippy::too_many_arguments, clippy::redundant_closure)]
     pub fn new(
         house_name: Option<String>,
         house_number: Option<String>,
@@ -155,18 +240,18 @@ impl PostalAddress {
         county: Option<String>,
         postcode: String,
         country: Option<String>,
-    ) -> Result<Self, String> {
-
-        let house_name = house_name.map(|hn| ConstrainedAddressString100::try_from(hn)).transpose().map_err(|e| e.to_string())?;
-        let house_number = house_number.map(|hn| hn.parse::<i32>()).transpose().map_err(|e| e.to_string())?;
-        let address_line_one = ConstrainedAddressString100::try_from(address_line_one).map_err(|e| e.to_string())?;
-        let address_line_two = address_line_two.map(|al| ConstrainedAddressString100::try_from(al)).transpose().map_err(|e| e.to_string())?;
-        let address_line_three = address_line_three.map(|al| ConstrainedAddressString100::try_from(al)).transpose().map_err(|e| e.to_string())?;
-        let address_line_four = address_line_four.map(|al| ConstrainedAddressString100::try_from(al)).transpose().map_err(|e| e.to_string())?;
-        let city = ConstrainedAddressString100::try_from(city).map_err(|e| e.to_string())?;
-        let county = county.map(|c| ConstrainedAddressString100::try_from(c)).transpose().map_err(|e| e.to_string())?;
-        let postcode = Postcode::new(postcode).map_err(|e| e.to_string())?;
-        let country = country.map(|c| ConstrainedAddressString100::try_from(c)).transpose().map_err(|e| e.to_string())?;
+    ) -> Result<Self, SimpleTypeError> {
+
+        let house_name = house_name.map(|hn| ConstrainedAddressString100::try_from(hn)).transpose()?;
+        let house_number = house_number.map(|hn| hn.parse::<i32>()).transpose().map_err(|_| SimpleTypeError::InvalidHouseNumber)?;
+        let address_line_one = ConstrainedAddressString100::try_from(address_line_one)?;
+        let address_line_two = address_line_two.map(|al| ConstrainedAddressString100::try_from(al)).transpose()?;
+        let address_line_three = address_line_three.map(|al| ConstrainedAddressString100::try_from(al)).transpose()?;
+        let address_line_four = address_line_four.map(|al| ConstrainedAddressString100::try_from(al)).transpose()?;
+        let city = ConstrainedAddressString100::try_from(city)?;
+        let county = county.map(|c| ConstrainedAddressString100::try_from(c)).transpose()?;
+        let postcode = Postcode::new(postcode)?;
+        let country = country.map(|c| ConstrainedAddressString100::try_from(c)).transpose()?;
 
         Ok(Self(PhysicalAddress {
             house_name,
@@ -218,9 +303,44 @@ impl PostalAddress {
     pub fn country(&self) -> Option<&ConstrainedAddressString100> {
         self.0.country.as_ref()
     }
+
+    /// The address as printed on an envelope, with the house number on the
+    /// first street line and the post town in capitals.
+    pub fn address_lines(&self) -> Vec<String> {
+        self.lines(true)
+    }
+
+    /// The address lines without the postcode, for forms that take the postcode
+    /// in a field of its own.
+    pub fn address_lines_without_postcode(&self) -> Vec<String> {
+        self.lines(false)
+    }
+
+    fn lines(&self, with_postcode: bool) -> Vec<String> {
+        let first_street_line = match self.0.house_number {
+            Some(house_number) => format!("{} {}", house_number, self.0.address_line_one.value()),
+            None => self.0.address_line_one.value().to_string(),
+        };
+
+        let mut lines: Vec<String> = self.0.house_name.iter().map(|house_name| house_name.value().to_string()).collect();
+        lines.push(first_street_line);
+        lines.extend(
+            [&self.0.address_line_two, &self.0.address_line_three, &self.0.address_line_four]
+                .into_iter()
+                .flatten()
+                .map(|line| line.value().to_string()),
+        );
+        lines.push(self.0.city.value().to_uppercase());
+        lines.extend(self.0.county.iter().map(|county| county.value().to_string()));
+        if with_postcode {
+            lines.push(self.0.postcode.value().to_string());
+        }
+        lines.extend(self.0.country.iter().map(|country| country.value().to_string()));
+        lines
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
 pub struct ConstainedTrustOrCompanyNameString200(String);
 
 impl ConstainedTrustOrCompanyNameString200 {
@@ -230,17 +350,17 @@ impl ConstainedTrustOrCompanyNameString200 {
 }
 
 impl TryFrom<String> for ConstainedTrustOrCompanyNameString200 {
-    type Error = &'static str;
+    type Error = SimpleTypeError;
 
     fn try_from(trust_or_company_name: String) -> Result<Self, Self::Error> {
         let regex_unusual_trust_or_comany_name_characters = Regex::new(r"[!£$%^*(){}\\/]").unwrap();
         
         if trust_or_company_name.is_empty() {
-            Err("Constrained string 100 characters must have at least one character.")
+            Err(SimpleTypeError::NameEmpty)
         } else if trust_or_company_name.len() > 200 {
-            Err("Constrained string 100 must not have more than 100 characters.")
+            Err(SimpleTypeError::NameTooLong)
         } else if regex_unusual_trust_or_comany_name_characters.is_match(&trust_or_company_name) {
-            Err("Constrained trust or company name string 100 must not have any unusual special characters such as ! £ $ % ^ * () {} \\ / _ in it.")
+            Err(SimpleTypeError::TrustOrCompanyNameHasUnusualCharacters)
         } else {
             Ok(Self(trust_or_company_name))
         }
@@ -248,6 +368,194 @@ impl TryFrom<String> for ConstainedTrustOrCompanyNameString200 {
 }
 
 
+/// How a phone number is charged and whether it can receive SMS.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum PhoneNumberKind {
+    Mobile,
+    Landline,
+    /// UK numbers not tied to a place, such as 03, 0800 and 0845 numbers.
+    NonGeographic,
+    /// UK numbers charged at a premium rate, such as 09 and 070 numbers.
+    Premium,
+    International,
+    /// UK numbers too short to classify. Validation rejects these, but they
+    /// can still arrive through deserialisation.
+    Unknown,
+}
+
+/// A phone number in E.164 format, e.g. `+447700900123`.
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+pub struct PhoneNumber(String);
+
+impl PhoneNumber {
+    pub fn value(&self) -> &str {
+        &self.0
+    }
+
+    /// The number after the +44 country code, for UK numbers.
+    fn uk_national_number(&self) -> Option<&str> {
+        self.0.strip_prefix("+44")
+    }
+
+    pub fn kind(&self) -> PhoneNumberKind {
+        let Some(national_number) = self.uk_national_number() else {
+            return PhoneNumberKind::International;
+        };
+
+        let Some(prefix) = national_number.get(..2) else {
+            return PhoneNumberKind::Unknown;
+        };
+
+        match prefix {
+            "70" | "87" | "90" | "91" | "98" => PhoneNumberKind::Premium,
+            prefix if prefix.starts_with('7') && prefix != "76" => PhoneNumberKind::Mobile,
+            prefix if prefix.starts_with('1') || prefix.starts_with('2') => PhoneNumberKind::Landline,
+            _ => PhoneNumberKind::NonGeographic,
+        }
+    }
+
+    pub fn is_mobile(&self) -> bool {
+        self.kind() == PhoneNumberKind::Mobile
+    }
+
+    /// UK numbers in the usual national format, e.g. "020 7946 0123" or
+    /// "07700 900123". International numbers are shown in E.164 format.
+    pub fn display(&self) -> String {
+        let Some(national_number) = self.uk_national_number() else {
+            return self.0.clone();
+        };
+
+        let national_number = format!("0{}", national_number);
+        let groups: &[usize] = match national_number.as_bytes() {
+            [b'0', b'2', ..] => &[3, 4, 4],
+            [b'0', b'1', b'1', ..] | [b'0', b'1', _, b'1', ..] => &[4, 3, 4],
+            [b'0', b'1', ..] | [b'0', b'7', ..] => &[5, 6],
+            _ if national_number.len() == 10 => &[4, 6],
+            _ => &[4, 3, 4],
+        };
+
+        let mut remaining = national_number.as_str();
+        let mut parts = Vec::new();
+        for length in groups {
+            let (part, rest) = remaining.split_at((*length).min(remaining.len()));
+            parts.push(part);
+            remaining = rest;
+        }
+        parts.retain(|part| !part.is_empty());
+        parts.join(" ")
+    }
+}
+
+impl TryFrom<String> for PhoneNumber {
+    type Error = SimpleTypeError;
+
+    /// Accepts UK numbers written nationally ("07700 900123") or with the
+    /// country code ("+44 (0)7700 900123", "0044 7700 900123"), and other
+    /// numbers with a "+" or "00" international prefix.
+    fn try_from(phone_number: String) -> Result<Self, Self::Error> {
+        let compact: String = phone_number
+            .replace("(0)", "")
+            .chars()
+            .filter(|character| !matches!(character, ' ' | '-' | '.' | '(' | ')'))
+            .collect();
+
+        let international_number = if let Some(number) = compact.strip_prefix('+') {
+            number
+        } else if let Some(number) = compact.strip_prefix("00") {
+            number
+        } else if let Some(national_number) = compact.strip_prefix('0') {
+            return uk_phone_number(national_number);
+        } else {
+            return Err(SimpleTypeError::PhoneNumberPrefix);
+        };
+
+        if !international_number.chars().all(|character| character.is_ascii_digit()) {
+            Err(SimpleTypeError::PhoneNumberCharacters)
+        } else if let Some(national_number) = international_number.strip_prefix("44") {
+            uk_phone_number(national_number.strip_prefix('0').unwrap_or(national_number))
+        } else if international_number.starts_with('0') || !(7..=15).contains(&international_number.len()) {
+            Err(SimpleTypeError::InternationalPhoneNumberLength)
+        } else {
+            Ok(PhoneNumber(format!("+{}", international_number)))
+        }
+    }
+}
+
+/// Validates a UK number without its leading 0 or country code.
+fn uk_phone_number(national_number: &str) -> Result<PhoneNumber, SimpleTypeError> {
+    let valid_length = match national_number.chars().next() {
+        Some('1') => (9..=10).contains(&national_number.len()),
+        Some('8') if national_number.starts_with("800") => (9..=10).contains(&national_number.len()),
+        Some('2' | '3' | '5' | '7' | '8' | '9') => national_number.len() == 10,
+        _ => return Err(SimpleTypeError::UkPhoneNumberPrefix),
+    };
+
+    if !national_number.chars().all(|character| character.is_ascii_digit()) {
+        Err(SimpleTypeError::PhoneNumberCharacters)
+    } else if !valid_length {
+        Err(SimpleTypeError::UkPhoneNumberLength)
+    } else {
+        Ok(PhoneNumber(format!("+44{}", national_number)))
+    }
+}
+
+
+// Personal data is masked in Debug and Display output; use `value()` for the full value.
+
+impl Redact for ConstrainedAddressString100 {
+    fn redacted(&self) -> String {
+        mask_text(&self.0)
+    }
+}
+
+impl Redact for ConstrainedIndividualNameString100 {
+    fn redacted(&self) -> String {
+        mask_text(&self.0)
+    }
+}
+
+impl Redact for EmailAddress {
+    fn redacted(&self) -> String {
+        mask_email(&self.0)
+    }
+}
+
+impl Redact for Postcode {
+    fn redacted(&self) -> String {
+        mask_postcode(&self.0)
+    }
+}
+
+/// Only the postcode's outward code is shown, enough to tell regions apart.
+impl Redact for PostalAddress {
+    fn redacted(&self) -> String {
+        self.0.postcode.redacted()
+    }
+}
+
+/// Trust names usually include the family name, so they are masked like personal names.
+impl Redact for ConstainedTrustOrCompanyNameString200 {
+    fn redacted(&self) -> String {
+        mask_text(&self.0)
+    }
+}
+
+impl Redact for PhoneNumber {
+    fn redacted(&self) -> String {
+        mask_phone_number(&self.0)
+    }
+}
+
+redacted_debug_and_display!(
+    ConstrainedAddressString100,
+    ConstrainedIndividualNameString100,
+    EmailAddress,
+    Postcode,
+    PostalAddress,
+    ConstainedTrustOrCompanyNameString200,
+    PhoneNumber,
+);
+
 #[cfg(test)]
 mod tests {
     use crate::tests::test_utils::shared::{INDIVIDUAL_FIRST_NAME};
@@ -268,7 +576,7 @@ mod tests {
         let name = "".to_string();
         let result = ConstrainedIndividualNameString100::try_from(name);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Constrained string 100 characters must have at least one character.");
+        assert_eq!(result.unwrap_err().to_string(), "Constrained string 100 characters must have at least one character.");
     }
 
     #[test]
@@ -276,7 +584,7 @@ mod tests {
         let name = "JohnDoe123".to_string();
         let result = ConstrainedIndividualNameString100::try_from(name);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Constrained name string 100 must not have any numbers in it.");
+        assert_eq!(result.unwrap_err().to_string(), "Constrained name string 100 must not have any numbers in it.");
     }
 
     #[test]
@@ -284,7 +592,7 @@ mod tests {
         let name = "JohnDoe!@#".to_string();
         let result = ConstrainedIndividualNameString100::try_from(name);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Constrained name string 100 must not have any unusual special characters such as ! £ $ % ^ & * () {} \\ / _ + in it.");
+        assert_eq!(result.unwrap_err().to_string(), "Constrained name string 100 must not have any unusual special characters such as ! £ $ % ^ & * () {} \\ / _ + in it.");
     }
 
     #[test]
@@ -292,7 +600,7 @@ mod tests {
         let name = "a".repeat(101);
         let result = ConstrainedIndividualNameString100::try_from(name);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Constrained string 100 must not have more than 100 characters.");
+        assert_eq!(result.unwrap_err().to_string(), "Constrained string 100 must not have more than 100 characters.");
     }
 
     #[test]
@@ -304,6 +612,12 @@ mod tests {
         assert_eq!(constrained_name.value(), "John-Doe Example.Name");
     }
 
+    #[test]
+    fn test_name_with_space() {
+        let result = ConstrainedIndividualNameString100::try_from(INDIVIDUAL_FIRST_NAME.to_string());
+        assert_eq!(result.unwrap().value(), INDIVIDUAL_FIRST_NAME);
+    }
+
     #[test]
     fn test_valid_email() {
         let email = "example@example.com".to_string();
@@ -313,12 +627,20 @@ mod tests {
         assert_eq!(email_address.value(), "example@example.com");
     }
 
+    #[test]
+    fn test_valid_email_with_multi_level_domain() {
+        let email = "mark.ashworth@gcwm.co.uk".to_string();
+        let result = EmailAddress::try_from(email);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().value(), "mark.ashworth@gcwm.co.uk");
+    }
+
     #[test]
     fn test_invalid_email_no_at() {
         let email = "example.com".to_string();
         let result = EmailAddress::try_from(email);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid email address format.");
+        assert_eq!(result.unwrap_err().to_string(), "Invalid email address format.");
     }
 
     #[test]
@@ -326,7 +648,7 @@ mod tests {
         let email = "example@".to_string();
         let result = EmailAddress::try_from(email);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid email address format.");
+        assert_eq!(result.unwrap_err().to_string(), "Invalid email address format.");
     }
 
     #[test]
@@ -334,7 +656,7 @@ mod tests {
         let email = "example@exa!mple.com".to_string();
         let result = EmailAddress::try_from(email);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid email address format.");
+        assert_eq!(result.unwrap_err().to_string(), "Invalid email address format.");
     }
 
     #[test]
@@ -342,7 +664,7 @@ mod tests {
         let email = "example@example..com".to_string();
         let result = EmailAddress::try_from(email);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid email address format.");
+        assert_eq!(result.unwrap_err().to_string(), "Invalid email address format.");
     }
 
     #[test]
@@ -356,7 +678,7 @@ mod tests {
         let name = ConstainedTrustOrCompanyNameString200::try_from("".to_string());
         assert!(name.is_err());
         if let Err(err) = name {
-            assert_eq!(err, "Constrained string 100 characters must have at least one character.");
+            assert_eq!(err.to_string(), "Constrained string 100 characters must have at least one character.");
         } else {
             panic!("Expected error for empty string");
         }
@@ -368,7 +690,7 @@ mod tests {
         let name = ConstainedTrustOrCompanyNameString200::try_from(long_name);
         assert!(name.is_err());
         if let Err(err) = name {
-            assert_eq!(err, "Constrained string 100 must not have more than 100 characters.");
+            assert_eq!(err.to_string(), "Constrained string 100 must not have more than 100 characters.");
         } else {
             panic!("Expected error for too long string");
         }
@@ -379,7 +701,7 @@ mod tests {
         let name = ConstainedTrustOrCompanyNameString200::try_from("Invalid!Name".to_string());
         assert!(name.is_err());
         if let Err(err) = name {
-            assert_eq!(err, "Constrained trust or company name string 100 must not have any unusual special characters such as ! £ $ % ^ * () {} \\ / _ in it.");
+            assert_eq!(err.to_string(), "Constrained trust or company name string 100 must not have any unusual special characters such as ! £ $ % ^ * () {} \\ / _ in it.");
         } else {
             panic!("Expected error for name with unusual characters");
         }
@@ -391,6 +713,73 @@ mod tests {
         assert!(name.is_ok());
     }
 
+    #[test]
+    fn test_postal_address_lines() {
+        let address = PostalAddress::new(
+            Some("Rose Cottage".to_string()),
+            Some("3".to_string()),
+            "Church Lane".to_string(),
+            Some("Shere".to_string()),
+            None,
+            None,
+            "Guildford".to_string(),
+            Some("Surrey".to_string()),
+            "GU5 9HF".to_string(),
+            None,
+        ).unwrap();
+
+        assert_eq!(address.address_lines(), vec!["Rose Cottage", "3 Church Lane", "Shere", "GUILDFORD", "Surrey", "GU5 9HF"]);
+        assert_eq!(address.address_lines_without_postcode(), vec!["Rose Cottage", "3 Church Lane", "Shere", "GUILDFORD", "Surrey"]);
+    }
+
+    #[test]
+    fn test_phone_number_formats_parse_to_e164() {
+        for number in ["07700 900123", "+44 7700 900123", "+44 (0)7700 900123", "0044-7700-900-123", "(07700) 900123"] {
+            assert_eq!(PhoneNumber::try_from(number.to_string()).unwrap().value(), "+447700900123", "{}", number);
+        }
+        assert_eq!(PhoneNumber::try_from("+33 1 23 45 67 89".to_string()).unwrap().value(), "+33123456789");
+        assert_eq!(PhoneNumber::try_from("00 1 202 555 0143".to_string()).unwrap().value(), "+12025550143");
+    }
+
+    #[test]
+    fn test_invalid_phone_numbers() {
+        assert!(PhoneNumber::try_from("7700 900123".to_string()).is_err());
+        assert!(PhoneNumber::try_from("07700 90012".to_string()).is_err());
+        assert!(PhoneNumber::try_from("04700 900123".to_string()).is_err());
+        assert!(PhoneNumber::try_from("07700 9001ab".to_string()).is_err());
+        assert!(PhoneNumber::try_from("+0 123456789".to_string()).is_err());
+        assert!(PhoneNumber::try_from("".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_phone_number_kind() {
+        let kind = |number: &str| PhoneNumber::try_from(number.to_string()).unwrap().kind();
+
+        assert_eq!(kind("07700 900123"), PhoneNumberKind::Mobile);
+        assert_eq!(kind("020 7946 0123"), PhoneNumberKind::Landline);
+        assert_eq!(kind("01483 960123"), PhoneNumberKind::Landline);
+        assert_eq!(kind("0300 123 4567"), PhoneNumberKind::NonGeographic);
+        assert_eq!(kind("0800 123 4567"), PhoneNumberKind::NonGeographic);
+        assert_eq!(kind("0909 879 0000"), PhoneNumberKind::Premium);
+        assert_eq!(kind("070 1234 5678"), PhoneNumberKind::Premium);
+        assert_eq!(kind("+33 6 12 34 56 78"), PhoneNumberKind::International);
+        assert_eq!(serde_json::from_str::<PhoneNumber>(r#""+447""#).unwrap().kind(), PhoneNumberKind::Unknown);
+    }
+
+    #[test]
+    fn test_phone_number_display() {
+        let display = |number: &str| PhoneNumber::try_from(number.to_string()).unwrap().display();
+
+        assert_eq!(display("+447700900123"), "07700 900123");
+        assert_eq!(display("+442079460123"), "020 7946 0123");
+        assert_eq!(display("01134960123"), "0113 496 0123");
+        assert_eq!(display("01614960123"), "0161 496 0123");
+        assert_eq!(display("01483960123"), "01483 960123");
+        assert_eq!(display("03001234567"), "0300 123 4567");
+        assert_eq!(display("0800123456"), "0800 123456");
+        assert_eq!(display("+33123456789"), "+33123456789");
+    }
+
     #[test]
     fn test_constrained_trust_or_company_name_string_200_value() {
         let name = ConstainedTrustOrCompanyNameString200::try_from("Valid Trust or Company Name".to_string()).unwrap();
@@ -0,0 +1,221 @@
+//! An SMTP server on a local port that records what it receives, for testing
+//! mail delivery end to end without a real mail server.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex},
+    thread::JoinHandle,
+};
+
+use crate::encoding::base64;
+use super::SmtpReply;
+
+/// A message accepted by the fake server, with the data un-stuffed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedMessage {
+    sender: String,
+    recipients: Vec<String>,
+    data: String,
+}
+
+impl ReceivedMessage {
+    pub fn sender(&self) -> &String {
+        &self.sender
+    }
+
+    pub fn recipients(&self) -> &Vec<String> {
+        &self.recipients
+    }
+
+    pub fn data(&self) -> &String {
+        &self.data
+    }
+}
+
+/// The behaviour of the fake server. Recipients accept and messages are
+/// queued unless a reply is configured for them.
+#[derive(Debug, Clone, Default)]
+pub struct FakeSmtpServer {
+    recipient_replies: HashMap<String, SmtpReply>,
+    data_reply: Option<SmtpReply>,
+    credentials: Option<(String, String)>,
+}
+
+impl FakeSmtpServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_recipient_reply(mut self, recipient: &str, reply: SmtpReply) -> Self {
+        self.recipient_replies.insert(recipient.to_lowercase(), reply);
+        self
+    }
+
+    /// The reply after the message data, replacing `250 Queued`.
+    pub fn with_data_reply(mut self, reply: SmtpReply) -> Self {
+        self.data_reply = Some(reply);
+        self
+    }
+
+    /// Offers AUTH PLAIN and requires these credentials before MAIL FROM.
+    pub fn with_credentials(mut self, username: &str, password: &str) -> Self {
+        self.credentials = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Listens on an ephemeral port on 127.0.0.1 until the returned server is dropped.
+    pub fn start(self) -> std::io::Result<RunningFakeSmtpServer> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let messages = Arc::clone(&messages);
+            let stopped = Arc::clone(&stopped);
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        // A client that misbehaves only ends its own session.
+                        let _ = self.session(stream, &messages);
+                    }
+                }
+            })
+        };
+
+        Ok(RunningFakeSmtpServer { port, messages, stopped, thread: Some(thread) })
+    }
+
+    fn session(&self, stream: TcpStream, messages: &Mutex<Vec<ReceivedMessage>>) -> std::io::Result<()> {
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+        let mut reply = |line: &str| writer.write_all(format!("{}\r\n", line).as_bytes());
+
+        reply("220 localhost ESMTP fake")?;
+
+        let mut authenticated = self.credentials.is_none();
+        let mut sender: Option<String> = None;
+        let mut recipients = Vec::new();
+
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            let upper = line.to_uppercase();
+
+            if upper.starts_with("EHLO") || upper.starts_with("HELO") {
+                if self.credentials.is_some() {
+                    reply("250-localhost")?;
+                    reply("250 AUTH PLAIN")?;
+                } else {
+                    reply("250 localhost")?;
+                }
+            } else if upper.starts_with("AUTH PLAIN ") {
+                let expected = self.credentials.as_ref()
+                    .map(|(username, password)| base64(format!("\0{}\0{}", username, password).as_bytes()));
+                if expected.as_deref() == Some(&line[11..]) {
+                    authenticated = true;
+                    reply("235 Authentication succeeded")?;
+                } else {
+                    reply("535 Authentication credentials invalid")?;
+                }
+            } else if upper.starts_with("MAIL FROM:") {
+                if authenticated {
+                    sender = Some(address(&line[10..]));
+                    recipients.clear();
+                    reply("250 OK")?;
+                } else {
+                    reply("530 Authentication required")?;
+                }
+            } else if upper.starts_with("RCPT TO:") {
+                let recipient = address(&line[8..]);
+                match (&sender, self.recipient_replies.get(&recipient.to_lowercase())) {
+                    (None, _) => reply("503 Need MAIL command")?,
+                    (Some(_), Some(configured)) => reply(&configured.to_string())?,
+                    (Some(_), None) => {
+                        recipients.push(recipient);
+                        reply("250 OK")?;
+                    }
+                }
+            } else if upper == "DATA" {
+                if sender.is_none() || recipients.is_empty() {
+                    reply("503 Need RCPT command")?;
+                    continue;
+                }
+                reply("354 End data with <CR><LF>.<CR><LF>")?;
+
+                let mut data = String::new();
+                loop {
+                    let mut data_line = String::new();
+                    if reader.read_line(&mut data_line)? == 0 {
+                        return Ok(());
+                    }
+                    if data_line == ".\r\n" {
+                        break;
+                    }
+                    data.push_str(data_line.strip_prefix('.').unwrap_or(&data_line));
+                }
+
+                match &self.data_reply {
+                    Some(configured) => reply(&configured.to_string())?,
+                    None => {
+                        messages.lock().unwrap().push(ReceivedMessage {
+                            sender: sender.take().unwrap_or_default(),
+                            recipients: std::mem::take(&mut recipients),
+                            data,
+                        });
+                        reply("250 Queued")?;
+                    }
+                }
+            } else if upper == "RSET" {
+                sender = None;
+                recipients.clear();
+                reply("250 OK")?;
+            } else if upper == "QUIT" {
+                reply("221 Bye")?;
+                return Ok(());
+            } else {
+                reply("502 Command not implemented")?;
+            }
+        }
+    }
+}
+
+fn address(path: &str) -> String {
+    path.trim().trim_start_matches('<').trim_end_matches('>').to_string()
+}
+
+pub struct RunningFakeSmtpServer {
+    port: u16,
+    messages: Arc<Mutex<Vec<ReceivedMessage>>>,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RunningFakeSmtpServer {
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn received_messages(&self) -> Vec<ReceivedMessage> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+impl Drop for RunningFakeSmtpServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the stop flag.
+        let _ = TcpStream::connect(("127.0.0.1", self.port));
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,466 @@
+//! A small blocking SMTP client (RFC 5321), and an in-process fake server
+//! for tests.
+//!
+//! The client sends one message per connection. Each recipient is offered
+//! with its own `RCPT TO` so that a rejection of one recipient is reported
+//! without losing the others. STARTTLS needs the `starttls` feature.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use crate::encoding::base64;
+
+#[cfg(test)]
+pub mod fake_server;
+
+/// A reply from the server, e.g. `550 No such user`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmtpReply {
+    code: u16,
+    message: String,
+}
+
+impl SmtpReply {
+    pub fn new(code: u16, message: &str) -> Self {
+        Self { code, message: message.to_string() }
+    }
+
+    pub fn code(&self) -> u16 {
+        self.code
+    }
+
+    pub fn message(&self) -> &String {
+        &self.message
+    }
+
+    pub fn is_positive(&self) -> bool {
+        (200..400).contains(&self.code)
+    }
+
+    /// 5xx replies will fail again if retried.
+    pub fn is_permanent_failure(&self) -> bool {
+        self.code >= 500
+    }
+}
+
+impl std::fmt::Display for SmtpReply {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.code, self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmtpError {
+    Io(String),
+    Protocol(String),
+    Configuration(String),
+    Reply(SmtpReply),
+}
+
+impl SmtpError {
+    /// Whether sending again unchanged cannot succeed.
+    pub fn is_permanent(&self) -> bool {
+        match self {
+            SmtpError::Io(_) | SmtpError::Protocol(_) => false,
+            SmtpError::Configuration(_) => true,
+            SmtpError::Reply(reply) => reply.is_permanent_failure(),
+        }
+    }
+}
+
+impl std::fmt::Display for SmtpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            SmtpError::Io(ref desc) => write!(f, "SMTP connection error: {}", desc),
+            SmtpError::Protocol(ref desc) => write!(f, "SMTP protocol error: {}", desc),
+            SmtpError::Configuration(ref desc) => write!(f, "SMTP configuration error: {}", desc),
+            SmtpError::Reply(ref reply) => write!(f, "SMTP server replied: {}", reply),
+        }
+    }
+}
+
+impl std::error::Error for SmtpError {}
+
+impl From<std::io::Error> for SmtpError {
+    fn from(error: std::io::Error) -> Self {
+        SmtpError::Io(error.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TlsMode {
+    None,
+    /// Upgrade with STARTTLS and fail if the server does not offer it.
+    StartTls,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct SmtpConfig {
+    host: String,
+    port: u16,
+    helo_domain: String,
+    tls_mode: TlsMode,
+    credentials: Option<(String, String)>,
+    timeout: Duration,
+}
+
+impl SmtpConfig {
+    pub fn new(host: &str, port: u16, helo_domain: &str) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            helo_domain: helo_domain.to_string(),
+            tls_mode: TlsMode::None,
+            credentials: None,
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    pub fn with_starttls(mut self) -> Self {
+        self.tls_mode = TlsMode::StartTls;
+        self
+    }
+
+    /// Authenticates with AUTH PLAIN after EHLO. Sending fails unless STARTTLS
+    /// is also set, as the password would otherwise cross the network in clear.
+    pub fn with_credentials(mut self, username: &str, password: &str) -> Self {
+        self.credentials = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn host(&self) -> &String {
+        &self.host
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn tls_mode(&self) -> TlsMode {
+        self.tls_mode
+    }
+}
+
+/// The password is left out.
+impl std::fmt::Debug for SmtpConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmtpConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("helo_domain", &self.helo_domain)
+            .field("tls_mode", &self.tls_mode)
+            .field("username", &self.credentials.as_ref().map(|(username, _)| username))
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The outcome of a transaction in which the server accepted the message for
+/// at least one recipient, or rejected every recipient.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmtpSendReport {
+    accepted: Vec<String>,
+    rejected: Vec<(String, SmtpReply)>,
+}
+
+impl SmtpSendReport {
+    pub fn accepted(&self) -> &Vec<String> {
+        &self.accepted
+    }
+
+    pub fn rejected(&self) -> &Vec<(String, SmtpReply)> {
+        &self.rejected
+    }
+}
+
+trait Stream: Read + Write {}
+
+impl<T: Read + Write> Stream for T {}
+
+struct Connection {
+    reader: BufReader<Box<dyn Stream>>,
+}
+
+impl Connection {
+    fn new(stream: Box<dyn Stream>) -> Self {
+        Self { reader: BufReader::new(stream) }
+    }
+
+    fn read_reply(&mut self) -> Result<(SmtpReply, Vec<String>), SmtpError> {
+        let mut lines = Vec::new();
+
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(SmtpError::Io("Connection closed by server".to_string()));
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+
+            let code = line.get(..3).and_then(|code| code.parse::<u16>().ok())
+                .ok_or_else(|| SmtpError::Protocol(format!("Invalid reply line '{}'", line)))?;
+            let is_last = line.as_bytes().get(3) != Some(&b'-');
+            lines.push(line.get(4..).unwrap_or_default().to_string());
+
+            if is_last {
+                let message = lines.last().cloned().unwrap_or_default();
+                return Ok((SmtpReply::new(code, &message), lines));
+            }
+        }
+    }
+
+    fn command(&mut self, command: &str) -> Result<(SmtpReply, Vec<String>), SmtpError> {
+        self.write(&format!("{}\r\n", command))?;
+        self.read_reply()
+    }
+
+    /// Sends a command and returns an error unless the reply is positive.
+    fn expect(&mut self, command: &str) -> Result<Vec<String>, SmtpError> {
+        let (reply, lines) = self.command(command)?;
+        if reply.is_positive() { Ok(lines) } else { Err(SmtpError::Reply(reply)) }
+    }
+
+    fn write(&mut self, data: &str) -> Result<(), SmtpError> {
+        let stream = self.reader.get_mut();
+        stream.write_all(data.as_bytes())?;
+        stream.flush()?;
+        Ok(())
+    }
+}
+
+pub struct SmtpClient {
+    config: SmtpConfig,
+}
+
+impl SmtpClient {
+    pub fn new(config: SmtpConfig) -> Self {
+        Self { config }
+    }
+
+    /// Sends `message`, a complete RFC 5322 message with CRLF line endings, to
+    /// each of `recipients`. Rejected recipients are reported rather than
+    /// failing the whole send; an error means nothing was accepted.
+    pub fn send(&self, sender: &str, recipients: &[&str], message: &str) -> Result<SmtpSendReport, SmtpError> {
+        let mut connection = self.connect()?;
+
+        let result = self.transaction(&mut connection, sender, recipients, message);
+        // The message is already accepted or rejected by this point, so a failed QUIT is ignored.
+        let _ = connection.command("QUIT");
+
+        result
+    }
+
+    fn connect(&self) -> Result<Connection, SmtpError> {
+        if self.config.credentials.is_some() && self.config.tls_mode != TlsMode::StartTls {
+            return Err(SmtpError::Configuration("Credentials are only sent after STARTTLS".to_string()));
+        }
+
+        let address = (self.config.host.as_str(), self.config.port).to_socket_addrs()?
+            .next()
+            .ok_or_else(|| SmtpError::Io(format!("Could not resolve {}", self.config.host)))?;
+        let stream = TcpStream::connect_timeout(&address, self.config.timeout)?;
+        stream.set_read_timeout(Some(self.config.timeout))?;
+        stream.set_write_timeout(Some(self.config.timeout))?;
+
+        let mut connection = Connection::new(Box::new(stream.try_clone()?));
+        let (greeting, _) = connection.read_reply()?;
+        if greeting.code() != 220 {
+            return Err(SmtpError::Reply(greeting));
+        }
+
+        let mut extensions = connection.expect(&format!("EHLO {}", self.config.helo_domain))?;
+
+        if self.config.tls_mode == TlsMode::StartTls {
+            if !has_extension(&extensions, "STARTTLS") {
+                return Err(SmtpError::Configuration(format!("{} does not offer STARTTLS", self.config.host)));
+            }
+            connection.expect("STARTTLS")?;
+            connection = self.upgrade(stream)?;
+            extensions = connection.expect(&format!("EHLO {}", self.config.helo_domain))?;
+        }
+
+        if let Some((username, password)) = &self.config.credentials {
+            if !extensions.iter().any(|extension| extension.to_uppercase().starts_with("AUTH") && extension.to_uppercase().contains("PLAIN")) {
+                return Err(SmtpError::Configuration(format!("{} does not offer AUTH PLAIN", self.config.host)));
+            }
+            connection.expect(&auth_plain(username, password))?;
+        }
+
+        Ok(connection)
+    }
+
+    #[cfg(feature = "starttls")]
+    fn upgrade(&self, stream: TcpStream) -> Result<Connection, SmtpError> {
+        let connector = native_tls::TlsConnector::new().map_err(|e| SmtpError::Configuration(e.to_string()))?;
+        let stream = connector.connect(&self.config.host, stream).map_err(|e| SmtpError::Io(e.to_string()))?;
+        Ok(Connection::new(Box::new(stream)))
+    }
+
+    #[cfg(not(feature = "starttls"))]
+    fn upgrade(&self, _stream: TcpStream) -> Result<Connection, SmtpError> {
+        Err(SmtpError::Configuration("STARTTLS needs the starttls feature".to_string()))
+    }
+
+    fn transaction(&self, connection: &mut Connection, sender: &str, recipients: &[&str], message: &str) -> Result<SmtpSendReport, SmtpError> {
+        connection.expect(&format!("MAIL FROM:<{}>", sender))?;
+
+        let mut accepted = Vec::new();
+        let mut rejected = Vec::new();
+        for recipient in recipients {
+            let (reply, _) = connection.command(&format!("RCPT TO:<{}>", recipient))?;
+            if reply.is_positive() {
+                accepted.push(recipient.to_string());
+            } else {
+                rejected.push((recipient.to_string(), reply));
+            }
+        }
+
+        if accepted.is_empty() {
+            connection.command("RSET")?;
+            return Ok(SmtpSendReport { accepted, rejected });
+        }
+
+        let (reply, _) = connection.command("DATA")?;
+        if reply.code() != 354 {
+            return Err(SmtpError::Reply(reply));
+        }
+
+        connection.write(&dot_stuff(message))?;
+        let (reply, _) = connection.read_reply()?;
+        if !reply.is_positive() {
+            return Err(SmtpError::Reply(reply));
+        }
+
+        Ok(SmtpSendReport { accepted, rejected })
+    }
+}
+
+fn has_extension(extensions: &[String], name: &str) -> bool {
+    extensions.iter().any(|extension| extension.split_whitespace().next().is_some_and(|keyword| keyword.eq_ignore_ascii_case(name)))
+}
+
+fn auth_plain(username: &str, password: &str) -> String {
+    format!("AUTH PLAIN {}", base64(format!("\0{}\0{}", username, password).as_bytes()))
+}
+
+/// Escapes lines starting with a full stop and appends the end of data marker.
+fn dot_stuff(message: &str) -> String {
+    let mut data = message.split("\r\n")
+        .map(|line| if line.starts_with('.') { format!(".{}", line) } else { line.to_string() })
+        .collect::<Vec<String>>()
+        .join("\r\n");
+
+    if !data.ends_with("\r\n") {
+        data.push_str("\r\n");
+    }
+    data.push_str(".\r\n");
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fake_server::FakeSmtpServer, *};
+
+    const MESSAGE: &str = "Subject: Test\r\n\r\nHello\r\n.hidden line\r\n";
+
+    fn client(server: &fake_server::RunningFakeSmtpServer) -> SmtpClient {
+        SmtpClient::new(SmtpConfig::new("127.0.0.1", server.port(), "gcwm.co.uk").with_timeout(Duration::from_secs(5)))
+    }
+
+    #[test]
+    fn test_send_to_fake_server() {
+        let server = FakeSmtpServer::new().start().unwrap();
+
+        let report = client(&server).send("invites@gcwm.co.uk", &["james@example.com", "sarah@example.com"], MESSAGE).unwrap();
+        assert_eq!(report.accepted(), &vec!["james@example.com".to_string(), "sarah@example.com".to_string()]);
+        assert!(report.rejected().is_empty());
+
+        let messages = server.received_messages();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].sender(), "invites@gcwm.co.uk");
+        assert_eq!(messages[0].recipients(), &vec!["james@example.com".to_string(), "sarah@example.com".to_string()]);
+        assert_eq!(messages[0].data(), MESSAGE);
+    }
+
+    #[test]
+    fn test_rejected_recipient_is_reported() {
+        let server = FakeSmtpServer::new()
+            .with_recipient_reply("sarah@example.com", SmtpReply::new(550, "No such user"))
+            .start()
+            .unwrap();
+
+        let report = client(&server).send("invites@gcwm.co.uk", &["james@example.com", "sarah@example.com"], MESSAGE).unwrap();
+        assert_eq!(report.accepted(), &vec!["james@example.com".to_string()]);
+        assert_eq!(report.rejected(), &vec![("sarah@example.com".to_string(), SmtpReply::new(550, "No such user"))]);
+        assert_eq!(server.received_messages()[0].recipients(), &vec!["james@example.com".to_string()]);
+    }
+
+    #[test]
+    fn test_data_failure_is_an_error() {
+        let server = FakeSmtpServer::new().with_data_reply(SmtpReply::new(451, "Try again later")).start().unwrap();
+
+        let error = client(&server).send("invites@gcwm.co.uk", &["james@example.com"], MESSAGE).unwrap_err();
+        assert_eq!(error, SmtpError::Reply(SmtpReply::new(451, "Try again later")));
+        assert!(!error.is_permanent());
+        assert!(server.received_messages().is_empty());
+    }
+
+    #[test]
+    fn test_credentials_use_auth_plain() {
+        assert_eq!(auth_plain("mailer", "secret"), "AUTH PLAIN AG1haWxlcgBzZWNyZXQ=");
+    }
+
+    #[test]
+    fn test_credentials_are_never_sent_without_tls() {
+        let server = FakeSmtpServer::new().with_credentials("mailer", "secret").start().unwrap();
+
+        let config = SmtpConfig::new("127.0.0.1", server.port(), "gcwm.co.uk").with_credentials("mailer", "secret");
+        let error = SmtpClient::new(config).send("invites@gcwm.co.uk", &["james@example.com"], MESSAGE).unwrap_err();
+        assert!(matches!(error, SmtpError::Configuration(_)));
+        assert!(server.received_messages().is_empty());
+    }
+
+    #[test]
+    fn test_config_debug_leaves_out_password() {
+        let config = SmtpConfig::new("smtp.gcwm.co.uk", 587, "gcwm.co.uk").with_starttls().with_credentials("mailer", "secret");
+        let debug = format!("{:?}", config);
+        assert!(debug.contains("mailer"));
+        assert!(!debug.contains("secret"));
+    }
+
+    #[test]
+    fn test_starttls_is_never_downgraded() {
+        let server = FakeSmtpServer::new().start().unwrap();
+
+        let config = SmtpConfig::new("127.0.0.1", server.port(), "gcwm.co.uk").with_starttls();
+        let error = SmtpClient::new(config).send("invites@gcwm.co.uk", &["james@example.com"], MESSAGE).unwrap_err();
+        assert!(matches!(error, SmtpError::Configuration(_)));
+        assert!(server.received_messages().is_empty());
+    }
+
+    #[test]
+    fn test_unreachable_server_is_transient() {
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+
+        let config = SmtpConfig::new("127.0.0.1", port, "gcwm.co.uk").with_timeout(Duration::from_secs(1));
+        let error = SmtpClient::new(config).send("invites@gcwm.co.uk", &["james@example.com"], MESSAGE).unwrap_err();
+        assert!(matches!(error, SmtpError::Io(_)));
+        assert!(!error.is_permanent());
+    }
+
+    #[test]
+    fn test_dot_stuffing() {
+        assert_eq!(dot_stuff(".a\r\nb"), "..a\r\nb\r\n.\r\n");
+        assert_eq!(dot_stuff("a\r\n"), "a\r\n.\r\n");
+    }
+}
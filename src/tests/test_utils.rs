@@ -1,6 +1,241 @@
 #[cfg(test)]
 pub mod shared {
+    use chrono::NaiveDate;
+
+    use crate::contexts::annual_review::book_review_meeting::{
+        invites::{create_annual_review_invites, AnnualReviewInvite, SendElectronicAnnualReviewInvite, SendPostAnnualReviewInvite},
+        types::{ClientContactInformation, UnvalidatedAnnualReviewInformation, ValidatedAnnualReviewDueDate, ValidatedAnnualReviewInformation, ValidatedClientContactInformation},
+    };
 
     pub const INDIVIDUAL_FIRST_NAME: &str = "James May";
 
-}
\ No newline at end of file
+    pub const ADVISER_NAME: &str = "Mark Ashworth";
+
+    pub const ADMINISTRATOR_EMAIL: &str = "reviews@gcwm.co.uk";
+
+    pub const REVIEW_ID: &str = "7c9e6679-7425-40de-944b-e07fc1f90ae7";
+
+    pub const JOINT_INDIVIDUALS_ELECTRONIC_CONTACT: &str = r#"{
+        "type": "JointIndividualsElectronicContact",
+        "primary_contact_first_name": "James",
+        "individual_two_first_name": "Sarah",
+        "primary_contact_email_address": "james@example.com",
+        "individual_two_email_address": "sarah@example.com"
+    }"#;
+
+    pub const SINGLE_INDIVIDUAL_ELECTRONIC_CONTACT: &str = r#"{
+        "type": "SingleIndividualElectronicContact",
+        "first_name": "Siân",
+        "email_address": "sian@example.com"
+    }"#;
+
+    pub const JOINT_INDIVIDUALS_POST_CONTACT: &str = r#"{
+        "type": "JointIndividualsPostContact",
+        "primary_contact_first_name": "James",
+        "individual_two_first_name": "Sarah",
+        "house_name": "",
+        "house_number": "12",
+        "address_line_one": "High Street",
+        "address_line_two": "",
+        "address_line_three": "",
+        "address_line_four": "",
+        "city": "Guildford",
+        "county": "Surrey",
+        "postcode": "GU1 3AA",
+        "country": ""
+    }"#;
+
+    pub const SINGLE_INDIVIDUAL_POST_CONTACT_WITH_MOBILE: &str = r#"{
+        "type": "SingleIndividualPostContact",
+        "first_name": "Siân",
+        "house_name": "",
+        "house_number": "4",
+        "address_line_one": "Quarry Street",
+        "address_line_two": "",
+        "address_line_three": "",
+        "address_line_four": "",
+        "city": "Guildford",
+        "county": "Surrey",
+        "postcode": "GU1 3UY",
+        "country": "",
+        "phone_number": "07700 900123"
+    }"#;
+
+    pub const MULTIPLE_TRUSTEES_POST_CONTACT: &str = r#"{
+        "type": "MultipleTrusteesPostContact",
+        "trust_name": "May Family Trust",
+        "trustees": [
+            {
+                "first_name": "Alice",
+                "house_name": "",
+                "house_number": "12",
+                "address_line_one": "High Street",
+                "address_line_two": "",
+                "address_line_three": "",
+                "address_line_four": "",
+                "city": "Guildford",
+                "county": "Surrey",
+                "postcode": "GU1 3AA",
+                "country": ""
+            },
+            {
+                "first_name": "Bob",
+                "house_name": "",
+                "house_number": "12",
+                "address_line_one": "High Street",
+                "address_line_two": "",
+                "address_line_three": "",
+                "address_line_four": "",
+                "city": "Guildford",
+                "county": "Surrey",
+                "postcode": "GU1 3AA",
+                "country": ""
+            },
+            {
+                "first_name": "Carol",
+                "house_name": "Rose Cottage",
+                "house_number": "",
+                "address_line_one": "Church Lane",
+                "address_line_two": "Shere",
+                "address_line_three": "",
+                "address_line_four": "",
+                "city": "Guildford",
+                "county": "Surrey",
+                "postcode": "GU5 9HF",
+                "country": ""
+            }
+        ]
+    }"#;
+
+    pub const PRIMARY_DIRECTOR_ELECTRONIC_CONTACT: &str = r#"{
+        "type": "PrimaryDirectorElectronicContact",
+        "company_name": "May Holdings Ltd",
+        "primary_director_first_name": "James",
+        "primary_director_email_address": "james@mayholdings.co.uk"
+    }"#;
+
+    pub fn validated_contact(client_contact_information: &str) -> ValidatedClientContactInformation {
+        let client_contact_information: ClientContactInformation = serde_json::from_str(client_contact_information).unwrap();
+        ValidatedClientContactInformation::validate(client_contact_information).unwrap()
+    }
+
+    /// The review due on 30 June 2024, a year after the last.
+    pub fn due_date() -> ValidatedAnnualReviewDueDate {
+        ValidatedAnnualReviewDueDate::validate("30/06/2024".to_string(), "30/06/2023".to_string()).unwrap()
+    }
+
+    /// The day clients are asked to reply to invites by.
+    pub fn reply_deadline() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, 31).unwrap()
+    }
+
+    /// Annual review information for a test, built from JSON. Each optional
+    /// part is left out unless set, e.g.
+    /// `ReviewInformationFixture::new(contact).with_deceased(deceased).validated()`.
+    pub struct ReviewInformationFixture {
+        client_contact_information: String,
+        contact_preferences: String,
+        vulnerability_profile: String,
+        authorised_contacts: String,
+        deceased_clients: String,
+    }
+
+    impl ReviewInformationFixture {
+        pub fn new(client_contact_information: &str) -> Self {
+            Self {
+                client_contact_information: client_contact_information.to_string(),
+                contact_preferences: "null".to_string(),
+                vulnerability_profile: "null".to_string(),
+                authorised_contacts: "[]".to_string(),
+                deceased_clients: "[]".to_string(),
+            }
+        }
+
+        pub fn with_contact_preferences(mut self, contact_preferences: &str) -> Self {
+            self.contact_preferences = contact_preferences.to_string();
+            self
+        }
+
+        pub fn with_vulnerability(mut self, vulnerability_profile: &str) -> Self {
+            self.vulnerability_profile = vulnerability_profile.to_string();
+            self
+        }
+
+        /// The attorneys and deputies, as a JSON array.
+        pub fn with_authorised_contacts(mut self, authorised_contacts: &str) -> Self {
+            self.authorised_contacts = authorised_contacts.to_string();
+            self
+        }
+
+        /// The deceased clients, as a JSON array.
+        pub fn with_deceased(mut self, deceased_clients: &str) -> Self {
+            self.deceased_clients = deceased_clients.to_string();
+            self
+        }
+
+        pub fn unvalidated(&self) -> UnvalidatedAnnualReviewInformation {
+            serde_json::from_str(&format!(
+                r#"{{
+                    "annual_review_due_date": "30/06/2024",
+                    "last_annual_review_due_date": "30/06/2023",
+                    "adviser_name": "{}",
+                    "administrator_email": "{}",
+                    "client_contact_information": {},
+                    "contact_preferences": {},
+                    "vulnerability_profile": {},
+                    "authorised_contacts": {},
+                    "deceased_clients": {}
+                }}"#,
+                ADVISER_NAME,
+                ADMINISTRATOR_EMAIL,
+                self.client_contact_information,
+                self.contact_preferences,
+                self.vulnerability_profile,
+                self.authorised_contacts,
+                self.deceased_clients,
+            )).unwrap()
+        }
+
+        pub fn validated(&self) -> ValidatedAnnualReviewInformation {
+            ValidatedAnnualReviewInformation::validate(self.unvalidated()).unwrap()
+        }
+
+        /// The invites to the contact, to reply by [`reply_deadline`].
+        pub fn invites(&self) -> Vec<AnnualReviewInvite> {
+            let review_information = self.validated();
+            create_annual_review_invites(review_information.client_contact_information(), review_information.annual_review_due_date(), reply_deadline()).unwrap()
+        }
+
+        pub fn electronic_invites(&self) -> Vec<SendElectronicAnnualReviewInvite> {
+            self.invites().into_iter().map(|invite| match invite {
+                AnnualReviewInvite::SendElectronicAnnualReviewInvite(invite) => invite,
+                _ => panic!("Expected an electronic invite"),
+            }).collect()
+        }
+
+        pub fn post_invites(&self) -> Vec<SendPostAnnualReviewInvite> {
+            self.invites().into_iter().map(|invite| match invite {
+                AnnualReviewInvite::SendPostAnnualReviewInvite(invite) => invite,
+                _ => panic!("Expected a post invite"),
+            }).collect()
+        }
+    }
+
+    /// Compares `actual` with the snapshot file `src/tests/snapshots/{name}`, stored with
+    /// LF line endings. Set UPDATE_SNAPSHOTS=1 to write new snapshots or rewrite them after
+    /// an intended change; a missing snapshot otherwise fails the test.
+    pub fn assert_snapshot(name: &str, actual: &str) {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/snapshots").join(name);
+        let actual = actual.replace("\r\n", "\n");
+
+        if std::env::var("UPDATE_SNAPSHOTS").is_ok() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &actual).unwrap();
+        }
+
+        let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!("Snapshot {} is missing. Rerun the test with UPDATE_SNAPSHOTS=1 to create it.", name)
+        });
+        assert_eq!(actual, expected, "Snapshot {} differs", name);
+    }
+}